@@ -1,13 +1,15 @@
 use {
     crate::{
-        config::RunOptions,
-        state::{Aggregates, Benchmarks, Cache, Metrics},
+        config::rpc,
+        state::{
+            aggregate::derived_feeds::DerivedFeed, Aggregates, Benchmarks, Cache, Metrics, Wormhole,
+        },
     },
     anyhow::Result,
     axum::{extract::Extension, middleware::from_fn_with_state, routing::get, Router},
     ipnet::IpNet,
     serde_qs::axum::QsQueryConfig,
-    std::sync::Arc,
+    std::{collections::BTreeMap, sync::Arc},
     tower_http::cors::CorsLayer,
     utoipa::OpenApi,
     utoipa_swagger_ui::SwaggerUi,
@@ -23,6 +25,8 @@ pub struct ApiState<S> {
     pub state: Arc<S>,
     pub ws: Arc<ws::WsState>,
     pub metrics: Arc<metrics_middleware::ApiMetrics>,
+    /// The configured derived feeds by name.
+    pub derived_feeds: Arc<BTreeMap<String, DerivedFeed>>,
 }
 
 /// Manually implement `Clone` as the derive macro will try and slap `Clone` on
@@ -33,43 +37,59 @@ impl<S> Clone for ApiState<S> {
             state: self.state.clone(),
             ws: self.ws.clone(),
             metrics: self.metrics.clone(),
+            derived_feeds: self.derived_feeds.clone(),
         }
     }
 }
 
 impl<S> ApiState<S> {
-    pub fn new(state: Arc<S>, ws_whitelist: Vec<IpNet>, requester_ip_header_name: String) -> Self
+    pub fn new(
+        state: Arc<S>,
+        ws_whitelist: Vec<IpNet>,
+        requester_ip_header_name: String,
+        derived_feeds: Vec<DerivedFeed>,
+    ) -> Self
     where
         S: Metrics,
         S: Send + Sync + 'static,
     {
+        let metrics = Arc::new(metrics_middleware::ApiMetrics::new(state.clone()));
         Self {
-            metrics: Arc::new(metrics_middleware::ApiMetrics::new(state.clone())),
             ws: Arc::new(ws::WsState::new(
                 ws_whitelist,
                 requester_ip_header_name,
+                metrics.clone(),
                 state.clone(),
             )),
+            metrics,
+            derived_feeds: Arc::new(
+                derived_feeds
+                    .into_iter()
+                    .map(|derived_feed| (derived_feed.name.clone(), derived_feed))
+                    .collect(),
+            ),
             state,
         }
     }
 }
 
 #[tracing::instrument(skip(opts, state))]
-pub async fn spawn<S>(opts: RunOptions, state: Arc<S>) -> Result<()>
+pub async fn spawn<S>(opts: rpc::Options, state: Arc<S>) -> Result<()>
 where
     S: Aggregates,
     S: Benchmarks,
     S: Cache,
     S: Metrics,
+    S: Wormhole,
     S: Send + Sync + 'static,
 {
     let state = {
         let opts = opts.clone();
         ApiState::new(
             state,
-            opts.rpc.ws_whitelist,
-            opts.rpc.requester_ip_header_name,
+            opts.ws_whitelist,
+            opts.requester_ip_header_name,
+            opts.derived_feeds,
         )
     };
 
@@ -81,15 +101,16 @@ where
 /// Currently this is based on Axum due to the simplicity and strong ecosystem support for the
 /// packages they are based on (tokio & hyper).
 #[tracing::instrument(skip(opts, state))]
-pub async fn run<S>(opts: RunOptions, state: ApiState<S>) -> Result<()>
+pub async fn run<S>(opts: rpc::Options, state: ApiState<S>) -> Result<()>
 where
     S: Aggregates,
     S: Benchmarks,
     S: Cache,
     S: Metrics,
+    S: Wormhole,
     S: Send + Sync + 'static,
 {
-    tracing::info!(endpoint = %opts.rpc.listen_addr, "Starting RPC Server.");
+    tracing::info!(endpoint = %opts.listen_addr, "Starting RPC Server.");
 
     #[derive(OpenApi)]
     #[openapi(
@@ -103,8 +124,11 @@ where
             rest::latest_price_updates,
             rest::latest_twaps,
             rest::latest_publisher_stake_caps,
+            rest::timestamp_publisher_stake_caps,
+            rest::publisher_stake_caps_diff,
             rest::timestamp_price_updates,
             rest::price_feeds_metadata,
+            rest::price_feeds_metadata_stream_sse_handler,
             rest::price_stream_sse_handler,
         ),
         components(
@@ -118,14 +142,20 @@ where
                 types::RpcPriceFeedMetadata,
                 types::RpcPriceIdentifier,
                 types::EncodingType,
+                types::TargetChain,
                 types::PriceUpdate,
                 types::BinaryUpdate,
                 types::ParsedPriceUpdate,
+                types::ParsedDerivedPriceUpdate,
                 types::RpcPriceFeedMetadataV2,
+                types::MarketStatus,
                 types::PriceFeedMetadata,
+                types::PriceFeedMetadataChange,
                 types::LatestPublisherStakeCapsUpdateDataResponse,
                 types::ParsedPublisherStakeCapsUpdate,
                 types::ParsedPublisherStakeCap,
+                types::PublisherStakeCapChange,
+                types::PublisherStakeCapsDiffResponse,
                 types::AssetType,
                 types::TwapsResponse,
                 types::ParsedPriceFeedTwap,
@@ -168,11 +198,23 @@ where
             "/v2/updates/publisher_stake_caps/latest",
             get(rest::latest_publisher_stake_caps),
         )
+        .route(
+            "/v2/updates/publisher_stake_caps/diff",
+            get(rest::publisher_stake_caps_diff),
+        )
+        .route(
+            "/v2/updates/publisher_stake_caps/:publish_time",
+            get(rest::timestamp_publisher_stake_caps),
+        )
         .route(
             "/v2/updates/price/:publish_time",
             get(rest::timestamp_price_updates),
         )
         .route("/v2/price_feeds", get(rest::price_feeds_metadata))
+        .route(
+            "/v2/price_feeds/stream",
+            get(rest::price_feeds_metadata_stream_sse_handler),
+        )
         .route("/live", get(rest::live))
         .route("/ready", get(rest::ready))
         .route("/ws", get(ws::ws_route_handler))
@@ -189,7 +231,7 @@ where
 
     // Binds the axum's server to the configured address and port. This is a blocking call and will
     // not return until the server is shutdown.
-    axum::Server::try_bind(&opts.rpc.listen_addr)?
+    axum::Server::try_bind(&opts.listen_addr)?
         .serve(app.into_make_service())
         .with_graceful_shutdown(async {
             let _ = crate::EXIT.subscribe().changed().await;
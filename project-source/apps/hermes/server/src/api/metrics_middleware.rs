@@ -1,6 +1,6 @@
 use {
     super::ApiState,
-    crate::state::metrics::Metrics,
+    crate::state::{aggregate::UnixTimestamp, metrics::Metrics},
     axum::{
         extract::{MatchedPath, State},
         http::Request,
@@ -8,16 +8,20 @@ use {
         response::IntoResponse,
     },
     prometheus_client::{
-        encoding::EncodeLabelSet,
+        encoding::{EncodeLabelSet, EncodeLabelValue},
         metrics::{counter::Counter, family::Family, histogram::Histogram},
     },
-    std::sync::Arc,
+    std::{
+        sync::Arc,
+        time::{SystemTime, UNIX_EPOCH},
+    },
     tokio::time::Instant,
 };
 
 pub struct ApiMetrics {
     pub requests: Family<Labels, Counter>,
     pub latencies: Family<Labels, Histogram>,
+    pub delivery_latencies: Family<DeliveryLabels, Histogram>,
 }
 
 impl ApiMetrics {
@@ -36,11 +40,15 @@ impl ApiMetrics {
                     .into_iter(),
                 )
             }),
+            delivery_latencies: Family::new_with_constructor(|| {
+                Histogram::new([1.0, 2.0, 3.0, 4.0, 5.0, 10.0, 20.0, 30.0, 60.0].into_iter())
+            }),
         };
 
         {
             let requests = new.requests.clone();
             let latencies = new.latencies.clone();
+            let delivery_latencies = new.delivery_latencies.clone();
 
             tokio::spawn(async move {
                 Metrics::register(
@@ -58,11 +66,46 @@ impl ApiMetrics {
                     ),
                 )
                 .await;
+
+                Metrics::register(
+                    &*state,
+                    (
+                        "api_price_update_delivery_latency_seconds",
+                        "Latency between the publish time of a price update and its delivery to a client in seconds",
+                        delivery_latencies,
+                    ),
+                )
+                .await;
             });
         }
 
         new
     }
+
+    /// Observe the delivery of a price update to a client. The publish time has a resolution of a
+    /// second, so the latency is only accurate to a second and the buckets are whole seconds.
+    /// Deliveries are only labelled by channel to keep the number of series bounded.
+    pub fn observe_delivery(&self, channel: DeliveryChannel, publish_time: UnixTimestamp) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+
+        self.delivery_latencies
+            .get_or_create(&DeliveryLabels { channel })
+            .observe((now - publish_time as f64).max(0.0));
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, EncodeLabelValue)]
+pub enum DeliveryChannel {
+    Ws,
+    Sse,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub struct DeliveryLabels {
+    pub channel: DeliveryChannel,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, EncodeLabelSet)]
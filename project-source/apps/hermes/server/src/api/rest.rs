@@ -1,11 +1,22 @@
 use {
-    super::ApiState,
-    crate::state::aggregate::Aggregates,
+    super::{
+        types::{ParsedPriceUpdate, TargetChain},
+        ApiState,
+    },
+    crate::state::{
+        aggregate::{
+            wormhole_merkle::shape_update_data, Aggregates, PriceFeedUpdate, UnixTimestamp,
+        },
+        price_feeds_metadata::PriceFeedMeta,
+        wormhole::Wormhole,
+    },
     axum::{
         http::StatusCode,
         response::{IntoResponse, Response},
     },
+    chrono::DateTime,
     pyth_sdk::PriceIdentifier,
+    std::collections::HashSet,
 };
 
 mod get_price_feed;
@@ -31,7 +42,8 @@ pub use {
     ready::*,
     v2::{
         latest_price_updates::*, latest_publisher_stake_caps::*, latest_twaps::*,
-        price_feeds_metadata::*, sse::*, timestamp_price_updates::*,
+        price_feeds_metadata::*, publisher_stake_caps_diff::*, sse::*, timestamp_price_updates::*,
+        timestamp_publisher_stake_caps::*,
     },
 };
 
@@ -42,7 +54,9 @@ pub enum RestError {
     CcipUpdateDataNotFound,
     InvalidCCIPInput,
     PriceIdsNotFound { missing_ids: Vec<PriceIdentifier> },
+    DerivedFeedsNotFound { missing_names: Vec<String> },
     RpcConnectionError { message: String },
+    UpdateDataShapingError,
 }
 
 impl IntoResponse for RestError {
@@ -80,9 +94,19 @@ impl IntoResponse for RestError {
                 )
                     .into_response()
             }
+            RestError::DerivedFeedsNotFound { missing_names } => (
+                StatusCode::NOT_FOUND,
+                format!("Derived feeds not found: {}", missing_names.join(", ")),
+            )
+                .into_response(),
             RestError::RpcConnectionError { message } => {
                 (StatusCode::INTERNAL_SERVER_ERROR, message).into_response()
             }
+            RestError::UpdateDataShapingError => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to shape the update data for the target chain",
+            )
+                .into_response(),
         }
     }
 }
@@ -118,6 +142,101 @@ where
         })
     }
 }
+
+/// Shape the update data for the target chain. The update data is returned as is without a target.
+pub async fn shape_update_data_for_target<S>(
+    state: &ApiState<S>,
+    update_data: Vec<Vec<u8>>,
+    target: Option<TargetChain>,
+) -> Result<Vec<Vec<u8>>, RestError>
+where
+    S: Wormhole,
+{
+    let Some(target) = target else {
+        return Ok(update_data);
+    };
+
+    let guardian_set_quorums = Wormhole::get_guardian_set_quorums(&*state.state).await;
+    shape_update_data(update_data, &target.update_data_shape(), |index| {
+        guardian_set_quorums.get(&index).copied()
+    })
+    .map_err(|e| {
+        tracing::warn!("Error shaping update data for {:?}: {:?}", target, e);
+        RestError::UpdateDataShapingError
+    })
+}
+
+/// Get the ids of the price feeds whose latest price is stale relative to their market schedule
+/// at `at`. Price feeds without a market schedule are never stale.
+pub async fn get_stale_price_ids<S>(
+    state: &ApiState<S>,
+    price_ids: &[PriceIdentifier],
+    at: UnixTimestamp,
+) -> Result<HashSet<PriceIdentifier>, RestError>
+where
+    S: Aggregates,
+{
+    let market_schedules = PriceFeedMeta::get_market_schedules(&*state.state, price_ids).await;
+    let Some(at) = DateTime::from_timestamp(at, 0) else {
+        return Ok(HashSet::new());
+    };
+
+    let scheduled_price_ids: Vec<PriceIdentifier> = market_schedules.keys().copied().collect();
+    let publish_times = Aggregates::get_latest_publish_times(&*state.state, &scheduled_price_ids)
+        .await
+        .map_err(|e| {
+            tracing::warn!(
+                "Error getting the latest publish times of {:?}: {:?}",
+                scheduled_price_ids,
+                e
+            );
+            RestError::UpdateDataNotFound
+        })?;
+
+    Ok(market_schedules
+        .iter()
+        .filter(|(price_id, market_schedule)| {
+            publish_times
+                .get(*price_id)
+                .and_then(|publish_time| DateTime::from_timestamp(*publish_time, 0))
+                .map_or(false, |publish_time| {
+                    !market_schedule.is_fresh(publish_time, at)
+                })
+        })
+        .map(|(price_id, _)| *price_id)
+        .collect())
+}
+
+/// Parse the price feed updates, annotating each of them with the status of its market at `at`
+/// if the price feed has a market schedule.
+pub async fn parse_price_feed_updates<S>(
+    state: &ApiState<S>,
+    price_feeds: Vec<PriceFeedUpdate>,
+    at: UnixTimestamp,
+) -> Vec<ParsedPriceUpdate>
+where
+    S: PriceFeedMeta,
+{
+    let price_ids: Vec<PriceIdentifier> = price_feeds
+        .iter()
+        .map(|price_feed| price_feed.price_feed.id)
+        .collect();
+    let market_schedules = PriceFeedMeta::get_market_schedules(&*state.state, &price_ids).await;
+
+    price_feeds
+        .into_iter()
+        .map(|price_feed| {
+            let market_schedule = market_schedules.get(&price_feed.price_feed.id);
+            let parsed_price_update = ParsedPriceUpdate::from(price_feed);
+            match market_schedule {
+                Some(market_schedule) => {
+                    parsed_price_update.with_market_schedule(market_schedule, at)
+                }
+                None => parsed_price_update,
+            }
+        })
+        .collect()
+}
 #[cfg(test)]
 #[allow(clippy::unwrap_used, reason = "tests")]
 mod tests {
@@ -134,7 +253,10 @@ mod tests {
             price_feeds_metadata::PriceFeedMetaState,
         },
         anyhow::Result,
-        std::{collections::HashSet, sync::Arc},
+        std::{
+            collections::{HashMap, HashSet},
+            sync::Arc,
+        },
         tokio::sync::broadcast::Receiver,
     };
 
@@ -186,6 +308,13 @@ mod tests {
             unimplemented!("Not needed for this test")
         }
 
+        async fn get_latest_publish_times(
+            &self,
+            _price_ids: &[PriceIdentifier],
+        ) -> Result<HashMap<PriceIdentifier, UnixTimestamp>> {
+            unimplemented!("Not needed for this test")
+        }
+
         async fn get_price_feeds_with_update_data(
             &self,
             _price_ids: &[PriceIdentifier],
@@ -194,8 +323,9 @@ mod tests {
             unimplemented!("Not needed for this test")
         }
 
-        async fn get_latest_publisher_stake_caps_with_update_data(
+        async fn get_publisher_stake_caps_with_update_data(
             &self,
+            _request_time: RequestTime,
         ) -> Result<PublisherStakeCapsWithUpdateData> {
             unimplemented!("Not needed for this test")
         }
@@ -219,7 +349,7 @@ mod tests {
         available_ids.insert(id2);
 
         let mock_state = MockAggregates { available_ids };
-        let api_state = ApiState::new(Arc::new(mock_state), vec![], String::new());
+        let api_state = ApiState::new(Arc::new(mock_state), vec![], String::new(), vec![]);
 
         let input_ids = vec![id1, id2];
         let result = validate_price_ids(&api_state, &input_ids, false).await;
@@ -238,7 +368,7 @@ mod tests {
         available_ids.insert(id2);
 
         let mock_state = MockAggregates { available_ids };
-        let api_state = ApiState::new(Arc::new(mock_state), vec![], String::new());
+        let api_state = ApiState::new(Arc::new(mock_state), vec![], String::new(), vec![]);
 
         let input_ids = vec![id1, id2, id3];
         let result = validate_price_ids(&api_state, &input_ids, true).await;
@@ -257,7 +387,7 @@ mod tests {
         available_ids.insert(id2);
 
         let mock_state = MockAggregates { available_ids };
-        let api_state = ApiState::new(Arc::new(mock_state), vec![], String::new());
+        let api_state = ApiState::new(Arc::new(mock_state), vec![], String::new(), vec![]);
 
         let input_ids = vec![id1, id2, id3];
         let result = validate_price_ids(&api_state, &input_ids, false).await;
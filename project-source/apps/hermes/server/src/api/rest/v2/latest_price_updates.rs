@@ -1,15 +1,25 @@
 use {
     crate::{
         api::{
-            rest::{validate_price_ids, RestError},
-            types::{BinaryUpdate, EncodingType, ParsedPriceUpdate, PriceIdInput, PriceUpdate},
+            rest::{
+                get_stale_price_ids, parse_price_feed_updates, shape_update_data_for_target,
+                validate_price_ids, RestError,
+            },
+            types::{
+                BinaryUpdate, EncodingType, ParsedDerivedPriceUpdate, ParsedPriceUpdate,
+                PriceIdInput, PriceUpdate, TargetChain,
+            },
             ApiState,
         },
-        state::aggregate::{Aggregates, RequestTime},
+        state::{
+            aggregate::{Aggregates, RequestTime},
+            wormhole::Wormhole,
+        },
     },
     anyhow::Result,
     axum::{extract::State, Json},
     base64::{engine::general_purpose::STANDARD as base64_standard_engine, Engine as _},
+    chrono::Utc,
     pyth_sdk::PriceIdentifier,
     serde::Deserialize,
     serde_qs::axum::QsQuery,
@@ -42,6 +52,36 @@ pub struct LatestPriceUpdatesQueryParams {
     /// If true, invalid price IDs in the `ids` parameter are ignored. Only applicable to the v2 APIs. Default is `false`.
     #[serde(default)]
     ignore_invalid_price_ids: bool,
+
+    /// If true, price updates that are stale relative to their market schedule, because the
+    /// market is closed or has closed since the price was published, are excluded from both the
+    /// binary and parsed updates. Price feeds without a market schedule are never excluded.
+    /// Default is `false`.
+    #[serde(default)]
+    only_fresh: bool,
+
+    /// Get the derived price feeds with these names, computed from the latest updates of their
+    /// source feeds. The source feed updates are included in the binary update data so the
+    /// derived prices can be recomputed on-chain. Derived feeds whose source feeds are excluded
+    /// by `only_fresh` are left out.
+    ///
+    /// This parameter can be provided multiple times to retrieve multiple derived feeds,
+    /// for example see the following query string:
+    ///
+    /// ```
+    /// ?ids[]=a12...&derived[]=ETHBTC&derived[]=USDJPY
+    /// ```
+    #[serde(default)]
+    #[param(rename = "derived[]")]
+    derived: Vec<String>,
+
+    /// Optional chain the update data is submitted to. If set, the update data is shaped for the
+    /// Pyth contract of the chain: a single blob per slot with a quorum of guardian signatures for
+    /// `evm`, `sui`, `aptos`, `near` and `cosmwasm`, and a blob per price update with the 5
+    /// guardian signatures verified by `post_update_atomic` for `solana`. By default the update
+    /// data is returned as aggregated.
+    #[serde(default)]
+    target: Option<TargetChain>,
 }
 
 fn default_true() -> bool {
@@ -56,7 +96,7 @@ fn default_true() -> bool {
     path = "/v2/updates/price/latest",
     responses(
         (status = 200, description = "Price updates retrieved successfully", body = PriceUpdate),
-        (status = 404, description = "Price ids not found", body = String)
+        (status = 404, description = "Price ids or derived feeds not found", body = String)
     ),
     params(
         LatestPriceUpdatesQueryParams
@@ -68,52 +108,98 @@ pub async fn latest_price_updates<S>(
 ) -> Result<Json<PriceUpdate>, RestError>
 where
     S: Aggregates,
+    S: Wormhole,
 {
     let price_id_inputs: Vec<PriceIdentifier> =
         params.ids.into_iter().map(|id| id.into()).collect();
-    let price_ids: Vec<PriceIdentifier> =
+    let mut price_ids: Vec<PriceIdentifier> =
         validate_price_ids(&state, &price_id_inputs, params.ignore_invalid_price_ids).await?;
 
-    let state = &*state.state;
-    let price_feeds_with_update_data =
-        Aggregates::get_price_feeds_with_update_data(state, &price_ids, RequestTime::Latest)
-            .await
-            .map_err(|e| {
-                tracing::warn!(
-                    "Error getting price feeds {:?} with update data: {:?}",
-                    price_ids,
-                    e
-                );
-                RestError::UpdateDataNotFound
-            })?;
-
-    let price_update_data = price_feeds_with_update_data.update_data;
-    let encoded_data: Vec<String> = price_update_data
-        .into_iter()
-        .map(|data| match params.encoding {
-            EncodingType::Base64 => base64_standard_engine.encode(data),
-            EncodingType::Hex => hex::encode(data),
-        })
-        .collect();
+    let mut derived_feeds = vec![];
+    let mut missing_names = vec![];
+    for name in &params.derived {
+        match state.derived_feeds.get(name) {
+            Some(derived_feed) => derived_feeds.push(derived_feed),
+            None => missing_names.push(name.clone()),
+        }
+    }
+    if !missing_names.is_empty() {
+        return Err(RestError::DerivedFeedsNotFound { missing_names });
+    }
+
+    // The source feeds of the derived feeds are always required, regardless of
+    // `ignore_invalid_price_ids`, as the derived prices cannot be computed without them.
+    let mut source_ids: Vec<PriceIdentifier> = vec![];
+    for source_id in derived_feeds
+        .iter()
+        .flat_map(|derived_feed| derived_feed.source_ids())
+    {
+        if !price_ids.contains(&source_id) && !source_ids.contains(&source_id) {
+            source_ids.push(source_id);
+        }
+    }
+    price_ids.extend(validate_price_ids(&state, &source_ids, false).await?);
+
+    let now = Utc::now().timestamp();
+    if params.only_fresh {
+        // Leave the stale price feeds out before building the update data so that it is only
+        // built once. A price that is fresh now stays fresh if a newer one lands in the meantime.
+        let stale_price_ids = get_stale_price_ids(&state, &price_ids, now).await?;
+        price_ids.retain(|price_id| !stale_price_ids.contains(price_id));
+    }
+
+    let (parsed_price_updates, price_update_data) = if price_ids.is_empty() {
+        (vec![], vec![])
+    } else {
+        let price_feeds_with_update_data = Aggregates::get_price_feeds_with_update_data(
+            &*state.state,
+            &price_ids,
+            RequestTime::Latest,
+        )
+        .await
+        .map_err(|e| {
+            tracing::warn!(
+                "Error getting price feeds {:?} with update data: {:?}",
+                price_ids,
+                e
+            );
+            RestError::UpdateDataNotFound
+        })?;
+        (
+            parse_price_feed_updates(&state, price_feeds_with_update_data.price_feeds, now).await,
+            price_feeds_with_update_data.update_data,
+        )
+    };
+
+    let encoded_data: Vec<String> =
+        shape_update_data_for_target(&state, price_update_data, params.target)
+            .await?
+            .into_iter()
+            .map(|data| match params.encoding {
+                EncodingType::Base64 => base64_standard_engine.encode(data),
+                EncodingType::Hex => hex::encode(data),
+            })
+            .collect();
     let binary_price_update = BinaryUpdate {
         encoding: params.encoding,
         data: encoded_data,
     };
-    let parsed_price_updates: Option<Vec<ParsedPriceUpdate>> = if params.parsed {
-        Some(
-            price_feeds_with_update_data
-                .price_feeds
-                .into_iter()
-                .map(|price_feed| price_feed.into())
-                .collect(),
-        )
-    } else {
-        None
-    };
+    let derived_price_updates: Option<Vec<ParsedDerivedPriceUpdate>> = (!derived_feeds.is_empty())
+        .then(|| {
+            derived_feeds
+                .iter()
+                .filter_map(|derived_feed| {
+                    ParsedDerivedPriceUpdate::compute(derived_feed, &parsed_price_updates)
+                })
+                .collect()
+        });
+    let parsed_price_updates: Option<Vec<ParsedPriceUpdate>> =
+        params.parsed.then_some(parsed_price_updates);
 
     let compressed_price_update = PriceUpdate {
         binary: binary_price_update,
         parsed: parsed_price_updates,
+        derived: derived_price_updates,
     };
 
     Ok(Json(compressed_price_update))
@@ -8,7 +8,7 @@ use {
             },
             ApiState,
         },
-        state::Aggregates,
+        state::{aggregate::RequestTime, Aggregates},
     },
     anyhow::Result,
     axum::{extract::State, Json},
@@ -30,6 +30,19 @@ pub struct LatestPublisherStakeCapsUpdateData {
     /// If true, include the parsed update in the `parsed` field of each returned feed. Default is `true`.
     #[serde(default = "default_true")]
     parsed: bool,
+
+    /// Only include the stake caps of these publishers in the parsed update. The binary update
+    /// always contains the stake caps of all the publishers.
+    ///
+    /// This parameter can be provided multiple times to filter by multiple publishers,
+    /// for example see the following query string:
+    ///
+    /// ```
+    /// ?publishers[]=4Z1...&publishers[]=9vK...
+    /// ```
+    #[serde(default)]
+    #[param(rename = "publishers[]")]
+    publishers: Vec<String>,
 }
 
 fn default_true() -> bool {
@@ -56,7 +69,7 @@ where
 {
     let state = &*state.state;
     let publisher_stake_caps_with_update_data =
-        Aggregates::get_latest_publisher_stake_caps_with_update_data(state)
+        Aggregates::get_publisher_stake_caps_with_update_data(state, RequestTime::Latest)
             .await
             .map_err(|e| {
                 tracing::warn!(
@@ -81,7 +94,11 @@ where
     };
 
     let parsed: Option<Vec<ParsedPublisherStakeCapsUpdate>> = if params.parsed {
-        Some(publisher_stake_caps_with_update_data.publisher_stake_caps)
+        let mut publisher_stake_caps = publisher_stake_caps_with_update_data.publisher_stake_caps;
+        for update in &mut publisher_stake_caps {
+            update.retain_publishers(&params.publishers);
+        }
+        Some(publisher_stake_caps)
     } else {
         None
     };
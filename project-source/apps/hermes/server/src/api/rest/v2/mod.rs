@@ -2,5 +2,7 @@ pub mod latest_price_updates;
 pub mod latest_publisher_stake_caps;
 pub mod latest_twaps;
 pub mod price_feeds_metadata;
+pub mod publisher_stake_caps_diff;
 pub mod sse;
 pub mod timestamp_price_updates;
+pub mod timestamp_publisher_stake_caps;
@@ -2,18 +2,37 @@ use {
     crate::{
         api::{
             rest::RestError,
-            types::{AssetType, PriceFeedMetadata},
+            types::{AssetType, PriceFeedMetadata, PriceFeedMetadataChange},
             ApiState,
         },
-        state::price_feeds_metadata::PriceFeedMeta,
+        state::price_feeds_metadata::{
+            PriceFeedMeta, PriceFeedsMetadataFilter, PriceFeedsMetadataVersion,
+        },
     },
     anyhow::Result,
-    axum::{extract::State, Json},
+    axum::{
+        extract::State,
+        http::{header, HeaderMap, HeaderValue, StatusCode},
+        response::{
+            sse::{Event, KeepAlive, Sse},
+            IntoResponse, Response,
+        },
+        Json,
+    },
+    futures::Stream,
     serde::Deserialize,
     serde_qs::axum::QsQuery,
+    std::{collections::BTreeMap, convert::Infallible, time::Duration},
+    tokio::time::Instant,
+    tokio_stream::{wrappers::BroadcastStream, StreamExt as _},
     utoipa::IntoParams,
 };
 
+const MAX_CONNECTION_DURATION: Duration = Duration::from_secs(24 * 60 * 60); // 24 hours
+
+/// Header carrying the number of price feeds matching the filters, before pagination.
+const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
 #[derive(Debug, Deserialize, IntoParams)]
 #[into_params(parameter_in=Query)]
 pub struct PriceFeedsMetadataQueryParams {
@@ -24,17 +43,46 @@ pub struct PriceFeedsMetadataQueryParams {
     /// Optional query parameter. If provided, the results will be filtered by asset type. Possible values are crypto, equity, fx, metal, rates. Filter string is case insensitive.
     #[param(example = "crypto")]
     asset_type: Option<AssetType>,
+
+    /// Optional query parameter. If provided, the results will be filtered to the price feeds
+    /// whose attributes are equal to all the given values. Values are case insensitive.
+    ///
+    /// For example, the following query string returns the price feeds quoted in USD with BTC
+    /// as the base asset:
+    ///
+    /// ```
+    /// ?attributes[base]=btc&attributes[quote_currency]=usd
+    /// ```
+    #[serde(default)]
+    #[param(value_type = Option<Object>, style = DeepObject, explode)]
+    attributes: BTreeMap<String, String>,
+
+    /// Optional query parameter. The number of matching price feeds to skip. Price feeds are
+    /// sorted by id. Default is `0`.
+    #[param(example = 0)]
+    offset: Option<usize>,
+
+    /// Optional query parameter. The maximum number of price feeds to return. All the matching
+    /// price feeds are returned if it is not provided.
+    #[param(example = 100)]
+    limit: Option<usize>,
 }
 
 /// Get the set of price feeds.
 ///
-/// This endpoint fetches all price feeds from the Pyth network. It can be filtered by asset type
-/// and query string.
+/// This endpoint fetches all price feeds from the Pyth network. It can be filtered by asset type,
+/// query string and attributes, and paginated with `offset` and `limit`. The total number of
+/// matching price feeds is returned in the `X-Total-Count` header.
+///
+/// Responses carry `ETag` and `Last-Modified` headers identifying the current version of the
+/// price feeds metadata. A request with a matching `If-None-Match` header receives a
+/// `304 Not Modified` response.
 #[utoipa::path(
     get,
     path = "/v2/price_feeds",
     responses(
-        (status = 200, description = "Price feeds metadata retrieved successfully", body = Vec<PriceFeedMetadata>)
+        (status = 200, description = "Price feeds metadata retrieved successfully", body = Vec<PriceFeedMetadata>),
+        (status = 304, description = "Price feeds metadata has not changed since the version in If-None-Match")
     ),
     params(
         PriceFeedsMetadataQueryParams
@@ -42,21 +90,117 @@ pub struct PriceFeedsMetadataQueryParams {
 )]
 pub async fn price_feeds_metadata<S>(
     State(state): State<ApiState<S>>,
+    headers: HeaderMap,
     QsQuery(params): QsQuery<PriceFeedsMetadataQueryParams>,
-) -> Result<Json<Vec<PriceFeedMetadata>>, RestError>
+) -> Result<Response, RestError>
 where
     S: PriceFeedMeta,
 {
     let state = &state.state;
-    let price_feeds_metadata = state
-        .get_price_feeds_metadata(params.query, params.asset_type)
-        .await
-        .map_err(|e| {
-            tracing::warn!("RPC connection error: {}", e);
-            RestError::RpcConnectionError {
-                message: format!("RPC connection error: {}", e),
-            }
-        })?;
-
-    Ok(Json(price_feeds_metadata))
+    let version = state.get_price_feeds_metadata_version().await;
+
+    if is_not_modified(&headers, &version) {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        insert_version_headers(response.headers_mut(), &version);
+        return Ok(response);
+    }
+
+    let filter = PriceFeedsMetadataFilter {
+        query: params.query,
+        asset_type: params.asset_type,
+        attributes: params.attributes,
+    };
+    let price_feeds_metadata = state.get_price_feeds_metadata(&filter).await.map_err(|e| {
+        tracing::warn!("RPC connection error: {}", e);
+        RestError::RpcConnectionError {
+            message: format!("RPC connection error: {}", e),
+        }
+    })?;
+
+    let total_count = price_feeds_metadata.len();
+    let price_feeds_metadata: Vec<PriceFeedMetadata> = price_feeds_metadata
+        .into_iter()
+        .skip(params.offset.unwrap_or(0))
+        .take(params.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    let mut response = Json(price_feeds_metadata).into_response();
+    insert_version_headers(response.headers_mut(), &version);
+    response
+        .headers_mut()
+        .insert(TOTAL_COUNT_HEADER, HeaderValue::from(total_count));
+    Ok(response)
+}
+
+fn is_not_modified(headers: &HeaderMap, version: &PriceFeedsMetadataVersion) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .any(|etag| etag == "*" || etag.trim_start_matches("W/") == version.etag)
+        })
+}
+
+fn insert_version_headers(headers: &mut HeaderMap, version: &PriceFeedsMetadataVersion) {
+    if let Ok(etag) = HeaderValue::from_str(&version.etag) {
+        headers.insert(header::ETAG, etag);
+    }
+    if let Some(last_modified) = version.last_modified {
+        if let Ok(last_modified) = HeaderValue::from_str(
+            &last_modified
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string(),
+        ) {
+            headers.insert(header::LAST_MODIFIED, last_modified);
+        }
+    }
+}
+
+/// SSE route handler for streaming changes to the set of price feeds.
+///
+/// An event is sent whenever a price feed is listed, delisted or has its attributes changed.
+/// Clients should fetch `/v2/price_feeds` once and then apply the changes from this stream.
+///
+/// The connection will automatically close after 24 hours to prevent resource leaks.
+#[utoipa::path(
+    get,
+    path = "/v2/price_feeds/stream",
+    responses(
+        (status = 200, description = "Price feeds metadata changes streamed successfully", body = PriceFeedMetadataChange)
+    )
+)]
+pub async fn price_feeds_metadata_stream_sse_handler<S>(
+    State(state): State<ApiState<S>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>>
+where
+    S: PriceFeedMeta,
+    S: Send + Sync + 'static,
+{
+    let changes_rx = state.state.subscribe_price_feeds_metadata_changes();
+    let start_time = Instant::now();
+
+    let sse_stream = BroadcastStream::new(changes_rx)
+        .take_while(move |_| start_time.elapsed() < MAX_CONNECTION_DURATION)
+        .map(|message| match message {
+            Ok(change) => Ok(Event::default()
+                .json_data(change)
+                .unwrap_or_else(error_event)),
+            Err(e) => Ok(error_event(e)),
+        })
+        .chain(futures::stream::once(async {
+            Ok(Event::default()
+                .event("error")
+                .data("Connection timeout reached (24h)"))
+        }));
+
+    Sse::new(sse_stream).keep_alive(KeepAlive::default())
+}
+
+fn error_event<E: std::fmt::Debug>(e: E) -> Event {
+    Event::default()
+        .event("error")
+        .data(format!("Error receiving change: {:?}", e))
 }
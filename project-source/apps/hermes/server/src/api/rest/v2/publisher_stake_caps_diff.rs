@@ -0,0 +1,107 @@
+use {
+    crate::{
+        api::{
+            doc_examples,
+            rest::RestError,
+            types::{ParsedPublisherStakeCapsUpdate, PublisherStakeCapsDiffResponse},
+            ApiState,
+        },
+        state::aggregate::{Aggregates, RequestTime, UnixTimestamp},
+    },
+    anyhow::Result,
+    axum::{extract::State, Json},
+    serde::Deserialize,
+    serde_qs::axum::QsQuery,
+    utoipa::IntoParams,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in=Query)]
+pub struct PublisherStakeCapsDiffQueryParams {
+    /// The unix timestamp in seconds to compute the changes from. The first update whose
+    /// publish_time is >= the provided value is used.
+    #[param(value_type = i64)]
+    #[param(example = doc_examples::timestamp_example)]
+    from: UnixTimestamp,
+
+    /// The unix timestamp in seconds to compute the changes to. The first update whose
+    /// publish_time is >= the provided value is used. Default is the latest update.
+    #[param(value_type = Option<i64>)]
+    #[param(example = doc_examples::timestamp_example)]
+    to: Option<UnixTimestamp>,
+
+    /// Only include the stake cap changes of these publishers.
+    ///
+    /// This parameter can be provided multiple times to filter by multiple publishers,
+    /// for example see the following query string:
+    ///
+    /// ```
+    /// ?publishers[]=4Z1...&publishers[]=9vK...
+    /// ```
+    #[serde(default)]
+    #[param(rename = "publishers[]")]
+    publishers: Vec<String>,
+}
+
+/// Get the publisher stake cap changes between two times.
+///
+/// Publisher stake caps are served from the recent updates cached by Hermes, so only recent
+/// timestamps can be queried.
+#[utoipa::path(
+    get,
+    path = "/v2/updates/publisher_stake_caps/diff",
+    responses(
+        (status = 200, description = "Publisher stake cap changes retrieved successfully", body = PublisherStakeCapsDiffResponse),
+        (status = 404, description = "Update data not found", body = String)
+    ),
+    params(
+        PublisherStakeCapsDiffQueryParams
+    )
+)]
+pub async fn publisher_stake_caps_diff<S>(
+    State(state): State<ApiState<S>>,
+    QsQuery(params): QsQuery<PublisherStakeCapsDiffQueryParams>,
+) -> Result<Json<PublisherStakeCapsDiffResponse>, RestError>
+where
+    S: Aggregates,
+{
+    let state = &*state.state;
+    let from = get_publisher_stake_caps(state, RequestTime::FirstAfter(params.from)).await?;
+    let to = get_publisher_stake_caps(
+        state,
+        params
+            .to
+            .map_or(RequestTime::Latest, RequestTime::FirstAfter),
+    )
+    .await?;
+
+    let mut diff = PublisherStakeCapsDiffResponse::new(&from, &to);
+    if !params.publishers.is_empty() {
+        diff.changes
+            .retain(|change| params.publishers.contains(&change.publisher));
+    }
+
+    Ok(Json(diff))
+}
+
+async fn get_publisher_stake_caps<S>(
+    state: &S,
+    request_time: RequestTime,
+) -> Result<ParsedPublisherStakeCapsUpdate, RestError>
+where
+    S: Aggregates,
+{
+    Aggregates::get_publisher_stake_caps_with_update_data(state, request_time)
+        .await
+        .map_err(|e| {
+            tracing::warn!(
+                "Error getting publisher stake caps with update data: {:?}",
+                e
+            );
+            RestError::UpdateDataNotFound
+        })?
+        .publisher_stake_caps
+        .into_iter()
+        .next()
+        .ok_or(RestError::UpdateDataNotFound)
+}
@@ -1,20 +1,28 @@
 use {
     crate::{
         api::{
-            rest::{validate_price_ids, RestError},
+            metrics_middleware::DeliveryChannel,
+            rest::{
+                parse_price_feed_updates, shape_update_data_for_target, validate_price_ids,
+                RestError,
+            },
             types::{
                 BinaryUpdate, EncodingType, ParsedPriceUpdate, PriceIdInput, PriceUpdate,
-                RpcPriceIdentifier,
+                RpcPriceIdentifier, TargetChain,
             },
             ApiState,
         },
-        state::aggregate::{Aggregates, AggregationEvent, RequestTime},
+        state::{
+            aggregate::{Aggregates, AggregationEvent, RequestTime},
+            wormhole::Wormhole,
+        },
     },
-    anyhow::Result,
+    anyhow::{anyhow, Result},
     axum::{
         extract::State,
         response::sse::{Event, KeepAlive, Sse},
     },
+    chrono::Utc,
     futures::Stream,
     pyth_sdk::PriceIdentifier,
     serde::Deserialize,
@@ -22,6 +30,8 @@ use {
     std::{convert::Infallible, time::Duration},
     tokio::{sync::broadcast, time::Instant},
     tokio_stream::{wrappers::BroadcastStream, StreamExt as _},
+    tracing::Instrument,
+    tracing_opentelemetry::OpenTelemetrySpanExt,
     utoipa::IntoParams,
 };
 
@@ -59,9 +69,21 @@ pub struct StreamPriceUpdatesQueryParams {
     #[serde(default)]
     benchmarks_only: bool,
 
+    /// If true, price updates that are stale relative to their market schedule, because the
+    /// market is closed or has closed since the price was published, are excluded from the
+    /// stream. Price feeds without a market schedule are never excluded. Default is `false`.
+    #[serde(default)]
+    only_fresh: bool,
+
     /// If true, invalid price IDs in the `ids` parameter are ignored. Only applicable to the v2 APIs. Default is `false`.
     #[serde(default)]
     ignore_invalid_price_ids: bool,
+
+    /// Optional chain the update data is submitted to. If set, the update data of each event is
+    /// shaped for the Pyth contract of the chain, as for `/v2/updates/price/latest`. By default the
+    /// update data is streamed as aggregated.
+    #[serde(default)]
+    target: Option<TargetChain>,
 }
 
 fn default_true() -> bool {
@@ -87,6 +109,7 @@ pub async fn price_stream_sse_handler<S>(
 ) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, RestError>
 where
     S: Aggregates,
+    S: Wormhole,
     S: Send + Sync + 'static,
 {
     let price_id_inputs: Vec<PriceIdentifier> = params.ids.into_iter().map(Into::into).collect();
@@ -110,6 +133,9 @@ where
             async move {
                 match message {
                     Ok(event) => {
+                        let span =
+                            tracing::info_span!("deliver_price_updates", slot = event.slot());
+                        span.set_parent(event.trace_context().0.clone());
                         match handle_aggregation_event(
                             event,
                             state_clone,
@@ -117,8 +143,11 @@ where
                             params.encoding,
                             params.parsed,
                             params.benchmarks_only,
+                            params.only_fresh,
                             params.allow_unordered,
+                            params.target,
                         )
+                        .instrument(span)
                         .await
                         {
                             Ok(Some(update)) => Some(Ok(Event::default()
@@ -142,6 +171,10 @@ where
     Ok(Sse::new(sse_stream).keep_alive(KeepAlive::default()))
 }
 
+#[allow(
+    clippy::too_many_arguments,
+    reason = "the arguments are the stream query parameters"
+)]
 async fn handle_aggregation_event<S>(
     event: AggregationEvent,
     state: ApiState<S>,
@@ -149,10 +182,13 @@ async fn handle_aggregation_event<S>(
     encoding: EncodingType,
     parsed: bool,
     benchmarks_only: bool,
+    only_fresh: bool,
     allow_unordered: bool,
+    target: Option<TargetChain>,
 ) -> Result<Option<PriceUpdate>>
 where
     S: Aggregates,
+    S: Wormhole,
 {
     // Handle out-of-order events
     if let AggregationEvent::OutOfOrder { .. } = event {
@@ -173,11 +209,17 @@ where
     )
     .await?;
 
-    let mut parsed_price_updates: Vec<ParsedPriceUpdate> = price_feeds_with_update_data
-        .price_feeds
-        .into_iter()
-        .map(|price_feed| price_feed.into())
-        .collect();
+    let mut parsed_price_updates: Vec<ParsedPriceUpdate> = parse_price_feed_updates(
+        &state,
+        price_feeds_with_update_data.price_feeds,
+        Utc::now().timestamp(),
+    )
+    .await;
+
+    if only_fresh {
+        // Remove the price updates that are stale relative to their market schedule
+        parsed_price_updates.retain(|price_feed| !price_feed.is_stale());
+    }
 
     if benchmarks_only {
         // Remove those with metadata.prev_publish_time != price.publish_time from parsed_price_updates
@@ -189,6 +231,9 @@ where
                     prev_time != price_feed.price.publish_time
                 })
         });
+    }
+
+    if benchmarks_only || only_fresh {
         // Retain price id in price_ids that are in parsed_price_updates
         price_ids.retain(|price_id| {
             parsed_price_updates
@@ -208,7 +253,16 @@ where
         return Ok(None);
     }
 
-    let price_update_data = price_feeds_with_update_data.update_data;
+    for price_update in &parsed_price_updates {
+        state
+            .metrics
+            .observe_delivery(DeliveryChannel::Sse, price_update.price.publish_time);
+    }
+
+    let price_update_data =
+        shape_update_data_for_target(&state, price_feeds_with_update_data.update_data, target)
+            .await
+            .map_err(|e| anyhow!("Failed to shape the update data: {:?}", e))?;
     let encoded_data: Vec<String> = price_update_data
         .into_iter()
         .map(|data| encoding.encode_str(&data))
@@ -225,6 +279,7 @@ where
         } else {
             None
         },
+        derived: None,
     }))
 }
 
@@ -2,11 +2,20 @@ use {
     crate::{
         api::{
             doc_examples,
-            rest::{validate_price_ids, RestError},
-            types::{BinaryUpdate, EncodingType, ParsedPriceUpdate, PriceIdInput, PriceUpdate},
+            rest::{
+                parse_price_feed_updates, shape_update_data_for_target, validate_price_ids,
+                RestError,
+            },
+            types::{
+                BinaryUpdate, EncodingType, ParsedPriceUpdate, PriceIdInput, PriceUpdate,
+                TargetChain,
+            },
             ApiState,
         },
-        state::aggregate::{Aggregates, RequestTime, UnixTimestamp},
+        state::{
+            aggregate::{Aggregates, RequestTime, UnixTimestamp},
+            wormhole::Wormhole,
+        },
     },
     anyhow::Result,
     axum::{
@@ -55,6 +64,14 @@ pub struct TimestampPriceUpdatesQueryParams {
     /// If true, invalid price IDs in the `ids` parameter are ignored. Only applicable to the v2 APIs. Default is `false`.
     #[serde(default)]
     ignore_invalid_price_ids: bool,
+
+    /// Optional chain the update data is submitted to. If set, the update data is shaped for the
+    /// Pyth contract of the chain: a single blob per slot with a quorum of guardian signatures for
+    /// `evm`, `sui`, `aptos`, `near` and `cosmwasm`, and a blob per price update with the 5
+    /// guardian signatures verified by `post_update_atomic` for `solana`. By default the update
+    /// data is returned as aggregated.
+    #[serde(default)]
+    target: Option<TargetChain>,
 }
 
 fn default_true() -> bool {
@@ -83,6 +100,7 @@ pub async fn timestamp_price_updates<S>(
 ) -> Result<Json<PriceUpdate>, RestError>
 where
     S: Aggregates,
+    S: Wormhole,
 {
     let price_id_inputs: Vec<PriceIdentifier> =
         query_params.ids.into_iter().map(|id| id.into()).collect();
@@ -93,9 +111,8 @@ where
     )
     .await?;
 
-    let state = &*state.state;
     let price_feeds_with_update_data = Aggregates::get_price_feeds_with_update_data(
-        state,
+        &*state.state,
         &price_ids,
         RequestTime::FirstAfter(path_params.publish_time),
     )
@@ -110,21 +127,25 @@ where
     })?;
 
     let price_update_data = price_feeds_with_update_data.update_data;
-    let encoded_data: Vec<String> = price_update_data
-        .into_iter()
-        .map(|data| query_params.encoding.encode_str(&data))
-        .collect();
+    let encoded_data: Vec<String> =
+        shape_update_data_for_target(&state, price_update_data, query_params.target)
+            .await?
+            .into_iter()
+            .map(|data| query_params.encoding.encode_str(&data))
+            .collect();
     let binary_price_update = BinaryUpdate {
         encoding: query_params.encoding,
         data: encoded_data,
     };
     let parsed_price_updates: Option<Vec<ParsedPriceUpdate>> = if query_params.parsed {
+        // The market status is reported as of the requested time.
         Some(
-            price_feeds_with_update_data
-                .price_feeds
-                .into_iter()
-                .map(|price_feed| price_feed.into())
-                .collect(),
+            parse_price_feed_updates(
+                &state,
+                price_feeds_with_update_data.price_feeds,
+                path_params.publish_time,
+            )
+            .await,
         )
     } else {
         None
@@ -133,6 +154,7 @@ where
     let compressed_price_update = PriceUpdate {
         binary: binary_price_update,
         parsed: parsed_price_updates,
+        derived: None,
     };
 
     Ok(Json(compressed_price_update))
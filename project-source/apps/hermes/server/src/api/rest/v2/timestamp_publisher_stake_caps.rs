@@ -0,0 +1,127 @@
+use {
+    crate::{
+        api::{
+            doc_examples,
+            rest::RestError,
+            types::{
+                BinaryUpdate, EncodingType, LatestPublisherStakeCapsUpdateDataResponse,
+                ParsedPublisherStakeCapsUpdate,
+            },
+            ApiState,
+        },
+        state::aggregate::{Aggregates, RequestTime, UnixTimestamp},
+    },
+    anyhow::Result,
+    axum::{
+        extract::{Path, State},
+        Json,
+    },
+    serde::Deserialize,
+    serde_qs::axum::QsQuery,
+    utoipa::IntoParams,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in=Path)]
+pub struct TimestampPublisherStakeCapsPathParams {
+    /// The unix timestamp in seconds. This endpoint will return the first update whose
+    /// publish_time is >= the provided value.
+    #[param(value_type = i64)]
+    #[param(example = doc_examples::timestamp_example)]
+    publish_time: UnixTimestamp,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in=Query)]
+pub struct TimestampPublisherStakeCapsQueryParams {
+    /// Optional encoding type. If true, return the message in the encoding specified by the encoding parameter. Default is `hex`.
+    #[serde(default)]
+    encoding: EncodingType,
+
+    /// If true, include the parsed update in the `parsed` field of each returned feed. Default is `true`.
+    #[serde(default = "default_true")]
+    parsed: bool,
+
+    /// Only include the stake caps of these publishers in the parsed update. The binary update
+    /// always contains the stake caps of all the publishers.
+    ///
+    /// This parameter can be provided multiple times to filter by multiple publishers,
+    /// for example see the following query string:
+    ///
+    /// ```
+    /// ?publishers[]=4Z1...&publishers[]=9vK...
+    /// ```
+    #[serde(default)]
+    #[param(rename = "publishers[]")]
+    publishers: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Get the publisher stake caps update data at a given time.
+///
+/// Publisher stake caps are served from the recent updates cached by Hermes, so only recent
+/// timestamps can be queried.
+#[utoipa::path(
+    get,
+    path = "/v2/updates/publisher_stake_caps/{publish_time}",
+    responses(
+        (status = 200, description = "Publisher stake caps update data retrieved successfully", body = LatestPublisherStakeCapsUpdateDataResponse),
+        (status = 404, description = "Update data not found", body = String)
+    ),
+    params(
+        TimestampPublisherStakeCapsPathParams,
+        TimestampPublisherStakeCapsQueryParams
+    )
+)]
+pub async fn timestamp_publisher_stake_caps<S>(
+    State(state): State<ApiState<S>>,
+    Path(path_params): Path<TimestampPublisherStakeCapsPathParams>,
+    QsQuery(query_params): QsQuery<TimestampPublisherStakeCapsQueryParams>,
+) -> Result<Json<LatestPublisherStakeCapsUpdateDataResponse>, RestError>
+where
+    S: Aggregates,
+{
+    let state = &*state.state;
+    let publisher_stake_caps_with_update_data =
+        Aggregates::get_publisher_stake_caps_with_update_data(
+            state,
+            RequestTime::FirstAfter(path_params.publish_time),
+        )
+        .await
+        .map_err(|e| {
+            tracing::warn!(
+                "Error getting publisher stake caps with update data: {:?}",
+                e
+            );
+            RestError::UpdateDataNotFound
+        })?;
+
+    let encoded_data: Vec<String> = publisher_stake_caps_with_update_data
+        .update_data
+        .into_iter()
+        .map(|data| query_params.encoding.encode_str(&data))
+        .collect();
+
+    let binary = BinaryUpdate {
+        encoding: query_params.encoding,
+        data: encoded_data,
+    };
+
+    let parsed: Option<Vec<ParsedPublisherStakeCapsUpdate>> = if query_params.parsed {
+        let mut publisher_stake_caps = publisher_stake_caps_with_update_data.publisher_stake_caps;
+        for update in &mut publisher_stake_caps {
+            update.retain_publishers(&query_params.publishers);
+        }
+        Some(publisher_stake_caps)
+    } else {
+        None
+    };
+
+    Ok(Json(LatestPublisherStakeCapsUpdateDataResponse {
+        binary,
+        parsed,
+    }))
+}
@@ -1,17 +1,23 @@
 use {
     super::doc_examples,
-    crate::state::aggregate::{
-        PriceFeedTwap, PriceFeedUpdate, PriceFeedsWithUpdateData, Slot, UnixTimestamp,
+    crate::state::{
+        aggregate::{
+            derived_feeds::DerivedFeed,
+            wormhole_merkle::{SignatureLimit, UpdateDataShape},
+            PriceFeedTwap, PriceFeedUpdate, PriceFeedsWithUpdateData, Slot, UnixTimestamp,
+        },
+        price_feeds_metadata::market_schedule::MarketSchedule,
     },
     anyhow::Result,
     base64::{engine::general_purpose::STANDARD as base64_standard_engine, Engine as _},
     borsh::{BorshDeserialize, BorshSerialize},
+    chrono::DateTime,
     derive_more::{Deref, DerefMut},
     pyth_sdk::{Price, PriceFeed, PriceIdentifier},
     rust_decimal::Decimal,
     serde::{Deserialize, Serialize},
     std::{
-        collections::BTreeMap,
+        collections::{BTreeMap, BTreeSet},
         fmt::{Display, Formatter, Result as FmtResult},
     },
     utoipa::ToSchema,
@@ -58,6 +64,25 @@ pub struct RpcPriceFeedMetadataV2 {
     pub proof_available_time: Option<UnixTimestamp>,
     #[schema(value_type = Option<i64>, example=doc_examples::timestamp_example)]
     pub prev_publish_time: Option<UnixTimestamp>,
+    /// The status of the market of the price feed according to its schedule. It is not set for
+    /// price feeds without a market schedule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market_status: Option<MarketStatus>,
+    /// The next time the market opens, if it is closed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<i64>, example=doc_examples::timestamp_example)]
+    pub next_market_open: Option<UnixTimestamp>,
+    /// True if the price is stale relative to the market schedule, that is the market is closed
+    /// or has closed since the price was published.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketStatus {
+    Open,
+    Closed,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -151,6 +176,28 @@ pub struct RpcPrice {
     pub publish_time: UnixTimestamp,
 }
 
+impl From<Price> for RpcPrice {
+    fn from(price: Price) -> Self {
+        Self {
+            price: price.price,
+            conf: price.conf,
+            expo: price.expo,
+            publish_time: price.publish_time,
+        }
+    }
+}
+
+impl From<RpcPrice> for Price {
+    fn from(price: RpcPrice) -> Self {
+        Self {
+            price: price.price,
+            conf: price.conf,
+            expo: price.expo,
+            publish_time: price.publish_time,
+        }
+    }
+}
+
 #[derive(
     Copy,
     Clone,
@@ -207,6 +254,48 @@ impl EncodingType {
     }
 }
 
+/// The number of signatures the Pyth Solana receiver verifies by default.
+const SOLANA_RECEIVER_SIGNATURES: usize = 5;
+
+/// The chain the update data is submitted to.
+///
+/// * `evm`, `sui`, `aptos`, `near` and `cosmwasm` verify the VAA against a quorum of the guardian
+///   set, so each update data holds all the price updates of a slot with only a quorum of the
+///   signatures of the guardian set that signed the VAA.
+/// * `solana` posts each price update with `post_update_atomic`, which verifies the VAA in the
+///   same transaction, so each update data holds a single price update and its VAA trimmed to the
+///   signatures the Pyth receiver verifies. A VAA with that many signatures and a single price
+///   update fit in a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetChain {
+    Evm,
+    Solana,
+    Sui,
+    Aptos,
+    Near,
+    Cosmwasm,
+}
+
+impl TargetChain {
+    pub fn update_data_shape(&self) -> UpdateDataShape {
+        match self {
+            TargetChain::Evm
+            | TargetChain::Sui
+            | TargetChain::Aptos
+            | TargetChain::Near
+            | TargetChain::Cosmwasm => UpdateDataShape {
+                max_signatures: Some(SignatureLimit::Quorum),
+                ..Default::default()
+            },
+            TargetChain::Solana => UpdateDataShape {
+                max_updates: 1,
+                max_signatures: Some(SignatureLimit::Fixed(SOLANA_RECEIVER_SIGNATURES)),
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BinaryUpdate {
     pub encoding: EncodingType,
@@ -243,10 +332,97 @@ impl From<PriceFeedUpdate> for ParsedPriceUpdate {
                 proof_available_time: price_feed_update.received_at,
                 slot: price_feed_update.slot,
                 prev_publish_time: price_feed_update.prev_publish_time,
+                market_status: None,
+                next_market_open: None,
+                stale: None,
             },
         }
     }
 }
+
+impl ParsedPriceUpdate {
+    /// Annotate the price update with the status of its market at the given time.
+    pub fn with_market_schedule(mut self, schedule: &MarketSchedule, at: UnixTimestamp) -> Self {
+        let (Some(at), Some(publish_time)) = (
+            DateTime::from_timestamp(at, 0),
+            DateTime::from_timestamp(self.price.publish_time, 0),
+        ) else {
+            return self;
+        };
+
+        let is_open = schedule.is_open(at);
+        self.metadata.market_status = Some(if is_open {
+            MarketStatus::Open
+        } else {
+            MarketStatus::Closed
+        });
+        self.metadata.next_market_open = (!is_open)
+            .then(|| schedule.next_open(at))
+            .flatten()
+            .map(|time| time.timestamp());
+        self.metadata.stale = Some(!schedule.is_fresh(publish_time, at));
+        self
+    }
+
+    /// Whether the price is known to be stale relative to its market schedule.
+    pub fn is_stale(&self) -> bool {
+        self.metadata.stale.unwrap_or(false)
+    }
+}
+
+/// A price computed by Hermes from the price updates of its source feeds. Derived prices are not
+/// part of the binary update data, which contains the updates of the source feeds instead.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ParsedDerivedPriceUpdate {
+    /// The name of the derived feed.
+    pub name: String,
+    /// The id of the base feed, absent for inverted feeds.
+    pub base_id: Option<RpcPriceIdentifier>,
+    /// The id of the quote feed.
+    pub quote_id: RpcPriceIdentifier,
+    /// The price of the base feed in the unit of the quote feed.
+    pub price: RpcPrice,
+    /// The EMA price of the base feed in the unit of the quote feed.
+    pub ema_price: RpcPrice,
+}
+
+impl ParsedDerivedPriceUpdate {
+    /// Compute the derived feed from the parsed price updates of its source feeds. Returns `None`
+    /// if a source feed is missing or the price cannot be computed.
+    pub fn compute(
+        derived_feed: &DerivedFeed,
+        price_updates: &[ParsedPriceUpdate],
+    ) -> Option<Self> {
+        let find_price_update = |id: PriceIdentifier| {
+            price_updates
+                .iter()
+                .find(|price_update| PriceIdentifier::from(price_update.id) == id)
+        };
+        let base = match derived_feed.base_id {
+            Some(base_id) => Some(find_price_update(base_id)?),
+            None => None,
+        };
+        let quote = find_price_update(derived_feed.quote_id)?;
+
+        let price = derived_feed.compute(
+            base.map(|base| Price::from(base.price)).as_ref(),
+            &quote.price.into(),
+        )?;
+        let ema_price = derived_feed.compute(
+            base.map(|base| Price::from(base.ema_price)).as_ref(),
+            &quote.ema_price.into(),
+        )?;
+
+        Some(Self {
+            name: derived_feed.name.clone(),
+            base_id: derived_feed.base_id.map(RpcPriceIdentifier::from),
+            quote_id: derived_feed.quote_id.into(),
+            price: price.into(),
+            ema_price: ema_price.into(),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ParsedPriceFeedTwap {
     pub id: RpcPriceIdentifier,
@@ -292,6 +468,8 @@ pub struct TwapsResponse {
 
 #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize, Clone, ToSchema)]
 pub struct ParsedPublisherStakeCapsUpdate {
+    #[schema(value_type = i64, example=doc_examples::timestamp_example)]
+    pub publish_time: UnixTimestamp,
     pub publisher_stake_caps: Vec<ParsedPublisherStakeCap>,
 }
 
@@ -308,11 +486,84 @@ pub struct LatestPublisherStakeCapsUpdateDataResponse {
     pub parsed: Option<Vec<ParsedPublisherStakeCapsUpdate>>,
 }
 
+impl ParsedPublisherStakeCapsUpdate {
+    /// Keep only the stake caps of the given publishers. All the stake caps are kept if no
+    /// publisher is given.
+    pub fn retain_publishers(&mut self, publishers: &[String]) {
+        if !publishers.is_empty() {
+            self.publisher_stake_caps
+                .retain(|cap| publishers.contains(&cap.publisher));
+        }
+    }
+}
+
+/// The change of the stake cap of a single publisher between two publisher stake caps updates.
+/// A missing cap means the publisher is not part of the corresponding update.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PublisherStakeCapChange {
+    pub publisher: String,
+    pub previous_cap: Option<u64>,
+    pub cap: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PublisherStakeCapsDiffResponse {
+    /// The publish time of the update the changes are computed from.
+    #[schema(value_type = i64, example=doc_examples::timestamp_example)]
+    pub from_publish_time: UnixTimestamp,
+    /// The publish time of the update the changes are computed to.
+    #[schema(value_type = i64, example=doc_examples::timestamp_example)]
+    pub to_publish_time: UnixTimestamp,
+    /// The publishers whose stake cap changed, sorted by publisher.
+    pub changes: Vec<PublisherStakeCapChange>,
+}
+
+impl PublisherStakeCapsDiffResponse {
+    /// Compute the stake cap changes between two publisher stake caps updates.
+    pub fn new(from: &ParsedPublisherStakeCapsUpdate, to: &ParsedPublisherStakeCapsUpdate) -> Self {
+        let caps_by_publisher = |update: &ParsedPublisherStakeCapsUpdate| {
+            update
+                .publisher_stake_caps
+                .iter()
+                .map(|cap| (cap.publisher.clone(), cap.cap))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let previous_caps = caps_by_publisher(from);
+        let caps = caps_by_publisher(to);
+
+        let publishers = previous_caps
+            .keys()
+            .chain(caps.keys())
+            .collect::<BTreeSet<_>>();
+        let changes = publishers
+            .into_iter()
+            .filter_map(|publisher| {
+                let previous_cap = previous_caps.get(publisher).copied();
+                let cap = caps.get(publisher).copied();
+                (previous_cap != cap).then(|| PublisherStakeCapChange {
+                    publisher: publisher.clone(),
+                    previous_cap,
+                    cap,
+                })
+            })
+            .collect();
+
+        Self {
+            from_publish_time: from.publish_time,
+            to_publish_time: to.publish_time,
+            changes,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PriceUpdate {
     pub binary: BinaryUpdate,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parsed: Option<Vec<ParsedPriceUpdate>>,
+    /// The requested derived feeds, computed from the source feed updates included in `binary`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derived: Option<Vec<ParsedDerivedPriceUpdate>>,
 }
 
 impl TryFrom<PriceUpdate> for PriceFeedsWithUpdateData {
@@ -362,7 +613,7 @@ impl TryFrom<PriceUpdate> for PriceFeedsWithUpdateData {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub struct PriceFeedMetadata {
     pub id: RpcPriceIdentifier,
     // BTreeMap is used to automatically sort the keys to ensure consistent ordering of attributes in the JSON response.
@@ -370,6 +621,18 @@ pub struct PriceFeedMetadata {
     pub attributes: BTreeMap<String, String>,
 }
 
+/// A change in the set of price feeds, sent on the price feeds metadata stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PriceFeedMetadataChange {
+    /// A new price feed was listed.
+    Added { price_feed: PriceFeedMetadata },
+    /// The attributes of an existing price feed changed.
+    Updated { price_feed: PriceFeedMetadata },
+    /// A price feed was delisted.
+    Removed { id: RpcPriceIdentifier },
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AssetType {
@@ -439,4 +702,66 @@ mod tests {
                 .trim_matches('"')
         );
     }
+
+    fn create_publisher_stake_caps_update(
+        publish_time: UnixTimestamp,
+        caps: &[(&str, u64)],
+    ) -> ParsedPublisherStakeCapsUpdate {
+        ParsedPublisherStakeCapsUpdate {
+            publish_time,
+            publisher_stake_caps: caps
+                .iter()
+                .map(|(publisher, cap)| ParsedPublisherStakeCap {
+                    publisher: publisher.to_string(),
+                    cap: *cap,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_publisher_stake_caps_diff() {
+        let from = create_publisher_stake_caps_update(10, &[("a", 1), ("b", 2), ("c", 3)]);
+        let to = create_publisher_stake_caps_update(20, &[("b", 2), ("c", 4), ("d", 5)]);
+
+        let diff = PublisherStakeCapsDiffResponse::new(&from, &to);
+        assert_eq!(diff.from_publish_time, 10);
+        assert_eq!(diff.to_publish_time, 20);
+        assert_eq!(
+            diff.changes,
+            vec![
+                PublisherStakeCapChange {
+                    publisher: "a".to_string(),
+                    previous_cap: Some(1),
+                    cap: None,
+                },
+                PublisherStakeCapChange {
+                    publisher: "c".to_string(),
+                    previous_cap: Some(3),
+                    cap: Some(4),
+                },
+                PublisherStakeCapChange {
+                    publisher: "d".to_string(),
+                    previous_cap: None,
+                    cap: Some(5),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_publisher_stake_caps_retain_publishers() {
+        let mut update = create_publisher_stake_caps_update(10, &[("a", 1), ("b", 2)]);
+        update.retain_publishers(&[]);
+        assert_eq!(update.publisher_stake_caps.len(), 2);
+
+        update.retain_publishers(&["b".to_string()]);
+        assert_eq!(
+            update.publisher_stake_caps,
+            vec![ParsedPublisherStakeCap {
+                publisher: "b".to_string(),
+                cap: 2,
+            }]
+        );
+    }
 }
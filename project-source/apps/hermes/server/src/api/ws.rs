@@ -1,10 +1,11 @@
 use {
     super::{
+        metrics_middleware::{ApiMetrics, DeliveryChannel},
         types::{PriceIdInput, RpcPriceFeed},
         ApiState,
     },
     crate::state::{
-        aggregate::{Aggregates, AggregationEvent, RequestTime},
+        aggregate::{Aggregates, AggregationEvent, PriceFeedUpdate, RequestTime},
         metrics::Metrics,
         Benchmarks, Cache, PriceFeedMeta,
     },
@@ -28,7 +29,7 @@ use {
         encoding::{EncodeLabelSet, EncodeLabelValue},
         metrics::{counter::Counter, family::Family},
     },
-    pyth_sdk::PriceIdentifier,
+    pyth_sdk::{Price, PriceIdentifier},
     serde::{Deserialize, Serialize},
     std::{
         collections::HashMap,
@@ -41,9 +42,14 @@ use {
         time::Duration,
     },
     tokio::{
-        sync::{broadcast::Receiver, watch},
+        sync::{
+            broadcast::{error::RecvError, Receiver},
+            watch,
+        },
         time::Instant,
     },
+    tracing::Instrument,
+    tracing_opentelemetry::OpenTelemetrySpanExt,
 };
 
 const PING_INTERVAL_DURATION: Duration = Duration::from_secs(30);
@@ -54,11 +60,132 @@ const MAX_CONNECTION_DURATION: Duration = Duration::from_secs(24 * 60 * 60); //
 /// If the limit is exceeded, the connection is closed.
 const BYTES_LIMIT_PER_IP_PER_SECOND: u32 = 256 * 1024; // 256 KiB
 
+/// The maximum interval a client can ask for between two updates of the same price feed.
+const MAX_MIN_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Clone)]
 pub struct PriceFeedClientConfig {
     verbose: bool,
     binary: bool,
     allow_out_of_order: bool,
+    /// Minimum time between two updates of the feed. Updates arriving in between are conflated
+    /// and only the latest one is sent once the interval has elapsed.
+    min_interval: Option<Duration>,
+    /// Only send an update if the price moved by at least this many basis points since the last
+    /// update sent for the feed.
+    only_on_price_change_bps: Option<u32>,
+}
+
+/// The last update that was sent to the client for a price feed.
+struct SentPriceFeedUpdate {
+    sent_at: Instant,
+    price: Price,
+}
+
+/// Throttling state of a subscriber: the last update sent for each price feed and the updates
+/// held back until the minimum interval of their feed has elapsed.
+#[derive(Default)]
+struct Conflator {
+    last_sent_updates: HashMap<PriceIdentifier, SentPriceFeedUpdate>,
+    conflated_updates: HashMap<PriceIdentifier, PriceFeedUpdate>,
+}
+
+impl Conflator {
+    /// Returns the update if it must be sent now. Otherwise it is either held back until the
+    /// minimum interval of the feed has elapsed, or dropped because the price did not move enough.
+    fn accept(
+        &mut self,
+        update: PriceFeedUpdate,
+        config: &PriceFeedClientConfig,
+        now: Instant,
+    ) -> Option<PriceFeedUpdate> {
+        let price_feed_id = update.price_feed.id;
+        let last_sent = self.last_sent_updates.get(&price_feed_id);
+
+        // A pending conflated update is always replaced by the newest one, so the client
+        // eventually receives the latest value even if it did not move enough on its own.
+        if !self.conflated_updates.contains_key(&price_feed_id) {
+            if let (Some(bps), Some(last_sent)) = (config.only_on_price_change_bps, last_sent) {
+                if !has_price_changed_by_bps(
+                    &last_sent.price,
+                    &update.price_feed.get_price_unchecked(),
+                    bps,
+                ) {
+                    return None;
+                }
+            }
+        }
+
+        if let (Some(min_interval), Some(last_sent)) = (config.min_interval, last_sent) {
+            if now < last_sent.sent_at + min_interval {
+                self.conflated_updates.insert(price_feed_id, update);
+                return None;
+            }
+        }
+
+        self.conflated_updates.remove(&price_feed_id);
+        Some(update)
+    }
+
+    /// The earliest time at which one of the conflated updates can be sent to the client.
+    fn next_deadline(
+        &self,
+        price_feeds_with_config: &HashMap<PriceIdentifier, PriceFeedClientConfig>,
+    ) -> Option<Instant> {
+        self.conflated_updates
+            .keys()
+            .filter_map(|price_feed_id| {
+                let min_interval = price_feeds_with_config.get(price_feed_id)?.min_interval?;
+                let last_sent = self.last_sent_updates.get(price_feed_id)?;
+                Some(last_sent.sent_at + min_interval)
+            })
+            .min()
+    }
+
+    /// Removes and returns the conflated updates whose minimum interval has elapsed. Updates of
+    /// feeds that are no longer subscribed are dropped.
+    fn take_due(
+        &mut self,
+        price_feeds_with_config: &HashMap<PriceIdentifier, PriceFeedClientConfig>,
+        now: Instant,
+    ) -> Vec<PriceFeedUpdate> {
+        let due_price_feed_ids = self
+            .conflated_updates
+            .keys()
+            .filter(|price_feed_id| {
+                match (
+                    price_feeds_with_config
+                        .get(*price_feed_id)
+                        .and_then(|config| config.min_interval),
+                    self.last_sent_updates.get(*price_feed_id),
+                ) {
+                    (Some(min_interval), Some(last_sent)) => {
+                        now >= last_sent.sent_at + min_interval
+                    }
+                    _ => true,
+                }
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        due_price_feed_ids
+            .into_iter()
+            .filter_map(|price_feed_id| self.conflated_updates.remove(&price_feed_id))
+            .filter(|update| price_feeds_with_config.contains_key(&update.price_feed.id))
+            .collect()
+    }
+
+    /// Records that an update of the feed with `price` was sent to the client at `sent_at`.
+    fn record_sent(&mut self, price_feed_id: PriceIdentifier, price: Price, sent_at: Instant) {
+        self.last_sent_updates
+            .insert(price_feed_id, SentPriceFeedUpdate { sent_at, price });
+    }
+
+    /// Forgets everything about the feed, used when it is unsubscribed.
+    fn remove(&mut self, price_feed_id: &PriceIdentifier) {
+        self.last_sent_updates.remove(price_feed_id);
+        self.conflated_updates.remove(price_feed_id);
+    }
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelValue)]
@@ -123,10 +250,16 @@ pub struct WsState {
     pub rate_limiter: DefaultKeyedRateLimiter<IpAddr>,
     pub requester_ip_header_name: String,
     pub metrics: WsMetrics,
+    pub api_metrics: Arc<ApiMetrics>,
 }
 
 impl WsState {
-    pub fn new<S>(whitelist: Vec<IpNet>, requester_ip_header_name: String, state: Arc<S>) -> Self
+    pub fn new<S>(
+        whitelist: Vec<IpNet>,
+        requester_ip_header_name: String,
+        api_metrics: Arc<ApiMetrics>,
+        state: Arc<S>,
+    ) -> Self
     where
         S: Metrics,
         S: Send + Sync + 'static,
@@ -139,6 +272,7 @@ impl WsState {
             bytes_limit_whitelist: whitelist,
             requester_ip_header_name,
             metrics: WsMetrics::new(state.clone()),
+            api_metrics,
         }
     }
 }
@@ -157,6 +291,10 @@ enum ClientMessage {
         allow_out_of_order: bool,
         #[serde(default)]
         ignore_invalid_price_ids: bool,
+        #[serde(default)]
+        min_interval_ms: Option<u64>,
+        #[serde(default)]
+        only_on_price_change_bps: Option<u32>,
     },
     #[serde(rename = "unsubscribe")]
     Unsubscribe { ids: Vec<PriceIdInput> },
@@ -255,6 +393,7 @@ pub struct Subscriber<S> {
     receiver: SplitStream<WebSocket>,
     sender: SplitSink<WebSocket, Message>,
     price_feeds_with_config: HashMap<PriceIdentifier, PriceFeedClientConfig>,
+    conflator: Conflator,
     ping_interval: tokio::time::Interval,
     connection_deadline: Instant,
     exit: watch::Receiver<bool>,
@@ -284,6 +423,7 @@ where
             receiver,
             sender,
             price_feeds_with_config: HashMap::new(),
+            conflator: Conflator::default(),
             ping_interval: tokio::time::interval(PING_INTERVAL_DURATION),
             connection_deadline: Instant::now() + MAX_CONNECTION_DURATION,
             exit: crate::EXIT.subscribe(),
@@ -302,13 +442,37 @@ where
     }
 
     async fn handle_next(&mut self) -> Result<()> {
+        let conflation_deadline = self.conflator.next_deadline(&self.price_feeds_with_config);
         tokio::select! {
             maybe_update_feeds_event = self.notify_receiver.recv() => {
                 match maybe_update_feeds_event {
-                    Ok(event) => self.handle_price_feeds_update(event).await,
+                    Ok(event) => {
+                        let span = tracing::info_span!(
+                            "deliver_price_updates",
+                            subscriber = self.id,
+                            slot = event.slot()
+                        );
+                        span.set_parent(event.trace_context().0.clone());
+                        self.handle_price_feeds_update(event).instrument(span).await
+                    }
+                    // Subscribers that throttle all their feeds only care about the latest values,
+                    // so falling behind the store is not an error for them. Other subscribers would
+                    // silently miss updates, so their connection is closed.
+                    Err(RecvError::Lagged(skipped)) if tolerates_lag(&self.price_feeds_with_config) => {
+                        tracing::debug!(
+                            subscriber = self.id,
+                            skipped,
+                            "Throttled subscriber lagged behind, skipping to latest updates."
+                        );
+                        Ok(())
+                    }
                     Err(e) => Err(anyhow!("Failed to receive update from store: {:?}", e)),
                 }
             },
+            _ = tokio::time::sleep_until(conflation_deadline.unwrap_or(self.connection_deadline)),
+                if conflation_deadline.is_some() => {
+                self.flush_conflated_updates().await
+            },
             maybe_message_or_err = self.receiver.next() => {
                 self.handle_client_message(
                     maybe_message_or_err.ok_or(anyhow!("Client channel is closed"))??
@@ -401,88 +565,134 @@ where
             }
         };
 
+        let now = Instant::now();
         for update in updates.price_feeds {
             let config = self
                 .price_feeds_with_config
                 .get(&update.price_feed.id)
                 .ok_or(anyhow::anyhow!(
                     "Config missing, price feed list was poisoned during iteration."
-                ))?;
+                ))?
+                .clone();
 
-            if let AggregationEvent::OutOfOrder { slot: _ } = event {
+            if let AggregationEvent::OutOfOrder { .. } = event {
                 if !config.allow_out_of_order {
                     continue;
                 }
             }
 
-            let message = serde_json::to_string(&ServerMessage::PriceUpdate {
-                price_feed: RpcPriceFeed::from_price_feed_update(
-                    update,
-                    config.verbose,
-                    config.binary,
-                ),
-            })?;
-
-            // Close the connection if rate limit is exceeded and the ip is not whitelisted.
-            // If the ip address is None no rate limiting is applied.
-            if let Some(ip_addr) = self.ip_addr {
-                if !self
-                    .ws_state
-                    .bytes_limit_whitelist
-                    .iter()
-                    .any(|ip_net| ip_net.contains(&ip_addr))
-                    && self.ws_state.rate_limiter.check_key_n(
-                        &ip_addr,
-                        NonZeroU32::new(message.len().try_into()?)
-                            .ok_or(anyhow!("Empty message"))?,
-                    ) != Ok(Ok(()))
-                {
-                    tracing::info!(
-                        self.id,
-                        ip = %ip_addr,
-                        "Rate limit exceeded. Closing connection.",
-                    );
-                    self.ws_state
-                        .metrics
-                        .interactions
-                        .get_or_create(&Labels {
-                            interaction: Interaction::RateLimit,
-                            status: Status::Error,
-                        })
-                        .inc();
-
-                    self.sender
-                        .send(
-                            serde_json::to_string(&ServerResponseMessage::Err {
-                                error: "Rate limit exceeded".to_string(),
-                            })?
-                            .into(),
-                        )
-                        .await?;
-                    self.sender.close().await?;
-                    self.closed = true;
-                    return Ok(());
-                }
+            let Some(update) = self.conflator.accept(update, &config, now) else {
+                continue;
+            };
+            if !self.feed_price_feed_update(update, &config).await? {
+                return Ok(());
             }
+        }
+
+        self.sender.flush().await?;
+        Ok(())
+    }
 
-            // `sender.feed` buffers a message to the client but does not flush it, so we can send
-            // multiple messages and flush them all at once.
-            self.sender.feed(message.into()).await?;
-
-            self.ws_state
-                .metrics
-                .interactions
-                .get_or_create(&Labels {
-                    interaction: Interaction::PriceUpdate,
-                    status: Status::Success,
-                })
-                .inc();
+    /// Send the conflated updates whose minimum interval has elapsed.
+    async fn flush_conflated_updates(&mut self) -> Result<()> {
+        let due_updates = self
+            .conflator
+            .take_due(&self.price_feeds_with_config, Instant::now());
+
+        for update in due_updates {
+            let Some(config) = self
+                .price_feeds_with_config
+                .get(&update.price_feed.id)
+                .cloned()
+            else {
+                continue;
+            };
+            if !self.feed_price_feed_update(update, &config).await? {
+                return Ok(());
+            }
         }
 
         self.sender.flush().await?;
         Ok(())
     }
 
+    /// Buffer a price feed update to be sent to the client, applying the per IP rate limit.
+    ///
+    /// Returns false if the connection was closed because the rate limit was exceeded.
+    async fn feed_price_feed_update(
+        &mut self,
+        update: PriceFeedUpdate,
+        config: &PriceFeedClientConfig,
+    ) -> Result<bool> {
+        let price_feed_id = update.price_feed.id;
+        let price = update.price_feed.get_price_unchecked();
+        let message = serde_json::to_string(&ServerMessage::PriceUpdate {
+            price_feed: RpcPriceFeed::from_price_feed_update(update, config.verbose, config.binary),
+        })?;
+
+        // Close the connection if rate limit is exceeded and the ip is not whitelisted.
+        // If the ip address is None no rate limiting is applied.
+        if let Some(ip_addr) = self.ip_addr {
+            if !self
+                .ws_state
+                .bytes_limit_whitelist
+                .iter()
+                .any(|ip_net| ip_net.contains(&ip_addr))
+                && self.ws_state.rate_limiter.check_key_n(
+                    &ip_addr,
+                    NonZeroU32::new(message.len().try_into()?).ok_or(anyhow!("Empty message"))?,
+                ) != Ok(Ok(()))
+            {
+                tracing::info!(
+                    self.id,
+                    ip = %ip_addr,
+                    "Rate limit exceeded. Closing connection.",
+                );
+                self.ws_state
+                    .metrics
+                    .interactions
+                    .get_or_create(&Labels {
+                        interaction: Interaction::RateLimit,
+                        status: Status::Error,
+                    })
+                    .inc();
+
+                self.sender
+                    .send(
+                        serde_json::to_string(&ServerResponseMessage::Err {
+                            error: "Rate limit exceeded".to_string(),
+                        })?
+                        .into(),
+                    )
+                    .await?;
+                self.sender.close().await?;
+                self.closed = true;
+                return Ok(false);
+            }
+        }
+
+        // `sender.feed` buffers a message to the client but does not flush it, so we can send
+        // multiple messages and flush them all at once.
+        self.sender.feed(message.into()).await?;
+
+        self.conflator
+            .record_sent(price_feed_id, price, Instant::now());
+
+        self.ws_state
+            .metrics
+            .interactions
+            .get_or_create(&Labels {
+                interaction: Interaction::PriceUpdate,
+                status: Status::Success,
+            })
+            .inc();
+        self.ws_state
+            .api_metrics
+            .observe_delivery(DeliveryChannel::Ws, price.publish_time);
+
+        Ok(true)
+    }
+
     #[tracing::instrument(skip(self, message))]
     async fn handle_client_message(&mut self, message: Message) -> Result<()> {
         let maybe_client_message = match message {
@@ -559,7 +769,27 @@ where
                 binary,
                 allow_out_of_order,
                 ignore_invalid_price_ids,
+                min_interval_ms,
+                only_on_price_change_bps,
             }) => {
+                let min_interval = min_interval_ms.map(Duration::from_millis);
+                if min_interval.map_or(false, |min_interval| min_interval > MAX_MIN_INTERVAL) {
+                    self.sender
+                        .send(
+                            serde_json::to_string(&ServerMessage::Response(
+                                ServerResponseMessage::Err {
+                                    error: format!(
+                                        "min_interval_ms must not exceed {}",
+                                        MAX_MIN_INTERVAL.as_millis()
+                                    ),
+                                },
+                            ))?
+                            .into(),
+                        )
+                        .await?;
+                    return Ok(());
+                }
+
                 let price_ids: Vec<PriceIdentifier> = ids.into_iter().map(|id| id.into()).collect();
                 let available_price_ids = Aggregates::get_price_feed_ids(&*self.state).await;
 
@@ -596,6 +826,8 @@ where
                                 verbose,
                                 binary,
                                 allow_out_of_order,
+                                min_interval,
+                                only_on_price_change_bps,
                             },
                         );
                     }
@@ -605,6 +837,7 @@ where
                 for id in ids {
                     let price_id: PriceIdentifier = id.into();
                     self.price_feeds_with_config.remove(&price_id);
+                    self.conflator.remove(&price_id);
                 }
             }
         }
@@ -628,3 +861,267 @@ where
         Ok(())
     }
 }
+
+/// Returns true if a subscriber to these price feeds can fall behind the store without missing
+/// updates it asked for, i.e. all its feeds are throttled and only care about the latest value.
+fn tolerates_lag(
+    price_feeds_with_config: &HashMap<PriceIdentifier, PriceFeedClientConfig>,
+) -> bool {
+    !price_feeds_with_config.is_empty()
+        && price_feeds_with_config
+            .values()
+            .all(|config| config.min_interval.is_some())
+}
+
+/// Returns true if `new` differs from `old` by at least `bps` basis points of `old`.
+///
+/// Prices with different exponents are always considered changed.
+fn has_price_changed_by_bps(old: &Price, new: &Price, bps: u32) -> bool {
+    if old.expo != new.expo {
+        return true;
+    }
+    let diff = (i128::from(new.price) - i128::from(old.price)).unsigned_abs();
+    diff * 10_000 >= u128::from(bps) * i128::from(old.price).unsigned_abs()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing, reason = "tests")]
+mod test {
+    use super::*;
+
+    fn price(price: i64, expo: i32) -> Price {
+        Price {
+            price,
+            conf: 0,
+            expo,
+            publish_time: 0,
+        }
+    }
+
+    fn config(min_interval_ms: Option<u64>, bps: Option<u32>) -> PriceFeedClientConfig {
+        PriceFeedClientConfig {
+            verbose: false,
+            binary: false,
+            allow_out_of_order: false,
+            min_interval: min_interval_ms.map(Duration::from_millis),
+            only_on_price_change_bps: bps,
+        }
+    }
+
+    fn update(id: PriceIdentifier, value: i64) -> PriceFeedUpdate {
+        PriceFeedUpdate {
+            price_feed: pyth_sdk::PriceFeed::new(id, price(value, -2), price(value, -2)),
+            slot: None,
+            received_at: None,
+            update_data: None,
+            prev_publish_time: None,
+        }
+    }
+
+    /// Simulates the subscriber: the update is sent at `now` if the conflator accepts it.
+    fn deliver(
+        conflator: &mut Conflator,
+        update: PriceFeedUpdate,
+        config: &PriceFeedClientConfig,
+        now: Instant,
+    ) -> Option<i64> {
+        let update = conflator.accept(update, config, now)?;
+        let price = update.price_feed.get_price_unchecked();
+        conflator.record_sent(update.price_feed.id, price, now);
+        Some(price.price)
+    }
+
+    #[test]
+    fn test_conflation_keeps_latest_update_within_interval() {
+        let id = PriceIdentifier::new([1; 32]);
+        let configs = HashMap::from([(id, config(Some(1_000), None))]);
+        let mut conflator = Conflator::default();
+        let start = Instant::now();
+
+        // The first update is sent right away, the next ones within the interval are conflated.
+        assert_eq!(
+            deliver(&mut conflator, update(id, 100), &configs[&id], start),
+            Some(100)
+        );
+        assert_eq!(
+            deliver(
+                &mut conflator,
+                update(id, 101),
+                &configs[&id],
+                start + Duration::from_millis(100)
+            ),
+            None
+        );
+        assert_eq!(
+            deliver(
+                &mut conflator,
+                update(id, 102),
+                &configs[&id],
+                start + Duration::from_millis(200)
+            ),
+            None
+        );
+
+        // The pending update is due once the interval has elapsed since the last sent update.
+        assert_eq!(
+            conflator.next_deadline(&configs),
+            Some(start + Duration::from_millis(1_000))
+        );
+        assert!(conflator
+            .take_due(&configs, start + Duration::from_millis(999))
+            .is_empty());
+        let due = conflator.take_due(&configs, start + Duration::from_millis(1_000));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].price_feed.get_price_unchecked().price, 102);
+        assert_eq!(conflator.next_deadline(&configs), None);
+
+        // An update arriving after the interval is sent right away.
+        assert_eq!(
+            deliver(
+                &mut conflator,
+                update(id, 103),
+                &configs[&id],
+                start + Duration::from_millis(1_500)
+            ),
+            Some(103)
+        );
+    }
+
+    #[test]
+    fn test_conflation_only_on_price_change() {
+        let id = PriceIdentifier::new([1; 32]);
+        let configs = HashMap::from([(id, config(Some(1_000), Some(100)))]);
+        let mut conflator = Conflator::default();
+        let start = Instant::now();
+
+        assert_eq!(
+            deliver(&mut conflator, update(id, 10_000), &configs[&id], start),
+            Some(10_000)
+        );
+
+        // Moves below the threshold are dropped.
+        assert_eq!(
+            deliver(
+                &mut conflator,
+                update(id, 10_050),
+                &configs[&id],
+                start + Duration::from_millis(100)
+            ),
+            None
+        );
+        assert_eq!(conflator.next_deadline(&configs), None);
+
+        // A large enough move within the interval is conflated, and a small move then replaces
+        // it so that the client eventually gets the latest value.
+        assert_eq!(
+            deliver(
+                &mut conflator,
+                update(id, 10_200),
+                &configs[&id],
+                start + Duration::from_millis(200)
+            ),
+            None
+        );
+        assert_eq!(
+            deliver(
+                &mut conflator,
+                update(id, 10_010),
+                &configs[&id],
+                start + Duration::from_millis(300)
+            ),
+            None
+        );
+        assert_eq!(
+            conflator.next_deadline(&configs),
+            Some(start + Duration::from_millis(1_000))
+        );
+        let sent_at = start + Duration::from_millis(1_000);
+        let due = conflator.take_due(&configs, sent_at);
+        assert_eq!(due.len(), 1);
+        let price = due[0].price_feed.get_price_unchecked();
+        assert_eq!(price.price, 10_010);
+        conflator.record_sent(id, price, sent_at);
+
+        // Moves below the threshold are still dropped once the interval has elapsed.
+        assert_eq!(
+            deliver(
+                &mut conflator,
+                update(id, 10_050),
+                &configs[&id],
+                start + Duration::from_millis(3_000)
+            ),
+            None
+        );
+        assert_eq!(conflator.next_deadline(&configs), None);
+    }
+
+    #[test]
+    fn test_conflation_drops_unsubscribed_feeds() {
+        let id = PriceIdentifier::new([1; 32]);
+        let mut configs = HashMap::from([(id, config(Some(1_000), None))]);
+        let mut conflator = Conflator::default();
+        let start = Instant::now();
+
+        deliver(&mut conflator, update(id, 100), &configs[&id], start);
+        deliver(&mut conflator, update(id, 101), &configs[&id], start);
+
+        configs.remove(&id);
+        assert_eq!(conflator.next_deadline(&configs), None);
+        assert!(conflator
+            .take_due(&configs, start + Duration::from_millis(1_000))
+            .is_empty());
+        assert!(conflator.conflated_updates.is_empty());
+    }
+
+    #[test]
+    fn test_tolerates_lag_only_when_all_feeds_are_throttled() {
+        let throttled = PriceIdentifier::new([1; 32]);
+        let not_throttled = PriceIdentifier::new([2; 32]);
+
+        assert!(!tolerates_lag(&HashMap::new()));
+        assert!(tolerates_lag(&HashMap::from([(
+            throttled,
+            config(Some(1_000), None)
+        )])));
+        assert!(!tolerates_lag(&HashMap::from([(
+            not_throttled,
+            config(None, Some(100))
+        )])));
+        assert!(!tolerates_lag(&HashMap::from([
+            (throttled, config(Some(1_000), None)),
+            (not_throttled, config(None, None)),
+        ])));
+    }
+
+    #[test]
+    fn test_has_price_changed_by_bps() {
+        // 1% move is 100 bps.
+        assert!(has_price_changed_by_bps(
+            &price(10_000, -2),
+            &price(10_100, -2),
+            100
+        ));
+        assert!(has_price_changed_by_bps(
+            &price(10_000, -2),
+            &price(9_900, -2),
+            100
+        ));
+        assert!(!has_price_changed_by_bps(
+            &price(10_000, -2),
+            &price(10_099, -2),
+            100
+        ));
+
+        // Zero threshold always passes, and exponent changes are always reported.
+        assert!(has_price_changed_by_bps(
+            &price(10_000, -2),
+            &price(10_000, -2),
+            0
+        ));
+        assert!(has_price_changed_by_bps(
+            &price(10_000, -2),
+            &price(10_000, -3),
+            100
+        ));
+    }
+}
@@ -3,9 +3,12 @@ use clap::{crate_authors, crate_description, crate_name, crate_version, Args, Pa
 mod aggregate;
 mod benchmarks;
 mod cache;
-mod metrics;
+pub mod metrics;
 mod pythnet;
-mod rpc;
+mod record;
+pub mod replay;
+pub mod rpc;
+pub mod telemetry;
 mod wormhole;
 
 // `Options` is a structup definition to provide clean command-line args for Hermes.
@@ -22,10 +25,24 @@ pub enum Options {
     /// Run the Hermes Price Service.
     Run(RunOptions),
 
+    /// Replay a capture recorded with `run --record-path` while serving the API.
+    Replay(ReplayOptions),
+
     /// Show Overridden Environment Variables.
     ShowEnv(ShowEnvOptions),
 }
 
+impl Options {
+    /// The telemetry options of the subcommands that run the service.
+    pub fn telemetry(&self) -> Option<&telemetry::Options> {
+        match self {
+            Options::Run(opts) => Some(&opts.telemetry),
+            Options::Replay(opts) => Some(&opts.telemetry),
+            Options::ShowEnv(_) => None,
+        }
+    }
+}
+
 #[derive(Args, Clone, Debug)]
 pub struct RunOptions {
     /// Cache Options
@@ -55,6 +72,45 @@ pub struct RunOptions {
     /// Wormhole Options.
     #[command(flatten)]
     pub wormhole: wormhole::Options,
+
+    /// Record Options
+    #[command(flatten)]
+    pub record: record::Options,
+
+    /// Telemetry Options
+    #[command(flatten)]
+    pub telemetry: telemetry::Options,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ReplayOptions {
+    /// Cache Options
+    #[command(flatten)]
+    pub cache: cache::Options,
+
+    /// Aggregate Options
+    #[command(flatten)]
+    pub aggregate: aggregate::Options,
+
+    /// Benchmarks Options
+    #[command(flatten)]
+    pub benchmarks: benchmarks::Options,
+
+    /// Metrics Options
+    #[command(flatten)]
+    pub metrics: metrics::Options,
+
+    /// RPC Options
+    #[command(flatten)]
+    pub rpc: rpc::Options,
+
+    /// Replay Options
+    #[command(flatten)]
+    pub replay: replay::Options,
+
+    /// Telemetry Options
+    #[command(flatten)]
+    pub telemetry: telemetry::Options,
 }
 
 #[derive(Args, Clone, Debug)]
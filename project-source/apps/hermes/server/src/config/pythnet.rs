@@ -6,15 +6,35 @@ const DEFAULT_PYTHNET_ORACLE_PROGRAM_ADDR: &str = "FsJ3A3u2vn5cTVofAjvy6y5kwABJA
 #[command(next_help_heading = "Pythnet Options")]
 #[group(id = "Pythnet")]
 pub struct Options {
-    /// Address of a PythNet compatible websocket RPC endpoint.
+    /// Addresses of PythNet compatible websocket RPC endpoints (separated by comma).
+    ///
+    /// Accumulator messages are ingested from all the endpoints concurrently. The messages of a
+    /// slot are stored once `--pythnet-ws-quorum` endpoints delivered identical messages for it.
     #[arg(long = "pythnet-ws-addr")]
+    #[arg(value_delimiter = ',')]
+    #[arg(required = true)]
     #[arg(env = "PYTHNET_WS_ADDR")]
-    pub ws_addr: String,
+    pub ws_addrs: Vec<String>,
 
-    /// Address of a PythNet compatible HTP RPC endpoint.
+    /// Number of websocket endpoints that must deliver identical accumulator messages for a slot
+    /// before they are stored.
+    ///
+    /// With the default of 1 the first endpoint to deliver a slot wins and the other endpoints only
+    /// act as failover. A higher value protects against a faulty endpoint at the cost of waiting
+    /// for the slower endpoints.
+    #[arg(long = "pythnet-ws-quorum")]
+    #[arg(default_value = "1")]
+    #[arg(env = "PYTHNET_WS_QUORUM")]
+    pub ws_quorum: usize,
+
+    /// Addresses of PythNet compatible HTTP RPC endpoints (separated by comma).
+    ///
+    /// The endpoints are tried in order and the next one is used if a request fails.
     #[arg(long = "pythnet-http-addr")]
+    #[arg(value_delimiter = ',')]
+    #[arg(required = true)]
     #[arg(env = "PYTHNET_HTTP_ADDR")]
-    pub http_addr: String,
+    pub http_addrs: Vec<String>,
 
     /// Pythnet oracle program address.
     #[arg(long = "pythnet-oracle-program-addr")]
@@ -0,0 +1,15 @@
+use {clap::Args, std::path::PathBuf};
+
+#[derive(Args, Clone, Debug)]
+#[command(next_help_heading = "Record Options")]
+#[group(id = "Record")]
+pub struct Options {
+    /// Path of a capture file to record the received updates to.
+    ///
+    /// Every VAA and accumulator update handed to the aggregate store is appended to the file
+    /// together with its receive time, so the traffic can later be fed back to Hermes with the
+    /// `replay` command.
+    #[arg(long = "record-path")]
+    #[arg(env = "RECORD_PATH")]
+    pub path: Option<PathBuf>,
+}
@@ -0,0 +1,22 @@
+use {clap::Args, std::path::PathBuf};
+
+const DEFAULT_REPLAY_SPEED: &str = "1";
+
+#[derive(Args, Clone, Debug)]
+#[command(next_help_heading = "Replay Options")]
+#[group(id = "Replay")]
+pub struct Options {
+    /// Path of the capture file to replay, as recorded with `run --record-path`.
+    #[arg(long = "replay-path")]
+    #[arg(env = "REPLAY_PATH")]
+    pub path: PathBuf,
+
+    /// Speed of the replay relative to the recorded receive times.
+    ///
+    /// For example 10 replays the capture ten times faster than it was recorded. Set it to 0 to
+    /// replay the capture as fast as possible.
+    #[arg(long = "replay-speed")]
+    #[arg(default_value = DEFAULT_REPLAY_SPEED)]
+    #[arg(env = "REPLAY_SPEED")]
+    pub speed: f64,
+}
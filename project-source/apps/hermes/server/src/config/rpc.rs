@@ -1,4 +1,7 @@
-use {clap::Args, ipnet::IpNet, std::net::SocketAddr};
+use {
+    crate::state::aggregate::derived_feeds::DerivedFeed, clap::Args, ipnet::IpNet,
+    std::net::SocketAddr,
+};
 
 const DEFAULT_RPC_LISTEN_ADDR: &str = "127.0.0.1:33999";
 const DEFAULT_RPC_REQUESTER_IP_HEADER_NAME: &str = "X-Forwarded-For";
@@ -24,4 +27,15 @@ pub struct Options {
     #[arg(default_value = DEFAULT_RPC_REQUESTER_IP_HEADER_NAME)]
     #[arg(env = "RPC_REQUESTER_IP_HEADER_NAME")]
     pub requester_ip_header_name: String,
+
+    /// Derived price feeds served by the price update endpoints (separated by comma).
+    ///
+    /// Each feed is defined as `<name>=<base_id>/<quote_id>[@<expo>]`, for example
+    /// `ETHBTC=<ETH/USD id>/<BTC/USD id>` for a cross rate or `USDJPY=1/<JPY/USD id>@-8` for an
+    /// inverted feed. The exponent defaults to the exponent of the base feed, or of the quote
+    /// feed for inverted feeds.
+    #[arg(long = "rpc-derived-feeds")]
+    #[arg(value_delimiter = ',')]
+    #[arg(env = "RPC_DERIVED_FEEDS")]
+    pub derived_feeds: Vec<DerivedFeed>,
 }
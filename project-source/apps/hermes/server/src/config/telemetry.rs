@@ -0,0 +1,27 @@
+use clap::Args;
+
+const DEFAULT_TELEMETRY_SERVICE_NAME: &str = "hermes";
+const DEFAULT_TELEMETRY_SAMPLE_RATIO: &str = "1.0";
+
+#[derive(Args, Clone, Debug)]
+#[command(next_help_heading = "Telemetry Options")]
+#[group(id = "Telemetry")]
+pub struct Options {
+    /// Address of an OpenTelemetry collector gRPC endpoint to export traces to, for example
+    /// `http://127.0.0.1:4317`. Traces are not exported if not set.
+    #[arg(long = "telemetry-otlp-endpoint")]
+    #[arg(env = "TELEMETRY_OTLP_ENDPOINT")]
+    pub otlp_endpoint: Option<String>,
+
+    /// Service name the exported traces are attributed to.
+    #[arg(long = "telemetry-service-name")]
+    #[arg(default_value = DEFAULT_TELEMETRY_SERVICE_NAME)]
+    #[arg(env = "TELEMETRY_SERVICE_NAME")]
+    pub service_name: String,
+
+    /// Ratio of the traces to export, between 0 and 1.
+    #[arg(long = "telemetry-sample-ratio")]
+    #[arg(default_value = DEFAULT_TELEMETRY_SAMPLE_RATIO)]
+    #[arg(env = "TELEMETRY_SAMPLE_RATIO")]
+    pub sample_ratio: f64,
+}
@@ -3,7 +3,6 @@ use {
     clap::{CommandFactory, Parser},
     futures::future::join_all,
     lazy_static::lazy_static,
-    std::io::IsTerminal,
     tokio::{spawn, sync::watch},
 };
 
@@ -13,6 +12,7 @@ mod metrics_server;
 mod network;
 mod serde;
 mod state;
+mod telemetry;
 
 lazy_static! {
     /// A static exit flag to indicate to running threads that we're shutting down. This is used to
@@ -28,19 +28,23 @@ lazy_static! {
 }
 
 /// Initialize the Application. This can be invoked either by real main, or by the Geyser plugin.
-#[tracing::instrument]
-async fn init() -> Result<()> {
+#[tracing::instrument(skip(opts))]
+async fn init(opts: config::Options) -> Result<()> {
     tracing::info!("Initializing Hermes...");
 
-    // Parse the command line arguments with StructOpt, will exit automatically on `--help` or
-    // with invalid arguments.
-    match config::Options::parse() {
+    match opts {
         config::Options::Run(opts) => {
             tracing::info!("Starting hermes service...");
 
             // The update broadcast channel is used to send store update notifications to the public API.
             let (update_tx, _) = tokio::sync::broadcast::channel(1000);
 
+            // Record the received updates if a capture file is configured.
+            let recorder = match &opts.record.path {
+                Some(path) => Some(state::aggregate::capture::Recorder::create(path).await?),
+                None => None,
+            };
+
             // Initialize a cache store with a 1000 element circular buffer.
             let state = state::new(
                 update_tx.clone(),
@@ -48,27 +52,47 @@ async fn init() -> Result<()> {
                 opts.benchmarks.endpoint.clone(),
                 opts.aggregate.readiness_staleness_threshold.into(),
                 opts.aggregate.readiness_max_allowed_slot_lag,
+                recorder,
             );
 
-            // Listen for Ctrl+C so we can set the exit flag and wait for a graceful shutdown.
-            spawn(async move {
-                tracing::info!("Registered shutdown signal handler...");
-                match tokio::signal::ctrl_c().await {
-                    Ok(()) => {
-                        tracing::info!("Shut down signal received, waiting for tasks...");
-                        let _ = EXIT.send(true);
-                    }
-                    Err(err) => tracing::warn!("failed to register shutdown signal handler: {err}"),
-                }
-            });
+            spawn_shutdown_signal_handler();
 
             // Spawn all worker tasks, and wait for all to complete (which will happen if a shutdown
             // signal has been observed).
             let tasks = join_all(vec![
                 spawn(network::wormhole::spawn(opts.clone(), state.clone())),
                 spawn(network::pythnet::spawn(opts.clone(), state.clone())),
-                spawn(metrics_server::run(opts.clone(), state.clone())),
-                spawn(api::spawn(opts.clone(), state.clone())),
+                spawn(metrics_server::run(opts.metrics.clone(), state.clone())),
+                spawn(api::spawn(opts.rpc.clone(), state.clone())),
+            ])
+            .await;
+
+            for task in tasks {
+                task??;
+            }
+        }
+
+        config::Options::Replay(opts) => {
+            tracing::info!("Starting hermes service in replay mode...");
+
+            let (update_tx, _) = tokio::sync::broadcast::channel(1000);
+            let state = state::new(
+                update_tx.clone(),
+                opts.cache.size_slots,
+                opts.benchmarks.endpoint.clone(),
+                opts.aggregate.readiness_staleness_threshold.into(),
+                opts.aggregate.readiness_max_allowed_slot_lag,
+                None,
+            );
+
+            spawn_shutdown_signal_handler();
+
+            // The captured updates replace the Pythnet and Wormhole listeners, the API and metrics
+            // are served as usual.
+            let tasks = join_all(vec![
+                spawn(network::replay::spawn(opts.replay.clone(), state.clone())),
+                spawn(metrics_server::run(opts.metrics.clone(), state.clone())),
+                spawn(api::spawn(opts.rpc.clone(), state.clone())),
             ])
             .await;
 
@@ -108,27 +132,35 @@ async fn init() -> Result<()> {
     Ok(())
 }
 
+/// Listen for Ctrl+C so we can set the exit flag and wait for a graceful shutdown.
+fn spawn_shutdown_signal_handler() {
+    spawn(async move {
+        tracing::info!("Registered shutdown signal handler...");
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => {
+                tracing::info!("Shut down signal received, waiting for tasks...");
+                let _ = EXIT.send(true);
+            }
+            Err(err) => tracing::warn!("failed to register shutdown signal handler: {err}"),
+        }
+    });
+}
+
 #[tokio::main]
 #[tracing::instrument]
 async fn main() -> Result<()> {
-    // Initialize a Tracing Subscriber
-    let fmt_builder = tracing_subscriber::fmt()
-        .with_file(false)
-        .with_line_number(true)
-        .with_thread_ids(true)
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .with_ansi(std::io::stderr().is_terminal());
-
-    // Use the compact formatter if we're in a terminal, otherwise use the JSON formatter.
-    if std::io::stderr().is_terminal() {
-        tracing::subscriber::set_global_default(fmt_builder.compact().finish())?;
-    } else {
-        tracing::subscriber::set_global_default(fmt_builder.json().finish())?;
-    }
+    // Parse the command line arguments with StructOpt, will exit automatically on `--help` or
+    // with invalid arguments.
+    let opts = config::Options::parse();
+
+    // Initialize a Tracing Subscriber, exporting the traces if an OTLP endpoint is configured.
+    telemetry::init(opts.telemetry())?;
 
     // Launch the application. If it fails, print the full backtrace and exit. RUST_BACKTRACE
     // should be set to 1 for this otherwise it will only print the top-level error.
-    if let Err(result) = init().await {
+    let result = init(opts).await;
+    telemetry::shutdown();
+    if let Err(result) = result {
         eprintln!("{}", result.backtrace());
         result.chain().for_each(|cause| eprintln!("{cause}"));
         std::process::exit(1);
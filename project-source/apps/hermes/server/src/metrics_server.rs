@@ -3,19 +3,19 @@
 //! This server serves metrics over /metrics in OpenMetrics format.
 
 use {
-    crate::{config::RunOptions, state::metrics::Metrics},
+    crate::{config, state::metrics::Metrics},
     anyhow::Result,
     axum::{extract::State, http::header, response::IntoResponse, routing::get, Router},
     std::sync::Arc,
 };
 
 #[tracing::instrument(skip(opts, state))]
-pub async fn run<S>(opts: RunOptions, state: Arc<S>) -> Result<()>
+pub async fn run<S>(opts: config::metrics::Options, state: Arc<S>) -> Result<()>
 where
     S: Metrics,
     S: Send + Sync + 'static,
 {
-    tracing::info!(endpoint = %opts.metrics.server_listen_addr, "Starting Metrics Server.");
+    tracing::info!(endpoint = %opts.server_listen_addr, "Starting Metrics Server.");
 
     let app = Router::new();
     let app = app
@@ -24,7 +24,7 @@ where
 
     // Binds the axum's server to the configured address and port. This is a blocking call and will
     // not return until the server is shutdown.
-    axum::Server::try_bind(&opts.metrics.server_listen_addr)?
+    axum::Server::try_bind(&opts.server_listen_addr)?
         .serve(app.into_make_service())
         .with_graceful_shutdown(async {
             let _ = crate::EXIT.subscribe().changed().await;
@@ -1,6 +1,10 @@
-//! This module connects to the Pythnet RPC server and listens for accumulator
+//! This module connects to the Pythnet RPC servers and listens for accumulator
 //! updates. It then sends the updates to the store module for processing and
 //! storage. It also periodically fetches and stores the latest price feeds metadata.
+//!
+//! Multiple Pythnet endpoints can be configured. Accumulator updates are ingested from all of
+//! them concurrently and the updates of a slot are stored once a quorum of endpoints delivered
+//! identical updates for it. HTTP requests fall back to the next endpoint when one fails.
 
 use {
     crate::{
@@ -8,16 +12,23 @@ use {
         config::RunOptions,
         network::wormhole::{BridgeData, GuardianSet, GuardianSetData},
         state::{
-            aggregate::{AccumulatorMessages, Aggregates, Update},
+            aggregate::{AccumulatorMessages, Aggregates, Slot, Update},
+            metrics::Metrics,
             price_feeds_metadata::{PriceFeedMeta, DEFAULT_PRICE_FEEDS_CACHE_UPDATE_INTERVAL},
             wormhole::Wormhole,
         },
     },
     anyhow::{anyhow, bail, Result},
     borsh::{BorshDeserialize, BorshSerialize},
-    futures::{stream::StreamExt, SinkExt},
+    futures::{future::join_all, stream::StreamExt, SinkExt},
+    prometheus_client::{
+        encoding::EncodeLabelSet,
+        metrics::{family::Family, gauge::Gauge},
+    },
     pyth_sdk::PriceIdentifier,
     pyth_sdk_solana::state::load_product_account,
+    reqwest::Url,
+    sha3::{Digest, Keccak256},
     solana_account_decoder::UiAccountEncoding,
     solana_client::{
         nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
@@ -27,12 +38,20 @@ use {
     solana_sdk::{
         account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, system_program,
     },
-    std::{collections::BTreeMap, sync::Arc, time::Duration},
-    tokio::time::Instant,
+    std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    },
+    tokio::{
+        sync::{Mutex, RwLock},
+        time::Instant,
+    },
     tokio_tungstenite::{
         connect_async,
         tungstenite::{client::IntoClientRequest, Message},
     },
+    tracing::Instrument,
 };
 
 /// Using a Solana RPC endpoint, fetches the target GuardianSet based on an index.
@@ -107,7 +126,187 @@ async fn fetch_bridge_data(
     }
 }
 
-pub async fn run<S>(store: Arc<S>, pythnet_ws_endpoint: String) -> Result<()>
+/// How often each websocket listener checks whether its endpoint has fallen behind the others.
+const ENDPOINT_STALL_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Number of slots behind the latest slot for which the agreement of the endpoints is tracked.
+/// Deliveries for older slots are dropped.
+const SLOT_QUORUM_WINDOW: Slot = 64;
+
+/// Tracks which endpoints delivered which accumulator messages for the recent slots.
+///
+/// The messages of a slot are released once `quorum` distinct endpoints delivered identical
+/// messages (compared by their hash) for it. Each slot is released at most once.
+pub struct SlotQuorum {
+    quorum: usize,
+    deliveries: BTreeMap<Slot, SlotDeliveries>,
+}
+
+#[derive(Default)]
+struct SlotDeliveries {
+    released: bool,
+    endpoints_by_hash: HashMap<[u8; 32], HashSet<usize>>,
+}
+
+impl SlotQuorum {
+    pub fn new(quorum: usize) -> Self {
+        Self {
+            quorum,
+            deliveries: BTreeMap::new(),
+        }
+    }
+
+    /// Record that the endpoint at `endpoint_index` delivered `data` for `slot`. Returns `true`
+    /// if this delivery completes the quorum for the slot, in which case the data must be stored.
+    pub fn observe(&mut self, endpoint_index: usize, slot: Slot, data: &[u8]) -> bool {
+        let latest_slot = self
+            .deliveries
+            .last_key_value()
+            .map_or(slot, |(latest, _)| (*latest).max(slot));
+        let oldest_tracked_slot = latest_slot.saturating_sub(SLOT_QUORUM_WINDOW);
+        if slot < oldest_tracked_slot {
+            return false;
+        }
+        self.deliveries = self.deliveries.split_off(&oldest_tracked_slot);
+
+        let deliveries = self.deliveries.entry(slot).or_default();
+        if deliveries.released {
+            return false;
+        }
+
+        let hash: [u8; 32] = Keccak256::digest(data).into();
+        let endpoints = deliveries.endpoints_by_hash.entry(hash).or_default();
+        endpoints.insert(endpoint_index);
+        if endpoints.len() >= self.quorum {
+            deliveries.released = true;
+            deliveries.endpoints_by_hash.clear();
+            return true;
+        }
+        false
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct EndpointLabels {
+    pub index: String,
+    pub host: String,
+}
+
+/// Tracks the latest slot received from each Pythnet websocket endpoint.
+///
+/// The lag of an endpoint is the difference between the highest slot received from any endpoint
+/// and the highest slot received from that endpoint. It is exported as a metric and is used by
+/// the listeners to detect and reconnect stalled endpoints.
+///
+/// It also checks the agreement of the endpoints on the accumulator messages of each slot.
+pub struct PythnetEndpoints {
+    labels: Vec<EndpointLabels>,
+    latest_slots: RwLock<Vec<Option<Slot>>>,
+    slot_quorum: Mutex<SlotQuorum>,
+    latest_slot_metric: Family<EndpointLabels, Gauge>,
+    slot_lag_metric: Family<EndpointLabels, Gauge>,
+}
+
+impl PythnetEndpoints {
+    pub fn new<S>(ws_addrs: &[String], quorum: usize, state: Arc<S>) -> Self
+    where
+        S: Metrics,
+        S: Send + Sync + 'static,
+    {
+        let labels = ws_addrs
+            .iter()
+            .enumerate()
+            .map(|(index, addr)| EndpointLabels {
+                index: index.to_string(),
+                // Only the host is used as the label, the full address might contain API keys.
+                host: Url::parse(addr)
+                    .ok()
+                    .and_then(|url| url.host_str().map(ToOwned::to_owned))
+                    .unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+
+        let new = Self {
+            latest_slots: RwLock::new(vec![None; labels.len()]),
+            slot_quorum: Mutex::new(SlotQuorum::new(quorum)),
+            labels,
+            latest_slot_metric: Family::default(),
+            slot_lag_metric: Family::default(),
+        };
+
+        {
+            let latest_slot_metric = new.latest_slot_metric.clone();
+            let slot_lag_metric = new.slot_lag_metric.clone();
+
+            tokio::spawn(async move {
+                Metrics::register(
+                    &*state,
+                    (
+                        "pythnet_endpoint_latest_slot",
+                        "Latest accumulator slot received from each Pythnet endpoint",
+                        latest_slot_metric,
+                    ),
+                )
+                .await;
+
+                Metrics::register(
+                    &*state,
+                    (
+                        "pythnet_endpoint_slot_lag",
+                        "Number of slots each Pythnet endpoint is behind the most advanced endpoint",
+                        slot_lag_metric,
+                    ),
+                )
+                .await;
+            });
+        }
+
+        new
+    }
+
+    /// Record a slot received from the endpoint at `index` and refresh the lag of all endpoints.
+    pub async fn observe(&self, index: usize, slot: Slot) {
+        let mut latest_slots = self.latest_slots.write().await;
+        if let Some(latest) = latest_slots.get_mut(index) {
+            *latest = Some(latest.map_or(slot, |latest| latest.max(slot)));
+        }
+
+        let max_slot = latest_slots.iter().flatten().max().copied();
+        for (labels, latest) in self.labels.iter().zip(latest_slots.iter()) {
+            if let (Some(latest), Some(max_slot)) = (latest, max_slot) {
+                self.latest_slot_metric
+                    .get_or_create(labels)
+                    .set(i64::try_from(*latest).unwrap_or(i64::MAX));
+                self.slot_lag_metric
+                    .get_or_create(labels)
+                    .set(i64::try_from(max_slot - latest).unwrap_or(i64::MAX));
+            }
+        }
+    }
+
+    /// Record the accumulator messages `data` received from the endpoint at `index` for `slot`.
+    /// Returns `true` if they reached the quorum and must be stored.
+    pub async fn reach_quorum(&self, index: usize, slot: Slot, data: &[u8]) -> bool {
+        self.slot_quorum.lock().await.observe(index, slot, data)
+    }
+
+    /// Number of slots the endpoint at `index` is behind the most advanced endpoint. Returns
+    /// `None` if no slot has been received from any endpoint yet.
+    pub async fn lag(&self, index: usize) -> Option<Slot> {
+        let latest_slots = self.latest_slots.read().await;
+        let max_slot = latest_slots.iter().flatten().max().copied()?;
+        let latest = latest_slots.get(index).copied().flatten().unwrap_or(0);
+        Some(max_slot.saturating_sub(latest))
+    }
+}
+
+pub async fn run<S>(
+    store: Arc<S>,
+    pythnet_ws_endpoint: String,
+    endpoints: Arc<PythnetEndpoints>,
+    endpoint_index: usize,
+    max_allowed_slot_lag: Slot,
+) -> Result<()>
 where
     S: Aggregates,
     S: Wormhole,
@@ -133,7 +332,32 @@ where
         .program_subscribe(&system_program::id(), Some(config))
         .await?;
 
-    while let Some(update) = notif.next().await {
+    let mut stall_check = tokio::time::interval_at(
+        Instant::now() + ENDPOINT_STALL_CHECK_INTERVAL,
+        ENDPOINT_STALL_CHECK_INTERVAL,
+    );
+
+    loop {
+        let update = tokio::select! {
+            update = notif.next() => match update {
+                Some(update) => update,
+                None => break,
+            },
+            _ = stall_check.tick() => {
+                // If the other endpoints are ahead of this one by more than the lag we tolerate
+                // for readiness, this subscription is considered stalled and is recycled.
+                match endpoints.lag(endpoint_index).await {
+                    Some(lag) if lag > max_allowed_slot_lag => {
+                        return Err(anyhow!(
+                            "Pythnet endpoint is {} slots behind the other endpoints",
+                            lag
+                        ));
+                    }
+                    _ => continue,
+                }
+            }
+        };
+
         let account: Account = match update.value.account.decode() {
             Some(account) => account,
             None => {
@@ -154,17 +378,38 @@ where
                 );
 
                 if candidate.to_string() == update.value.pubkey {
-                    let store = store.clone();
-                    tokio::spawn(async move {
-                        if let Err(err) = Aggregates::store_update(
-                            &*store,
-                            Update::AccumulatorMessages(accumulator_messages),
-                        )
+                    endpoints
+                        .observe(endpoint_index, accumulator_messages.slot)
+                        .await;
+
+                    if !endpoints
+                        .reach_quorum(endpoint_index, accumulator_messages.slot, &account.data)
                         .await
-                        {
-                            tracing::error!(error = ?err, "Failed to store accumulator messages.");
+                    {
+                        continue;
+                    }
+
+                    // The receipt of the accumulator messages starts the trace of the slot, it is
+                    // propagated through the aggregation to the delivery of the price updates.
+                    let span = tracing::info_span!(
+                        "receive_accumulator_messages",
+                        slot = accumulator_messages.slot,
+                        endpoint = endpoint_index
+                    );
+                    let store = store.clone();
+                    tokio::spawn(
+                        async move {
+                            if let Err(err) = Aggregates::store_update(
+                                &*store,
+                                Update::AccumulatorMessages(accumulator_messages),
+                            )
+                            .await
+                            {
+                                tracing::error!(error = ?err, "Failed to store accumulator messages.");
+                            }
                         }
-                    });
+                        .instrument(span),
+                    );
                 } else {
                     tracing::error!(
                         ?candidate,
@@ -188,9 +433,39 @@ where
 /// This method performs the necessary work to pull down the bridge state and associated guardian
 /// sets from a deployed Wormhole contract. Note that we only fetch the last two accounts due to
 /// the fact that during a Wormhole upgrade, there will only be messages produces from those two.
+///
+/// The endpoints are tried in order until one of them succeeds.
 async fn fetch_existing_guardian_sets<S>(
     state: Arc<S>,
-    pythnet_http_endpoint: String,
+    pythnet_http_endpoints: &[String],
+    wormhole_contract_addr: Pubkey,
+) -> Result<()>
+where
+    S: Wormhole,
+    S: Send + Sync + 'static,
+{
+    let mut last_err = anyhow!("No Pythnet HTTP endpoint configured");
+    for pythnet_http_endpoint in pythnet_http_endpoints {
+        match fetch_existing_guardian_sets_from(
+            state.clone(),
+            pythnet_http_endpoint,
+            wormhole_contract_addr,
+        )
+        .await
+        {
+            Ok(()) => return Ok(()),
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to fetch guardian sets, trying next Pythnet endpoint.");
+                last_err = err;
+            }
+        }
+    }
+    Err(last_err)
+}
+
+async fn fetch_existing_guardian_sets_from<S>(
+    state: Arc<S>,
+    pythnet_http_endpoint: &str,
     wormhole_contract_addr: Pubkey,
 ) -> Result<()>
 where
@@ -237,13 +512,23 @@ where
 pub async fn fetch_and_store_price_feeds_metadata<S>(
     state: &S,
     oracle_program_address: &Pubkey,
-    rpc_client: &RpcClient,
+    rpc_clients: &[RpcClient],
 ) -> Result<Vec<PriceFeedMetadata>>
 where
     S: PriceFeedMeta + Aggregates,
 {
-    let price_feeds_metadata =
-        fetch_price_feeds_metadata(oracle_program_address, rpc_client).await?;
+    let mut price_feeds_metadata = Err(anyhow!("No Pythnet HTTP endpoint configured"));
+    for rpc_client in rpc_clients {
+        price_feeds_metadata = fetch_price_feeds_metadata(oracle_program_address, rpc_client).await;
+        match &price_feeds_metadata {
+            Ok(_) => break,
+            Err(err) => tracing::warn!(
+                error = ?err,
+                "Failed to fetch price feeds metadata, trying next Pythnet endpoint."
+            ),
+        }
+    }
+    let price_feeds_metadata = price_feeds_metadata?;
 
     // Wait for the crosschain price feed ids to be available in the state
     // This is to prune the price feeds that are not available crosschain yet (i.e. they are coming soon)
@@ -337,45 +622,80 @@ async fn fetch_price_feeds_metadata(
 pub async fn spawn<S>(opts: RunOptions, state: Arc<S>) -> Result<()>
 where
     S: Wormhole,
+    S: Metrics,
     S: Send + Sync + 'static,
 {
-    tracing::info!(endpoint = opts.pythnet.ws_addr, "Started Pythnet Listener.");
+    tracing::info!(endpoints = ?opts.pythnet.ws_addrs, "Started Pythnet Listener.");
 
-    // Create RpcClient instance here
-    let rpc_client = RpcClient::new(opts.pythnet.http_addr.clone());
+    // Create RpcClient instances here
+    let rpc_clients = opts
+        .pythnet
+        .http_addrs
+        .iter()
+        .map(|http_addr| RpcClient::new(http_addr.clone()))
+        .collect::<Vec<_>>();
 
     fetch_existing_guardian_sets(
         state.clone(),
-        opts.pythnet.http_addr.clone(),
+        &opts.pythnet.http_addrs,
         opts.wormhole.contract_addr,
     )
     .await?;
 
-    let task_listener = {
-        let store = state.clone();
-        let pythnet_ws_endpoint = opts.pythnet.ws_addr.clone();
-        let mut exit = crate::EXIT.subscribe();
-        tokio::spawn(async move {
-            loop {
-                let current_time = Instant::now();
-                tokio::select! {
-                    _ = exit.changed() => break,
-                    Err(err) = run(store.clone(), pythnet_ws_endpoint.clone()) => {
-                        tracing::error!(error = ?err, "Error in Pythnet network listener.");
-                        if current_time.elapsed() < Duration::from_secs(30) {
-                            tracing::error!("Pythnet listener restarting too quickly. Sleep 1s.");
-                            tokio::time::sleep(Duration::from_secs(1)).await;
+    if opts.pythnet.ws_quorum == 0 || opts.pythnet.ws_quorum > opts.pythnet.ws_addrs.len() {
+        bail!(
+            "The Pythnet websocket quorum must be between 1 and the number of websocket endpoints ({})",
+            opts.pythnet.ws_addrs.len()
+        );
+    }
+
+    let endpoints = Arc::new(PythnetEndpoints::new(
+        &opts.pythnet.ws_addrs,
+        opts.pythnet.ws_quorum,
+        state.clone(),
+    ));
+
+    // Every websocket endpoint gets its own listener. Updates for the same slot coming from
+    // different endpoints are stored once they reach the quorum.
+    let task_listeners = opts
+        .pythnet
+        .ws_addrs
+        .iter()
+        .enumerate()
+        .map(|(endpoint_index, pythnet_ws_endpoint)| {
+            let store = state.clone();
+            let endpoints = endpoints.clone();
+            let pythnet_ws_endpoint = pythnet_ws_endpoint.clone();
+            let max_allowed_slot_lag = opts.aggregate.readiness_max_allowed_slot_lag;
+            let mut exit = crate::EXIT.subscribe();
+            tokio::spawn(async move {
+                loop {
+                    let current_time = Instant::now();
+                    tokio::select! {
+                        _ = exit.changed() => break,
+                        Err(err) = run(
+                            store.clone(),
+                            pythnet_ws_endpoint.clone(),
+                            endpoints.clone(),
+                            endpoint_index,
+                            max_allowed_slot_lag,
+                        ) => {
+                            tracing::error!(error = ?err, endpoint_index, "Error in Pythnet network listener.");
+                            if current_time.elapsed() < Duration::from_secs(30) {
+                                tracing::error!(endpoint_index, "Pythnet listener restarting too quickly. Sleep 1s.");
+                                tokio::time::sleep(Duration::from_secs(1)).await;
+                            }
                         }
                     }
                 }
-            }
-            tracing::info!("Shutting down Pythnet listener...");
+                tracing::info!(endpoint_index, "Shutting down Pythnet listener...");
+            })
         })
-    };
+        .collect::<Vec<_>>();
 
     let task_guardian_watcher = {
         let store = state.clone();
-        let pythnet_http_endpoint = opts.pythnet.http_addr.clone();
+        let pythnet_http_endpoints = opts.pythnet.http_addrs.clone();
         let mut exit = crate::EXIT.subscribe();
         tokio::spawn(async move {
             loop {
@@ -384,7 +704,7 @@ where
                     _ = tokio::time::sleep(Duration::from_secs(60)) => {
                         if let Err(err) = fetch_existing_guardian_sets(
                             store.clone(),
-                            pythnet_http_endpoint.clone(),
+                            &pythnet_http_endpoints,
                             opts.wormhole.contract_addr,
                         )
                         .await
@@ -407,7 +727,7 @@ where
             if let Err(e) = fetch_and_store_price_feeds_metadata(
                 price_feeds_state.as_ref(),
                 &opts.pythnet.oracle_program_addr,
-                &rpc_client,
+                &rpc_clients,
             )
             .await
             {
@@ -424,7 +744,7 @@ where
                         if let Err(e) = fetch_and_store_price_feeds_metadata(
                             price_feeds_state.as_ref(),
                             &opts.pythnet.oracle_program_addr,
-                            &rpc_client,
+                            &rpc_clients,
                         )
                         .await
                         {
@@ -465,7 +785,7 @@ where
     };
 
     let _ = tokio::join!(
-        task_listener,
+        join_all(task_listeners),
         task_guardian_watcher,
         task_price_feeds_metadata_updater,
         task_quorum_listener,
@@ -531,3 +851,44 @@ where
     }
     Err(anyhow!("Pyth quorum stream terminated."))
 }
+
+#[cfg(test)]
+mod test {
+    use super::{SlotQuorum, SLOT_QUORUM_WINDOW};
+
+    #[test]
+    fn test_slot_quorum_of_one_releases_first_delivery() {
+        let mut quorum = SlotQuorum::new(1);
+        assert!(quorum.observe(0, 10, b"messages"));
+        assert!(!quorum.observe(1, 10, b"messages"));
+        assert!(!quorum.observe(0, 10, b"messages"));
+        assert!(quorum.observe(1, 11, b"messages"));
+    }
+
+    #[test]
+    fn test_slot_quorum_requires_agreement() {
+        let mut quorum = SlotQuorum::new(2);
+
+        // The same endpoint delivering twice does not count twice.
+        assert!(!quorum.observe(0, 10, b"messages"));
+        assert!(!quorum.observe(0, 10, b"messages"));
+
+        // An endpoint delivering different messages does not count either.
+        assert!(!quorum.observe(1, 10, b"forged"));
+
+        // The quorum is reached once a second endpoint agrees, and only once.
+        assert!(quorum.observe(2, 10, b"messages"));
+        assert!(!quorum.observe(1, 10, b"messages"));
+    }
+
+    #[test]
+    fn test_slot_quorum_drops_old_slots() {
+        let mut quorum = SlotQuorum::new(2);
+        assert!(!quorum.observe(0, 10, b"messages"));
+        assert!(!quorum.observe(0, 10 + SLOT_QUORUM_WINDOW + 1, b"messages"));
+
+        // Slot 10 is out of the window, its deliveries were dropped.
+        assert!(!quorum.observe(1, 10, b"messages"));
+        assert!(quorum.observe(1, 10 + SLOT_QUORUM_WINDOW + 1, b"messages"));
+    }
+}
@@ -0,0 +1,74 @@
+//! Replay of a capture file recorded with `run --record-path`.
+//!
+//! The captured updates are fed to the aggregate store in order, spaced by their recorded receive
+//! times divided by the replay speed, as if they were received from Pythnet and Wormhole.
+
+use {
+    crate::{
+        config::replay,
+        state::aggregate::{capture::CaptureReader, Aggregates, Update},
+    },
+    anyhow::{ensure, Result},
+    std::{sync::Arc, time::Duration},
+    tokio::time::Instant,
+};
+
+#[tracing::instrument(skip(opts, state))]
+pub async fn spawn<S>(opts: replay::Options, state: Arc<S>) -> Result<()>
+where
+    S: Aggregates,
+    S: Send + Sync + 'static,
+{
+    ensure!(
+        opts.speed.is_finite() && opts.speed >= 0.0,
+        "Replay speed must be a non-negative number, got {}",
+        opts.speed
+    );
+
+    let mut exit = crate::EXIT.subscribe();
+    tokio::select! {
+        _ = exit.changed() => {
+            tracing::info!("Shutting down capture replay...");
+            Ok(())
+        }
+        result = run(opts, state) => result,
+    }
+}
+
+async fn run<S>(opts: replay::Options, state: Arc<S>) -> Result<()>
+where
+    S: Aggregates,
+{
+    tracing::info!(path = %opts.path.display(), speed = opts.speed, "Started Capture Replay.");
+    let mut reader = CaptureReader::open(&opts.path).await?;
+
+    // The receive time of the first update and the instant it was replayed at, every other update
+    // is replayed relative to them.
+    let mut start: Option<(u64, Instant)> = None;
+    let mut replayed = 0usize;
+
+    while let Some(captured_update) = reader.next_update().await? {
+        let (first_received_at_us, started_at) =
+            *start.get_or_insert((captured_update.received_at_us, Instant::now()));
+
+        if opts.speed > 0.0 {
+            let elapsed_us = captured_update
+                .received_at_us
+                .saturating_sub(first_received_at_us);
+            let delay = Duration::from_micros(elapsed_us).div_f64(opts.speed);
+            tokio::time::sleep_until(started_at + delay).await;
+        }
+
+        let update = Update::try_from(captured_update.update)?;
+        if let Err(err) = Aggregates::store_update(&*state, update).await {
+            tracing::error!(error = ?err, "Failed to store replayed update.");
+        }
+        replayed += 1;
+    }
+
+    tracing::info!(
+        updates = replayed,
+        "Capture replay completed, the API keeps serving the replayed state."
+    );
+    Ok(())
+}
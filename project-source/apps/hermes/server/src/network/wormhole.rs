@@ -26,6 +26,13 @@ pub struct GuardianSet {
     pub keys: Vec<[u8; 20]>,
 }
 
+impl GuardianSet {
+    /// The number of signatures required for a VAA to be valid, more than two thirds of the keys.
+    pub fn quorum(&self) -> usize {
+        (self.keys.len() * 2) / 3 + 1
+    }
+}
+
 impl std::fmt::Display for GuardianSet {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
@@ -2,7 +2,7 @@
 
 use {
     self::{
-        aggregate::{AggregateState, AggregationEvent},
+        aggregate::{capture::Recorder, AggregateState, AggregationEvent},
         benchmarks::BenchmarksState,
         cache::CacheState,
         metrics::MetricsState,
@@ -60,6 +60,7 @@ pub fn new(
     benchmarks_endpoint: Option<Url>,
     readiness_staleness_threshold: Duration,
     readiness_max_allowed_slot_lag: Slot,
+    recorder: Option<Recorder>,
 ) -> Arc<impl Metrics + Wormhole> {
     let mut metrics_registry = Registry::default();
     Arc::new(State {
@@ -70,6 +71,7 @@ pub fn new(
             update_tx,
             readiness_staleness_threshold,
             readiness_max_allowed_slot_lag,
+            recorder,
             &mut metrics_registry,
         ),
         wormhole: WormholeState::new(),
@@ -80,7 +82,10 @@ pub fn new(
 #[cfg(test)]
 pub mod test {
     use {
-        super::{aggregate::AggregationEvent, Aggregates, Wormhole},
+        super::{
+            aggregate::{capture::Recorder, AggregationEvent},
+            Aggregates, Wormhole,
+        },
         crate::network::wormhole::GuardianSet,
         std::{sync::Arc, time::Duration},
         tokio::sync::broadcast::Receiver,
@@ -88,9 +93,23 @@ pub mod test {
 
     pub async fn setup_state(
         cache_size: usize,
+    ) -> (Arc<impl Aggregates>, Receiver<AggregationEvent>) {
+        setup_state_with_recorder(cache_size, None).await
+    }
+
+    pub async fn setup_state_with_recorder(
+        cache_size: usize,
+        recorder: Option<Recorder>,
     ) -> (Arc<impl Aggregates>, Receiver<AggregationEvent>) {
         let (update_tx, update_rx) = tokio::sync::broadcast::channel(1000);
-        let state = super::new(update_tx, cache_size, None, Duration::from_secs(30), 10);
+        let state = super::new(
+            update_tx,
+            cache_size,
+            None,
+            Duration::from_secs(30),
+            10,
+            recorder,
+        );
 
         // Add an initial guardian set with public key 0
         Wormhole::update_guardian_set(
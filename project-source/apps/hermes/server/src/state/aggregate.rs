@@ -8,9 +8,13 @@ use pythnet_sdk::messages::TwapMessage;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use {
-    self::wormhole_merkle::{
-        construct_message_states_proofs, construct_update_data,
-        store_wormhole_merkle_verified_message, WormholeMerkleMessageProof, WormholeMerkleState,
+    self::{
+        capture::Recorder,
+        wormhole_merkle::{
+            construct_message_states_proofs, construct_update_data,
+            store_wormhole_merkle_verified_message, WormholeMerkleMessageProof,
+            WormholeMerkleState,
+        },
     },
     crate::{
         api::types::{ParsedPublisherStakeCap, ParsedPublisherStakeCapsUpdate},
@@ -25,6 +29,7 @@ use {
     anyhow::{anyhow, Result},
     borsh::BorshDeserialize,
     byteorder::BigEndian,
+    opentelemetry::trace::TraceContextExt,
     prometheus_client::registry::Registry,
     pyth_sdk::{Price, PriceFeed, PriceIdentifier},
     pythnet_sdk::{
@@ -37,13 +42,22 @@ use {
     rust_decimal::Decimal,
     serde::Serialize,
     solana_sdk::pubkey::Pubkey,
-    std::{collections::HashSet, time::Duration},
-    tokio::sync::{
-        broadcast::{Receiver, Sender},
-        RwLock,
+    std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        time::Duration,
+    },
+    tokio::{
+        sync::{
+            broadcast::{Receiver, Sender},
+            RwLock,
+        },
+        time::Instant,
     },
+    tracing_opentelemetry::OpenTelemetrySpanExt,
     wormhole_sdk::Vaa,
 };
+pub mod capture;
+pub mod derived_feeds;
 pub mod metrics;
 pub mod wormhole_merkle;
 
@@ -69,22 +83,52 @@ pub enum RequestTime {
 
 pub type RawMessage = Vec<u8>;
 
+const MAX_OBSERVED_SLOT_TRACE_CONTEXTS: usize = 1000;
+
 /// An event that is emitted when an aggregation is completed.
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, Debug)]
 pub enum AggregationEvent {
-    New { slot: Slot },
-    OutOfOrder { slot: Slot },
+    New {
+        slot: Slot,
+        trace_context: TraceContext,
+    },
+    OutOfOrder {
+        slot: Slot,
+        trace_context: TraceContext,
+    },
 }
 
 impl AggregationEvent {
     pub fn slot(&self) -> Slot {
         match self {
-            AggregationEvent::New { slot } => *slot,
-            AggregationEvent::OutOfOrder { slot } => *slot,
+            AggregationEvent::New { slot, .. } => *slot,
+            AggregationEvent::OutOfOrder { slot, .. } => *slot,
+        }
+    }
+
+    /// The trace context of the update that completed the aggregation.
+    pub fn trace_context(&self) -> &TraceContext {
+        match self {
+            AggregationEvent::New { trace_context, .. } => trace_context,
+            AggregationEvent::OutOfOrder { trace_context, .. } => trace_context,
         }
     }
 }
 
+/// The trace context of the update that completed an aggregation. The spans delivering the event
+/// to the clients are its children, so a trace follows an update from its receipt to its delivery.
+///
+/// The context is carried instead of the span itself as a span is only exported once all its
+/// handles are dropped.
+#[derive(Clone, Debug, Default)]
+pub struct TraceContext(pub opentelemetry::Context);
+
+impl TraceContext {
+    pub fn current() -> Self {
+        Self(tracing::Span::current().context())
+    }
+}
+
 pub struct AggregateStateData {
     /// The latest completed slot. This is used to check whether a completed state is new or out of
     /// order.
@@ -108,6 +152,11 @@ pub struct AggregateStateData {
 
     /// Aggregate Specific Metrics
     pub metrics: metrics::Metrics,
+
+    /// The trace context of the first update observed at each recent slot. The span of the update
+    /// completing the slot is linked to it so a trace covers both the accumulator messages and the
+    /// VAA.
+    pub observed_slot_trace_contexts: BTreeMap<Slot, TraceContext>,
 }
 
 impl AggregateStateData {
@@ -123,6 +172,18 @@ impl AggregateStateData {
             metrics: metrics::Metrics::new(metrics_registry),
             readiness_staleness_threshold,
             readiness_max_allowed_slot_lag,
+            observed_slot_trace_contexts: BTreeMap::new(),
+        }
+    }
+
+    /// Keep the current trace context if it is the first one observed at the slot.
+    fn observe_slot_trace_context(&mut self, slot: Slot) {
+        self.observed_slot_trace_contexts
+            .entry(slot)
+            .or_insert_with(TraceContext::current);
+
+        while self.observed_slot_trace_contexts.len() > MAX_OBSERVED_SLOT_TRACE_CONTEXTS {
+            self.observed_slot_trace_contexts.pop_first();
         }
     }
 }
@@ -130,6 +191,9 @@ impl AggregateStateData {
 pub struct AggregateState {
     pub data: RwLock<AggregateStateData>,
     pub api_update_tx: Sender<AggregationEvent>,
+
+    /// Records every update handed to the store when Hermes runs with a capture file.
+    pub recorder: Option<Recorder>,
 }
 
 impl AggregateState {
@@ -137,6 +201,7 @@ impl AggregateState {
         update_tx: Sender<AggregationEvent>,
         readiness_staleness_threshold: Duration,
         readiness_max_allowed_slot_lag: Slot,
+        recorder: Option<Recorder>,
         metrics_registry: &mut Registry,
     ) -> Self {
         Self {
@@ -146,6 +211,7 @@ impl AggregateState {
                 metrics_registry,
             )),
             api_update_tx: update_tx,
+            recorder,
         }
     }
 }
@@ -235,13 +301,18 @@ where
     async fn is_ready(&self) -> (bool, ReadinessMetadata);
     async fn store_update(&self, update: Update) -> Result<()>;
     async fn get_price_feed_ids(&self) -> HashSet<PriceIdentifier>;
+    async fn get_latest_publish_times(
+        &self,
+        price_ids: &[PriceIdentifier],
+    ) -> Result<HashMap<PriceIdentifier, UnixTimestamp>>;
     async fn get_price_feeds_with_update_data(
         &self,
         price_ids: &[PriceIdentifier],
         request_time: RequestTime,
     ) -> Result<PriceFeedsWithUpdateData>;
-    async fn get_latest_publisher_stake_caps_with_update_data(
+    async fn get_publisher_stake_caps_with_update_data(
         &self,
+        request_time: RequestTime,
     ) -> Result<PublisherStakeCapsWithUpdateData>;
     async fn get_twaps_with_update_data(
         &self,
@@ -273,8 +344,13 @@ where
     }
 
     /// Stores the update data in the store
-    #[tracing::instrument(skip(self, update))]
+    #[tracing::instrument(skip(self, update), fields(slot))]
     async fn store_update(&self, update: Update) -> Result<()> {
+        // Record the update before any deduplication so the capture reflects the received traffic.
+        if let Some(recorder) = &self.into().recorder {
+            recorder.record(&update);
+        }
+
         // The slot that the update is originating from. It should be available
         // in all the updates.
         let slot = match update {
@@ -303,12 +379,11 @@ where
                             return Ok(());
                         }
 
-                        self.into()
-                            .data
-                            .write()
-                            .await
+                        let mut aggregate_state = self.into().data.write().await;
+                        aggregate_state
                             .metrics
                             .observe(proof.slot, metrics::Event::Vaa);
+                        aggregate_state.observe_slot_trace_context(proof.slot);
 
                         proof.slot
                     }
@@ -334,16 +409,17 @@ where
                     return Ok(());
                 }
 
-                self.into()
-                    .data
-                    .write()
-                    .await
+                let mut aggregate_state = self.into().data.write().await;
+                aggregate_state
                     .metrics
                     .observe(slot, metrics::Event::AccumulatorMessages);
+                aggregate_state.observe_slot_trace_context(slot);
                 slot
             }
         };
 
+        tracing::Span::current().record("slot", slot);
+
         // Update the aggregate state with the latest observed slot
         {
             let mut aggregate_state = self.into().data.write().await;
@@ -365,6 +441,7 @@ where
 
         // Once the accumulator reaches a complete state for a specific slot
         // we can build the message states
+        let build_start = Instant::now();
         let message_states = build_message_states(accumulator_messages, wormhole_merkle_state)?;
 
         let message_state_keys = message_states
@@ -374,22 +451,45 @@ where
 
         tracing::info!(len = message_states.len(), "Storing Message States.");
         self.store_message_states(message_states).await?;
+        let build_latency = build_start.elapsed();
 
         // Update the aggregate state
         let mut aggregate_state = self.into().data.write().await;
+        aggregate_state
+            .metrics
+            .observe_message_states_build(build_latency);
+
+        // The update completing the slot carries the trace to the clients, it is linked to the
+        // first update observed at the slot.
+        if let Some(observed_trace_context) =
+            aggregate_state.observed_slot_trace_contexts.remove(&slot)
+        {
+            tracing::Span::current()
+                .add_link(observed_trace_context.0.span().span_context().clone());
+        }
+        let trace_context = TraceContext::current();
 
         // Atomic check and update
         let event = match aggregate_state.latest_completed_slot {
             None => {
                 aggregate_state.latest_completed_slot = Some(slot);
-                AggregationEvent::New { slot }
+                AggregationEvent::New {
+                    slot,
+                    trace_context,
+                }
             }
             Some(latest) if slot > latest => {
                 self.prune_removed_keys(message_state_keys).await;
                 aggregate_state.latest_completed_slot = Some(slot);
-                AggregationEvent::New { slot }
+                AggregationEvent::New {
+                    slot,
+                    trace_context,
+                }
             }
-            _ => AggregationEvent::OutOfOrder { slot },
+            _ => AggregationEvent::OutOfOrder {
+                slot,
+                trace_context,
+            },
         };
 
         // Only send the event after the state has been updated
@@ -446,13 +546,17 @@ where
         }
     }
 
-    async fn get_latest_publisher_stake_caps_with_update_data(
+    /// Get the publisher stake caps update at the given time. Publisher stake caps are only
+    /// served from the cache as the benchmarks service does not store them, so historical
+    /// requests are limited to the cached updates.
+    async fn get_publisher_stake_caps_with_update_data(
         &self,
+        request_time: RequestTime,
     ) -> Result<PublisherStakeCapsWithUpdateData> {
         let messages = self
             .fetch_message_states(
                 vec![PUBLISHER_STAKE_CAPS_MESSAGE_FEED_ID],
-                RequestTime::Latest,
+                request_time,
                 MessageStateFilter::Only(MessageType::PublisherStakeCapsMessage),
             )
             .await?;
@@ -461,6 +565,7 @@ where
             .iter()
             .map(|message_state| match message_state.message.clone() {
                 Message::PublisherStakeCapsMessage(message) => Ok(ParsedPublisherStakeCapsUpdate {
+                    publish_time: message.publish_time,
                     publisher_stake_caps: message
                         .caps
                         .iter()
@@ -490,6 +595,34 @@ where
             .collect()
     }
 
+    /// Get the publish time of the latest price of each feed, without building its update data.
+    async fn get_latest_publish_times(
+        &self,
+        price_ids: &[PriceIdentifier],
+    ) -> Result<HashMap<PriceIdentifier, UnixTimestamp>> {
+        let messages = self
+            .fetch_message_states(
+                price_ids
+                    .iter()
+                    .map(|price_id| price_id.to_bytes())
+                    .collect(),
+                RequestTime::Latest,
+                MessageStateFilter::Only(MessageType::PriceFeedMessage),
+            )
+            .await?;
+
+        Ok(messages
+            .iter()
+            .filter_map(|message_state| match message_state.message {
+                Message::PriceFeedMessage(price_feed) => Some((
+                    PriceIdentifier::new(price_feed.feed_id),
+                    price_feed.publish_time,
+                )),
+                _ => None,
+            })
+            .collect())
+    }
+
     async fn is_ready(&self) -> (bool, ReadinessMetadata) {
         let state_data = self.into().data.read().await;
         let price_feeds_metadata = PriceFeedMeta::retrieve_price_feeds_metadata(self)
@@ -851,6 +984,7 @@ mod test {
         rust_decimal::prelude::FromPrimitive,
         serde_wormhole::RawMessage,
         std::sync::Arc,
+        tracing::Instrument,
         wormhole_sdk::{Address, Chain},
     };
 
@@ -948,10 +1082,10 @@ mod test {
         .await;
 
         // Check that the update_rx channel has received a message
-        assert_eq!(
+        assert!(matches!(
             update_rx.recv().await,
-            Ok(AggregationEvent::New { slot: 10 })
-        );
+            Ok(AggregationEvent::New { slot: 10, .. })
+        ));
 
         // Check the price ids are stored correctly
         assert_eq!(
@@ -1073,10 +1207,10 @@ mod test {
         .await;
 
         // Check that the update_rx channel has received a message
-        assert_eq!(
+        assert!(matches!(
             update_rx.recv().await,
-            Ok(AggregationEvent::New { slot: 10 })
-        );
+            Ok(AggregationEvent::New { slot: 10, .. })
+        ));
 
         // Check the price ids are stored correctly
         assert_eq!(
@@ -1110,10 +1244,10 @@ mod test {
         .await;
 
         // Check that the update_rx channel has received a message
-        assert_eq!(
+        assert!(matches!(
             update_rx.recv().await,
-            Ok(AggregationEvent::New { slot: 15 })
-        );
+            Ok(AggregationEvent::New { slot: 15, .. })
+        ));
 
         // Check that price feed 2 does not exist anymore
         assert_eq!(
@@ -1561,10 +1695,10 @@ mod test {
         .await;
 
         // Check that we received the New event for slot 100
-        assert_eq!(
+        assert!(matches!(
             update_rx.recv().await,
-            Ok(AggregationEvent::New { slot: 100 })
-        );
+            Ok(AggregationEvent::New { slot: 100, .. })
+        ));
 
         // Next, process slot 101
         store_multiple_concurrent_valid_updates(
@@ -1574,10 +1708,10 @@ mod test {
         .await;
 
         // Check that we received the New event for slot 101
-        assert_eq!(
+        assert!(matches!(
             update_rx.recv().await,
-            Ok(AggregationEvent::New { slot: 101 })
-        );
+            Ok(AggregationEvent::New { slot: 101, .. })
+        ));
 
         // Now, process slot 100 again
         store_multiple_concurrent_valid_updates(
@@ -1631,10 +1765,10 @@ mod test {
         futures::future::join_all(futures).await;
 
         // Check that only one AggregationEvent::New is received
-        assert_eq!(
+        assert!(matches!(
             update_rx.recv().await,
-            Ok(AggregationEvent::New { slot: 10 })
-        );
+            Ok(AggregationEvent::New { slot: 10, .. })
+        ));
 
         // Try to receive another event with a timeout to ensure no more events were sent
         let timeout_result =
@@ -1676,6 +1810,85 @@ mod calculate_twap_unit_tests {
         }
     }
 
+    /// Sets an OpenTelemetry subscriber for the current thread so that the spans have valid trace
+    /// contexts. The provider has to be kept alive for as long as the spans are created.
+    fn set_tracing_subscriber() -> (
+        opentelemetry_sdk::trace::TracerProvider,
+        tracing::subscriber::DefaultGuard,
+    ) {
+        use {opentelemetry::trace::TracerProvider as _, tracing_subscriber::layer::SubscriberExt};
+
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let tracer = provider.tracer("hermes-test");
+        let guard = tracing::subscriber::set_default(
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer)),
+        );
+        (provider, guard)
+    }
+
+    #[tokio::test]
+    pub async fn test_aggregation_event_carries_trace_of_received_updates() {
+        let (_provider, _guard) = set_tracing_subscriber();
+        let (state, mut update_rx) = setup_state(10).await;
+
+        let span = tracing::info_span!("receive_updates");
+        let trace_id = span.context().span().span_context().trace_id();
+        for update in generate_update(
+            vec![Message::PriceFeedMessage(create_dummy_price_feed_message(
+                100, 10, 9,
+            ))],
+            10,
+            20,
+        ) {
+            state
+                .store_update(update)
+                .instrument(span.clone())
+                .await
+                .unwrap();
+        }
+
+        let event = update_rx.recv().await.unwrap();
+        assert_eq!(event.slot(), 10);
+        let span_context = event.trace_context().0.span().span_context().clone();
+        assert!(span_context.is_valid());
+        assert_eq!(span_context.trace_id(), trace_id);
+    }
+
+    #[test]
+    fn test_observe_slot_trace_context_keeps_first_context_and_evicts_oldest_slots() {
+        let (_provider, _guard) = set_tracing_subscriber();
+        let mut data =
+            AggregateStateData::new(Duration::from_secs(30), 10, &mut Registry::default());
+
+        let first_span = tracing::info_span!("first_update");
+        let first_span_id = first_span.context().span().span_context().span_id();
+        first_span.in_scope(|| data.observe_slot_trace_context(0));
+        tracing::info_span!("second_update").in_scope(|| data.observe_slot_trace_context(0));
+        assert_eq!(
+            data.observed_slot_trace_contexts[&0]
+                .0
+                .span()
+                .span_context()
+                .span_id(),
+            first_span_id
+        );
+
+        // Slots that never complete are evicted, oldest first.
+        for slot in 1..(MAX_OBSERVED_SLOT_TRACE_CONTEXTS as Slot + 10) {
+            data.observe_slot_trace_context(slot);
+        }
+        assert_eq!(
+            data.observed_slot_trace_contexts.len(),
+            MAX_OBSERVED_SLOT_TRACE_CONTEXTS
+        );
+        assert_eq!(
+            data.observed_slot_trace_contexts
+                .first_key_value()
+                .map(|(slot, _)| *slot),
+            Some(10)
+        );
+    }
+
     #[test]
     fn test_valid_twap() {
         let start = create_basic_twap_message(100, 100, 90, 1000);
@@ -0,0 +1,325 @@
+//! Capture of the updates received by the aggregate store.
+//!
+//! A capture file contains one JSON encoded [`CapturedUpdate`] per line, in the order the updates
+//! were handed to the aggregate store. Captures are written by the [`Recorder`] and fed back to
+//! the aggregate store by the `replay` command, which makes it possible to reproduce issues with
+//! production traffic without connecting to Pythnet and Wormhole.
+
+use {
+    super::{AccumulatorMessages, Update},
+    anyhow::{anyhow, Context, Result},
+    serde::{Deserialize, Serialize},
+    std::{
+        path::Path,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    tokio::{
+        fs::File,
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter, Lines},
+        sync::mpsc,
+    },
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CapturedUpdate {
+    /// The time at which Hermes received the update, in microseconds since the Unix epoch.
+    pub received_at_us: u64,
+    #[serde(flatten)]
+    pub update: CapturedUpdateData,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CapturedUpdateData {
+    Vaa {
+        #[serde(with = "hex::serde")]
+        vaa: Vec<u8>,
+    },
+    AccumulatorMessages {
+        #[serde(with = "hex::serde")]
+        magic: [u8; 4],
+        slot: u64,
+        ring_size: u32,
+        /// Hex encoded raw messages.
+        raw_messages: Vec<String>,
+    },
+}
+
+impl From<&Update> for CapturedUpdateData {
+    fn from(update: &Update) -> Self {
+        match update {
+            Update::Vaa(vaa) => CapturedUpdateData::Vaa { vaa: vaa.clone() },
+            Update::AccumulatorMessages(accumulator_messages) => {
+                CapturedUpdateData::AccumulatorMessages {
+                    magic: accumulator_messages.magic,
+                    slot: accumulator_messages.slot,
+                    ring_size: accumulator_messages.ring_size,
+                    raw_messages: accumulator_messages
+                        .raw_messages
+                        .iter()
+                        .map(hex::encode)
+                        .collect(),
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<CapturedUpdateData> for Update {
+    type Error = anyhow::Error;
+
+    fn try_from(update: CapturedUpdateData) -> Result<Self> {
+        match update {
+            CapturedUpdateData::Vaa { vaa } => Ok(Update::Vaa(vaa)),
+            CapturedUpdateData::AccumulatorMessages {
+                magic,
+                slot,
+                ring_size,
+                raw_messages,
+            } => Ok(Update::AccumulatorMessages(AccumulatorMessages {
+                magic,
+                slot,
+                ring_size,
+                raw_messages: raw_messages
+                    .iter()
+                    .map(hex::decode)
+                    .collect::<Result<_, _>>()?,
+            })),
+        }
+    }
+}
+
+/// Records the updates to a capture file.
+///
+/// Updates are written by a background task so recording never blocks the aggregate store. The
+/// file is flushed after every update so the capture survives a crash of Hermes.
+pub struct Recorder {
+    tx: mpsc::UnboundedSender<CapturedUpdate>,
+}
+
+impl Recorder {
+    pub async fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .await
+            .with_context(|| format!("Failed to create capture file {}", path.display()))?;
+        tracing::info!(path = %path.display(), "Recording updates to capture file.");
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<CapturedUpdate>();
+        tokio::spawn(async move {
+            let mut writer = BufWriter::new(file);
+            while let Some(captured_update) = rx.recv().await {
+                if let Err(err) = write_captured_update(&mut writer, &captured_update).await {
+                    tracing::error!(error = ?err, "Failed to write update to capture file.");
+                }
+            }
+        });
+
+        Ok(Self { tx })
+    }
+
+    pub fn record(&self, update: &Update) {
+        let received_at_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| u64::try_from(duration.as_micros()).unwrap_or(u64::MAX))
+            .unwrap_or_default();
+
+        let captured_update = CapturedUpdate {
+            received_at_us,
+            update: update.into(),
+        };
+        if self.tx.send(captured_update).is_err() {
+            tracing::warn!("Capture file writer stopped, update not recorded.");
+        }
+    }
+}
+
+async fn write_captured_update(
+    writer: &mut BufWriter<File>,
+    captured_update: &CapturedUpdate,
+) -> Result<()> {
+    let mut line = serde_json::to_vec(captured_update)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Reads the updates of a capture file in order.
+pub struct CaptureReader {
+    lines: Lines<BufReader<File>>,
+    line_number: usize,
+}
+
+impl CaptureReader {
+    pub async fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .await
+            .with_context(|| format!("Failed to open capture file {}", path.display()))?;
+        Ok(Self {
+            lines: BufReader::new(file).lines(),
+            line_number: 0,
+        })
+    }
+
+    /// Returns the next update of the capture, or `None` at the end of the capture.
+    pub async fn next_update(&mut self) -> Result<Option<CapturedUpdate>> {
+        loop {
+            let Some(line) = self.lines.next_line().await? else {
+                return Ok(None);
+            };
+            self.line_number += 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return serde_json::from_str(&line)
+                .map(Some)
+                .map_err(|err| anyhow!("Invalid capture line {}: {}", self.line_number, err));
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::panic, reason = "tests")]
+mod test {
+    use {
+        super::*,
+        crate::state::{
+            aggregate::{
+                test::{create_dummy_price_feed_message, generate_update},
+                Aggregates, RequestTime,
+            },
+            test::{setup_state, setup_state_with_recorder},
+        },
+        pyth_sdk::PriceIdentifier,
+        pythnet_sdk::messages::Message,
+        std::time::Duration,
+    };
+
+    /// Waits until the recorder has written `count` updates to the capture file.
+    async fn wait_for_capture(path: &Path, count: usize) {
+        for _ in 0..100 {
+            let content = tokio::fs::read_to_string(path).await.unwrap_or_default();
+            if content.lines().count() >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("The recorder did not write {count} updates to the capture file");
+    }
+
+    #[test]
+    fn test_captured_update_round_trip() {
+        let accumulator_messages = AccumulatorMessages {
+            magic: *b"PAS1",
+            slot: 10,
+            ring_size: 100,
+            raw_messages: vec![vec![1, 2, 3], vec![4, 5]],
+        };
+        let update = Update::AccumulatorMessages(accumulator_messages.clone());
+
+        let captured_update = CapturedUpdate {
+            received_at_us: 1_700_000_000_000_000,
+            update: (&update).into(),
+        };
+        let line = serde_json::to_string(&captured_update).unwrap();
+        let decoded: CapturedUpdate = serde_json::from_str(&line).unwrap();
+        assert_eq!(decoded, captured_update);
+
+        assert!(matches!(
+            Update::try_from(decoded.update).unwrap(),
+            Update::AccumulatorMessages(decoded) if decoded == accumulator_messages
+        ));
+
+        let captured_update = CapturedUpdate {
+            received_at_us: 1_700_000_000_000_000,
+            update: (&Update::Vaa(vec![9, 9])).into(),
+        };
+        let line = serde_json::to_string(&captured_update).unwrap();
+        assert!(line.contains(r#""type":"vaa""#));
+        assert!(matches!(
+            Update::try_from(serde_json::from_str::<CapturedUpdate>(&line).unwrap().update)
+                .unwrap(),
+            Update::Vaa(vaa) if vaa == vec![9, 9]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_recorded_updates_replay_into_fresh_state() {
+        let path = std::env::temp_dir().join(format!(
+            "hermes-capture-replay-test-{}.jsonl",
+            std::process::id()
+        ));
+        let (recorded_state, _) =
+            setup_state_with_recorder(10, Some(Recorder::create(&path).await.unwrap())).await;
+
+        let updates: Vec<Update> = [
+            generate_update(
+                vec![Message::PriceFeedMessage(create_dummy_price_feed_message(
+                    100, 10, 9,
+                ))],
+                10,
+                20,
+            ),
+            generate_update(
+                vec![
+                    Message::PriceFeedMessage(create_dummy_price_feed_message(100, 11, 10)),
+                    Message::PriceFeedMessage(create_dummy_price_feed_message(200, 11, 10)),
+                ],
+                11,
+                21,
+            ),
+        ]
+        .concat();
+        let update_count = updates.len();
+        for update in updates {
+            recorded_state.store_update(update).await.unwrap();
+        }
+        wait_for_capture(&path, update_count).await;
+
+        // Replay the capture into a fresh state the same way the `replay` command does.
+        let (replayed_state, _) = setup_state(10).await;
+        let mut reader = CaptureReader::open(&path).await.unwrap();
+        let mut replayed = 0;
+        while let Some(captured_update) = reader.next_update().await.unwrap() {
+            replayed_state
+                .store_update(Update::try_from(captured_update.update).unwrap())
+                .await
+                .unwrap();
+            replayed += 1;
+        }
+        tokio::fs::remove_file(&path).await.unwrap();
+        assert_eq!(replayed, update_count);
+
+        let price_ids = [
+            PriceIdentifier::new([100; 32]),
+            PriceIdentifier::new([200; 32]),
+        ];
+        assert_eq!(
+            replayed_state.get_price_feed_ids().await,
+            recorded_state.get_price_feed_ids().await
+        );
+        let recorded = recorded_state
+            .get_price_feeds_with_update_data(&price_ids, RequestTime::Latest)
+            .await
+            .unwrap();
+        let replayed = replayed_state
+            .get_price_feeds_with_update_data(&price_ids, RequestTime::Latest)
+            .await
+            .unwrap();
+        assert_eq!(replayed.update_data, recorded.update_data);
+        assert_eq!(
+            replayed
+                .price_feeds
+                .iter()
+                .map(|price_feed| (price_feed.price_feed, price_feed.slot))
+                .collect::<Vec<_>>(),
+            recorded
+                .price_feeds
+                .iter()
+                .map(|price_feed| (price_feed.price_feed, price_feed.slot))
+                .collect::<Vec<_>>()
+        );
+    }
+}
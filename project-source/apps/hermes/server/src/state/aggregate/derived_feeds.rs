@@ -0,0 +1,193 @@
+//! Derived price feeds computed from verified price feeds.
+//!
+//! A derived feed is the price of a base feed expressed in the unit of a quote feed, for example
+//! ETH/BTC from ETH/USD and BTC/USD, or the inverse of a single feed, for example USD/JPY from
+//! JPY/USD. Derived feeds are computed by Hermes on request and are not signed by Wormhole, so
+//! consumers that need a verifiable price should recompute them on-chain from the source feeds.
+
+use {
+    anyhow::{anyhow, Result},
+    pyth_sdk::{Price, PriceIdentifier},
+    std::str::FromStr,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivedFeed {
+    /// The name the derived feed is requested by.
+    pub name: String,
+    /// The feed whose price is expressed in the unit of the quote feed, `None` for inverted feeds
+    /// where the base price is exactly 1.
+    pub base_id: Option<PriceIdentifier>,
+    pub quote_id: PriceIdentifier,
+    /// The exponent of the derived price. Defaults to the base feed exponent, or to the quote feed
+    /// exponent for inverted feeds.
+    pub expo: Option<i32>,
+}
+
+/// Parses a derived feed definition of the form `<name>=<base_id>/<quote_id>[@<expo>]`, with
+/// `1` as the base id for inverted feeds, for example `USDJPY=1/ef2c98c8...@-8`.
+impl FromStr for DerivedFeed {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, definition) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Derived feed `{}` is missing `=`", s))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("Derived feed `{}` has an empty name", s));
+        }
+
+        let (pair, expo) =
+            match definition.split_once('@') {
+                Some((pair, expo)) => (
+                    pair,
+                    Some(expo.trim().parse::<i32>().map_err(|err| {
+                        anyhow!("Invalid exponent in derived feed `{}`: {}", s, err)
+                    })?),
+                ),
+                None => (definition, None),
+            };
+
+        let (base, quote) = pair
+            .split_once('/')
+            .ok_or_else(|| anyhow!("Derived feed `{}` is missing `/`", s))?;
+        let base_id = match base.trim() {
+            "1" => None,
+            base => Some(parse_price_id(base)?),
+        };
+        let quote_id = parse_price_id(quote.trim())?;
+
+        Ok(Self {
+            name: name.to_string(),
+            base_id,
+            quote_id,
+            expo,
+        })
+    }
+}
+
+fn parse_price_id(id: &str) -> Result<PriceIdentifier> {
+    PriceIdentifier::from_hex(id.strip_prefix("0x").unwrap_or(id))
+        .map_err(|err| anyhow!("Invalid price id `{}`: {}", id, err))
+}
+
+impl DerivedFeed {
+    /// The ids of the price feeds the derived feed is computed from.
+    pub fn source_ids(&self) -> Vec<PriceIdentifier> {
+        self.base_id.into_iter().chain([self.quote_id]).collect()
+    }
+
+    /// Compute the derived price from the prices of its source feeds, `base` is ignored for
+    /// inverted feeds.
+    ///
+    /// The price is computed with `Price::get_price_in_quote` so the confidence interval is
+    /// propagated the same way as the Pyth SDKs do it. The publish time is the earliest publish
+    /// time of the sources. Returns `None` if the computation overflows or the quote price is not
+    /// positive.
+    pub fn compute(&self, base: Option<&Price>, quote: &Price) -> Option<Price> {
+        if quote.price <= 0 {
+            return None;
+        }
+
+        let (base, expo) = match self.base_id {
+            Some(_) => {
+                let base = base?;
+                (*base, self.expo.unwrap_or(base.expo))
+            }
+            // 1 is represented with 8 decimals as the division keeps as many significant digits as
+            // its operands have.
+            None => (
+                Price {
+                    price: 100_000_000,
+                    conf: 0,
+                    expo: -8,
+                    publish_time: quote.publish_time,
+                },
+                self.expo.unwrap_or(quote.expo),
+            ),
+        };
+
+        base.get_price_in_quote(quote, expo)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "tests")]
+mod test {
+    use super::*;
+
+    const ETH_USD: &str = "ff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace";
+    const BTC_USD: &str = "e62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43";
+
+    fn price(price: i64, conf: u64, expo: i32, publish_time: i64) -> Price {
+        Price {
+            price,
+            conf,
+            expo,
+            publish_time,
+        }
+    }
+
+    #[test]
+    fn test_parse_derived_feed() {
+        let feed: DerivedFeed = format!("ETHBTC=0x{}/{}", ETH_USD, BTC_USD).parse().unwrap();
+        assert_eq!(feed.name, "ETHBTC");
+        assert_eq!(
+            feed.base_id,
+            Some(PriceIdentifier::from_hex(ETH_USD).unwrap())
+        );
+        assert_eq!(feed.quote_id, PriceIdentifier::from_hex(BTC_USD).unwrap());
+        assert_eq!(feed.expo, None);
+        assert_eq!(feed.source_ids().len(), 2);
+
+        let feed: DerivedFeed = format!("USDBTC=1/{}@-10", BTC_USD).parse().unwrap();
+        assert_eq!(feed.base_id, None);
+        assert_eq!(feed.expo, Some(-10));
+        assert_eq!(feed.source_ids(), vec![feed.quote_id]);
+
+        assert!("ETHBTC".parse::<DerivedFeed>().is_err());
+        assert!(format!("={}/{}", ETH_USD, BTC_USD)
+            .parse::<DerivedFeed>()
+            .is_err());
+        assert!(format!("ETHBTC={}", ETH_USD)
+            .parse::<DerivedFeed>()
+            .is_err());
+        assert!("ETHBTC=1/abc".parse::<DerivedFeed>().is_err());
+        assert!(format!("USDBTC=1/{}@x", BTC_USD)
+            .parse::<DerivedFeed>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_compute_cross_rate() {
+        let feed: DerivedFeed = format!("ETHBTC={}/{}", ETH_USD, BTC_USD).parse().unwrap();
+
+        // ETH/USD = 3000 +- 3 and BTC/USD = 60000 +- 60, both with exponent -8.
+        let eth_usd = price(300_000_000_000, 300_000_000, -8, 100);
+        let btc_usd = price(6_000_000_000_000, 6_000_000_000, -8, 99);
+
+        let eth_btc = feed.compute(Some(&eth_usd), &btc_usd).unwrap();
+        assert_eq!(eth_btc.expo, -8);
+        assert_eq!(eth_btc.price, 5_000_000);
+        // The relative confidences (0.1% each) are added up.
+        assert_eq!(eth_btc.conf, 10_000);
+        assert_eq!(eth_btc.publish_time, 99);
+
+        assert_eq!(feed.compute(None, &btc_usd), None);
+    }
+
+    #[test]
+    fn test_compute_inverted() {
+        let feed: DerivedFeed = format!("USDBTC=1/{}@-12", BTC_USD).parse().unwrap();
+        let btc_usd = price(6_000_000_000_000, 6_000_000_000, -8, 100);
+
+        let usd_btc = feed.compute(None, &btc_usd).unwrap();
+        assert_eq!(usd_btc.expo, -12);
+        assert_eq!(usd_btc.price, 16_666_666);
+        assert_eq!(usd_btc.conf, 16_666);
+        assert_eq!(usd_btc.publish_time, 100);
+
+        assert_eq!(feed.compute(None, &price(0, 0, -8, 100)), None);
+    }
+}
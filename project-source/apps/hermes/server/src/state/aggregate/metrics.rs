@@ -5,7 +5,10 @@ use {
         metrics::{counter::Counter, family::Family, histogram::Histogram},
         registry::Registry,
     },
-    std::collections::{BTreeMap, HashMap},
+    std::{
+        collections::{BTreeMap, HashMap},
+        time::Duration,
+    },
     tokio::time::Instant,
 };
 
@@ -34,6 +37,7 @@ struct ObservedSlotLabels {
 pub struct Metrics {
     observed_slot: Family<ObservedSlotLabels, Counter>,
     observed_slot_latency: Family<ObservedSlotLabels, Histogram>,
+    message_states_build_latency: Histogram,
     first_observed_time_of_slot: BTreeMap<Slot, Instant>,
     newest_observed_slot: HashMap<Event, Slot>,
 }
@@ -50,6 +54,12 @@ impl Metrics {
                     .into_iter(),
                 )
             }),
+            message_states_build_latency: Histogram::new(
+                [
+                    0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1.0, 2.0, 5.0,
+                ]
+                .into_iter(),
+            ),
             first_observed_time_of_slot: BTreeMap::new(),
             newest_observed_slot: HashMap::new(),
         };
@@ -57,6 +67,7 @@ impl Metrics {
         {
             let observed_slot = new.observed_slot.clone();
             let observed_slot_latency = new.observed_slot_latency.clone();
+            let message_states_build_latency = new.message_states_build_latency.clone();
 
             metrics_registry.register(
                 "aggregate_observed_slot",
@@ -69,11 +80,23 @@ impl Metrics {
                 "Latency of observed slots in seconds",
                 observed_slot_latency,
             );
+
+            metrics_registry.register(
+                "aggregate_message_states_build_latency_seconds",
+                "Latency of building and storing the message states of a completed slot in seconds",
+                message_states_build_latency,
+            );
         }
 
         new
     }
 
+    /// Observe the time it took to build and store the message states of a completed slot.
+    pub fn observe_message_states_build(&self, latency: Duration) {
+        self.message_states_build_latency
+            .observe(latency.as_secs_f64());
+    }
+
     /// Observe a slot and event. An event at a slot should be observed only once.
     pub fn observe(&mut self, slot: Slot, event: Event) {
         let order = if self
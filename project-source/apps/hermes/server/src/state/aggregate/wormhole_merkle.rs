@@ -4,7 +4,7 @@ use {
         network::wormhole::VaaBytes,
         state::cache::{Cache, MessageState},
     },
-    anyhow::{anyhow, Result},
+    anyhow::{anyhow, bail, Result},
     pythnet_sdk::{
         accumulators::{
             merkle::{MerklePath, MerkleTree},
@@ -22,6 +22,41 @@ use {
 // u8 in the wire format. So, we can't have more than 255 messages.
 pub const MAX_MESSAGE_IN_SINGLE_UPDATE_DATA: usize = 255;
 
+// A VAA starts with its version (1 byte), guardian set index (4 bytes) and number of signatures
+// (1 byte), followed by the signatures (66 bytes each) and the body.
+const VAA_GUARDIAN_SET_INDEX_OFFSET: usize = 1;
+const VAA_SIGNATURE_COUNT_OFFSET: usize = 5;
+const VAA_SIGNATURES_OFFSET: usize = 6;
+const VAA_SIGNATURE_SIZE: usize = 66;
+
+/// The number of guardian signatures a VAA is trimmed to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SignatureLimit {
+    /// A quorum of the guardian set that signed the VAA, as verified by the Wormhole contracts.
+    Quorum,
+    /// A fixed number of signatures, for receivers configured to verify fewer than a quorum.
+    Fixed(usize),
+}
+
+/// The layout of the update data expected by a target chain.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UpdateDataShape {
+    /// The maximum number of price updates in a single update data.
+    pub max_updates: usize,
+    /// The number of guardian signatures the VAA is trimmed to. The extra signatures only make the
+    /// update data larger and costlier to verify.
+    pub max_signatures: Option<SignatureLimit>,
+}
+
+impl Default for UpdateDataShape {
+    fn default() -> Self {
+        Self {
+            max_updates: MAX_MESSAGE_IN_SINGLE_UPDATE_DATA,
+            max_signatures: None,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct WormholeMerkleState {
     pub root: WormholeMerkleRoot,
@@ -141,6 +176,100 @@ pub fn construct_update_data(mut messages: Vec<RawMessageWithMerkleProof>) -> Re
     Ok(result)
 }
 
+/// Reshape update data built by [`construct_update_data`] for a target chain.
+///
+/// The price updates of the update data sharing the same VAA are merged and split again
+/// according to the shape, and their VAA is trimmed to the maximum number of signatures.
+/// `guardian_set_quorum` returns the quorum of a guardian set given its index.
+pub fn shape_update_data(
+    update_data: Vec<Vec<u8>>,
+    shape: &UpdateDataShape,
+    guardian_set_quorum: impl Fn(u32) -> Option<usize>,
+) -> Result<Vec<Vec<u8>>> {
+    // Update data are ordered by slot, so the updates sharing a VAA are adjacent.
+    let mut proofs: Vec<(Vec<u8>, Vec<MerklePriceUpdate>)> = vec![];
+    for update_data in update_data {
+        let Proof::WormholeMerkle { vaa, updates } =
+            AccumulatorUpdateData::try_from_slice(&update_data)
+                .map_err(|err| anyhow!("Invalid update data: {:?}", err))?
+                .proof;
+        let vaa: Vec<u8> = vaa.into();
+
+        match proofs.last_mut() {
+            Some((last_vaa, last_updates)) if *last_vaa == vaa => last_updates.extend(updates),
+            _ => proofs.push((vaa, updates)),
+        }
+    }
+
+    let mut result: Vec<Vec<u8>> = vec![];
+    for (vaa, updates) in proofs {
+        let vaa = match shape.max_signatures {
+            Some(SignatureLimit::Fixed(max_signatures)) => {
+                trim_vaa_signatures(&vaa, max_signatures)?
+            }
+            Some(SignatureLimit::Quorum) => {
+                let guardian_set_index = vaa_guardian_set_index(&vaa)?;
+                let quorum = guardian_set_quorum(guardian_set_index)
+                    .ok_or_else(|| anyhow!("Unknown guardian set: {}", guardian_set_index))?;
+                trim_vaa_signatures(&vaa, quorum)?
+            }
+            None => vaa,
+        };
+
+        for chunk in updates.chunks(shape.max_updates.max(1)) {
+            result.push(encode_update_data(&vaa, chunk.to_vec())?);
+        }
+    }
+
+    Ok(result)
+}
+
+fn encode_update_data(vaa: &[u8], updates: Vec<MerklePriceUpdate>) -> Result<Vec<u8>> {
+    Ok(to_vec::<_, byteorder::BE>(&AccumulatorUpdateData::new(
+        Proof::WormholeMerkle {
+            vaa: vaa.to_vec().into(),
+            updates,
+        },
+    ))?)
+}
+
+fn vaa_guardian_set_index(vaa: &[u8]) -> Result<u32> {
+    let index_bytes = vaa
+        .get(VAA_GUARDIAN_SET_INDEX_OFFSET..VAA_SIGNATURE_COUNT_OFFSET)
+        .ok_or_else(|| anyhow!("VAA is too short"))?;
+    Ok(u32::from_be_bytes(index_bytes.try_into()?))
+}
+
+/// Keep only the first `max_signatures` guardian signatures of a VAA. The signatures are not part
+/// of the signed body, so the remaining ones stay valid.
+pub fn trim_vaa_signatures(vaa: &[u8], max_signatures: usize) -> Result<Vec<u8>> {
+    let signatures = usize::from(
+        *vaa.get(VAA_SIGNATURE_COUNT_OFFSET)
+            .ok_or_else(|| anyhow!("VAA is too short"))?,
+    );
+    if signatures <= max_signatures {
+        return Ok(vaa.to_vec());
+    }
+
+    let kept_signatures_end = VAA_SIGNATURES_OFFSET + max_signatures * VAA_SIGNATURE_SIZE;
+    let body_start = VAA_SIGNATURES_OFFSET + signatures * VAA_SIGNATURE_SIZE;
+    let (Some(header), Some(kept_signatures), Some(body)) = (
+        vaa.get(..VAA_SIGNATURE_COUNT_OFFSET),
+        vaa.get(VAA_SIGNATURES_OFFSET..kept_signatures_end),
+        vaa.get(body_start..),
+    ) else {
+        bail!("VAA is too short for its {} signatures", signatures);
+    };
+
+    let mut trimmed =
+        Vec::with_capacity(VAA_SIGNATURES_OFFSET + kept_signatures.len() + body.len());
+    trimmed.extend_from_slice(header);
+    trimmed.push(u8::try_from(max_signatures)?);
+    trimmed.extend_from_slice(kept_signatures);
+    trimmed.extend_from_slice(body);
+    Ok(trimmed)
+}
+
 #[cfg(test)]
 #[allow(
     clippy::unwrap_used,
@@ -235,4 +364,119 @@ mod test {
             }
         }
     }
+
+    fn count_updates(update_data: &[u8]) -> usize {
+        match AccumulatorUpdateData::try_from_slice(update_data)
+            .unwrap()
+            .proof
+        {
+            Proof::WormholeMerkle { updates, .. } => updates.len(),
+        }
+    }
+
+    /// A VAA signed by guardian set 4 with the given number of signatures.
+    fn create_dummy_vaa(signatures: u8) -> Vec<u8> {
+        let mut vaa = vec![1, 0, 0, 0, 4, signatures];
+        for signature in 0..signatures {
+            vaa.extend([signature; VAA_SIGNATURE_SIZE]);
+        }
+        vaa.extend([7; 10]);
+        vaa
+    }
+
+    fn get_vaa(update_data: &[u8]) -> Vec<u8> {
+        match AccumulatorUpdateData::try_from_slice(update_data)
+            .unwrap()
+            .proof
+        {
+            Proof::WormholeMerkle { vaa, .. } => vaa.into(),
+        }
+    }
+
+    #[test]
+    fn test_shape_update_data_splits_by_count() {
+        let messages = (0..10)
+            .map(|_| create_dummy_raw_message_with_merkle_proof(10))
+            .collect();
+        let update_data = construct_update_data(messages).unwrap();
+        assert_eq!(update_data.len(), 1);
+
+        let shaped = shape_update_data(
+            update_data.clone(),
+            &UpdateDataShape {
+                max_updates: 3,
+                ..Default::default()
+            },
+            |_| None,
+        )
+        .unwrap();
+        let counts: Vec<usize> = shaped.iter().map(|data| count_updates(data)).collect();
+        assert_eq!(counts, vec![3, 3, 3, 1]);
+
+        // The default shape merges the update data back.
+        let shaped = shape_update_data(shaped, &UpdateDataShape::default(), |_| None).unwrap();
+        assert_eq!(shaped, update_data);
+    }
+
+    #[test]
+    fn test_shape_update_data_trims_vaa_to_guardian_set_quorum() {
+        let messages = (0..2)
+            .map(|_| {
+                let mut message = create_dummy_raw_message_with_merkle_proof(10);
+                message.proof.vaa = create_dummy_vaa(19);
+                message
+            })
+            .collect();
+        let update_data = construct_update_data(messages).unwrap();
+        let shape = UpdateDataShape {
+            max_signatures: Some(SignatureLimit::Quorum),
+            ..Default::default()
+        };
+
+        // A guardian set of 19 guardians has a quorum of 13.
+        let guardian_set_quorum = |index| (index == 4).then_some(19 * 2 / 3 + 1);
+        let shaped = shape_update_data(update_data.clone(), &shape, guardian_set_quorum).unwrap();
+        assert_eq!(shaped.len(), 1);
+        assert_eq!(
+            get_vaa(&shaped[0]),
+            trim_vaa_signatures(&create_dummy_vaa(19), 13).unwrap()
+        );
+
+        // The quorum of an unknown guardian set cannot be derived.
+        assert!(shape_update_data(update_data.clone(), &shape, |_| None).is_err());
+
+        let shaped = shape_update_data(
+            update_data,
+            &UpdateDataShape {
+                max_updates: 1,
+                max_signatures: Some(SignatureLimit::Fixed(5)),
+            },
+            |_| None,
+        )
+        .unwrap();
+        assert_eq!(shaped.len(), 2);
+        for data in &shaped {
+            assert_eq!(count_updates(data), 1);
+            assert_eq!(get_vaa(data)[VAA_SIGNATURE_COUNT_OFFSET], 5);
+        }
+    }
+
+    #[test]
+    fn test_trim_vaa_signatures() {
+        let vaa = create_dummy_vaa(3);
+
+        let trimmed = trim_vaa_signatures(&vaa, 2).unwrap();
+        assert_eq!(trimmed.len(), vaa.len() - VAA_SIGNATURE_SIZE);
+        assert_eq!(trimmed[..5], vaa[..5]);
+        assert_eq!(trimmed[5], 2);
+        assert_eq!(
+            trimmed[VAA_SIGNATURES_OFFSET..VAA_SIGNATURES_OFFSET + 2 * VAA_SIGNATURE_SIZE],
+            vaa[VAA_SIGNATURES_OFFSET..VAA_SIGNATURES_OFFSET + 2 * VAA_SIGNATURE_SIZE]
+        );
+        assert!(trimmed.ends_with(&[7; 10]));
+
+        assert_eq!(trim_vaa_signatures(&vaa, 3).unwrap(), vaa);
+        assert!(trim_vaa_signatures(&vaa[..100], 2).is_err());
+        assert!(trim_vaa_signatures(&[1, 0], 2).is_err());
+    }
 }
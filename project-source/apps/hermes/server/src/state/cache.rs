@@ -236,6 +236,17 @@ where
             return Ok(false);
         }
 
+        // Messages for a slot older than everything in a full cache would be evicted right away.
+        // This happens when a lagging Pythnet endpoint delivers a slot we have already processed
+        // and evicted, so we treat it as a duplicate.
+        if cache.len() >= self.into().cache_size
+            && cache
+                .first_key_value()
+                .map_or(false, |(first, _)| slot < *first)
+        {
+            return Ok(false);
+        }
+
         // Messages don't exist, store them
         cache.insert(slot, accumulator_messages);
         while cache.len() > self.into().cache_size {
@@ -844,6 +855,44 @@ mod test {
         assert!(state.fetch_accumulator_messages(5).await.unwrap().is_none());
     }
 
+    #[tokio::test]
+    pub async fn test_store_accumulator_messages_ignores_evicted_slots() {
+        // Initialize state with a cache size of 2 per key.
+        let (state, _) = setup_state(2).await;
+
+        // Fill the cache with accumulator messages at slots 10 and 15.
+        for slot in [10, 15] {
+            assert!(state
+                .store_accumulator_messages(create_empty_accumulator_messages_at_slot(slot))
+                .await
+                .unwrap());
+        }
+
+        // The same slot coming from another endpoint is a duplicate.
+        assert!(!state
+            .store_accumulator_messages(create_empty_accumulator_messages_at_slot(15))
+            .await
+            .unwrap());
+
+        // A slot older than the whole cache is also treated as a duplicate and does not evict
+        // the newer slots.
+        assert!(!state
+            .store_accumulator_messages(create_empty_accumulator_messages_at_slot(5))
+            .await
+            .unwrap());
+        assert!(state.fetch_accumulator_messages(5).await.unwrap().is_none());
+        assert!(state
+            .fetch_accumulator_messages(10)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(state
+            .fetch_accumulator_messages(15)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
     pub fn create_empty_wormhole_merkle_state_at_slot(slot: Slot) -> WormholeMerkleState {
         WormholeMerkleState {
             vaa: vec![],
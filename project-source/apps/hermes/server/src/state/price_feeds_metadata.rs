@@ -1,23 +1,78 @@
 use {
+    self::market_schedule::MarketSchedule,
     crate::{
-        api::types::{AssetType, PriceFeedMetadata},
+        api::types::{AssetType, PriceFeedMetadata, PriceFeedMetadataChange, RpcPriceIdentifier},
         state::State,
     },
     anyhow::Result,
-    tokio::sync::RwLock,
+    chrono::{DateTime, Utc},
+    pyth_sdk::PriceIdentifier,
+    sha3::{Digest, Keccak256},
+    std::collections::{BTreeMap, BTreeSet, HashMap},
+    tokio::sync::{
+        broadcast::{Receiver, Sender},
+        RwLock,
+    },
 };
 
+pub mod market_schedule;
+
 pub const DEFAULT_PRICE_FEEDS_CACHE_UPDATE_INTERVAL: u64 = 600;
 
+/// The number of metadata changes buffered for slow change feed subscribers.
+const PRICE_FEEDS_METADATA_CHANGES_CHANNEL_SIZE: usize = 1000;
+
+/// The attribute holding the market schedule of a price feed, see [`MarketSchedule`].
+const SCHEDULE_ATTRIBUTE: &str = "schedule";
+
+/// Index from a normalized attribute key and value to the ids of the price feeds having it.
+type AttributesIndex = HashMap<String, HashMap<String, BTreeSet<RpcPriceIdentifier>>>;
+
+/// Filters applied when searching the price feeds metadata.
+#[derive(Debug, Default)]
+pub struct PriceFeedsMetadataFilter {
+    /// Case insensitive substring match on the `symbol` attribute.
+    pub query: Option<String>,
+    pub asset_type: Option<AssetType>,
+    /// Case insensitive exact match on any attribute, for example `base=btc`.
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// Identifies a version of the price feeds metadata, used for HTTP caching.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PriceFeedsMetadataVersion {
+    pub etag: String,
+    /// The time at which the metadata last changed. It is `None` until metadata is loaded.
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
 #[derive(Default)]
+pub struct PriceFeedsMetadataData {
+    /// Price feeds metadata sorted by id, so pagination over it is stable.
+    pub price_feeds_metadata: Vec<PriceFeedMetadata>,
+    pub index: AttributesIndex,
+    /// Market schedules of the price feeds having a valid `schedule` attribute.
+    pub market_schedules: HashMap<PriceIdentifier, MarketSchedule>,
+    pub version: PriceFeedsMetadataVersion,
+}
+
 pub struct PriceFeedMetaState {
-    pub data: RwLock<Vec<PriceFeedMetadata>>,
+    pub data: RwLock<PriceFeedsMetadataData>,
+    pub changes_tx: Sender<PriceFeedMetadataChange>,
+}
+
+impl Default for PriceFeedMetaState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PriceFeedMetaState {
     pub fn new() -> Self {
         Self {
-            data: RwLock::new(Vec::new()),
+            data: RwLock::new(PriceFeedsMetadataData::default()),
+            changes_tx: tokio::sync::broadcast::channel(PRICE_FEEDS_METADATA_CHANGES_CHANNEL_SIZE)
+                .0,
         }
     }
 }
@@ -38,9 +93,14 @@ pub trait PriceFeedMeta {
     ) -> Result<()>;
     async fn get_price_feeds_metadata(
         &self,
-        query: Option<String>,
-        asset_type: Option<AssetType>,
+        filter: &PriceFeedsMetadataFilter,
     ) -> Result<Vec<PriceFeedMetadata>>;
+    async fn get_price_feeds_metadata_version(&self) -> PriceFeedsMetadataVersion;
+    fn subscribe_price_feeds_metadata_changes(&self) -> Receiver<PriceFeedMetadataChange>;
+    async fn get_market_schedules(
+        &self,
+        price_ids: &[PriceIdentifier],
+    ) -> HashMap<PriceIdentifier, MarketSchedule>;
 }
 
 #[async_trait::async_trait]
@@ -50,28 +110,73 @@ where
     T: Sync,
 {
     async fn retrieve_price_feeds_metadata(&self) -> Result<Vec<PriceFeedMetadata>> {
-        let price_feeds_metadata = self.into().data.read().await;
-        Ok(price_feeds_metadata.clone())
+        let data = self.into().data.read().await;
+        Ok(data.price_feeds_metadata.clone())
     }
 
     async fn store_price_feeds_metadata(
         &self,
         price_feeds_metadata: &[PriceFeedMetadata],
     ) -> Result<()> {
-        let mut price_feeds_metadata_write_guard = self.into().data.write().await;
-        *price_feeds_metadata_write_guard = price_feeds_metadata.to_vec();
+        let mut price_feeds_metadata = price_feeds_metadata.to_vec();
+        price_feeds_metadata.sort_by_key(|feed| feed.id);
+
+        let mut data = self.into().data.write().await;
+        let changes = diff_price_feeds_metadata(&data.price_feeds_metadata, &price_feeds_metadata);
+
+        let etag = compute_etag(&price_feeds_metadata);
+        if data.version.last_modified.is_none() || data.version.etag != etag {
+            data.version = PriceFeedsMetadataVersion {
+                etag,
+                last_modified: Some(Utc::now()),
+            };
+        }
+        data.index = build_attributes_index(&price_feeds_metadata);
+        data.market_schedules = parse_market_schedules(&price_feeds_metadata);
+        data.price_feeds_metadata = price_feeds_metadata;
+
+        // Only send the changes after the state has been updated. Sending fails only when there
+        // are no subscribers, which is fine.
+        for change in changes {
+            let _ = self.into().changes_tx.send(change);
+        }
+
         Ok(())
     }
 
     async fn get_price_feeds_metadata(
         &self,
-        query: Option<String>,
-        asset_type: Option<AssetType>,
+        filter: &PriceFeedsMetadataFilter,
     ) -> Result<Vec<PriceFeedMetadata>> {
-        let mut price_feeds_metadata = self.retrieve_price_feeds_metadata().await?;
+        let data = self.into().data.read().await;
+
+        // Narrow the candidates down using the attributes index first.
+        let mut candidates: Option<BTreeSet<RpcPriceIdentifier>> = None;
+        for (key, value) in &filter.attributes {
+            let ids = data
+                .index
+                .get(&normalize(key))
+                .and_then(|values| values.get(&normalize(value)))
+                .cloned()
+                .unwrap_or_default();
+            candidates = Some(match candidates {
+                Some(candidates) => candidates.intersection(&ids).copied().collect(),
+                None => ids,
+            });
+        }
+
+        let mut price_feeds_metadata = match candidates {
+            Some(candidates) => data
+                .price_feeds_metadata
+                .iter()
+                .filter(|feed| candidates.contains(&feed.id))
+                .cloned()
+                .collect(),
+            None => data.price_feeds_metadata.clone(),
+        };
 
         // Filter by query if provided
-        if let Some(query_str) = &query {
+        if let Some(query_str) = &filter.query {
             price_feeds_metadata.retain(|feed| {
                 feed.attributes.get("symbol").map_or(false, |symbol| {
                     symbol.to_lowercase().contains(&query_str.to_lowercase())
@@ -80,7 +185,7 @@ where
         }
 
         // Filter by asset_type if provided
-        if let Some(asset_type) = &asset_type {
+        if let Some(asset_type) = &filter.asset_type {
             price_feeds_metadata.retain(|feed| {
                 feed.attributes.get("asset_type").map_or(false, |type_str| {
                     type_str.to_lowercase().trim().replace(" ", "_")
@@ -91,4 +196,224 @@ where
 
         Ok(price_feeds_metadata)
     }
+
+    async fn get_price_feeds_metadata_version(&self) -> PriceFeedsMetadataVersion {
+        self.into().data.read().await.version.clone()
+    }
+
+    fn subscribe_price_feeds_metadata_changes(&self) -> Receiver<PriceFeedMetadataChange> {
+        self.into().changes_tx.subscribe()
+    }
+
+    async fn get_market_schedules(
+        &self,
+        price_ids: &[PriceIdentifier],
+    ) -> HashMap<PriceIdentifier, MarketSchedule> {
+        let data = self.into().data.read().await;
+        price_ids
+            .iter()
+            .filter_map(|price_id| {
+                data.market_schedules
+                    .get(price_id)
+                    .map(|schedule| (*price_id, schedule.clone()))
+            })
+            .collect()
+    }
+}
+
+fn normalize(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+fn build_attributes_index(price_feeds_metadata: &[PriceFeedMetadata]) -> AttributesIndex {
+    let mut index = AttributesIndex::new();
+    for feed in price_feeds_metadata {
+        for (key, value) in &feed.attributes {
+            index
+                .entry(normalize(key))
+                .or_default()
+                .entry(normalize(value))
+                .or_default()
+                .insert(feed.id);
+        }
+    }
+    index
+}
+
+fn parse_market_schedules(
+    price_feeds_metadata: &[PriceFeedMetadata],
+) -> HashMap<PriceIdentifier, MarketSchedule> {
+    price_feeds_metadata
+        .iter()
+        .filter_map(|feed| {
+            let schedule = feed.attributes.get(SCHEDULE_ATTRIBUTE)?;
+            match schedule.parse::<MarketSchedule>() {
+                Ok(schedule) => Some((feed.id.into(), schedule)),
+                Err(e) => {
+                    tracing::warn!(id = ?feed.id, schedule = %schedule, error = ?e, "Ignoring invalid market schedule.");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Compute the changes between two lists of price feeds metadata sorted by id.
+fn diff_price_feeds_metadata(
+    old: &[PriceFeedMetadata],
+    new: &[PriceFeedMetadata],
+) -> Vec<PriceFeedMetadataChange> {
+    let old_by_id = old
+        .iter()
+        .map(|feed| (feed.id, feed))
+        .collect::<BTreeMap<_, _>>();
+    let new_ids = new.iter().map(|feed| feed.id).collect::<BTreeSet<_>>();
+
+    let mut changes = new
+        .iter()
+        .filter_map(|feed| match old_by_id.get(&feed.id) {
+            None => Some(PriceFeedMetadataChange::Added {
+                price_feed: feed.clone(),
+            }),
+            Some(old_feed) if old_feed.attributes != feed.attributes => {
+                Some(PriceFeedMetadataChange::Updated {
+                    price_feed: feed.clone(),
+                })
+            }
+            Some(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    changes.extend(
+        old.iter()
+            .filter(|feed| !new_ids.contains(&feed.id))
+            .map(|feed| PriceFeedMetadataChange::Removed { id: feed.id }),
+    );
+
+    changes
+}
+
+/// Compute a strong ETag over the ids and attributes of all the price feeds.
+fn compute_etag(price_feeds_metadata: &[PriceFeedMetadata]) -> String {
+    let mut hasher = Keccak256::new();
+    for feed in price_feeds_metadata {
+        hasher.update(PriceIdentifier::from(feed.id).to_bytes());
+        for (key, value) in &feed.attributes {
+            hasher.update((key.len() as u64).to_be_bytes());
+            hasher.update(key.as_bytes());
+            hasher.update((value.len() as u64).to_be_bytes());
+            hasher.update(value.as_bytes());
+        }
+    }
+    format!("\"{}\"", hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "tests")]
+mod test {
+    use {super::*, crate::state::test::setup_state};
+
+    fn create_price_feed_metadata(seed: u8, attributes: &[(&str, &str)]) -> PriceFeedMetadata {
+        PriceFeedMetadata {
+            id: RpcPriceIdentifier::new([seed; 32]),
+            attributes: attributes
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn test_get_price_feeds_metadata_filters_by_attributes() {
+        let (state, _) = setup_state(10).await;
+
+        let btc_usd = create_price_feed_metadata(1, &[("base", "BTC"), ("quote_currency", "USD")]);
+        let eth_usd = create_price_feed_metadata(2, &[("base", "ETH"), ("quote_currency", "USD")]);
+        let eth_btc = create_price_feed_metadata(3, &[("base", "ETH"), ("quote_currency", "BTC")]);
+
+        state
+            .store_price_feeds_metadata(&[eth_btc.clone(), btc_usd.clone(), eth_usd.clone()])
+            .await
+            .unwrap();
+
+        // Values are matched case insensitively and results are sorted by id.
+        let filter = PriceFeedsMetadataFilter {
+            attributes: [("quote_currency".to_string(), "usd".to_string())].into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            state.get_price_feeds_metadata(&filter).await.unwrap(),
+            vec![btc_usd, eth_usd.clone()]
+        );
+
+        // Multiple attributes must all match.
+        let filter = PriceFeedsMetadataFilter {
+            attributes: [
+                ("base".to_string(), "eth".to_string()),
+                ("quote_currency".to_string(), "usd".to_string()),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            state.get_price_feeds_metadata(&filter).await.unwrap(),
+            vec![eth_usd]
+        );
+
+        // Unknown attributes match nothing.
+        let filter = PriceFeedsMetadataFilter {
+            attributes: [("tenor".to_string(), "1m".to_string())].into(),
+            ..Default::default()
+        };
+        assert!(state
+            .get_price_feeds_metadata(&filter)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    pub async fn test_store_price_feeds_metadata_sends_changes_and_updates_version() {
+        let (state, _) = setup_state(10).await;
+        let mut changes_rx = state.subscribe_price_feeds_metadata_changes();
+
+        let feed_1 = create_price_feed_metadata(1, &[("symbol", "Crypto.BTC/USD")]);
+        let feed_2 = create_price_feed_metadata(2, &[("symbol", "Crypto.ETH/USD")]);
+        state
+            .store_price_feeds_metadata(&[feed_1.clone(), feed_2.clone()])
+            .await
+            .unwrap();
+        let version = state.get_price_feeds_metadata_version().await;
+        assert!(version.last_modified.is_some());
+
+        assert_eq!(
+            changes_rx.recv().await.unwrap(),
+            PriceFeedMetadataChange::Added {
+                price_feed: feed_1.clone()
+            }
+        );
+        assert_eq!(
+            changes_rx.recv().await.unwrap(),
+            PriceFeedMetadataChange::Added { price_feed: feed_2 }
+        );
+
+        // Removing a feed changes the version and sends a removal.
+        state
+            .store_price_feeds_metadata(&[feed_1.clone()])
+            .await
+            .unwrap();
+        assert_ne!(state.get_price_feeds_metadata_version().await, version);
+        assert_eq!(
+            changes_rx.recv().await.unwrap(),
+            PriceFeedMetadataChange::Removed {
+                id: RpcPriceIdentifier::new([2; 32])
+            }
+        );
+
+        // Storing the same metadata again keeps the version and sends no changes.
+        let version = state.get_price_feeds_metadata_version().await;
+        state.store_price_feeds_metadata(&[feed_1]).await.unwrap();
+        assert_eq!(state.get_price_feeds_metadata_version().await, version);
+        assert!(changes_rx.try_recv().is_err());
+    }
 }
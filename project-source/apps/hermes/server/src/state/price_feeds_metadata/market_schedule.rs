@@ -0,0 +1,364 @@
+//! Market schedules parsed from the `schedule` attribute of the price feeds metadata.
+//!
+//! A schedule has the form `<timezone>;<weekly hours>;<holidays>`, for example:
+//!
+//! ```text
+//! America/New_York;0930-1600,0930-1600,0930-1600,0930-1600,0930-1600,C,C;1225/C,1128/0930-1300
+//! ```
+//!
+//! The weekly hours list the trading hours of each day from Monday to Sunday, and the optional
+//! holidays override the trading hours of a given day of the year (`MMDD`). Trading hours are
+//! either `O` (open all day), `C` (closed all day) or a `&` separated list of `HHMM-HHMM` ranges
+//! in the local time of the schedule, where the end of the range is exclusive and can be `2400`.
+
+use {
+    anyhow::{anyhow, Context, Result},
+    chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Timelike, Utc, Weekday},
+    chrono_tz::Tz,
+    std::{collections::HashMap, str::FromStr},
+};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// The number of days looked ahead when searching for the next market open or close. Markets are
+/// not expected to stay closed, or open without interruption, for longer than this.
+const MAX_SEARCH_DAYS: u32 = 14;
+
+/// Trading hours of a single day, in minutes since the local midnight.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TradingHours {
+    Open,
+    Closed,
+    /// Sorted, non-empty ranges of `[start, end)` minutes.
+    Ranges(Vec<(u32, u32)>),
+}
+
+impl TradingHours {
+    fn is_open_at(&self, minute: u32) -> bool {
+        match self {
+            TradingHours::Open => true,
+            TradingHours::Closed => false,
+            TradingHours::Ranges(ranges) => ranges
+                .iter()
+                .any(|(start, end)| *start <= minute && minute < *end),
+        }
+    }
+
+    /// The first minute at or after `minute` on this day at which the market is open.
+    fn next_open_at_or_after(&self, minute: u32) -> Option<u32> {
+        match self {
+            TradingHours::Open => Some(minute),
+            TradingHours::Closed => None,
+            TradingHours::Ranges(ranges) => ranges
+                .iter()
+                .find(|(_, end)| minute < *end)
+                .map(|(start, _)| (*start).max(minute)),
+        }
+    }
+
+    /// The first minute at or after `minute` on this day at which the market is closed.
+    fn next_close_at_or_after(&self, minute: u32) -> Option<u32> {
+        match self {
+            TradingHours::Open => None,
+            TradingHours::Closed => Some(minute),
+            TradingHours::Ranges(ranges) => {
+                // Ranges can be adjacent (e.g. `0930-1200&1200-1600`) so keep following them
+                // until reaching a closed minute.
+                let mut minute = minute;
+                while let Some((_, end)) = ranges
+                    .iter()
+                    .find(|(start, end)| *start <= minute && minute < *end)
+                {
+                    minute = *end;
+                }
+                (minute < MINUTES_PER_DAY).then_some(minute)
+            }
+        }
+    }
+}
+
+impl FromStr for TradingHours {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "O" => Ok(TradingHours::Open),
+            "C" => Ok(TradingHours::Closed),
+            ranges => {
+                let mut ranges = ranges
+                    .split('&')
+                    .map(parse_range)
+                    .collect::<Result<Vec<_>>>()?;
+                ranges.sort_unstable();
+                Ok(TradingHours::Ranges(ranges))
+            }
+        }
+    }
+}
+
+/// Parse a `HHMM-HHMM` range into minutes since midnight.
+fn parse_range(range: &str) -> Result<(u32, u32)> {
+    let (start, end) = range
+        .split_once('-')
+        .ok_or_else(|| anyhow!("Invalid trading hours range: {range}"))?;
+    let (start, end) = (parse_time(start)?, parse_time(end)?);
+    if start >= end {
+        return Err(anyhow!("Empty trading hours range: {range}"));
+    }
+    Ok((start, end))
+}
+
+/// Parse a `HHMM` time into minutes since midnight. `2400` is accepted as the end of the day.
+fn parse_time(time: &str) -> Result<u32> {
+    let time = time.trim();
+    if time.len() != 4 || !time.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("Invalid time: {time}"));
+    }
+    let value: u32 = time.parse()?;
+    let (hours, minutes) = (value / 100, value % 100);
+    let minute = hours * 60 + minutes;
+    if minutes >= 60 || minute > MINUTES_PER_DAY {
+        return Err(anyhow!("Invalid time: {time}"));
+    }
+    Ok(minute)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketSchedule {
+    pub timezone: Tz,
+    /// Trading hours from Monday to Sunday.
+    pub weekly_hours: [TradingHours; 7],
+    /// Trading hours overriding the weekly hours, keyed by `(month, day)`.
+    pub holidays: HashMap<(u32, u32), TradingHours>,
+}
+
+impl FromStr for MarketSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.trim().split(';');
+
+        let timezone = parts
+            .next()
+            .ok_or_else(|| anyhow!("Missing timezone"))?
+            .trim();
+        let timezone =
+            Tz::from_str(timezone).map_err(|e| anyhow!("Invalid timezone {timezone}: {e}"))?;
+
+        let weekly_hours: Vec<TradingHours> = parts
+            .next()
+            .ok_or_else(|| anyhow!("Missing weekly trading hours"))?
+            .split(',')
+            .map(TradingHours::from_str)
+            .collect::<Result<_>>()?;
+        let weekly_hours: [TradingHours; 7] = weekly_hours
+            .try_into()
+            .map_err(|_| anyhow!("Weekly trading hours must have 7 days"))?;
+
+        let mut holidays = HashMap::new();
+        if let Some(holiday_hours) = parts.next() {
+            for holiday in holiday_hours.split(',').filter(|h| !h.trim().is_empty()) {
+                let (date, hours) = holiday
+                    .split_once('/')
+                    .ok_or_else(|| anyhow!("Invalid holiday: {holiday}"))?;
+                let date: u32 = date
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("Invalid holiday date: {date}"))?;
+                let (month, day) = (date / 100, date % 100);
+                if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+                    return Err(anyhow!("Invalid holiday date: {date}"));
+                }
+                holidays.insert((month, day), hours.parse()?);
+            }
+        }
+
+        if parts.next().is_some() {
+            return Err(anyhow!("Unexpected trailing schedule section"));
+        }
+
+        Ok(MarketSchedule {
+            timezone,
+            weekly_hours,
+            holidays,
+        })
+    }
+}
+
+impl MarketSchedule {
+    fn trading_hours(&self, date: NaiveDate) -> &TradingHours {
+        if let Some(hours) = self.holidays.get(&(date.month(), date.day())) {
+            return hours;
+        }
+        let [mon, tue, wed, thu, fri, sat, sun] = &self.weekly_hours;
+        match date.weekday() {
+            Weekday::Mon => mon,
+            Weekday::Tue => tue,
+            Weekday::Wed => wed,
+            Weekday::Thu => thu,
+            Weekday::Fri => fri,
+            Weekday::Sat => sat,
+            Weekday::Sun => sun,
+        }
+    }
+
+    /// The local date and minute of the day of the given time.
+    fn local_minute(&self, at: DateTime<Utc>) -> (NaiveDate, u32) {
+        let local = at.with_timezone(&self.timezone);
+        (local.date_naive(), local.hour() * 60 + local.minute())
+    }
+
+    fn to_utc(&self, date: NaiveDate, minute: u32) -> Option<DateTime<Utc>> {
+        let time = NaiveTime::from_hms_opt(minute / 60, minute % 60, 0)?;
+        // A local time that falls in a daylight saving gap does not exist, in which case the
+        // market opens or closes when the clocks move forward.
+        self.timezone
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .or_else(|| self.to_utc(date, minute + 60))
+            .map(|time| time.with_timezone(&Utc))
+    }
+
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        let (date, minute) = self.local_minute(at);
+        self.trading_hours(date).is_open_at(minute)
+    }
+
+    /// The first time at or after `at` at which the market is open.
+    pub fn next_open(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let (mut date, mut minute) = self.local_minute(at);
+        for _ in 0..MAX_SEARCH_DAYS {
+            if let Some(open) = self.trading_hours(date).next_open_at_or_after(minute) {
+                return self.to_utc(date, open).map(|open| open.max(at));
+            }
+            date = date.succ_opt()?;
+            minute = 0;
+        }
+        None
+    }
+
+    /// The first time at or after `at` at which the market is closed. Returns `None` for markets
+    /// that never close, such as crypto markets.
+    pub fn next_close(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let (mut date, mut minute) = self.local_minute(at);
+        for _ in 0..MAX_SEARCH_DAYS {
+            if let Some(close) = self.trading_hours(date).next_close_at_or_after(minute) {
+                return self.to_utc(date, close).map(|close| close.max(at));
+            }
+            date = date.succ_opt()?;
+            minute = 0;
+        }
+        None
+    }
+
+    /// Whether a price published at `publish_time` is still current at `at`, that is the market
+    /// is open at `at` and has not closed since the price was published.
+    pub fn is_fresh(&self, publish_time: DateTime<Utc>, at: DateTime<Utc>) -> bool {
+        self.is_open(at)
+            && self
+                .next_close(publish_time)
+                .map_or(true, |close| close > at)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, reason = "tests")]
+mod test {
+    use super::*;
+
+    const US_EQUITY_SCHEDULE: &str =
+        "America/New_York;0930-1600,0930-1600,0930-1600,0930-1600,0930-1600,C,C;1225/C,1128/0930-1300";
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_market_schedule() {
+        let schedule: MarketSchedule = US_EQUITY_SCHEDULE.parse().unwrap();
+        assert_eq!(schedule.timezone, Tz::America__New_York);
+        let [monday, .., sunday] = &schedule.weekly_hours;
+        assert_eq!(monday, &TradingHours::Ranges(vec![(570, 960)]));
+        assert_eq!(sunday, &TradingHours::Closed);
+        assert_eq!(
+            schedule.holidays.get(&(12, 25)),
+            Some(&TradingHours::Closed)
+        );
+        assert_eq!(
+            schedule.holidays.get(&(11, 28)),
+            Some(&TradingHours::Ranges(vec![(570, 780)]))
+        );
+
+        let schedule: MarketSchedule = "UTC;O,O,O,O,O,O,O;".parse().unwrap();
+        assert!(schedule
+            .weekly_hours
+            .iter()
+            .all(|hours| hours == &TradingHours::Open));
+        assert!(schedule.holidays.is_empty());
+
+        assert!("Invalid/Zone;O,O,O,O,O,O,O"
+            .parse::<MarketSchedule>()
+            .is_err());
+        assert!("UTC;O,O,O,O,O,O".parse::<MarketSchedule>().is_err());
+        assert!("UTC;O,O,O,O,O,O,1600-0930"
+            .parse::<MarketSchedule>()
+            .is_err());
+        assert!("UTC;O,O,O,O,O,O,0960-1000"
+            .parse::<MarketSchedule>()
+            .is_err());
+        assert!("UTC;O,O,O,O,O,O,O;1301/C"
+            .parse::<MarketSchedule>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_market_schedule_open_and_close() {
+        let schedule: MarketSchedule = US_EQUITY_SCHEDULE.parse().unwrap();
+
+        // Wednesday 2024-11-27 10:00 EST.
+        let wednesday = utc("2024-11-27T15:00:00Z");
+        assert!(schedule.is_open(wednesday));
+        assert_eq!(schedule.next_open(wednesday), Some(wednesday));
+        assert_eq!(
+            schedule.next_close(wednesday),
+            Some(utc("2024-11-27T21:00:00Z"))
+        );
+
+        // Thanksgiving half day closes at 13:00 EST.
+        let thanksgiving = utc("2024-11-28T18:30:00Z");
+        assert!(!schedule.is_open(thanksgiving));
+
+        // Saturday opens on Monday at 09:30 EST.
+        let saturday = utc("2024-11-30T15:00:00Z");
+        assert!(!schedule.is_open(saturday));
+        assert_eq!(
+            schedule.next_open(saturday),
+            Some(utc("2024-12-02T14:30:00Z"))
+        );
+
+        // Christmas is closed, so the market opens on the next day.
+        let christmas = utc("2024-12-25T15:00:00Z");
+        assert!(!schedule.is_open(christmas));
+        assert_eq!(
+            schedule.next_open(christmas),
+            Some(utc("2024-12-26T14:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_market_schedule_is_fresh() {
+        let schedule: MarketSchedule = US_EQUITY_SCHEDULE.parse().unwrap();
+
+        // Published and served during the same session.
+        assert!(schedule.is_fresh(utc("2024-11-27T15:00:00Z"), utc("2024-11-27T15:01:00Z")));
+        // Served while the market is closed.
+        assert!(!schedule.is_fresh(utc("2024-11-27T20:59:00Z"), utc("2024-11-27T21:01:00Z")));
+        // Published during the previous session.
+        assert!(!schedule.is_fresh(utc("2024-11-27T20:59:00Z"), utc("2024-12-02T14:31:00Z")));
+
+        // Markets that never close are always fresh.
+        let schedule: MarketSchedule = "UTC;O,O,O,O,O,O,O".parse().unwrap();
+        assert_eq!(schedule.next_close(utc("2024-11-27T15:00:00Z")), None);
+        assert!(schedule.is_fresh(utc("2024-11-27T15:00:00Z"), utc("2024-12-27T15:00:00Z")));
+    }
+}
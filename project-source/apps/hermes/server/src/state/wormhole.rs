@@ -63,6 +63,7 @@ pub trait Wormhole: Aggregates {
     async fn store_vaa(&self, sequence: u64, vaa_bytes: Vec<u8>);
     async fn process_message(&self, vaa_bytes: Vec<u8>) -> Result<()>;
     async fn update_guardian_set(&self, id: u32, guardian_set: GuardianSet);
+    async fn get_guardian_set_quorums(&self) -> BTreeMap<u32, usize>;
 }
 
 #[async_trait::async_trait]
@@ -79,6 +80,17 @@ where
         guardian_sets.insert(id, guardian_set);
     }
 
+    /// Get the quorum of each known guardian set by its ID.
+    async fn get_guardian_set_quorums(&self) -> BTreeMap<u32, usize> {
+        self.into()
+            .guardian_set
+            .read()
+            .await
+            .iter()
+            .map(|(id, guardian_set)| (*id, guardian_set.quorum()))
+            .collect()
+    }
+
     #[tracing::instrument(skip(self, vaa_bytes))]
     async fn store_vaa(&self, sequence: u64, vaa_bytes: Vec<u8>) {
         // Check VAA hasn't already been seen, this may have been checked previously
@@ -171,11 +183,7 @@ fn verify_vaa<'a>(
 
     // Ideally we need to test the signatures but currently Wormhole doesn't give us any easy way
     // to do it, so we just bypass the check in tests.
-    let quorum = if cfg!(test) {
-        0
-    } else {
-        (guardian_set.keys.len() * 2) / 3 + 1
-    };
+    let quorum = if cfg!(test) { 0 } else { guardian_set.quorum() };
 
     let secp = Secp256k1::new();
     let mut last_signer_id: Option<usize> = None;
@@ -0,0 +1,84 @@
+//! Logging and trace export.
+//!
+//! Logs are written to stderr, in a compact format in a terminal and as JSON otherwise. When an
+//! OTLP endpoint is configured the `tracing` spans are also exported to it, which lets a trace
+//! follow an update from its receipt from Pythnet or Wormhole to its delivery to the clients.
+
+use {
+    crate::config::telemetry,
+    anyhow::{ensure, Result},
+    opentelemetry::KeyValue,
+    opentelemetry_otlp::WithExportConfig,
+    opentelemetry_sdk::{
+        trace::{self, Sampler},
+        Resource,
+    },
+    std::io::IsTerminal,
+    tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer},
+};
+
+/// Initialize the global tracing subscriber. Must be called within the Tokio runtime as the
+/// traces are exported by a background task.
+pub fn init(opts: Option<&telemetry::Options>) -> Result<()> {
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_file(false)
+        .with_line_number(true)
+        .with_thread_ids(true)
+        .with_ansi(std::io::stderr().is_terminal());
+
+    // Use the compact formatter if we're in a terminal, otherwise use the JSON formatter.
+    let fmt_layer = if std::io::stderr().is_terminal() {
+        fmt_layer.compact().boxed()
+    } else {
+        fmt_layer.json().boxed()
+    };
+
+    let otlp_layer = match opts {
+        Some(telemetry::Options {
+            otlp_endpoint: Some(otlp_endpoint),
+            service_name,
+            sample_ratio,
+        }) => {
+            ensure!(
+                (0.0..=1.0).contains(sample_ratio),
+                "Telemetry sample ratio must be between 0 and 1, got {}",
+                sample_ratio
+            );
+
+            // Spans whose parent is sampled are always sampled so the traces are complete.
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(otlp_endpoint),
+                )
+                .with_trace_config(
+                    trace::config()
+                        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                            *sample_ratio,
+                        ))))
+                        .with_resource(Resource::new(vec![KeyValue::new(
+                            "service.name",
+                            service_name.clone(),
+                        )])),
+                )
+                .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        _ => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::from_default_env())
+        .with(fmt_layer)
+        .with(otlp_layer)
+        .try_init()?;
+
+    Ok(())
+}
+
+/// Export the traces that are still buffered. Blocks until the export completes.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
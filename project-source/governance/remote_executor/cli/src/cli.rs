@@ -73,6 +73,34 @@ pub enum Action {
         #[clap(short, long, help = "Spill address")]
         spill: Pubkey,
     },
+    #[clap(
+        about = "Initialize the config of the remote executor with the chain passed as --chain, this has to be done right after deploying the program and before executing any VAA"
+    )]
+    Initialize {
+        #[clap(
+            long,
+            default_value = "~/.config/solana/id.json",
+            help = "Keypair file of the upgrade authority of the program, it is the funder of the transaction"
+        )]
+        keypair: String,
+        #[clap(
+            short,
+            long,
+            help = "Governance emitter, its executor key becomes the governance authority of the config"
+        )]
+        governance_emitter: Pubkey,
+    },
+    #[clap(about = "Get set chain id payload for squads-cli")]
+    GetSetChainIdPayload {
+        #[clap(
+            short,
+            long,
+            help = "Governance authority, the executor key of the governance emitter"
+        )]
+        governance_authority: Pubkey,
+        #[clap(short, long, parse(try_from_str = parse_chain), help = "New chain")]
+        new_chain: u16,
+    },
     #[clap(about = "Map solana key to pythnet key")]
     MapKey {
         #[clap(short, long, help = "Pubkey to map")]
@@ -17,7 +17,7 @@ use {
     clap::Parser,
     cli::{Action, Cli},
     remote_executor::{
-        accounts::ExecutePostedVaa,
+        accounts::{ExecutePostedVaa, Governance, Initialize},
         state::{
             governance_payload::{ExecutorPayload, GovernanceHeader, InstructionData},
             posted_vaa::AnchorVaa,
@@ -183,6 +183,46 @@ fn main() -> Result<()> {
             println!("Test payload : {:?}", hex::encode(payload));
             Ok(())
         }
+        Action::Initialize {
+            keypair,
+            governance_emitter,
+        } => {
+            let payer =
+                read_keypair_file(&*shellexpand::tilde(&keypair)).expect("Keypair not found");
+            let rpc_client = RpcClient::new_with_commitment(&cli.rpc_url, cli.commitment);
+
+            let instruction = Instruction {
+                program_id: ID,
+                accounts: Initialize::populate(&ID, &payer.pubkey()).to_account_metas(None),
+                data: remote_executor::instruction::Initialize {
+                    governance_emitter,
+                    chain_id: cli.chain,
+                }
+                .data(),
+            };
+
+            process_transaction(&rpc_client, vec![instruction], &vec![&payer])
+        }
+        Action::GetSetChainIdPayload {
+            governance_authority,
+            new_chain,
+        } => {
+            let instruction = Instruction {
+                program_id: ID,
+                accounts: Governance::populate(&ID, &governance_authority).to_account_metas(None),
+                data: remote_executor::instruction::SetChainId {
+                    chain_id: new_chain,
+                }
+                .data(),
+            };
+            let payload = ExecutorPayload {
+                header: GovernanceHeader::executor_governance_header(cli.chain),
+                instructions: vec![InstructionData::from(&instruction)],
+            }
+            .try_to_vec()?;
+            println!("Set chain id payload : {:?}", hex::encode(payload));
+            Ok(())
+        }
         Action::MapKey { pubkey } => {
             let executor_key = Pubkey::find_program_address(
                 &[EXECUTOR_KEY_SEED.as_bytes(), &pubkey.to_bytes()],
@@ -9,4 +9,6 @@ pub enum ExecutorError {
     GovernanceHeaderInvalidAction,
     GovernanceHeaderInvalidReceiverChain,
     PostedVaaHeaderWrongMagicNumber,
+    GovernanceAuthorityMismatch,
+    UpgradeAuthorityMismatch,
 }
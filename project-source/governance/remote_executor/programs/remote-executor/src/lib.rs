@@ -3,9 +3,9 @@
 #![allow(clippy::result_large_err)]
 
 use {
-    anchor_lang::{prelude::*, system_program},
+    anchor_lang::{prelude::*, solana_program::bpf_loader_upgradeable, system_program},
     error::ExecutorError,
-    state::{claim_record::ClaimRecord, posted_vaa::AnchorVaa},
+    state::{claim_record::ClaimRecord, config::Config, posted_vaa::AnchorVaa},
     wormhole_sdk::Chain::{self, Solana},
 };
 
@@ -26,13 +26,35 @@ pub mod remote_executor {
         anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed},
     };
 
+    /// The configuration is not set at compile time so that the same build can be deployed on every chain.
+    /// Only the upgrade authority of the program can call this, so that nobody can front-run the deployer with another configuration.
+    /// `execute_posted_vaa` fails until the config is initialized, so the program must be deployed (or upgraded from a version without a config) then initialized before any VAA is executed.
+    /// The governance authority is derived as the executor key of `governance_emitter`, so that the configuration can only be changed through VAAs of the governance emitter.
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        governance_emitter: Pubkey,
+        chain_id: u16,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.governance_authority = get_executor_key_address(&id(), &governance_emitter);
+        config.chain_id = chain_id;
+        Ok(())
+    }
+
+    /// Only the governance authority can call this, so it will usually be executed as part of a governance VAA
+    pub fn set_chain_id(ctx: Context<Governance>, chain_id: u16) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.chain_id = chain_id;
+        Ok(())
+    }
+
     pub fn execute_posted_vaa(ctx: Context<ExecutePostedVaa>) -> Result<()> {
         let posted_vaa = &ctx.accounts.posted_vaa;
         let claim_record = &mut ctx.accounts.claim_record;
         claim_record.sequence = posted_vaa.sequence;
 
         let payload = ExecutorPayload::try_from_slice(&posted_vaa.payload)?;
-        payload.check_header()?;
+        payload.check_header(ctx.accounts.config.chain_id)?;
 
         let (_, bump) = Pubkey::find_program_address(
             &[EXECUTOR_KEY_SEED.as_bytes(), &posted_vaa.emitter_address],
@@ -57,6 +79,28 @@ pub mod remote_executor {
 
 pub const EXECUTOR_KEY_SEED: &str = "EXECUTOR_KEY";
 pub const CLAIM_RECORD_SEED: &str = "CLAIM_RECORD";
+pub const CONFIG_SEED: &str = "CONFIG";
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::RemoteExecutor>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ExecutorError::UpgradeAuthorityMismatch)]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(init, space = 8 + Config::LEN, payer = payer, seeds = [CONFIG_SEED.as_bytes()], bump)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Governance<'info> {
+    #[account(constraint = governance_authority.key() == config.governance_authority @ ExecutorError::GovernanceAuthorityMismatch)]
+    pub governance_authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump)]
+    pub config: Account<'info, Config>,
+}
 
 #[derive(Accounts)]
 pub struct ExecutePostedVaa<'info> {
@@ -68,12 +112,35 @@ pub struct ExecutePostedVaa<'info> {
     #[account(init_if_needed, space = 8 + ClaimRecord::LEN, payer=payer, seeds = [CLAIM_RECORD_SEED.as_bytes(), &posted_vaa.emitter_address], bump)]
     pub claim_record: Account<'info, ClaimRecord>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump)]
+    pub config: Account<'info, Config>,
     // Additional accounts passed to the instruction will be passed down to the CPIs. Very importantly executor_key needs to be passed as it will be the signer of the CPIs.
     // Below is the "anchor specification" of that account
     // #[account(seeds = [EXECUTOR_KEY_SEED.as_bytes(), &posted_vaa.emitter_address], bump)]
     // pub executor_key: UncheckedAccount<'info>,
 }
 
+impl crate::accounts::Initialize {
+    pub fn populate(program_id: &Pubkey, payer: &Pubkey) -> Self {
+        crate::accounts::Initialize {
+            payer: *payer,
+            program: *program_id,
+            program_data: get_program_data_address(program_id),
+            config: get_config_address(program_id),
+            system_program: system_program::ID,
+        }
+    }
+}
+
+impl crate::accounts::Governance {
+    pub fn populate(program_id: &Pubkey, governance_authority: &Pubkey) -> Self {
+        crate::accounts::Governance {
+            governance_authority: *governance_authority,
+            config: get_config_address(program_id),
+        }
+    }
+}
+
 impl crate::accounts::ExecutePostedVaa {
     pub fn populate(
         program_id: &Pubkey,
@@ -91,6 +158,23 @@ impl crate::accounts::ExecutePostedVaa {
             claim_record,
             posted_vaa: *posted_vaa,
             system_program: system_program::ID,
+            config: get_config_address(program_id),
         }
     }
 }
+
+pub fn get_config_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED.as_bytes()], program_id).0
+}
+
+pub fn get_executor_key_address(program_id: &Pubkey, emitter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[EXECUTOR_KEY_SEED.as_bytes(), &emitter.to_bytes()],
+        program_id,
+    )
+    .0
+}
+
+pub fn get_program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID).0
+}
@@ -0,0 +1,18 @@
+use anchor_lang::{
+    account,
+    prelude::{borsh::BorshSchema, *},
+};
+
+#[account]
+#[derive(Default, BorshSchema, Debug, PartialEq, Eq)]
+/// This struct records the configuration of this deployment of the executor
+/// - `governance_authority` : the only key allowed to change the configuration, it is the executor key of the governance emitter chosen at initialization so that the configuration can only be changed through a governance VAA
+/// - `chain_id` : the wormhole chain id of the chain this executor is deployed on, only governance payloads targeting this chain can be executed
+pub struct Config {
+    pub governance_authority: Pubkey,
+    pub chain_id: u16,
+}
+
+impl Config {
+    pub const LEN: usize = 32 + 2;
+}
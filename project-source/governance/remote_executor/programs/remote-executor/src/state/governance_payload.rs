@@ -7,6 +7,8 @@ use {
 
 pub const MAGIC_NUMBER: u32 = 0x4d475450; // Reverse order of the solidity contract because borsh uses little endian numbers (the solidity contract uses 0x5054474d)
 
+/// The wormhole chain ids of the chains the executor is deployed on, only used by the CLI to build payloads.
+/// The executor itself reads the chain id it accepts payloads for from its `Config` account.
 pub const CHAIN_ID_ARRAY: &[(&str, u16)] = &[
     ("pythnet", 26),
     ("pythtest", 26),
@@ -21,36 +23,6 @@ pub const CHAIN_ID_ARRAY: &[(&str, u16)] = &[
     ("sonic_mainnet", 40009),
 ];
 
-#[cfg(any(feature = "pythnet", feature = "pythtest"))]
-pub const CHAIN_ID: u16 = 26;
-
-#[cfg(feature = "eclipse_devnet")]
-pub const CHAIN_ID: u16 = 40001;
-
-#[cfg(feature = "eclipse_testnet")]
-pub const CHAIN_ID: u16 = 40002;
-
-#[cfg(feature = "eclipse_mainnet")]
-pub const CHAIN_ID: u16 = 40003;
-
-#[cfg(feature = "mantis_testnet")]
-pub const CHAIN_ID: u16 = 40004;
-
-#[cfg(feature = "sonic_devnet")]
-pub const CHAIN_ID: u16 = 40005;
-
-#[cfg(feature = "sonic_testnet")]
-pub const CHAIN_ID: u16 = 40006;
-
-#[cfg(feature = "atlas_testnet")]
-pub const CHAIN_ID: u16 = 40007;
-
-#[cfg(feature = "mantis_mainnet")]
-pub const CHAIN_ID: u16 = 40008;
-
-#[cfg(feature = "sonic_mainnet")]
-pub const CHAIN_ID: u16 = 40009;
-
 #[derive(AnchorDeserialize, AnchorSerialize, Debug, PartialEq, Eq)]
 pub struct ExecutorPayload {
     pub header: GovernanceHeader,
@@ -190,14 +162,14 @@ impl ExecutorPayload {
     const MODULE: Module = Module::Executor;
     const ACTION: Action = Action::ExecutePostedVaa;
 
-    pub fn check_header(&self) -> Result<()> {
+    pub fn check_header(&self, chain_id: u16) -> Result<()> {
         (self.header.magic_number == MAGIC_NUMBER)
             .ok_or(error!(ExecutorError::GovernanceHeaderInvalidMagicNumber))?;
         (self.header.module == ExecutorPayload::MODULE)
             .ok_or(error!(ExecutorError::GovernanceHeaderInvalidModule))?;
         (self.header.action == ExecutorPayload::ACTION)
             .ok_or(error!(ExecutorError::GovernanceHeaderInvalidAction))?;
-        (self.header.chain.value == CHAIN_ID)
+        (self.header.chain.value == chain_id)
             .ok_or(error!(ExecutorError::GovernanceHeaderInvalidReceiverChain))
     }
 }
@@ -208,7 +180,7 @@ pub mod tests {
         super::ExecutorPayload,
         crate::{
             error::ExecutorError,
-            state::governance_payload::{InstructionData, CHAIN_ID},
+            state::governance_payload::{InstructionData, CHAIN_ID_ARRAY},
         },
         anchor_lang::{
             prelude::{Pubkey, *},
@@ -216,6 +188,8 @@ pub mod tests {
         },
     };
 
+    const CHAIN_ID: u16 = 26;
+
     #[test]
     fn test_check_deserialization_serialization() {
         // No instructions
@@ -224,7 +198,7 @@ pub mod tests {
             instructions: vec![],
         };
 
-        assert!(payload.check_header().is_ok());
+        assert!(payload.check_header(CHAIN_ID).is_ok());
 
         let payload_bytes = payload.try_to_vec().unwrap();
         assert_eq!(
@@ -281,7 +255,7 @@ pub mod tests {
             )],
         };
 
-        assert!(payload.check_header().is_ok());
+        assert!(payload.check_header(CHAIN_ID).is_ok());
 
         let payload_bytes = payload.try_to_vec().unwrap();
         assert_eq!(
@@ -342,7 +316,7 @@ pub mod tests {
         let deserialized_payload =
             ExecutorPayload::try_from_slice(payload_bytes.as_slice()).unwrap();
         assert_eq!(
-            deserialized_payload.check_header(),
+            deserialized_payload.check_header(CHAIN_ID),
             Err(error!(ExecutorError::GovernanceHeaderInvalidModule))
         );
 
@@ -364,7 +338,7 @@ pub mod tests {
         let deserialized_payload =
             ExecutorPayload::try_from_slice(payload_bytes.as_slice()).unwrap();
         assert_eq!(
-            deserialized_payload.check_header(),
+            deserialized_payload.check_header(CHAIN_ID),
             Err(error!(ExecutorError::GovernanceHeaderInvalidMagicNumber))
         );
 
@@ -403,7 +377,7 @@ pub mod tests {
         let deserialized_payload =
             ExecutorPayload::try_from_slice(payload_bytes.as_slice()).unwrap();
         assert_eq!(
-            deserialized_payload.check_header(),
+            deserialized_payload.check_header(CHAIN_ID),
             Err(error!(ExecutorError::GovernanceHeaderInvalidReceiverChain))
         );
 
@@ -424,4 +398,25 @@ pub mod tests {
         ];
         assert!(ExecutorPayload::try_from_slice(payload_bytes.as_slice()).is_err());
     }
+
+    #[test]
+    fn test_check_header_receiver_chain() {
+        // A payload for one chain can't be replayed on the other chains
+        for &(_, chain_id) in CHAIN_ID_ARRAY {
+            let payload = ExecutorPayload {
+                header: super::GovernanceHeader::executor_governance_header(chain_id),
+                instructions: vec![],
+            };
+            for &(_, other_chain_id) in CHAIN_ID_ARRAY {
+                if other_chain_id == chain_id {
+                    assert!(payload.check_header(other_chain_id).is_ok());
+                } else {
+                    assert_eq!(
+                        payload.check_header(other_chain_id),
+                        Err(error!(ExecutorError::GovernanceHeaderInvalidReceiverChain))
+                    );
+                }
+            }
+        }
+    }
 }
@@ -1,3 +1,4 @@
 pub mod claim_record;
+pub mod config;
 pub mod governance_payload;
 pub mod posted_vaa;
@@ -16,17 +16,8 @@ impl Owner for AnchorVaa {
         Pubkey::from_str("EUrRARh92Cdc54xrDn6qzaqjA77NRrCcfbr8kPwoTL4z").unwrap()
     }
 
-    #[cfg(any(
-        feature = "eclipse_devnet",
-        feature = "eclipse_testnet",
-        feature = "eclipse_mainnet",
-        feature = "mantis_testnet",
-        feature = "sonic_devnet",
-        feature = "sonic_testnet",
-        feature = "atlas_testnet",
-        feature = "mantis_mainnet",
-        feature = "sonic_mainnet",
-    ))]
+    // All the other SVM chains use the same address for the wormhole bridge
+    #[cfg(not(any(feature = "pythnet", feature = "pythtest")))]
     fn owner() -> Pubkey {
         Pubkey::from_str("HDwcJBJXjL9FpJ7UBsYBtaDjsBUhuLCUYoz3zr8SWWaQ").unwrap()
     }
@@ -1,9 +1,13 @@
 use {
     crate::{
         error::ExecutorError,
+        get_config_address, get_executor_key_address, get_program_data_address,
         state::{
             claim_record::ClaimRecord,
-            governance_payload::{ExecutorPayload, GovernanceHeader, InstructionData, CHAIN_ID},
+            config::Config,
+            governance_payload::{
+                ExecutorPayload, GovernanceHeader, InstructionData, CHAIN_ID_ARRAY,
+            },
             posted_vaa::AnchorVaa,
         },
         CLAIM_RECORD_SEED, EXECUTOR_KEY_SEED,
@@ -19,12 +23,13 @@ use {
     },
     solana_sdk::{
         account::Account,
-        bpf_loader,
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
         instruction::{Instruction, InstructionError},
+        native_token::LAMPORTS_PER_SOL,
         signature::Keypair,
         signer::Signer,
         stake_history::Epoch,
-        system_instruction,
+        system_instruction, system_program,
         transaction::{Transaction, TransactionError},
     },
     std::{collections::HashMap, path::Path},
@@ -37,6 +42,9 @@ pub struct ExecutorBench {
     program_test: ProgramTest,
     program_id: Pubkey,
     seqno: HashMap<Pubkey, u64>,
+    chain_id: u16,
+    governance_emitter: Pubkey,
+    upgrade_authority: Keypair,
 }
 
 /// When passed to `add_vaa_account` modify the posted vaa in a way that makes the vaa invalid
@@ -52,7 +60,7 @@ pub enum VaaAttack {
 }
 
 impl ExecutorBench {
-    /// Deploys the executor program as upgradable
+    /// Deploys the executor program as upgradable, the upgrade authority is a new keypair that is funded so that it can initialize the config
     pub fn new() -> ExecutorBench {
         let bpf_data = read_file(
             std::env::current_dir()
@@ -62,27 +70,74 @@ impl ExecutorBench {
 
         let mut program_test = ProgramTest::default();
         let program_key = crate::id();
+        let program_data_key = get_program_data_address(&program_key);
+        let upgrade_authority = Keypair::new();
 
+        let program_account_data = bincode::serialize(&UpgradeableLoaderState::Program {
+            programdata_address: program_data_key,
+        })
+        .unwrap();
         let program_account = Account {
-            lamports: Rent::default().minimum_balance(bpf_data.len()),
-            data: bpf_data,
-            owner: bpf_loader::ID,
+            lamports: Rent::default().minimum_balance(program_account_data.len()),
+            data: program_account_data,
+            owner: bpf_loader_upgradeable::ID,
             executable: true,
             rent_epoch: Epoch::default(),
         };
 
+        // The program data account contains the serialized `ProgramData` state followed by the bytecode
+        let mut program_data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(upgrade_authority.pubkey()),
+        })
+        .unwrap();
+        program_data.extend_from_slice(&bpf_data);
+        let program_data_account = Account {
+            lamports: Rent::default().minimum_balance(program_data.len()),
+            data: program_data,
+            owner: bpf_loader_upgradeable::ID,
+            executable: false,
+            rent_epoch: Epoch::default(),
+        };
+
+        // The upgrade authority pays for the config account
+        let upgrade_authority_account = Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::ID,
+            executable: false,
+            rent_epoch: Epoch::default(),
+        };
+
         program_test.add_account(program_key, program_account);
+        program_test.add_account(program_data_key, program_data_account);
+        program_test.add_account(upgrade_authority.pubkey(), upgrade_authority_account);
 
         ExecutorBench {
             program_test,
             program_id: program_key.key(),
             seqno: HashMap::<Pubkey, u64>::new(),
+            chain_id: CHAIN_ID_ARRAY[0].1,
+            governance_emitter: Pubkey::new_unique(),
+            upgrade_authority,
         }
     }
 
-    /// Start local validator based on the current bench
+    /// Start local validator based on the current bench and initialize the config
     pub async fn start(self) -> ExecutorSimulator {
-        // Start validator
+        let governance_emitter = self.governance_emitter;
+        let chain_id = self.chain_id;
+
+        let mut sim = self.start_without_config().await;
+        let upgrade_authority = sim.get_upgrade_authority();
+        sim.initialize(&upgrade_authority, &governance_emitter, chain_id)
+            .await
+            .unwrap();
+        sim
+    }
+
+    /// Start local validator based on the current bench without initializing the config
+    pub async fn start_without_config(self) -> ExecutorSimulator {
         let (banks_client, genesis_keypair, recent_blockhash) = self.program_test.start().await;
 
         ExecutorSimulator {
@@ -90,6 +145,7 @@ impl ExecutorBench {
             payer: genesis_keypair,
             last_blockhash: recent_blockhash,
             program_id: self.program_id,
+            upgrade_authority: self.upgrade_authority,
         }
     }
 
@@ -99,6 +155,17 @@ impl ExecutorBench {
         emitter: &Pubkey,
         instructions: &[Instruction],
         validity: VaaAttack,
+    ) -> Pubkey {
+        self.add_vaa_account_for_chain(emitter, instructions, validity, self.chain_id)
+    }
+
+    /// Add VAA account with emitter and instructions for consumption by the remote_executor of the chain `chain_id`
+    pub fn add_vaa_account_for_chain(
+        &mut self,
+        emitter: &Pubkey,
+        instructions: &[Instruction],
+        validity: VaaAttack,
+        chain_id: u16,
     ) -> Pubkey {
         let emitter_chain: u16 = match validity {
             VaaAttack::WrongEmitterChain => Chain::Ethereum.into(),
@@ -116,7 +183,7 @@ impl ExecutorBench {
         };
 
         let payload = ExecutorPayload {
-            header: GovernanceHeader::executor_governance_header(CHAIN_ID),
+            header: GovernanceHeader::executor_governance_header(chain_id),
             instructions: instructions.iter().map(InstructionData::from).collect(),
         };
 
@@ -162,11 +229,7 @@ impl ExecutorBench {
 
     // Get executor key of an emitter, useful to construct instructions that will be in the VAA
     pub fn get_executor_key(&self, emitter: &Pubkey) -> Pubkey {
-        Pubkey::find_program_address(
-            &[EXECUTOR_KEY_SEED.as_bytes(), &emitter.to_bytes()],
-            &self.program_id,
-        )
-        .0
+        get_executor_key_address(&self.program_id, emitter)
     }
 
     // Get claim record of an emitter
@@ -177,12 +240,36 @@ impl ExecutorBench {
         )
         .0
     }
+
+    // Get the chain id the config will be initialized with
+    pub fn get_chain_id(&self) -> u16 {
+        self.chain_id
+    }
+
+    // Get the emitter whose executor key is the governance authority of the config
+    pub fn get_governance_emitter(&self) -> Pubkey {
+        self.governance_emitter
+    }
+
+    // Get an instruction setting the chain id, signed by the executor key of an emitter so that it can be in the VAA
+    pub fn get_set_chain_id_instruction(&self, emitter: &Pubkey, chain_id: u16) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: crate::accounts::Governance::populate(
+                &self.program_id,
+                &self.get_executor_key(emitter),
+            )
+            .to_account_metas(None),
+            data: crate::instruction::SetChainId { chain_id }.data(),
+        }
+    }
 }
 pub struct ExecutorSimulator {
     banks_client: BanksClient,
     payer: Keypair,
     last_blockhash: Hash,
     program_id: Pubkey,
+    upgrade_authority: Keypair,
 }
 
 /// When passed to execute_posted_vaa, try to impersonate some of the accounts
@@ -227,6 +314,52 @@ impl ExecutorSimulator {
         self.banks_client.process_transaction(transaction).await
     }
 
+    /// Initialize the config of the executor, `payer` pays for the config and has to be the upgrade authority of the program
+    pub async fn initialize(
+        &mut self,
+        payer: &Keypair,
+        governance_emitter: &Pubkey,
+        chain_id: u16,
+    ) -> Result<(), BanksClientError> {
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts: crate::accounts::Initialize::populate(&self.program_id, &payer.pubkey())
+                .to_account_metas(None),
+            data: crate::instruction::Initialize {
+                governance_emitter: *governance_emitter,
+                chain_id,
+            }
+            .data(),
+        };
+
+        self.process_ix(instruction, &vec![payer]).await
+    }
+
+    /// Get the upgrade authority of the program
+    pub fn get_upgrade_authority(&self) -> Keypair {
+        Keypair::from_bytes(&self.upgrade_authority.to_bytes()).unwrap()
+    }
+
+    /// Set the chain id of the executor, signing as `governance_authority`
+    pub async fn set_chain_id(
+        &mut self,
+        governance_authority: &Keypair,
+        chain_id: u16,
+    ) -> Result<(), BanksClientError> {
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts: crate::accounts::Governance::populate(
+                &self.program_id,
+                &governance_authority.pubkey(),
+            )
+            .to_account_metas(None),
+            data: crate::instruction::SetChainId { chain_id }.data(),
+        };
+
+        self.process_ix(instruction, &vec![governance_authority])
+            .await
+    }
+
     /// Execute the payload contained in the VAA at posted_vaa_address
     pub async fn execute_posted_vaa(
         &mut self,
@@ -332,6 +465,22 @@ impl ExecutorSimulator {
         let account = self.get_account(claim_record_key).await.unwrap();
         ClaimRecord::try_deserialize(&mut account.data.as_slice()).unwrap()
     }
+
+    /// Get the config. Returns `None` if it's not initialized.
+    pub async fn get_config_if_initialized(&mut self) -> Option<Config> {
+        self.get_account(get_config_address(&self.program_id))
+            .await
+            .map(|account| Config::try_deserialize(&mut account.data.as_slice()).unwrap())
+    }
+
+    /// Get config
+    pub async fn get_config(&mut self) -> Config {
+        let account = self
+            .get_account(get_config_address(&self.program_id))
+            .await
+            .unwrap();
+        Config::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
 }
 
 impl From<ExecutorError> for TransactionError {
@@ -0,0 +1,163 @@
+use {
+    super::executor_simulator::{ExecutorAttack, ExecutorBench, VaaAttack},
+    crate::{error::ExecutorError, state::config::Config},
+    anchor_lang::prelude::{Pubkey, Rent},
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL, signature::Keypair, signer::Signer,
+        system_instruction::transfer,
+    },
+};
+
+#[tokio::test]
+/// This test tests that :
+/// - VAAs can't be executed before the config is initialized
+/// - Only the upgrade authority of the program can initialize the config
+/// - The governance authority is the executor key of the governance emitter
+async fn test_initialize() {
+    let mut bench = ExecutorBench::new();
+    let emitter = Pubkey::new_unique();
+    let governance_emitter = bench.get_governance_emitter();
+    let chain_id = bench.get_chain_id();
+    let config = Config {
+        governance_authority: bench.get_executor_key(&governance_emitter),
+        chain_id,
+    };
+    let vaa_account = bench.add_vaa_account(&emitter, &[], VaaAttack::None);
+
+    let mut sim = bench.start_without_config().await;
+    assert!(sim.get_config_if_initialized().await.is_none());
+
+    // The config is not initialized yet
+    assert!(sim
+        .execute_posted_vaa(&vaa_account, &vec![], ExecutorAttack::None)
+        .await
+        .is_err());
+
+    // Initialization by a key that is not the upgrade authority
+    let attacker = Keypair::new();
+    sim.airdrop(&attacker.pubkey(), LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.initialize(&attacker, &governance_emitter, chain_id)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ExecutorError::UpgradeAuthorityMismatch.into()
+    );
+    assert!(sim.get_config_if_initialized().await.is_none());
+
+    // Initialization by the upgrade authority
+    let upgrade_authority = sim.get_upgrade_authority();
+    sim.initialize(&upgrade_authority, &governance_emitter, chain_id)
+        .await
+        .unwrap();
+    assert_eq!(sim.get_config().await, config);
+
+    sim.execute_posted_vaa(&vaa_account, &vec![], ExecutorAttack::None)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+/// This test file tests that :
+/// - VAAs targeting another chain are rejected, so that they can't be replayed across chains
+/// - The chain id can be changed through a VAA of the governance emitter
+/// - The chain id can't be changed by another emitter or by a direct call
+/// - The config can't be initialized twice
+async fn test_config() {
+    let mut bench = ExecutorBench::new();
+    let emitter = Pubkey::new_unique();
+    let governance_emitter = bench.get_governance_emitter();
+    let executor_key = bench.get_executor_key(&emitter);
+    let chain_id = bench.get_chain_id();
+    let other_chain_id = chain_id + 1;
+
+    let receiver = Pubkey::new_unique();
+    let transfer_instruction =
+        transfer(&executor_key, &receiver, Rent::default().minimum_balance(0));
+
+    // Setup VAAs
+    let vaa_account_other_chain = bench.add_vaa_account_for_chain(
+        &emitter,
+        &[transfer_instruction.clone()],
+        VaaAttack::None,
+        other_chain_id,
+    );
+    let set_chain_id_wrong_emitter = bench.get_set_chain_id_instruction(&emitter, other_chain_id);
+    let vaa_account_set_chain_id_wrong_emitter =
+        bench.add_vaa_account(&emitter, &[set_chain_id_wrong_emitter], VaaAttack::None);
+
+    let set_chain_id = bench.get_set_chain_id_instruction(&governance_emitter, other_chain_id);
+    let vaa_account_set_chain_id =
+        bench.add_vaa_account(&governance_emitter, &[set_chain_id], VaaAttack::None);
+    let vaa_account_old_chain = bench.add_vaa_account(&governance_emitter, &[], VaaAttack::None);
+
+    let mut sim = bench.start().await;
+    sim.airdrop(&executor_key, LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(sim.get_config().await.chain_id, chain_id);
+
+    // VAA targeting another chain
+    assert_eq!(
+        sim.execute_posted_vaa(&vaa_account_other_chain, &vec![], ExecutorAttack::None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ExecutorError::GovernanceHeaderInvalidReceiverChain.into()
+    );
+    assert!(sim.get_account(receiver).await.is_none());
+
+    // VAA of an emitter that is not the governance emitter
+    assert_eq!(
+        sim.execute_posted_vaa(
+            &vaa_account_set_chain_id_wrong_emitter,
+            &vec![],
+            ExecutorAttack::None
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        ExecutorError::GovernanceAuthorityMismatch.into()
+    );
+
+    // Direct call by a key that is not the governance authority
+    assert_eq!(
+        sim.set_chain_id(&Keypair::new(), other_chain_id)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ExecutorError::GovernanceAuthorityMismatch.into()
+    );
+
+    // The config is already initialized
+    let upgrade_authority = sim.get_upgrade_authority();
+    assert!(sim
+        .initialize(&upgrade_authority, &governance_emitter, chain_id)
+        .await
+        .is_err());
+    assert_eq!(sim.get_config().await.chain_id, chain_id);
+
+    // VAA of the governance emitter
+    sim.execute_posted_vaa(&vaa_account_set_chain_id, &vec![], ExecutorAttack::None)
+        .await
+        .unwrap();
+    assert_eq!(sim.get_config().await.chain_id, other_chain_id);
+
+    // VAAs targeting the previous chain are now rejected
+    assert_eq!(
+        sim.execute_posted_vaa(&vaa_account_old_chain, &vec![], ExecutorAttack::None)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        ExecutorError::GovernanceHeaderInvalidReceiverChain.into()
+    );
+
+    // VAAs targeting the new chain are now accepted
+    sim.execute_posted_vaa(&vaa_account_other_chain, &vec![], ExecutorAttack::None)
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.get_account(receiver).await.unwrap().lamports,
+        Rent::default().minimum_balance(0)
+    );
+}
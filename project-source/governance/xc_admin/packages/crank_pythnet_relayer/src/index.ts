@@ -28,6 +28,7 @@ import {
   WORMHOLE_API_ENDPOINT,
   CLAIM_RECORD_SEED,
   mapKey,
+  getRemoteExecutorConfigAddress,
   REMOTE_EXECUTOR_ADDRESS,
   envOrErr,
   PriceStoreMultisigInstruction,
@@ -190,6 +191,7 @@ async function run() {
             .executePostedVaa()
             .accounts({
               claimRecord: claimRecordAddress,
+              config: getRemoteExecutorConfigAddress(),
               postedVaa: derivePostedVaaKey(
                 WORMHOLE_ADDRESS[CLUSTER]!,
                 vaa.hash,
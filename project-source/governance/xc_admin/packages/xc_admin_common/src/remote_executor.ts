@@ -5,6 +5,11 @@ import { PublicKey } from "@solana/web3.js";
  */
 export const CLAIM_RECORD_SEED: string = "CLAIM_RECORD";
 
+/**
+ * Seed for the config PDA of the remote executor
+ */
+export const CONFIG_SEED: string = "CONFIG";
+
 /**
  * Seed for the executor PDA of the remote executor
  */
@@ -28,3 +33,14 @@ export function mapKey(key: PublicKey): PublicKey {
     REMOTE_EXECUTOR_ADDRESS,
   )[0];
 }
+
+/**
+ * Get the address of the config of the remote executor, which contains the chain id it executes payloads for
+ * @returns the address of the config
+ */
+export function getRemoteExecutorConfigAddress(): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(CONFIG_SEED)],
+    REMOTE_EXECUTOR_ADDRESS,
+  )[0];
+}
@@ -16,7 +16,8 @@ use {
         msg::{InstantiateMsg, MigrateMsg},
         state::{
             config, config_read, price_feed_bucket, price_feed_read_bucket, set_contract_version,
-            ConfigInfo, PythDataSource,
+            twap_snapshots_bucket, twap_snapshots_read_bucket, ConfigInfo, PythDataSource,
+            TwapSnapshot, TwapSnapshots, MAX_TWAP_SNAPSHOTS,
         },
     },
     byteorder::BigEndian,
@@ -28,14 +29,14 @@ use {
     cw_wormhole::{msg::QueryMsg as WormholeQueryMsg, state::ParsedVAA},
     pyth_sdk::{Identifier, UnixTimestamp},
     pyth_sdk_cw::{
-        error::PythContractError, ExecuteMsg, Price, PriceFeed, PriceFeedResponse, PriceIdentifier,
-        QueryMsg,
+        error::PythContractError, ExecuteMsg, ParsePriceFeedUpdatesResponse, Price, PriceFeed,
+        PriceFeedResponse, PriceIdentifier, QueryMsg, TwapPrice, TwapPriceResponse,
     },
     pythnet_sdk::legacy::{BatchPriceAttestation, PriceAttestation, PriceStatus},
     pythnet_sdk::{
         accumulators::merkle::MerkleRoot,
         hashers::keccak256_160::Keccak160,
-        messages::Message,
+        messages::{Message, TwapMessage},
         wire::{
             from_slice,
             v1::{
@@ -120,6 +121,22 @@ pub fn execute(
         ExecuteMsg::ExecuteGovernanceInstruction { data } => {
             execute_governance_instruction(deps, env, info, &data)
         }
+        ExecuteMsg::ParsePriceFeedUpdates {
+            data,
+            price_ids,
+            min_publish_time,
+            max_publish_time,
+            unique,
+        } => parse_price_feed_updates(
+            deps,
+            env,
+            info,
+            &data,
+            price_ids,
+            min_publish_time,
+            max_publish_time,
+            unique,
+        ),
     }
 }
 
@@ -426,14 +443,24 @@ fn verify_vaa_from_governance_source(state: &ConfigInfo, vaa: &ParsedVAA) -> Std
     Ok(())
 }
 
-fn parse_update(deps: &Deps, env: &Env, data: &Binary) -> StdResult<Vec<PriceFeed>> {
+/// The contents of a price update.
+#[derive(Default)]
+struct ParsedUpdate {
+    /// The price feeds of the update, each with the publish time of the previous update of the
+    /// feed.
+    price_feeds: Vec<(PriceFeed, UnixTimestamp)>,
+    /// Only accumulator updates can contain TWAP messages.
+    twap_messages: Vec<TwapMessage>,
+}
+
+fn parse_update(deps: &Deps, env: &Env, data: &Binary) -> StdResult<ParsedUpdate> {
     let header = data.get(0..4);
-    let feeds = if header == Some(PYTHNET_ACCUMULATOR_UPDATE_MAGIC.as_slice()) {
+    let update = if header == Some(PYTHNET_ACCUMULATOR_UPDATE_MAGIC.as_slice()) {
         parse_accumulator(deps, env, data)?
     } else {
         parse_batch_attestation(deps, env, data)?
     };
-    Ok(feeds)
+    Ok(update)
 }
 
 fn apply_updates(
@@ -445,18 +472,21 @@ fn apply_updates(
     let mut total_new_feeds: Vec<PriceFeed> = vec![];
 
     for datum in data {
-        let feeds = parse_update(&deps.as_ref(), env, datum)?;
-        num_total_attestations += feeds.len();
-        for feed in feeds {
+        let update = parse_update(&deps.as_ref(), env, datum)?;
+        num_total_attestations += update.price_feeds.len() + update.twap_messages.len();
+        for (feed, _) in update.price_feeds {
             if update_price_feed_if_new(deps, env, feed)? {
                 total_new_feeds.push(feed);
             }
         }
+        for twap_message in update.twap_messages {
+            update_twap_feed_if_new(deps, &twap_message)?;
+        }
     }
     Ok((num_total_attestations, total_new_feeds))
 }
 
-fn parse_accumulator(deps: &Deps, env: &Env, data: &[u8]) -> StdResult<Vec<PriceFeed>> {
+fn parse_accumulator(deps: &Deps, env: &Env, data: &[u8]) -> StdResult<ParsedUpdate> {
     let update_data = AccumulatorUpdateData::try_from_slice(data)
         .map_err(|_| PythContractError::InvalidAccumulatorPayload)?;
     match update_data.proof {
@@ -475,7 +505,7 @@ fn parse_accumulator(deps: &Deps, env: &Env, data: &[u8]) -> StdResult<Vec<Price
             let root: MerkleRoot<Keccak160> = MerkleRoot::new(match msg.payload {
                 WormholePayload::Merkle(merkle_root) => merkle_root.root,
             });
-            let mut feeds = vec![];
+            let mut parsed_update = ParsedUpdate::default();
             for update in updates {
                 let message_vec = Vec::from(update.message);
                 if !root.check(update.proof, &message_vec) {
@@ -502,33 +532,42 @@ fn parse_accumulator(deps: &Deps, env: &Env, data: &[u8]) -> StdResult<Vec<Price
                                 publish_time: price_feed_message.publish_time,
                             },
                         );
-                        feeds.push(price_feed);
+                        parsed_update
+                            .price_feeds
+                            .push((price_feed, price_feed_message.prev_publish_time));
+                    }
+                    Message::TwapMessage(twap_message) => {
+                        parsed_update.twap_messages.push(twap_message)
                     }
-                    _ => return Err(PythContractError::InvalidAccumulatorMessageType)?,
+                    // Publisher stake caps are only used on Pythnet. They are accepted so that
+                    // updates containing them can still be applied.
+                    Message::PublisherStakeCapsMessage(_) => {}
                 }
             }
-            Ok(feeds)
+            Ok(parsed_update)
         }
     }
 }
 
 /// Update the on-chain storage for any new price updates provided in `batch_attestation`.
-fn parse_batch_attestation(deps: &Deps, env: &Env, data: &Binary) -> StdResult<Vec<PriceFeed>> {
+fn parse_batch_attestation(deps: &Deps, env: &Env, data: &Binary) -> StdResult<ParsedUpdate> {
     let vaa = parse_and_verify_vaa(*deps, env.block.time.seconds(), data)?;
     let state = config_read(deps.storage).load()?;
     verify_vaa_from_data_source(&state, &vaa)?;
     let data = &vaa.payload;
     let batch_attestation = BatchPriceAttestation::deserialize(&data[..])
         .map_err(|_| PythContractError::InvalidUpdatePayload)?;
-    let mut feeds = vec![];
+    let mut parsed_update = ParsedUpdate::default();
 
     // Update prices
     for price_attestation in batch_attestation.price_attestations.iter() {
         let price_feed = create_price_feed_from_price_attestation(price_attestation);
-        feeds.push(price_feed);
+        parsed_update
+            .price_feeds
+            .push((price_feed, price_attestation.prev_publish_time));
     }
 
-    Ok(feeds)
+    Ok(parsed_update)
 }
 
 fn create_price_feed_from_price_attestation(price_attestation: &PriceAttestation) -> PriceFeed {
@@ -601,6 +640,82 @@ fn update_price_feed_if_new(
     Ok(is_new_price)
 }
 
+/// Returns true if the snapshot of `twap_message` was stored, i.e. it is newer than the stored
+/// snapshots of its feed. The oldest snapshot is dropped once `MAX_TWAP_SNAPSHOTS` are stored.
+///
+/// A message that is not the first one of its timestamp is ignored like an outdated one, as the
+/// TWAPs computed from it wouldn't be deterministic.
+fn update_twap_feed_if_new(deps: &mut DepsMut, twap_message: &TwapMessage) -> StdResult<bool> {
+    let snapshot = TwapSnapshot::from(twap_message);
+    if snapshot.prev_publish_time >= snapshot.publish_time {
+        return Ok(false);
+    }
+
+    let mut is_new_twap = true;
+    twap_snapshots_bucket(deps.storage).update(
+        &twap_message.feed_id,
+        |maybe_twap_snapshots| -> StdResult<TwapSnapshots> {
+            let mut twap_snapshots = maybe_twap_snapshots.unwrap_or_default();
+            match twap_snapshots.snapshots.last() {
+                Some(latest) if latest.publish_time >= snapshot.publish_time => {
+                    is_new_twap = false;
+                }
+                _ => {
+                    if twap_snapshots.snapshots.len() >= MAX_TWAP_SNAPSHOTS {
+                        twap_snapshots.snapshots.remove(0);
+                    }
+                    twap_snapshots.snapshots.push(snapshot);
+                }
+            }
+            Ok(twap_snapshots)
+        },
+    )?;
+    Ok(is_new_twap)
+}
+
+/// Calculate the TWAP between the `start` and `end` snapshots of a feed. The cumulative values
+/// grow by the price (or confidence) of each slot, so their increase over the window divided by
+/// its number of slots is the average. This matches the TWAPs served by Hermes.
+fn calculate_twap(
+    id: PriceIdentifier,
+    start: &TwapSnapshot,
+    end: &TwapSnapshot,
+) -> StdResult<TwapPrice> {
+    if end.publish_slot <= start.publish_slot {
+        Err(PythContractError::InvalidTwapWindow)?;
+    }
+    let slots = end.publish_slot - start.publish_slot;
+
+    let price = end
+        .cumulative_price
+        .checked_sub(start.cumulative_price)
+        .and_then(|sum| i64::try_from(sum / i128::from(slots)).ok())
+        .ok_or(PythContractError::TwapOverflow)?;
+    let conf = end
+        .cumulative_conf
+        .checked_sub(start.cumulative_conf)
+        .and_then(|sum| u64::try_from(sum / u128::from(slots)).ok())
+        .ok_or(PythContractError::TwapOverflow)?;
+    // The ratio of down slots is scaled by 1_000_000, see `TwapPrice`.
+    let down_slots_ratio = end
+        .num_down_slots
+        .checked_sub(start.num_down_slots)
+        .and_then(|down_slots| {
+            u32::try_from(u128::from(down_slots) * 1_000_000 / u128::from(slots)).ok()
+        })
+        .ok_or(PythContractError::TwapOverflow)?;
+
+    Ok(TwapPrice {
+        id,
+        price,
+        conf,
+        expo: end.expo,
+        start_time: start.publish_time,
+        end_time: end.publish_time,
+        down_slots_ratio,
+    })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -611,12 +726,20 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         }
         QueryMsg::GetUpdateFee { vaas } => to_binary(&get_update_fee(&deps, &vaas)?),
         QueryMsg::GetValidTimePeriod => to_binary(&get_valid_time_period(&deps)?),
+        QueryMsg::TwapPrice { id } => to_binary(&query_twap_price(&deps, id, None)?),
+        QueryMsg::TwapPriceWithWindow { id, window_secs } => {
+            to_binary(&query_twap_price(&deps, id, Some(window_secs))?)
+        }
     }
 }
 
-/// This function is not used in the contract yet but mimicks the behavior implemented
-/// in the EVM contract. We are yet to finalize how the parsed prices should be consumed
-/// in injective as well as other chains.
+/// Parse and verify `updates` without storing them, and return the price feeds of `price_feeds`
+/// in the response data. This mimicks the behavior implemented in the EVM contract.
+///
+/// Only updates published between `min_publish_time` and `max_publish_time` are returned. If
+/// `unique` is set, an update is only returned if the previous update of its feed was published
+/// before `min_publish_time`.
+#[allow(clippy::too_many_arguments)]
 pub fn parse_price_feed_updates(
     deps: DepsMut,
     env: Env,
@@ -625,44 +748,40 @@ pub fn parse_price_feed_updates(
     price_feeds: Vec<Identifier>,
     min_publish_time: UnixTimestamp,
     max_publish_time: UnixTimestamp,
+    unique: bool,
 ) -> StdResult<Response<MsgWrapper>> {
-    let _config = config_read(deps.storage).load()?;
     if !is_fee_sufficient(&deps.as_ref(), info, updates)? {
         Err(PythContractError::InsufficientFee)?;
     }
-    let mut found_feeds = 0;
     let mut results: Vec<(Identifier, Option<PriceFeed>)> =
         price_feeds.iter().map(|id| (*id, None)).collect();
     for datum in updates {
-        let feeds = parse_update(&deps.as_ref(), &env, datum)?;
+        let update = parse_update(&deps.as_ref(), &env, datum)?;
         for result in results.as_mut_slice() {
             if result.1.is_some() {
                 continue;
             }
-            for feed in feeds.as_slice() {
-                if feed.get_price_unchecked().publish_time < min_publish_time
-                    || feed.get_price_unchecked().publish_time > max_publish_time
-                {
-                    continue;
-                }
-                if result.0 == feed.id {
-                    result.1 = Some(*feed);
-                    found_feeds += 1;
-                    break;
-                }
-            }
+            result.1 = update
+                .price_feeds
+                .iter()
+                .find(|(feed, prev_publish_time)| {
+                    let publish_time = feed.get_price_unchecked().publish_time;
+                    feed.id == result.0
+                        && publish_time >= min_publish_time
+                        && publish_time <= max_publish_time
+                        && (!unique || *prev_publish_time < min_publish_time)
+                })
+                .map(|(feed, _)| *feed);
         }
     }
-    if found_feeds != price_feeds.len() {
-        Err(PythContractError::InvalidUpdatePayload)?;
-    }
 
-    let _unwrapped_feeds = results
+    let price_feeds = results
         .into_iter()
-        .map(|(_, feed)| feed.unwrap())
-        .collect::<Vec<PriceFeed>>();
-    let response = Response::new();
-    Ok(response.add_attribute("action", "parse_price_feeds"))
+        .map(|(_, feed)| feed.ok_or(PythContractError::PriceFeedNotFoundWithinRange))
+        .collect::<Result<Vec<PriceFeed>, _>>()?;
+    Ok(Response::new()
+        .set_data(to_binary(&ParsePriceFeedUpdatesResponse { price_feeds })?)
+        .add_attribute("action", "parse_price_feeds"))
 }
 
 /// Get the most recent value of the price feed indicated by `feed_id`.
@@ -673,6 +792,36 @@ pub fn query_price_feed(deps: &Deps, feed_id: &[u8]) -> StdResult<PriceFeedRespo
     }
 }
 
+/// Get the TWAP of the feed indicated by `id` up to its latest TWAP update.
+///
+/// Without `window_secs`, the TWAP starts at the previous update. Otherwise, it starts at the
+/// most recent stored update published at least `window_secs` seconds before the latest one, so
+/// the window is longer than requested when no update was published exactly at its start. The
+/// query fails if no stored update is old enough.
+pub fn query_twap_price(
+    deps: &Deps,
+    id: PriceIdentifier,
+    window_secs: Option<u64>,
+) -> StdResult<TwapPriceResponse> {
+    let twap_snapshots = twap_snapshots_read_bucket(deps.storage)
+        .load(id.as_ref())
+        .map_err(|_| PythContractError::TwapPriceNotFound)?;
+    let (end, previous) = twap_snapshots
+        .snapshots
+        .split_last()
+        .ok_or(PythContractError::TwapPriceNotFound)?;
+    let start = match window_secs {
+        None => previous.last(),
+        Some(window_secs) => previous.iter().rev().find(|snapshot| {
+            u64::try_from(end.publish_time - snapshot.publish_time)
+                .map_or(false, |elapsed| elapsed >= window_secs)
+        }),
+    }
+    .ok_or(PythContractError::InvalidTwapWindow)?;
+    let twap = calculate_twap(id, start, end)?;
+    Ok(TwapPriceResponse { twap })
+}
+
 pub fn get_update_fee_amount(deps: &Deps, vaas: &[Binary]) -> StdResult<u128> {
     let config = config_read(deps.storage).load()?;
 
@@ -756,7 +905,7 @@ mod test {
         pythnet_sdk::legacy::PriceAttestation,
         pythnet_sdk::{
             accumulators::{merkle::MerkleTree, Accumulator},
-            messages::{PriceFeedMessage, TwapMessage},
+            messages::{PriceFeedMessage, PublisherStakeCapsMessage, TwapMessage},
             test_utils::{
                 create_accumulator_message, create_accumulator_message_from_updates,
                 create_dummy_price_feed_message, create_vaa_from_payload, DEFAULT_CHAIN_ID,
@@ -1377,31 +1526,309 @@ mod test {
         );
     }
 
+    fn create_twap_message(
+        cumulative_price: i128,
+        num_down_slots: u64,
+        publish_time: i64,
+        publish_slot: u64,
+    ) -> Message {
+        Message::TwapMessage(TwapMessage {
+            feed_id: [0; 32],
+            cumulative_price,
+            cumulative_conf: 1000,
+            num_down_slots,
+            exponent: -8,
+            publish_time,
+            prev_publish_time: publish_time - 1,
+            publish_slot,
+        })
+    }
+
+    #[test]
+    fn test_accumulator_twap_message() {
+        let (mut deps, env) = setup_test();
+        config(&mut deps.storage)
+            .save(&default_config_info())
+            .unwrap();
+        let id = PriceIdentifier::new([0; 32]);
+
+        let twap1 = create_twap_message(1_000, 10, 100, 1_000);
+        let msg = create_accumulator_message(&[&twap1], &[&twap1], false, false, None);
+        let result = update_price_feeds(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("123", &[]),
+            &[msg.into()],
+        );
+        assert!(result.is_ok());
+        // A single snapshot doesn't define a window.
+        assert_eq!(
+            query_twap_price(&deps.as_ref(), id, None),
+            Err(PythContractError::InvalidTwapWindow.into())
+        );
+
+        let twap2 = create_twap_message(5_000, 30, 160, 1_100);
+        let msg = create_accumulator_message(&[&twap2], &[&twap2], false, false, None);
+        let result = update_price_feeds(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("123", &[]),
+            &[msg.into()],
+        );
+        assert!(result.is_ok());
+
+        let expected = TwapPrice {
+            id,
+            price: 40,
+            conf: 0,
+            expo: -8,
+            start_time: 100,
+            end_time: 160,
+            down_slots_ratio: 200_000,
+        };
+        assert_eq!(
+            query_twap_price(&deps.as_ref(), id, None).unwrap().twap,
+            expected
+        );
+        assert_eq!(
+            query_twap_price(&deps.as_ref(), id, Some(60)).unwrap().twap,
+            expected
+        );
+        // The window starts at the most recent update that is old enough.
+        assert_eq!(
+            query_twap_price(&deps.as_ref(), id, Some(30)).unwrap().twap,
+            expected
+        );
+        assert_eq!(
+            query_twap_price(&deps.as_ref(), id, Some(61)),
+            Err(PythContractError::InvalidTwapWindow.into())
+        );
+        assert_eq!(
+            query_twap_price(&deps.as_ref(), PriceIdentifier::new([1; 32]), None),
+            Err(PythContractError::TwapPriceNotFound.into())
+        );
+
+        // Older updates remain available as the start of longer windows.
+        let twap3 = create_twap_message(9_000, 30, 190, 1_200);
+        let msg = create_accumulator_message(&[&twap3], &[&twap3], false, false, None);
+        let result = update_price_feeds(deps.as_mut(), env, mock_info("123", &[]), &[msg.into()]);
+        assert!(result.is_ok());
+        let window = |window_secs| {
+            query_twap_price(&deps.as_ref(), id, window_secs)
+                .map(|response| (response.twap.start_time, response.twap.end_time))
+        };
+        assert_eq!(window(None), Ok((160, 190)));
+        assert_eq!(window(Some(30)), Ok((160, 190)));
+        assert_eq!(window(Some(31)), Ok((100, 190)));
+        assert_eq!(window(Some(90)), Ok((100, 190)));
+    }
+
+    #[test]
+    fn test_accumulator_twap_snapshots_are_bounded() {
+        let (mut deps, env) = setup_test();
+        config(&mut deps.storage)
+            .save(&default_config_info())
+            .unwrap();
+        let id = PriceIdentifier::new([0; 32]);
+
+        let twaps: Vec<Message> = (0..MAX_TWAP_SNAPSHOTS as u64 + 1)
+            .map(|i| create_twap_message(i as i128 * 1_000, 0, 100 + i as i64, 1_000 + i))
+            .collect();
+        for twap in &twaps {
+            let msg = create_accumulator_message(&[twap], &[twap], false, false, None);
+            let result = update_price_feeds(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("123", &[]),
+                &[msg.into()],
+            );
+            assert!(result.is_ok());
+        }
+
+        // The first update was dropped, so the longest window starts at the second one.
+        let max_window = MAX_TWAP_SNAPSHOTS as u64 - 1;
+        assert_eq!(
+            query_twap_price(&deps.as_ref(), id, Some(max_window))
+                .unwrap()
+                .twap
+                .start_time,
+            101
+        );
+        assert_eq!(
+            query_twap_price(&deps.as_ref(), id, Some(max_window + 1)),
+            Err(PythContractError::InvalidTwapWindow.into())
+        );
+    }
+
     #[test]
-    fn test_invalid_accumulator_message_type() {
+    fn test_accumulator_twap_message_not_first_in_timestamp() {
         let (mut deps, env) = setup_test();
         config(&mut deps.storage)
             .save(&default_config_info())
             .unwrap();
-        // Although Twap Message is a valid message but it won't get stored on-chain via
-        // `update_price_feeds` and (will be) used in other methods
-        let feed1 = Message::TwapMessage(TwapMessage {
+        let twap = Message::TwapMessage(TwapMessage {
             feed_id: [0; 32],
             cumulative_price: 0,
             cumulative_conf: 0,
             num_down_slots: 0,
             exponent: 0,
-            publish_time: 0,
-            prev_publish_time: 0,
+            publish_time: 100,
+            prev_publish_time: 100,
             publish_slot: 0,
         });
-        let msg = create_accumulator_message(&[&feed1], &[&feed1], false, false, None);
+        let msg = create_accumulator_message(&[&twap], &[&twap], false, false, None);
         let info = mock_info("123", &[]);
+        // The message is skipped without failing the update.
         let result = update_price_feeds(deps.as_mut(), env, info, &[msg.into()]);
-        assert!(result.is_err());
+        assert!(result.is_ok());
         assert_eq!(
-            result.unwrap_err(),
-            StdError::from(PythContractError::InvalidAccumulatorMessageType)
+            query_twap_price(&deps.as_ref(), PriceIdentifier::new([0; 32]), None),
+            Err(PythContractError::TwapPriceNotFound.into())
+        );
+    }
+
+    #[test]
+    fn test_accumulator_publisher_stake_caps_message() {
+        let (mut deps, env) = setup_test();
+        config(&mut deps.storage)
+            .save(&default_config_info())
+            .unwrap();
+        let caps = Message::PublisherStakeCapsMessage(PublisherStakeCapsMessage {
+            publish_time: 100,
+            caps: vec![].into(),
+        });
+        let feed = create_dummy_price_feed_message(100);
+        let msg = create_accumulator_message(&[&caps, &feed], &[&caps, &feed], false, false, None);
+        let info = mock_info("123", &[]);
+        let result = update_price_feeds(deps.as_mut(), env, info, &[msg.into()]);
+        assert!(result.is_ok());
+        check_price_match(&deps, &feed);
+    }
+
+    fn create_price_feed_message_with_times(
+        feed_id: [u8; 32],
+        publish_time: i64,
+        prev_publish_time: i64,
+    ) -> Message {
+        Message::PriceFeedMessage(PriceFeedMessage {
+            feed_id,
+            price: 100,
+            conf: 10,
+            exponent: -8,
+            publish_time,
+            prev_publish_time,
+            ema_price: 100,
+            ema_conf: 10,
+        })
+    }
+
+    fn do_parse_price_feed_updates(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        updates: &[&Message],
+        price_ids: &[[u8; 32]],
+        min_publish_time: UnixTimestamp,
+        max_publish_time: UnixTimestamp,
+        unique: bool,
+    ) -> StdResult<Vec<PriceFeed>> {
+        let msg = create_accumulator_message(updates, updates, false, false, None);
+        let response = parse_price_feed_updates(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("123", &[]),
+            &[msg.into()],
+            price_ids
+                .iter()
+                .map(|id| PriceIdentifier::new(*id))
+                .collect(),
+            min_publish_time,
+            max_publish_time,
+            unique,
+        )?;
+        let data = response.data.unwrap();
+        Ok(from_binary::<ParsePriceFeedUpdatesResponse>(&data)?.price_feeds)
+    }
+
+    #[test]
+    fn test_parse_price_feed_updates() {
+        let (mut deps, env) = setup_test();
+        config(&mut deps.storage)
+            .save(&default_config_info())
+            .unwrap();
+
+        let feed1 = create_price_feed_message_with_times([1; 32], 100, 90);
+        let feed2 = create_price_feed_message_with_times([2; 32], 110, 100);
+        let feeds = do_parse_price_feed_updates(
+            &mut deps,
+            &env,
+            &[&feed1, &feed2],
+            &[[2; 32], [1; 32]],
+            100,
+            110,
+            false,
+        )
+        .unwrap();
+        assert_eq!(feeds.len(), 2);
+        assert_eq!(feeds[0].id, PriceIdentifier::new([2; 32]));
+        assert_eq!(feeds[0].get_price_unchecked().publish_time, 110);
+        assert_eq!(feeds[1].id, PriceIdentifier::new([1; 32]));
+        assert_eq!(feeds[1].get_price_unchecked().publish_time, 100);
+
+        // The parsed updates are not stored.
+        assert!(price_feed_read_bucket(&deps.storage)
+            .load(&[1; 32])
+            .is_err());
+
+        // feed1 is published before the range.
+        assert_eq!(
+            do_parse_price_feed_updates(
+                &mut deps,
+                &env,
+                &[&feed1, &feed2],
+                &[[1; 32], [2; 32]],
+                105,
+                110,
+                false,
+            ),
+            Err(PythContractError::PriceFeedNotFoundWithinRange.into())
+        );
+    }
+
+    #[test]
+    fn test_parse_price_feed_updates_unique() {
+        let (mut deps, env) = setup_test();
+        config(&mut deps.storage)
+            .save(&default_config_info())
+            .unwrap();
+
+        let feed = create_price_feed_message_with_times([1; 32], 100, 90);
+        let feeds =
+            do_parse_price_feed_updates(&mut deps, &env, &[&feed], &[[1; 32]], 95, 100, true)
+                .unwrap();
+        assert_eq!(feeds[0].get_price_unchecked().publish_time, 100);
+
+        // The previous update of the feed is also within the range.
+        assert_eq!(
+            do_parse_price_feed_updates(&mut deps, &env, &[&feed], &[[1; 32]], 90, 100, true),
+            Err(PythContractError::PriceFeedNotFoundWithinRange.into())
+        );
+        assert!(
+            do_parse_price_feed_updates(&mut deps, &env, &[&feed], &[[1; 32]], 90, 100, false)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_parse_price_feed_updates_insufficient_fee() {
+        let (mut deps, env) = setup_test();
+        let mut config_info = default_config_info();
+        config_info.fee = Coin::new(100, "foo");
+        config(&mut deps.storage).save(&config_info).unwrap();
+
+        let feed = create_price_feed_message_with_times([1; 32], 100, 90);
+        assert_eq!(
+            do_parse_price_feed_updates(&mut deps, &env, &[&feed], &[[1; 32]], 0, 100, false),
+            Err(PythContractError::InsufficientFee.into())
         );
     }
 
@@ -1584,10 +2011,13 @@ mod test {
             .save(&default_config_info())
             .unwrap();
         let msg = create_batch_price_update_msg_from_attestations(vec![price_attestation]);
-        let feeds = parse_batch_attestation(&deps.as_ref(), &env, &msg).unwrap();
+        let feeds = parse_batch_attestation(&deps.as_ref(), &env, &msg)
+            .unwrap()
+            .price_feeds;
         assert_eq!(feeds.len(), 1);
-        let price = feeds[0].get_price_unchecked();
-        let ema_price = feeds[0].get_ema_price_unchecked();
+        assert_eq!(feeds[0].1, 99);
+        let price = feeds[0].0.get_price_unchecked();
+        let ema_price = feeds[0].0.get_ema_price_unchecked();
 
         // for price
         assert_eq!(price.price, 99);
@@ -1626,10 +2056,13 @@ mod test {
             .save(&default_config_info())
             .unwrap();
         let msg = create_batch_price_update_msg_from_attestations(vec![price_attestation]);
-        let feeds = parse_batch_attestation(&deps.as_ref(), &env, &msg).unwrap();
+        let feeds = parse_batch_attestation(&deps.as_ref(), &env, &msg)
+            .unwrap()
+            .price_feeds;
         assert_eq!(feeds.len(), 1);
-        let price = feeds[0].get_price_unchecked();
-        let ema_price = feeds[0].get_ema_price_unchecked();
+        assert_eq!(feeds[0].1, 99);
+        let price = feeds[0].0.get_price_unchecked();
+        let ema_price = feeds[0].0.get_ema_price_unchecked();
 
         // for price
         assert_eq!(price.price, 100);
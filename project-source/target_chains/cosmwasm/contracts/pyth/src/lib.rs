@@ -4,6 +4,8 @@ extern crate lazy_static;
 pub mod contract;
 pub mod governance;
 pub mod msg;
+#[cfg(feature = "multi-test")]
+pub mod multi_test;
 pub mod state;
 
 #[cfg(feature = "injective")]
@@ -0,0 +1,132 @@
+//! Helpers for integration tests of contracts using Pyth with `cw-multi-test`.
+//!
+//! `instantiate_pyth` stores and instantiates the Wormhole contract and this contract in a
+//! `cw_multi_test::App`, so price updates are verified and charged for exactly as on chain. The
+//! Wormhole contract uses the test guardian set of `pythnet_sdk::test_utils` and the Pyth contract
+//! accepts updates from its default data source, which is what `create_price_update_data`
+//! produces.
+//!
+//! ```ignore
+//! let mut app = App::default();
+//! let pyth = instantiate_pyth(&mut app, 60, Coin::new(1, "uatom"));
+//! let data = create_price_update_data(&[price_feed]);
+//! ```
+use {
+    crate::{
+        contract::{execute, instantiate, migrate, query},
+        msg::InstantiateMsg,
+        state::PythDataSource,
+    },
+    cosmwasm_std::{Addr, Binary, Coin, Empty},
+    cw_multi_test::{App, Contract, ContractWrapper, Executor},
+    cw_wormhole::{
+        msg::InstantiateMsg as WormholeInstantiateMsg,
+        state::{GuardianAddress, GuardianSetInfo},
+    },
+    pyth_sdk_cw::PriceFeed,
+    pythnet_sdk::{
+        messages::{Message, PriceFeedMessage},
+        test_utils::{
+            create_accumulator_message, dummy_guardians_addresses, DataSource, DEFAULT_CHAIN_ID,
+            DEFAULT_DATA_SOURCE, DEFAULT_GOVERNANCE_SOURCE,
+        },
+    },
+};
+
+/// Returns the Pyth contract, to be stored in a `cw_multi_test::App`.
+pub fn pyth_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(execute, instantiate, query).with_migrate(migrate))
+}
+
+/// Returns the Wormhole contract used by the Pyth contract to verify VAAs.
+pub fn wormhole_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw_wormhole::contract::execute,
+        cw_wormhole::contract::instantiate,
+        cw_wormhole::contract::query,
+    ))
+}
+
+/// Instantiates the Wormhole and Pyth contracts in `app` and returns the address of the Pyth
+/// contract. `fee` is the fee of each price update.
+pub fn instantiate_pyth(app: &mut App, valid_time_period_secs: u16, fee: Coin) -> Addr {
+    let owner = Addr::unchecked("owner");
+
+    let wormhole_code_id = app.store_code(wormhole_contract());
+    let wormhole = app
+        .instantiate_contract(
+            wormhole_code_id,
+            owner.clone(),
+            &WormholeInstantiateMsg {
+                gov_chain: DEFAULT_GOVERNANCE_SOURCE.chain.into(),
+                gov_address: Binary(DEFAULT_GOVERNANCE_SOURCE.address.0.to_vec()),
+                initial_guardian_set: GuardianSetInfo {
+                    addresses: dummy_guardians_addresses()
+                        .iter()
+                        .map(|address| GuardianAddress {
+                            bytes: Binary(address.to_vec()),
+                        })
+                        .collect(),
+                    expiration_time: 0,
+                },
+                guardian_set_expirity: 86400,
+                chain_id: DEFAULT_CHAIN_ID.into(),
+                fee_denom: fee.denom.clone(),
+            },
+            &[],
+            "wormhole",
+            None,
+        )
+        .expect("failed to instantiate the Wormhole contract");
+
+    let pyth_code_id = app.store_code(pyth_contract());
+    app.instantiate_contract(
+        pyth_code_id,
+        owner,
+        &InstantiateMsg {
+            wormhole_contract: wormhole.into_string(),
+            data_sources: vec![pyth_data_source(&DEFAULT_DATA_SOURCE)],
+            governance_source: pyth_data_source(&DEFAULT_GOVERNANCE_SOURCE),
+            governance_source_index: 0,
+            governance_sequence_number: 0,
+            chain_id: DEFAULT_CHAIN_ID.into(),
+            valid_time_period_secs,
+            fee,
+        },
+        &[],
+        "pyth",
+        None,
+    )
+    .expect("failed to instantiate the Pyth contract")
+}
+
+/// Creates accumulator update data containing `price_feeds`, signed by the test guardian set.
+/// The previous publish time of each price feed is set to one second before its publish time.
+pub fn create_price_update_data(price_feeds: &[PriceFeed]) -> Binary {
+    let messages: Vec<Message> = price_feeds
+        .iter()
+        .map(|price_feed| {
+            let price = price_feed.get_price_unchecked();
+            let ema_price = price_feed.get_ema_price_unchecked();
+            Message::PriceFeedMessage(PriceFeedMessage {
+                feed_id: price_feed.id.to_bytes(),
+                price: price.price,
+                conf: price.conf,
+                exponent: price.expo,
+                publish_time: price.publish_time,
+                prev_publish_time: price.publish_time - 1,
+                ema_price: ema_price.price,
+                ema_conf: ema_price.conf,
+            })
+        })
+        .collect();
+    let messages: Vec<&Message> = messages.iter().collect();
+    create_accumulator_message(&messages, &messages, false, false, None).into()
+}
+
+fn pyth_data_source(data_source: &DataSource) -> PythDataSource {
+    PythDataSource {
+        emitter: Binary(data_source.address.0.to_vec()),
+        chain_id: data_source.chain.into(),
+    }
+}
@@ -5,6 +5,7 @@ use {
         Singleton,
     },
     pyth_sdk_cw::PriceFeed,
+    pythnet_sdk::messages::TwapMessage,
     schemars::JsonSchema,
     serde::{Deserialize, Serialize},
     std::{collections::HashSet, time::Duration},
@@ -12,6 +13,7 @@ use {
 
 pub static CONFIG_KEY: &[u8] = b"config_v1";
 pub static PRICE_FEED_KEY: &[u8] = b"price_feed";
+pub static TWAP_SNAPSHOTS_KEY: &[u8] = b"twap_snapshots";
 pub static CONTRACT_VERSION_KEY: &[u8] = b"contract_version";
 
 /// A `PythDataSource` identifies a specific contract (given by its Wormhole `emitter`) on
@@ -63,6 +65,51 @@ pub fn price_feed_read_bucket(storage: &dyn Storage) -> ReadonlyBucket<PriceFeed
     bucket_read(storage, PRICE_FEED_KEY)
 }
 
+/// The cumulative values of a price feed at a given time, as published in a `TwapMessage`.
+/// TWAPs are computed from the difference between two snapshots.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TwapSnapshot {
+    pub cumulative_price: i128,
+    pub cumulative_conf: u128,
+    pub num_down_slots: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub publish_slot: u64,
+}
+
+impl From<&TwapMessage> for TwapSnapshot {
+    fn from(twap_message: &TwapMessage) -> Self {
+        TwapSnapshot {
+            cumulative_price: twap_message.cumulative_price,
+            cumulative_conf: twap_message.cumulative_conf,
+            num_down_slots: twap_message.num_down_slots,
+            expo: twap_message.exponent,
+            publish_time: twap_message.publish_time,
+            prev_publish_time: twap_message.prev_publish_time,
+            publish_slot: twap_message.publish_slot,
+        }
+    }
+}
+
+/// Number of `TwapSnapshot`s stored for each price feed.
+pub const MAX_TWAP_SNAPSHOTS: usize = 16;
+
+/// The most recent `TwapSnapshot`s of a price feed, oldest first. At most `MAX_TWAP_SNAPSHOTS`
+/// snapshots are kept, the oldest one is dropped when a new one is stored.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TwapSnapshots {
+    pub snapshots: Vec<TwapSnapshot>,
+}
+
+pub fn twap_snapshots_bucket(storage: &mut dyn Storage) -> Bucket<TwapSnapshots> {
+    bucket(storage, TWAP_SNAPSHOTS_KEY)
+}
+
+pub fn twap_snapshots_read_bucket(storage: &dyn Storage) -> ReadonlyBucket<TwapSnapshots> {
+    bucket_read(storage, TWAP_SNAPSHOTS_KEY)
+}
+
 pub fn set_contract_version(storage: &mut dyn Storage, contract_version: &String) -> StdResult<()> {
     singleton(storage, CONTRACT_VERSION_KEY).save(contract_version)
 }
@@ -6,9 +6,13 @@ use {
         state::{State, STATE},
     },
     cosmwasm_std::{
-        to_binary, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+        to_binary, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError,
+        StdResult, SubMsg,
+    },
+    pyth_sdk_cw::{
+        create_parse_price_feed_updates_msg, get_update_fee, get_valid_time_period,
+        parse_price_feed_updates_reply_data, query_price_feed, PriceFeedResponse,
     },
-    pyth_sdk_cw::{get_update_fee, get_valid_time_period, query_price_feed, PriceFeedResponse},
     std::time::Duration,
 };
 
@@ -40,14 +44,93 @@ pub fn instantiate(
         .add_attribute("price_id", format!("{}", msg.price_feed_id)))
 }
 
+/// The id of the reply to the `ParsePriceFeedUpdates` submessage.
+const PARSE_PRICE_FEED_UPDATES_REPLY_ID: u64 = 1;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
+    deps: DepsMut,
+    env: Env,
     _info: MessageInfo,
-    _msg: ExecuteMsg,
+    msg: ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::SettleWithPriceUpdate { data } => {
+            execute_settle_with_price_update(deps, env, data)
+        }
+    }
+}
+
+/// Ask the Pyth contract to verify the price update `data` and return the price it contains.
+/// Unlike `UpdatePriceFeeds`, `ParsePriceFeedUpdates` doesn't store the price: it returns it in
+/// the response data, which is handled in `reply`. This lets the contract use a price of its
+/// choosing (e.g. the price at the time of an order) within a single transaction.
+fn execute_settle_with_price_update(
+    deps: DepsMut,
+    env: Env,
+    data: Vec<Binary>,
 ) -> StdResult<Response> {
-    Ok(Response::new().add_attribute("method", "execute"))
+    let state = STATE.load(deps.storage)?;
+
+    // The fee is paid from the funds of this contract, which include the funds sent with this
+    // message.
+    let fee = get_update_fee(
+        &deps.querier,
+        state.pyth_contract_addr.clone(),
+        data.as_slice(),
+    )?;
+
+    // Only accept prices published within the last 60 seconds. Set `unique` to true to only accept
+    // the first price published after `min_publish_time`, e.g. to settle orders placed at that time.
+    let current_time = env.block.time.seconds() as i64;
+    let msg = create_parse_price_feed_updates_msg(
+        state.pyth_contract_addr,
+        data,
+        vec![state.price_feed_id],
+        current_time - 60,
+        current_time,
+        false,
+        fee,
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            msg,
+            PARSE_PRICE_FEED_UPDATES_REPLY_ID,
+        ))
+        .add_attribute("method", "settle_with_price_update"))
+}
+
+/// Handle the price feeds returned by the Pyth contract. The submessage fails, and so does the
+/// whole transaction, if the price update is invalid or doesn't contain a price in the requested
+/// time range.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    if msg.id != PARSE_PRICE_FEED_UPDATES_REPLY_ID {
+        return Err(StdError::generic_err(format!(
+            "Unknown reply id: {}",
+            msg.id
+        )));
+    }
+    let data = msg
+        .result
+        .into_result()
+        .map_err(StdError::generic_err)?
+        .data
+        .ok_or_else(|| StdError::not_found("ParsePriceFeedUpdates response data"))?;
+    let response = parse_price_feed_updates_reply_data(&data)?;
+
+    // The price feeds are in the order of the requested price ids, and their prices were published
+    // in the requested time range.
+    let price = response.price_feeds[0].get_price_unchecked();
+
+    // Settle using `price` here.
+    Ok(Response::new()
+        .add_attribute("method", "reply")
+        .add_attribute("price", price.price.to_string())
+        .add_attribute("conf", price.conf.to_string())
+        .add_attribute("expo", price.expo.to_string())
+        .add_attribute("publish_time", price.publish_time.to_string()))
 }
 
 /// Query the Pyth contract the current price of the configured price feed.
@@ -113,11 +196,18 @@ mod test {
     use {
         super::*,
         cosmwasm_std::{
-            from_binary,
-            testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
-            Addr, Coin, OwnedDeps, QuerierResult, SystemError, SystemResult, Timestamp, WasmQuery,
+            coins, from_binary,
+            testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+            Addr, Coin, CosmosMsg, Empty, Event, OwnedDeps, QuerierResult, SubMsgResponse,
+            SubMsgResult, SystemError, SystemResult, Timestamp, WasmMsg, WasmQuery,
+        },
+        cw_multi_test::{App, Contract, ContractWrapper, Executor},
+        prost::Message,
+        pyth_cosmwasm::multi_test::{create_price_update_data, instantiate_pyth},
+        pyth_sdk_cw::{
+            testing::MockPyth, ExecuteMsg as PythExecuteMsg, ParsePriceFeedUpdatesResponse, Price,
+            PriceFeed, PriceIdentifier, UnixTimestamp,
         },
-        pyth_sdk_cw::{testing::MockPyth, Price, PriceFeed, PriceIdentifier, UnixTimestamp},
         std::{convert::TryFrom, time::Duration},
     };
 
@@ -127,6 +217,14 @@ mod test {
     // For real deployments, see list of price feed ids here https://pyth.network/developers/price-feed-ids
     const PRICE_ID: &str = "63f341689d98a12ef60a5cff1d7f85c70a9e17bf1575f0e7c0b2512d48b1c8b3";
 
+    /// The protobuf response of `MsgExecuteContract`, whose encoding is the data of the reply to a
+    /// `WasmMsg::Execute` submessage.
+    #[derive(Clone, PartialEq, Message)]
+    struct MsgExecuteContractResponse {
+        #[prost(bytes = "vec", tag = "1")]
+        data: Vec<u8>,
+    }
+
     fn default_state() -> State {
         State {
             pyth_contract_addr: Addr::unchecked(PYTH_CONTRACT_ADDR),
@@ -242,4 +340,155 @@ mod test {
         let result = query(deps.as_ref(), env, msg).and_then(|binary| from_binary::<Coin>(&binary));
         assert_eq!(result.map(|r| r.to_string()), Ok(String::from("1foo")))
     }
+
+    #[test]
+    fn test_settle_with_price_update() {
+        let current_unix_time = 10_000_000;
+
+        let mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        let (mut deps, env) = setup_test(&default_state(), &mock_pyth, current_unix_time);
+
+        let msg = ExecuteMsg::SettleWithPriceUpdate {
+            data: vec![Binary(vec![1, 2, 3])],
+        };
+        let response = execute(deps.as_mut(), env, mock_info("sender", &[]), msg).unwrap();
+        assert_eq!(response.messages.len(), 1);
+        assert_eq!(response.messages[0].id, PARSE_PRICE_FEED_UPDATES_REPLY_ID);
+        match &response.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                funds,
+                ..
+            }) => {
+                assert_eq!(contract_addr, PYTH_CONTRACT_ADDR);
+                assert_eq!(funds, &vec![Coin::new(1, "foo")]);
+            }
+            _ => panic!("unexpected message"),
+        }
+    }
+
+    #[test]
+    fn test_reply() {
+        let current_unix_time = 10_000_000;
+        let mock_pyth = MockPyth::new(Duration::from_secs(60), Coin::new(1, "foo"), &[]);
+        let (mut deps, env) = setup_test(&default_state(), &mock_pyth, current_unix_time);
+
+        let price = Price {
+            price: 100,
+            conf: 10,
+            expo: -1,
+            publish_time: current_unix_time,
+        };
+        let price_feed = PriceFeed::new(PriceIdentifier::from_hex(PRICE_ID).unwrap(), price, price);
+        let data = to_binary(&ParsePriceFeedUpdatesResponse {
+            price_feeds: vec![price_feed],
+        })
+        .unwrap();
+        let reply_data = MsgExecuteContractResponse { data: data.0 }.encode_to_vec();
+        let reply_msg = |reply_data: Vec<u8>| Reply {
+            id: PARSE_PRICE_FEED_UPDATES_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary(reply_data)),
+            }),
+        };
+
+        // The response data isn't wrapped in a `MsgExecuteContractResponse`.
+        assert!(reply(deps.as_mut(), env.clone(), reply_msg(data.0.clone())).is_err());
+        // The response data is truncated.
+        assert!(reply(
+            deps.as_mut(),
+            env.clone(),
+            reply_msg(reply_data[..reply_data.len() - 1].to_vec())
+        )
+        .is_err());
+
+        let response = reply(deps.as_mut(), env, reply_msg(reply_data)).unwrap();
+        let price_attribute = response
+            .attributes
+            .iter()
+            .find(|attribute| attribute.key == "price")
+            .unwrap();
+        assert_eq!(price_attribute.value, "100");
+    }
+
+    fn example_contract() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new(execute, instantiate, query).with_reply(reply))
+    }
+
+    // Instantiate the Pyth contract, with its Wormhole contract, and this contract in a
+    // `cw_multi_test::App`. Price updates accepted by Pyth are created with `create_price_update_data`.
+    fn setup_multi_test() -> (App, Addr, Addr) {
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked("sender"), coins(10, "foo"))
+                .unwrap();
+        });
+
+        let pyth = instantiate_pyth(&mut app, 60, Coin::new(1, "foo"));
+
+        let code_id = app.store_code(example_contract());
+        let contract = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked("owner"),
+                &InstantiateMsg {
+                    price_feed_id: PriceIdentifier::from_hex(PRICE_ID).unwrap(),
+                    pyth_contract_addr: pyth.to_string(),
+                },
+                &[],
+                "example",
+                None,
+            )
+            .unwrap();
+
+        (app, pyth, contract)
+    }
+
+    fn create_price_update(app: &App, price: i64) -> (Price, Binary) {
+        let price = Price {
+            price,
+            conf: 10,
+            expo: -1,
+            publish_time: app.block_info().time.seconds() as i64,
+        };
+        let price_feed = PriceFeed::new(PriceIdentifier::from_hex(PRICE_ID).unwrap(), price, price);
+        (price, create_price_update_data(&[price_feed]))
+    }
+
+    #[test]
+    fn test_update_and_fetch_price_with_pyth() {
+        let (mut app, pyth, contract) = setup_multi_test();
+        let (price, data) = create_price_update(&app, 100);
+
+        let msg = PythExecuteMsg::UpdatePriceFeeds { data: vec![data] };
+        // The update fails without the fee.
+        assert!(app
+            .execute_contract(Addr::unchecked("sender"), pyth.clone(), &msg, &[])
+            .is_err());
+        app.execute_contract(Addr::unchecked("sender"), pyth, &msg, &coins(1, "foo"))
+            .unwrap();
+
+        let response: FetchPriceResponse = app
+            .wrap()
+            .query_wasm_smart(contract, &QueryMsg::FetchPrice {})
+            .unwrap();
+        assert_eq!(response.current_price, price);
+    }
+
+    #[test]
+    fn test_settle_with_price_update_with_pyth() {
+        let (mut app, _, contract) = setup_multi_test();
+        let (_, data) = create_price_update(&app, 100);
+
+        let msg = ExecuteMsg::SettleWithPriceUpdate { data: vec![data] };
+        assert!(app
+            .execute_contract(Addr::unchecked("sender"), contract.clone(), &msg, &[])
+            .is_err());
+        let response = app
+            .execute_contract(Addr::unchecked("sender"), contract, &msg, &coins(1, "foo"))
+            .unwrap();
+        assert!(response.has_event(&Event::new("wasm").add_attribute("price", "100")));
+    }
 }
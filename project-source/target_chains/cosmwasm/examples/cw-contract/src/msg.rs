@@ -15,7 +15,11 @@ pub struct InstantiateMsg {
 }
 
 #[cw_serde]
-pub enum ExecuteMsg {}
+pub enum ExecuteMsg {
+    /// Settle using the price in the price update `data`, atomically. The price update fee must be
+    /// sent with this message.
+    SettleWithPriceUpdate { data: Vec<Binary> },
+}
 
 #[cw_serde]
 #[derive(QueryResponses)]
@@ -69,6 +69,22 @@ pub enum PythContractError {
     /// Merkle proof is invalid
     #[error("InvalidMerkleProof")]
     InvalidMerkleProof,
+
+    /// No TWAP is available for the price feed
+    #[error("TwapPriceNotFound")]
+    TwapPriceNotFound,
+
+    /// TWAP window is empty or no stored TWAP update is old enough for the requested window
+    #[error("InvalidTwapWindow")]
+    InvalidTwapWindow,
+
+    /// TWAP calculation overflowed
+    #[error("TwapOverflow")]
+    TwapOverflow,
+
+    /// No update of a requested price feed was published within the requested time range
+    #[error("PriceFeedNotFoundWithinRange")]
+    PriceFeedNotFoundWithinRange,
 }
 
 impl From<PythContractError> for StdError {
@@ -5,16 +5,37 @@ pub use pyth_sdk::{Price, PriceFeed, PriceIdentifier, UnixTimestamp};
 use {
     cosmwasm_schema::{cw_serde, QueryResponses},
     cosmwasm_std::{
-        to_binary, Addr, Binary, Coin, QuerierWrapper, QueryRequest, StdResult, WasmQuery,
+        from_binary, to_binary, Addr, Binary, Coin, CosmosMsg, QuerierWrapper, QueryRequest,
+        StdError, StdResult, WasmMsg, WasmQuery,
     },
+    cw_utils::parse_execute_response_data,
     std::time::Duration,
 };
 
 #[derive(Eq)]
 #[cw_serde]
 pub enum ExecuteMsg {
-    UpdatePriceFeeds { data: Vec<Binary> },
-    ExecuteGovernanceInstruction { data: Binary },
+    UpdatePriceFeeds {
+        data: Vec<Binary>,
+    },
+    ExecuteGovernanceInstruction {
+        data: Binary,
+    },
+    /// Parse and verify `data` without storing it, and return the price feeds of `price_ids` in
+    /// the response data as a `ParsePriceFeedUpdatesResponse`. The fee is the same as the fee of
+    /// `UpdatePriceFeeds`.
+    ///
+    /// Only updates published between `min_publish_time` and `max_publish_time` (inclusive) are
+    /// returned. If `unique` is set, an update is only returned if it is the first update of its
+    /// feed in that range, i.e. if the previous update of the feed was published before
+    /// `min_publish_time`.
+    ParsePriceFeedUpdates {
+        data: Vec<Binary>,
+        price_ids: Vec<PriceIdentifier>,
+        min_publish_time: UnixTimestamp,
+        max_publish_time: UnixTimestamp,
+        unique: bool,
+    },
 }
 
 #[cw_serde]
@@ -29,6 +50,13 @@ pub enum QueryMsg {
     GetUpdateFeeForDenom { denom: String, vaas: Vec<Binary> },
     #[returns(Duration)]
     GetValidTimePeriod,
+    #[returns(TwapPriceResponse)]
+    TwapPrice { id: PriceIdentifier },
+    #[returns(TwapPriceResponse)]
+    TwapPriceWithWindow {
+        id: PriceIdentifier,
+        window_secs: u64,
+    },
 }
 
 #[cw_serde]
@@ -36,6 +64,34 @@ pub struct PriceFeedResponse {
     pub price_feed: PriceFeed,
 }
 
+/// The response data of `ExecuteMsg::ParsePriceFeedUpdates`. The price feeds are in the order of
+/// the requested price ids.
+#[cw_serde]
+pub struct ParsePriceFeedUpdatesResponse {
+    pub price_feeds: Vec<PriceFeed>,
+}
+
+/// A time weighted average price over the window from `start_time` to `end_time`.
+///
+/// `down_slots_ratio` is the ratio of slots in the window in which the feed had no valid price,
+/// scaled by 1_000_000. A high ratio means that the TWAP is computed from few prices.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub struct TwapPrice {
+    pub id: PriceIdentifier,
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub start_time: UnixTimestamp,
+    pub end_time: UnixTimestamp,
+    pub down_slots_ratio: u32,
+}
+
+#[cw_serde]
+pub struct TwapPriceResponse {
+    pub twap: TwapPrice,
+}
+
 /// Queries the price on-chain
 pub fn query_price_feed(
     querier: &QuerierWrapper,
@@ -89,3 +145,73 @@ pub fn get_valid_time_period(querier: &QuerierWrapper, contract_addr: Addr) -> S
         msg: to_binary(&QueryMsg::GetValidTimePeriod)?,
     }))
 }
+
+/// Queries the TWAP over the window between the two most recent TWAP updates of a price feed.
+/// The caller is responsible for checking that the window and its end are suitable.
+pub fn query_twap_price(
+    querier: &QuerierWrapper,
+    contract_addr: Addr,
+    id: PriceIdentifier,
+) -> StdResult<TwapPriceResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract_addr.into_string(),
+        msg: to_binary(&QueryMsg::TwapPrice { id })?,
+    }))
+}
+
+/// Queries the TWAP of a price feed over at least the last `window_secs` seconds before its latest
+/// TWAP update. The window starts at the most recent TWAP update stored by the contract that was
+/// published at least `window_secs` seconds earlier, check `start_time` for its actual length.
+pub fn query_twap_price_with_window(
+    querier: &QuerierWrapper,
+    contract_addr: Addr,
+    id: PriceIdentifier,
+    window_secs: u64,
+) -> StdResult<TwapPriceResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract_addr.into_string(),
+        msg: to_binary(&QueryMsg::TwapPriceWithWindow { id, window_secs })?,
+    }))
+}
+
+/// Creates a message executing `ExecuteMsg::ParsePriceFeedUpdates` on the Pyth contract.
+/// `fee` should be the fee returned by `get_update_fee` for `data`.
+///
+/// Dispatch the message as a `SubMsg` and parse the data of its reply with
+/// `parse_price_feed_updates_reply_data` to get the parsed price feeds.
+pub fn create_parse_price_feed_updates_msg(
+    contract_addr: Addr,
+    data: Vec<Binary>,
+    price_ids: Vec<PriceIdentifier>,
+    min_publish_time: UnixTimestamp,
+    max_publish_time: UnixTimestamp,
+    unique: bool,
+    fee: Coin,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: contract_addr.into_string(),
+        msg: to_binary(&ExecuteMsg::ParsePriceFeedUpdates {
+            data,
+            price_ids,
+            min_publish_time,
+            max_publish_time,
+            unique,
+        })?,
+        funds: vec![fee],
+    }))
+}
+
+/// Parses the data of the reply to a `create_parse_price_feed_updates_msg` submessage.
+///
+/// The reply data is the protobuf encoded `MsgExecuteContractResponse` of the execution, whose
+/// only field is the response data of the Pyth contract.
+pub fn parse_price_feed_updates_reply_data(
+    reply_data: &Binary,
+) -> StdResult<ParsePriceFeedUpdatesResponse> {
+    let response = parse_execute_response_data(reply_data.as_slice())
+        .map_err(|e| StdError::parse_err("MsgExecuteContractResponse", e.to_string()))?;
+    let data = response
+        .data
+        .ok_or_else(|| StdError::not_found("ParsePriceFeedUpdatesResponse"))?;
+    from_binary(&data)
+}
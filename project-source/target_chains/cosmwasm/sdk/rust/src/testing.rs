@@ -1,5 +1,8 @@
 use {
-    crate::{error::PythContractError, PriceFeed, PriceFeedResponse, PriceIdentifier, QueryMsg},
+    crate::{
+        error::PythContractError, PriceFeed, PriceFeedResponse, PriceIdentifier, QueryMsg,
+        TwapPrice, TwapPriceResponse,
+    },
     cosmwasm_std::{
         from_binary, to_binary, Binary, Coin, ContractResult, QuerierResult, SystemError,
         SystemResult,
@@ -14,6 +17,7 @@ pub struct MockPyth {
     pub valid_time_period: Duration,
     pub fee_per_vaa: Coin,
     pub feeds: HashMap<PriceIdentifier, PriceFeed>,
+    pub twaps: HashMap<PriceIdentifier, TwapPrice>,
 }
 
 impl MockPyth {
@@ -29,6 +33,7 @@ impl MockPyth {
             valid_time_period,
             fee_per_vaa,
             feeds: feeds_map,
+            twaps: HashMap::new(),
         }
     }
 
@@ -37,6 +42,11 @@ impl MockPyth {
         self.feeds.insert(feed.id, feed);
     }
 
+    /// Add a TWAP that will be returned on queries.
+    pub fn add_twap(&mut self, twap: TwapPrice) {
+        self.twaps.insert(twap.id, twap);
+    }
+
     /// Handler for processing query messages.
     /// See the tests in `contract.rs`
     /// `https://github.com/pyth-network/pyth-crosschain/blob/main/target_chains/cosmwasm/examples/cw-contract/src/contract.rs#L13`
@@ -75,10 +85,32 @@ impl MockPyth {
                     .unwrap();
                 SystemResult::Ok(to_binary(&Coin::new(new_amount, denom)).into())
             }
+            Ok(QueryMsg::TwapPrice { id }) => self.twap_response(&id, None),
+            Ok(QueryMsg::TwapPriceWithWindow { id, window_secs }) => {
+                self.twap_response(&id, Some(window_secs))
+            }
             Err(_e) => SystemResult::Err(SystemError::InvalidRequest {
                 error: "Invalid message".into(),
                 request: msg.clone(),
             }),
         }
     }
+
+    fn twap_response(&self, id: &PriceIdentifier, window_secs: Option<u64>) -> QuerierResult {
+        match self.twaps.get(id) {
+            Some(twap)
+                if window_secs.map_or(true, |window_secs| {
+                    twap.end_time - twap.start_time >= window_secs as i64
+                }) =>
+            {
+                SystemResult::Ok(to_binary(&TwapPriceResponse { twap: *twap }).into())
+            }
+            Some(_) => SystemResult::Ok(ContractResult::from(Err(
+                PythContractError::InvalidTwapWindow,
+            ))),
+            None => SystemResult::Ok(ContractResult::from(Err(
+                PythContractError::TwapPriceNotFound,
+            ))),
+        }
+    }
 }
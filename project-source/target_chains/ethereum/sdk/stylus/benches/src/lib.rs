@@ -1,4 +1,4 @@
-use std::process::Command;
+use std::{path::PathBuf, process::Command};
 
 use alloy::{
     primitives::Address,
@@ -11,6 +11,7 @@ use koba::config::{Deploy, Generate, PrivateKey};
 use serde::Deserialize;
 
 pub mod extend_pyth_example;
+pub mod pyth_receiver;
 pub mod report;
 
 #[derive(Debug, Deserialize)]
@@ -51,10 +52,22 @@ async fn deploy(
             .join("src")
             .join("constructor.sol")
     });
+    deploy_wasm(account, wasm_path, sol_path, args, cache_opt).await
+}
+
+/// Deploys the contract compiled to `wasm_path`, using the constructor of `sol_path` if `args`
+/// are provided.
+async fn deploy_wasm(
+    account: &Account,
+    wasm_path: PathBuf,
+    sol_path: Option<PathBuf>,
+    args: Option<String>,
+    cache_opt: CacheOpt,
+) -> eyre::Result<Address> {
     let pk = account.pk();
     let config = Deploy {
         generate_config: Generate {
-            wasm: wasm_path.clone(),
+            wasm: wasm_path,
             sol: sol_path,
             args,
             legacy: false,
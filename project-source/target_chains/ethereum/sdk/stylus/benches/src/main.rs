@@ -1,4 +1,4 @@
-use benches::{extend_pyth_example, report::BenchmarkReport};
+use benches::{extend_pyth_example, pyth_receiver, report::BenchmarkReport};
 use futures::FutureExt;
 
 #[tokio::main]
@@ -6,6 +6,7 @@ async fn main() -> eyre::Result<()> {
     let report = futures::future::try_join_all([extend_pyth_example::bench().boxed()])
         .await?
         .into_iter()
+        .chain(pyth_receiver::bench().await?)
         .fold(BenchmarkReport::default(), BenchmarkReport::merge_with);
 
     println!();
@@ -0,0 +1,161 @@
+use std::str::FromStr;
+
+use alloy::{
+    network::{AnyNetwork, EthereumWallet},
+    primitives::{Address, Bytes, FixedBytes as TypeFixedBytes, U256},
+    providers::ProviderBuilder,
+    sol,
+    sol_types::{SolCall, SolConstructor},
+};
+use e2e::{env, receipt, Account};
+use pythnet_sdk::{
+    messages::Message,
+    test_utils::{
+        create_accumulator_message, create_dummy_price_feed_message, DEFAULT_DATA_SOURCE,
+        DEFAULT_GOVERNANCE_SOURCE,
+    },
+};
+
+use crate::{
+    report::{ContractReport, FunctionReport},
+    CacheOpt,
+};
+
+sol!(
+    #[sol(rpc)]
+    contract PythReceiver {
+        struct Price {
+            int64 price;
+            uint64 conf;
+            int32 expo;
+            uint publishTime;
+        }
+
+        function getPriceUnsafe(bytes32 id) external view returns (Price memory price);
+        function getEmaPriceUnsafe(bytes32 id) external view returns (Price memory price);
+        function getUpdateFee(bytes[] calldata updateData) external view returns (uint256 feeAmount);
+        function updatePriceFeeds(bytes[] calldata updateData) external payable;
+        function parsePriceFeedUpdates(bytes[] calldata updateData, bytes32[] calldata priceIds, uint64 minPublishTime, uint64 maxPublishTime) external payable;
+    }
+);
+
+mod constructor {
+    alloy::sol!("../receiver/src/constructor.sol");
+}
+
+const SINGLE_UPDATE_FEE: u64 = 1;
+const VALID_TIME_PERIOD: u64 = 60;
+
+/// Benchmarks the Stylus receiver against the Solidity receiver deployed at
+/// `SOLIDITY_PYTH_ADDRESS`. Both receivers must verify VAAs with a Wormhole contract using the
+/// test guardian set of `pythnet_sdk::test_utils`, deployed at `WORMHOLE_ADDRESS`.
+///
+/// The benchmark is skipped when either address isn't set, so the other benchmarks still run.
+pub async fn bench() -> eyre::Result<Vec<ContractReport>> {
+    for var in ["SOLIDITY_PYTH_ADDRESS", "WORMHOLE_ADDRESS"] {
+        if std::env::var(var).is_err() {
+            println!("Skipping the PythReceiver benchmark: `{var}` is not set");
+            return Ok(vec![]);
+        }
+    }
+
+    let reports = run_with(Receiver::Stylus(CacheOpt::None)).await?;
+    let report = reports.into_iter().try_fold(
+        ContractReport::new("PythReceiverStylus"),
+        ContractReport::add,
+    )?;
+
+    let cached_reports = run_with(Receiver::Stylus(CacheOpt::Bid(0))).await?;
+    let stylus_report = cached_reports
+        .into_iter()
+        .try_fold(report, ContractReport::add_cached)?;
+
+    // Solidity contracts can't be cached, so both columns report a run against the same
+    // contract.
+    let reports = run_with(Receiver::Solidity).await?;
+    let report = reports.into_iter().try_fold(
+        ContractReport::new("PythReceiverSolidity"),
+        ContractReport::add,
+    )?;
+    let reports = run_with(Receiver::Solidity).await?;
+    let solidity_report = reports
+        .into_iter()
+        .try_fold(report, ContractReport::add_cached)?;
+
+    Ok(vec![stylus_report, solidity_report])
+}
+
+enum Receiver {
+    Stylus(CacheOpt),
+    Solidity,
+}
+
+async fn run_with(receiver: Receiver) -> eyre::Result<Vec<FunctionReport>> {
+    let alice = Account::new().await?;
+    let alice_wallet = ProviderBuilder::new()
+        .network::<AnyNetwork>()
+        .with_recommended_fillers()
+        .wallet(EthereumWallet::from(alice.signer.clone()))
+        .on_http(alice.url().parse()?);
+
+    let contract_addr = match receiver {
+        Receiver::Stylus(cache_opt) => deploy(&alice, cache_opt).await?,
+        Receiver::Solidity => Address::from_str(&env("SOLIDITY_PYTH_ADDRESS")?)?,
+    };
+    let contract = PythReceiver::new(contract_addr, &alice_wallet);
+
+    let feeds: Vec<Message> = (1..=5).map(create_dummy_price_feed_message).collect();
+    let feeds: Vec<&Message> = feeds.iter().collect();
+    let ids: Vec<TypeFixedBytes<32>> = feeds
+        .iter()
+        .map(|feed| TypeFixedBytes::from(feed.feed_id()))
+        .collect();
+    let data = vec![Bytes::from(create_accumulator_message(
+        &feeds, &feeds, false, false, None,
+    ))];
+    let fee = U256::from(SINGLE_UPDATE_FEE * feeds.len() as u64);
+    let id = ids[0];
+
+    let _ = receipt!(contract.updatePriceFeeds(data.clone()).value(fee))?;
+
+    // IMPORTANT: Order matters!
+    use PythReceiver::*;
+    #[rustfmt::skip]
+    let receipts = vec![
+        (getPriceUnsafeCall::SIGNATURE, receipt!(contract.getPriceUnsafe(id))?),
+        (getEmaPriceUnsafeCall::SIGNATURE, receipt!(contract.getEmaPriceUnsafe(id))?),
+        (getUpdateFeeCall::SIGNATURE, receipt!(contract.getUpdateFee(data.clone()))?),
+        (updatePriceFeedsCall::SIGNATURE, receipt!(contract.updatePriceFeeds(data.clone()).value(fee))?),
+        (parsePriceFeedUpdatesCall::SIGNATURE, receipt!(contract.parsePriceFeedUpdates(data.clone(), ids.clone(), 0, u64::MAX).value(fee))?),
+    ];
+
+    receipts
+        .into_iter()
+        .map(FunctionReport::new)
+        .collect::<eyre::Result<Vec<_>>>()
+}
+
+async fn deploy(account: &Account, cache_opt: CacheOpt) -> eyre::Result<Address> {
+    let manifest_dir = std::env::current_dir()?;
+    let wasm_path = manifest_dir
+        .join("target")
+        .join("wasm32-unknown-unknown")
+        .join("release")
+        .join("pyth_receiver_stylus.wasm");
+    let sol_path = manifest_dir
+        .join("receiver")
+        .join("src")
+        .join("constructor.sol");
+    let args = constructor::PythReceiver::constructorCall {
+        _wormhole: Address::from_str(&env("WORMHOLE_ADDRESS")?)?,
+        _singleUpdateFeeInWei: U256::from(SINGLE_UPDATE_FEE),
+        _validTimePeriodSeconds: U256::from(VALID_TIME_PERIOD),
+        dataSourceEmitterChainIds: vec![DEFAULT_DATA_SOURCE.chain.into()],
+        dataSourceEmitterAddresses: vec![TypeFixedBytes::from(DEFAULT_DATA_SOURCE.address.0)],
+        governanceEmitterChainId: DEFAULT_GOVERNANCE_SOURCE.chain.into(),
+        governanceEmitterAddress: TypeFixedBytes::from(DEFAULT_GOVERNANCE_SOURCE.address.0),
+        governanceInitialSequence: 0,
+    };
+    let args = alloy::hex::encode(args.abi_encode());
+    crate::deploy_wasm(account, wasm_path, Some(sol_path), Some(args), cache_opt).await
+}
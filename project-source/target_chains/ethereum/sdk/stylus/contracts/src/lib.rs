@@ -16,7 +16,7 @@ pub mod utils;
 /// This module contains the types and functions for interacting with the Pyth oracle.
 pub mod pyth;
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", not(feature = "std")))]
 #[panic_handler]
 fn panic(_info: &core::panic::PanicInfo) -> ! {
     loop {}
@@ -90,6 +90,40 @@ sol! {
     #[allow(missing_docs)]
     error FalledDecodeData();
 
+    // The TWAP update data is invalid.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error InvalidTwapUpdateData();
+
+    // The TWAP update data set is invalid (e.g., there is no update for a price ID).
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error InvalidTwapUpdateDataSet();
+
+    // The input price is negative.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error NegativeInputPrice();
+
+    // The input exponent is invalid (less than -255).
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error InvalidInputExpo();
+
+    // The combined price overflows.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error CombinedPriceOverflow();
+
+    // The combined exponent is greater than 58 or less than -58.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error ExponentOverflow();
+
+    // The confidence interval of the price is wider than the accepted ratio.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error ConfidenceTooWide();
 
 }
 
@@ -111,6 +145,13 @@ pub enum Error {
     OldGovernanceMessage(OldGovernanceMessage),
     InvalidWormholeAddressToSet(InvalidWormholeAddressToSet),
     FalledDecodeData(FalledDecodeData),
+    InvalidTwapUpdateData(InvalidTwapUpdateData),
+    InvalidTwapUpdateDataSet(InvalidTwapUpdateDataSet),
+    NegativeInputPrice(NegativeInputPrice),
+    InvalidInputExpo(InvalidInputExpo),
+    CombinedPriceOverflow(CombinedPriceOverflow),
+    ExponentOverflow(ExponentOverflow),
+    ConfidenceTooWide(ConfidenceTooWide),
 }
 
 impl MethodError for Error {
@@ -1,10 +1,19 @@
 use alloy_sol_types::sol;
 
 sol! {
-  event PriceFeedUpdate(
+    event PriceFeedUpdate(
         bytes32 indexed id,
         uint64 publishTime,
         int64 price,
         uint64 conf
     );
+
+    event TwapPriceFeedUpdate(
+        bytes32 indexed id,
+        uint64 startTime,
+        uint64 endTime,
+        int64 twapPrice,
+        uint64 twapConf,
+        uint32 downSlotsRatio
+    );
 }
@@ -3,8 +3,8 @@ use crate::pyth::mock::DecodeDataType;
 use crate::pyth::types::{
     getEmaPriceNoOlderThanCall, getEmaPriceUnsafeCall, getPriceNoOlderThanCall, getPriceUnsafeCall,
     getUpdateFeeCall, getValidTimePeriodCall, parsePriceFeedUpdatesCall,
-    parsePriceFeedUpdatesUniqueCall, updatePriceFeedsCall, updatePriceFeedsIfNecessaryCall, Price,
-    PriceFeed,
+    parsePriceFeedUpdatesUniqueCall, parseTwapPriceFeedUpdatesCall, updatePriceFeedsCall,
+    updatePriceFeedsIfNecessaryCall, Price, PriceFeed, TwapPriceFeed,
 };
 use crate::utils::{call_helper, delegate_call_helper};
 use alloc::vec::Vec;
@@ -228,6 +228,30 @@ pub fn parse_price_feed_updates_unique(
     Ok(parse_price_feed_updates_call.priceFeeds)
 }
 
+/// Parses TWAP price feeds from the provided update data, which must contain the updates at the start and at the end of the TWAP period.
+///
+/// # Parameters
+/// - `storage`: A mutable reference to an implementation of `TopLevelStorage`.
+/// - `pyth_address`: The address of the Pyth price feed contract.
+/// - `update_data`: A vector of bytes containing the updates at the start and at the end of the TWAP period.
+/// - `price_ids`: A vector of fixed byte identifiers for the assets whose TWAP is being retrieved.
+///
+/// # Returns
+/// - `Result<Vec<TwapPriceFeed>, Vec<u8>>`: A `Result` containing a vector of `TwapPriceFeed` structs in the order of `price_ids` if successful, or an error message as a byte vector.
+pub fn parse_twap_price_feed_updates(
+    storage: &mut impl TopLevelStorage,
+    pyth_address: Address,
+    update_data: Vec<Bytes>,
+    price_ids: Vec<B256>,
+) -> Result<Vec<TwapPriceFeed>, Vec<u8>> {
+    let parse_twap_price_feed_updates_call = delegate_call_helper::<parseTwapPriceFeedUpdatesCall>(
+        storage,
+        pyth_address,
+        (update_data, price_ids),
+    )?;
+    Ok(parse_twap_price_feed_updates_call.twapPriceFeeds)
+}
+
 /// Creates the update data required for a price feed, encapsulating the current price, confidence interval,
 /// exponential moving average (EMA) price, and other relevant details.
 ///
@@ -275,3 +299,40 @@ pub fn create_price_feed_update_data(
     let price_feed_data_encoding = (price_feed_data, prev_publish_time);
     DecodeDataType::abi_encode(&price_feed_data_encoding)
 }
+
+/// Creates the update data of a TWAP price feed, as expected by the mock contract.
+///
+/// # Parameters
+/// - `id`: The fixed byte identifier for the asset.
+/// - `start_time`: The start time of the TWAP period.
+/// - `end_time`: The end time of the TWAP period, which is also the publish time of the TWAP price.
+/// - `price`: The TWAP price of the asset as a 64-bit signed integer.
+/// - `conf`: The confidence level of the TWAP price as a 64-bit unsigned integer.
+/// - `expo`: The exponent for the price, indicating its precision.
+/// - `down_slots_ratio`: The ratio of slots without a price update during the TWAP period, between 0 and 1e6.
+///
+/// # Returns
+/// - `Vec<u8>`: A byte vector containing the encoded update data for the TWAP price feed.
+pub fn create_twap_price_feed_update_data(
+    id: B256,
+    start_time: u64,
+    end_time: u64,
+    price: i64,
+    conf: u64,
+    expo: i32,
+    down_slots_ratio: u32,
+) -> Vec<u8> {
+    let twap_price_feed = TwapPriceFeed {
+        id,
+        start_time,
+        end_time,
+        twap: Price {
+            price,
+            conf,
+            expo,
+            publish_time: U256::from(end_time),
+        },
+        down_slots_ratio,
+    };
+    TwapPriceFeed::abi_encode(&twap_price_feed)
+}
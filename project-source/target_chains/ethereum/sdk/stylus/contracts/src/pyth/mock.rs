@@ -1,9 +1,11 @@
 #![allow(clippy::clone_on_copy)]
 #![allow(clippy::too_many_arguments)]
-use crate::pyth::errors::{Error, PriceFeedNotFound};
-use crate::pyth::events::PriceFeedUpdate;
-use crate::pyth::functions::create_price_feed_update_data;
-use crate::pyth::types::{Price, PriceFeed, StoragePriceFeed};
+use crate::pyth::errors::{
+    Error, InvalidTwapUpdateData, InvalidTwapUpdateDataSet, PriceFeedNotFound,
+};
+use crate::pyth::events::{PriceFeedUpdate, TwapPriceFeedUpdate};
+use crate::pyth::functions::{create_price_feed_update_data, create_twap_price_feed_update_data};
+use crate::pyth::types::{Price, PriceFeed, StoragePriceFeed, TwapPriceFeed};
 use crate::{
     pyth::errors::{FalledDecodeData, InsufficientFee, InvalidArgument},
     utils::CALL_RETDATA_DECODING_ERROR_MESSAGE,
@@ -144,6 +146,80 @@ impl MockPythContract {
         )
     }
 
+    /// Takes an array of encoded TWAP price feeds and returns the TWAP price feeds of `price_ids`.
+    /// You can create this data by calling createTwapPriceFeedUpdateData.
+    /// @note: The updateData expected here is different from the one used in the main contract.
+    /// In particular, the expected format is:
+    /// [
+    ///     abi.encode(
+    ///         PythStructs.TwapPriceFeed(
+    ///             bytes32 id,
+    ///             uint64 startTime,
+    ///             uint64 endTime,
+    ///             PythStructs.Price twap,
+    ///             uint32 downSlotsRatio
+    ///         )
+    ///     )
+    /// ]
+    #[payable]
+    fn parse_twap_price_feed_updates(
+        &mut self,
+        update_data: Vec<AbiBytes>,
+        price_ids: Vec<B256>,
+    ) -> Result<Vec<u8>, Vec<u8>> {
+        let required_fee = self.get_update_fee(update_data.clone());
+        if required_fee > msg::value() {
+            return Err(Error::InsufficientFee(InsufficientFee {}).into());
+        }
+
+        let mut twap_price_feeds = Vec::new();
+        for data in &update_data {
+            let twap_price_feed = <TwapPriceFeed as SolType>::abi_decode(data, false)
+                .map_err(|_| Error::InvalidTwapUpdateData(InvalidTwapUpdateData {}))?;
+            twap_price_feeds.push(twap_price_feed);
+        }
+
+        let mut result_feeds = Vec::new();
+        for price_id in price_ids {
+            let twap_price_feed = twap_price_feeds
+                .iter()
+                .find(|twap_price_feed| twap_price_feed.id == price_id)
+                .ok_or(Error::InvalidTwapUpdateDataSet(InvalidTwapUpdateDataSet {}))?;
+            evm::log(TwapPriceFeedUpdate {
+                id: price_id,
+                startTime: twap_price_feed.start_time,
+                endTime: twap_price_feed.end_time,
+                twapPrice: twap_price_feed.twap.price,
+                twapConf: twap_price_feed.twap.conf,
+                downSlotsRatio: twap_price_feed.down_slots_ratio,
+            });
+            result_feeds.push(*twap_price_feed);
+        }
+
+        Ok(result_feeds.abi_encode())
+    }
+
+    fn create_twap_price_feed_update_data(
+        &self,
+        id: B256,
+        start_time: u64,
+        end_time: u64,
+        price: i64,
+        conf: u64,
+        expo: i32,
+        down_slots_ratio: u32,
+    ) -> Vec<u8> {
+        create_twap_price_feed_update_data(
+            id,
+            start_time,
+            end_time,
+            price,
+            conf,
+            expo,
+            down_slots_ratio,
+        )
+    }
+
     fn create_price_feed_update_data(
         &self,
         id: B256,
@@ -277,9 +353,10 @@ pub fn create_price_feed_update_data_list() -> (Vec<Bytes>, Vec<B256>) {
 #[cfg(all(test, feature = "std"))]
 mod tests {
     use crate::pyth::mock::{DecodeDataType, MockPythContract};
+    use crate::pyth::types::TwapPriceFeed;
     use alloc::vec;
     use alloy_primitives::{B256, U256};
-    use alloy_sol_types::SolType;
+    use alloy_sol_types::{sol_data::Array, SolType};
     use stylus_sdk::abi::Bytes;
 
     /// Updated constants to use uppercase naming convention
@@ -380,4 +457,50 @@ mod tests {
         let valid_time_period = contract.get_valid_time_period();
         assert_eq!(valid_time_period, U256::from(1000));
     }
+
+    #[motsu::test]
+    fn can_parse_twap_price_feed_updates(contract: MockPythContract) {
+        let _ = contract.initialize(U256::from(1000), U256::from(1000));
+        let id = generate_bytes();
+        let other_id = B256::repeat_byte(31);
+        let update_data = vec![
+            Bytes::from(
+                contract
+                    .create_twap_price_feed_update_data(other_id, 100, 200, PRICE, CONF, EXPO, 0),
+            ),
+            Bytes::from(
+                contract.create_twap_price_feed_update_data(id, 100, 200, PRICE, CONF, EXPO, 10),
+            ),
+        ];
+        let twap_price_feeds = contract
+            .parse_twap_price_feed_updates(update_data, vec![id])
+            .unwrap();
+        let twap_price_feeds =
+            <Array<TwapPriceFeed> as SolType>::abi_decode(&twap_price_feeds, true).unwrap();
+        assert_eq!(twap_price_feeds.len(), 1);
+        assert_eq!(twap_price_feeds[0].id, id);
+        assert_eq!(twap_price_feeds[0].start_time, 100);
+        assert_eq!(twap_price_feeds[0].end_time, 200);
+        assert_eq!(twap_price_feeds[0].twap.price, PRICE);
+        assert_eq!(twap_price_feeds[0].twap.conf, CONF);
+        assert_eq!(twap_price_feeds[0].twap.publish_time, U256::from(200));
+        assert_eq!(twap_price_feeds[0].down_slots_ratio, 10);
+    }
+
+    #[motsu::test]
+    fn parse_twap_price_feed_updates_fails_without_update(contract: MockPythContract) {
+        let _ = contract.initialize(U256::from(1000), U256::from(1000));
+        let update_data = vec![Bytes::from(contract.create_twap_price_feed_update_data(
+            generate_bytes(),
+            100,
+            200,
+            PRICE,
+            CONF,
+            EXPO,
+            0,
+        ))];
+        let _err = contract
+            .parse_twap_price_feed_updates(update_data, vec![B256::repeat_byte(31)])
+            .expect_err("should not parse a TWAP without an update for the price ID");
+    }
 }
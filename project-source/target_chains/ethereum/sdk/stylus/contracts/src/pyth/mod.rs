@@ -1,5 +1,7 @@
-mod errors;
-mod events;
+/// Errors of the Pyth contract.
+pub mod errors;
+/// Events of the Pyth contract.
+pub mod events;
 /// Types and functions for interacting with the Pyth oracle.
 pub mod types;
 
@@ -9,5 +11,8 @@ pub mod mock;
 /// Functions for interacting with the Pyth oracle.
 pub mod functions;
 
+/// Price conversion and validation helpers, such as cross-rate derivation.
+pub mod utils;
+
 /// Contract for interacting with the Pyth oracle.
 pub mod pyth_contract;
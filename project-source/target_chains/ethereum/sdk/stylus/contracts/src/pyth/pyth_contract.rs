@@ -1,7 +1,8 @@
 pub use crate::pyth::functions::{
     get_ema_price_no_older_than, get_ema_price_unsafe, get_price_no_older_than, get_price_unsafe,
     get_update_fee, get_valid_time_period, parse_price_feed_updates,
-    parse_price_feed_updates_unique, update_price_feeds, update_price_feeds_if_necessary,
+    parse_price_feed_updates_unique, parse_twap_price_feed_updates, update_price_feeds,
+    update_price_feeds_if_necessary,
 };
 use alloc::vec::Vec;
 use alloy_primitives::{Bytes, B256, U256};
@@ -133,6 +134,20 @@ pub trait IPyth {
         min_publish_time: u64,
         max_publish_time: u64,
     ) -> Result<Vec<u8>, Self::Error>;
+
+    /// Parses the TWAP price feeds for specific price IDs between the two given updates.
+    ///
+    /// # Parameters
+    /// - `update_data`: Array containing the updates at the start and at the end of the TWAP period.
+    /// - `price_ids`: Array of price IDs to parse.
+    ///
+    /// # Returns
+    /// - `Result<Vec<u8>, Self::Error>`: Parsed TWAP price feed data in bytes, or an error.
+    fn parse_twap_price_feed_updates(
+        &mut self,
+        update_data: Vec<AbiBytes>,
+        price_ids: Vec<B256>,
+    ) -> Result<Vec<u8>, Self::Error>;
 }
 
 #[storage]
@@ -241,4 +256,16 @@ impl IPyth for PythContract {
         .abi_encode();
         Ok(encode_data)
     }
+
+    #[payable]
+    fn parse_twap_price_feed_updates(
+        &mut self,
+        update_data: Vec<AbiBytes>,
+        price_ids: Vec<B256>,
+    ) -> Result<Vec<u8>, Self::Error> {
+        let data = update_data.into_iter().map(|x| Bytes::from(x.0)).collect();
+        let encode_data =
+            parse_twap_price_feed_updates(self, self._ipyth.get(), data, price_ids)?.abi_encode();
+        Ok(encode_data)
+    }
 }
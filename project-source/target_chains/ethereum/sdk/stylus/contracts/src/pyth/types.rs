@@ -12,7 +12,7 @@ sol_storage! {
         uint publish_time;
     }
 
-   /// Represents a storage-optimized price feed structure containing an ID and associated price data.
+    /// Represents a storage-optimized price feed structure containing an ID and associated price data.
     pub struct StoragePriceFeed {
         bytes32 id;
         StoragePrice price;
@@ -28,8 +28,8 @@ sol! {
     /// - `conf`: The confidence level of the price as a `uint64`.
     /// - `expo`: The exponent value indicating the scale of the price as an `int32`.
     /// - `publish_time`: The timestamp of when the price was published as a `uint`.
-   #[derive(Debug, Copy)]
-   struct Price {
+    #[derive(Debug, Copy)]
+    struct Price {
         int64 price;
         uint64 conf;
         int32 expo;
@@ -45,13 +45,32 @@ sol! {
     /// - `id`: The unique identifier for the price feed as a `bytes32`.
     /// - `price`: The current price information as a `Price`.
     /// - `ema_price`: The Exponential Moving Average (EMA) price information as a `Price`.
-   #[derive(Debug, Copy)]
+    #[derive(Debug, Copy)]
     struct PriceFeed {
         bytes32 id;
         Price price;
         Price ema_price;
     }
-     /// Function call selector: Fetches the price associated with the given ID without validation.
+
+    /// Represents a time-weighted average price (TWAP) feed over the period between two updates.
+    ///
+    /// # Fields
+    /// - `id`: The unique identifier for the price feed as a `bytes32`.
+    /// - `start_time`: The start time of the TWAP period as a `uint64`.
+    /// - `end_time`: The end time of the TWAP period as a `uint64`.
+    /// - `twap`: The time-weighted average price over the period as a `Price`.
+    /// - `down_slots_ratio`: The ratio of slots without a price update during the period, as a
+    ///   fixed-point number between 0 and 1e6 (100%).
+    #[derive(Debug, Copy)]
+    struct TwapPriceFeed {
+        bytes32 id;
+        uint64 start_time;
+        uint64 end_time;
+        Price twap;
+        uint32 down_slots_ratio;
+    }
+
+        /// Function call selector: Fetches the price associated with the given ID without validation.
         /// Returns the raw `Price` data for the specified `bytes32` ID, acting as a return call selector.
         function getPriceUnsafe(bytes32 id) external view returns (int64,uint64,int32,uint);
 
@@ -118,6 +137,14 @@ sol! {
             uint64 maxPublishTime
         ) external payable returns (PriceFeed[] memory priceFeeds);
 
+        /// Function call selector: Parses TWAP price feeds from the provided data, which must
+        /// contain exactly two updates: the start and the end of the TWAP period.
+        /// Returns an array of `TwapPriceFeed` structs in the order of the given `bytes32` IDs.
+        function parseTwapPriceFeedUpdates(
+            bytes[] calldata updateData,
+            bytes32[] calldata priceIds
+        ) external payable returns (TwapPriceFeed[] memory twapPriceFeeds);
+
         /// Function call selector: Queries the price feed for a given ID.
         /// Returns an array of `PriceFeed` structs associated with the specified `bytes32` ID.
         function queryPriceFeed(
@@ -127,7 +154,7 @@ sol! {
         /// Function call selector: Checks if a price feed exists for the given ID.
         /// Returns an array of `PriceFeed` structs if it exists for the specified `bytes32` ID.
         function priceFeedExists(
-        bytes32 id
+            bytes32 id
         ) public view virtual returns (PriceFeed[] memory priceFeeds);
 
         /// Function call selector: Retrieves the valid time period for price feeds.
@@ -0,0 +1,190 @@
+use crate::pyth::errors::{
+    ConfidenceTooWide, Error, ExponentOverflow, InvalidArgument, InvalidInputExpo,
+    NegativeInputPrice,
+};
+use crate::pyth::types::Price;
+use alloc::vec::Vec;
+use alloy_primitives::U256;
+
+/// The maximum absolute value of a combined exponent, such that `10 ** exponent` multiplied by
+/// an `int64` price fits in a `U256`.
+const MAX_DELTA_EXPONENT: i64 = 58;
+
+/// The minimum exponent accepted as input.
+const MIN_INPUT_EXPONENT: i32 = -255;
+
+/// The denominator of ratios expressed in basis points.
+const BASIS_POINTS: u128 = 10_000;
+
+/// Converts a price to an unsigned integer with `target_decimals` decimals.
+///
+/// The result is rounded down, so precision is lost if `target_decimals` is less than the number of decimals of the price.
+///
+/// # Parameters
+/// - `price`: The price value as a 64-bit signed integer.
+/// - `expo`: The exponent of the price.
+/// - `target_decimals`: The number of decimals of the result.
+///
+/// # Returns
+/// - `Result<U256, Vec<u8>>`: A `Result` containing the converted price if successful, or an error message as a byte vector if the price is negative or the combined exponent is out of bounds.
+pub fn convert_to_uint(price: i64, expo: i32, target_decimals: u8) -> Result<U256, Vec<u8>> {
+    if price < 0 {
+        return Err(Error::NegativeInputPrice(NegativeInputPrice {}).into());
+    }
+    if expo < MIN_INPUT_EXPONENT {
+        return Err(Error::InvalidInputExpo(InvalidInputExpo {}).into());
+    }
+
+    let delta_exponent = i64::from(target_decimals) + i64::from(expo);
+    let scale = pow10_abs(delta_exponent)?;
+    let price = U256::from(price as u64);
+    if delta_exponent > 0 {
+        Ok(price * scale)
+    } else {
+        Ok(price / scale)
+    }
+}
+
+/// Derives the cross-rate `a/c` of a price `a/b` and a price `c/b`, normalized to `target_expo`.
+///
+/// The result is rounded down. The confidence interval of the cross-rate isn't computed.
+///
+/// # Parameters
+/// - `price1`: The price `a/b` as a 64-bit signed integer.
+/// - `expo1`: The exponent of `price1`.
+/// - `price2`: The price `c/b` as a 64-bit signed integer.
+/// - `expo2`: The exponent of `price2`.
+/// - `target_expo`: The exponent of the cross-rate.
+///
+/// # Returns
+/// - `Result<U256, Vec<u8>>`: A `Result` containing the cross-rate with exponent `target_expo` if successful, or an error message as a byte vector if a price is negative, `price2` is zero or the combined exponent is out of bounds.
+pub fn derive_cross_rate(
+    price1: i64,
+    expo1: i32,
+    price2: i64,
+    expo2: i32,
+    target_expo: i32,
+) -> Result<U256, Vec<u8>> {
+    if price1 < 0 || price2 < 0 {
+        return Err(Error::NegativeInputPrice(NegativeInputPrice {}).into());
+    }
+    if price2 == 0 {
+        return Err(Error::InvalidArgument(InvalidArgument {}).into());
+    }
+    if expo1 < MIN_INPUT_EXPONENT || expo2 < MIN_INPUT_EXPONENT || target_expo < MIN_INPUT_EXPONENT
+    {
+        return Err(Error::InvalidInputExpo(InvalidInputExpo {}).into());
+    }
+
+    let delta_exponent = i64::from(expo1) - (i64::from(expo2) + i64::from(target_expo));
+    let scale = pow10_abs(delta_exponent)?;
+    let price1 = U256::from(price1 as u64);
+    let price2 = U256::from(price2 as u64);
+    if delta_exponent > 0 {
+        Ok(price1 * scale / price2)
+    } else {
+        Ok(price1 / (scale * price2))
+    }
+}
+
+/// Checks that the confidence interval of `price` is at most `max_conf_ratio_bps` basis points of the price.
+///
+/// # Parameters
+/// - `price`: The price to check.
+/// - `max_conf_ratio_bps`: The maximum accepted ratio between the confidence interval and the price, in basis points.
+///
+/// # Returns
+/// - `Result<(), Vec<u8>>`: A `Result` that indicates success, or an error message as a byte vector if the confidence interval is too wide.
+pub fn check_confidence_ratio(price: &Price, max_conf_ratio_bps: u64) -> Result<(), Vec<u8>> {
+    let conf = u128::from(price.conf) * BASIS_POINTS;
+    let max_conf = u128::from(price.price.unsigned_abs()) * u128::from(max_conf_ratio_bps);
+    if conf > max_conf {
+        return Err(Error::ConfidenceTooWide(ConfidenceTooWide {}).into());
+    }
+    Ok(())
+}
+
+/// Returns `10 ** |delta_exponent|`, checking that `delta_exponent` is within bounds.
+fn pow10_abs(delta_exponent: i64) -> Result<U256, Vec<u8>> {
+    if !(-MAX_DELTA_EXPONENT..=MAX_DELTA_EXPONENT).contains(&delta_exponent) {
+        return Err(Error::ExponentOverflow(ExponentOverflow {}).into());
+    }
+    Ok(U256::from(10).pow(U256::from(delta_exponent.unsigned_abs())))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::pyth::types::Price;
+    use crate::pyth::utils::{check_confidence_ratio, convert_to_uint, derive_cross_rate};
+    use alloy_primitives::U256;
+
+    fn price(price: i64, conf: u64) -> Price {
+        Price {
+            price,
+            conf,
+            expo: -8,
+            publish_time: U256::from(1000),
+        }
+    }
+
+    #[motsu::test]
+    fn can_convert_to_uint() {
+        assert_eq!(
+            convert_to_uint(100_000_000, -8, 18).unwrap(),
+            U256::from(10).pow(U256::from(18))
+        );
+        assert_eq!(convert_to_uint(123_456, -6, 2).unwrap(), U256::from(12));
+        assert_eq!(convert_to_uint(123, -6, 2).unwrap(), U256::ZERO);
+        assert_eq!(convert_to_uint(5, 2, 0).unwrap(), U256::from(500));
+    }
+
+    #[motsu::test]
+    fn convert_to_uint_rejects_invalid_input() {
+        assert!(convert_to_uint(-1, -8, 18).is_err());
+        assert!(convert_to_uint(1, -256, 18).is_err());
+        assert!(convert_to_uint(1, 50, 10).is_err());
+        assert!(convert_to_uint(1, -70, 10).is_err());
+    }
+
+    #[motsu::test]
+    fn can_derive_cross_rate() {
+        // 500 USD/ETH and 100 USD/SOL gives 5.00 SOL/ETH.
+        assert_eq!(
+            derive_cross_rate(50_000_000_000, -8, 10_000_000_000, -8, -2).unwrap(),
+            U256::from(500)
+        );
+        // The exponents of the input prices are normalized.
+        assert_eq!(
+            derive_cross_rate(500_000, -3, 10_000_000_000, -8, -2).unwrap(),
+            U256::from(500)
+        );
+        assert_eq!(
+            derive_cross_rate(50_000_000_000, -8, 10_000_000_000, -8, 0).unwrap(),
+            U256::from(5)
+        );
+        // The result is rounded down.
+        assert_eq!(
+            derive_cross_rate(10_000_000_000, -8, 30_000_000_000, -8, -2).unwrap(),
+            U256::from(33)
+        );
+    }
+
+    #[motsu::test]
+    fn derive_cross_rate_rejects_invalid_input() {
+        assert!(derive_cross_rate(-1, -8, 1, -8, -2).is_err());
+        assert!(derive_cross_rate(1, -8, -1, -8, -2).is_err());
+        assert!(derive_cross_rate(1, -8, 0, -8, -2).is_err());
+        assert!(derive_cross_rate(1, -256, 1, -8, -2).is_err());
+        assert!(derive_cross_rate(1, 30, 1, -30, 0).is_err());
+    }
+
+    #[motsu::test]
+    fn can_check_confidence_ratio() {
+        // The confidence interval is 1% of the price.
+        assert!(check_confidence_ratio(&price(10_000, 100), 100).is_ok());
+        assert!(check_confidence_ratio(&price(10_000, 100), 99).is_err());
+        assert!(check_confidence_ratio(&price(-10_000, 100), 100).is_ok());
+        assert!(check_confidence_ratio(&price(0, 1), 10_000).is_err());
+        assert!(check_confidence_ratio(&price(i64::MIN, i64::MIN.unsigned_abs()), 10_000).is_ok());
+    }
+}
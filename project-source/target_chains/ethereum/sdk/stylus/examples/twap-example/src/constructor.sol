@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+contract TwapExample {
+    address private pythAddress;
+    bytes32 private basePriceId;
+    bytes32 private quotePriceId;
+    uint64 private maxConfRatioBps;
+
+    constructor(
+        address _pythAddress,
+        bytes32 _basePriceId,
+        bytes32 _quotePriceId,
+        uint64 _maxConfRatioBps
+    ) {
+        pythAddress = _pythAddress;
+        basePriceId = _basePriceId;
+        quotePriceId = _quotePriceId;
+        maxConfRatioBps = _maxConfRatioBps;
+    }
+}
@@ -0,0 +1,86 @@
+#![cfg_attr(not(test), no_std, no_main)]
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use alloy_primitives::{Bytes, U256};
+use alloy_sol_types::sol;
+use pyth_stylus::pyth::{
+    errors::{Error, InvalidTwapUpdateDataSet},
+    functions::{get_price_no_older_than, parse_twap_price_feed_updates},
+    utils::{check_confidence_ratio, derive_cross_rate},
+};
+use stylus_sdk::{
+    abi::Bytes as AbiBytes,
+    prelude::{entrypoint, public, storage},
+    storage::{StorageAddress, StorageB256, StorageU64},
+    stylus_proc::SolidityError,
+};
+
+/// The exponent of the cross-rate returned by `get_cross_rate`.
+const CROSS_RATE_EXPO: i32 = -8;
+
+/// The maximum down slots ratio of a TWAP, i.e. 10% of the slots without a price update.
+const MAX_DOWN_SLOTS_RATIO: u32 = 100_000;
+
+#[entrypoint]
+#[storage]
+struct TwapExample {
+    pyth_address: StorageAddress,
+    base_price_id: StorageB256,
+    quote_price_id: StorageB256,
+    max_conf_ratio_bps: StorageU64,
+}
+
+sol! {
+    error TwapTooSparse();
+}
+
+#[derive(SolidityError)]
+pub enum TwapExampleErrors {
+    TwapTooSparse(TwapTooSparse),
+}
+
+#[public]
+impl TwapExample {
+    /// Returns the price of the base asset in the quote asset, with exponent `CROSS_RATE_EXPO`.
+    pub fn get_cross_rate(&mut self, age: U256) -> Result<U256, Vec<u8>> {
+        let base_price =
+            get_price_no_older_than(self, self.pyth_address.get(), self.base_price_id.get(), age)?;
+        let quote_price = get_price_no_older_than(
+            self,
+            self.pyth_address.get(),
+            self.quote_price_id.get(),
+            age,
+        )?;
+        check_confidence_ratio(&base_price, self.max_conf_ratio_bps.get().to())?;
+        check_confidence_ratio(&quote_price, self.max_conf_ratio_bps.get().to())?;
+        derive_cross_rate(
+            base_price.price,
+            base_price.expo,
+            quote_price.price,
+            quote_price.expo,
+            CROSS_RATE_EXPO,
+        )
+    }
+
+    /// Returns the TWAP of the base asset between the two updates of `update_data`.
+    #[payable]
+    pub fn get_base_twap(&mut self, update_data: Vec<AbiBytes>) -> Result<i64, Vec<u8>> {
+        let data = update_data.into_iter().map(|x| Bytes::from(x.0)).collect();
+        let twap_price_feeds = parse_twap_price_feed_updates(
+            self,
+            self.pyth_address.get(),
+            data,
+            vec![self.base_price_id.get()],
+        )?;
+        let twap_price_feed = *twap_price_feeds
+            .first()
+            .ok_or(Error::InvalidTwapUpdateDataSet(InvalidTwapUpdateDataSet {}))?;
+        if twap_price_feed.down_slots_ratio > MAX_DOWN_SLOTS_RATIO {
+            return Err(TwapExampleErrors::TwapTooSparse(TwapTooSparse {}).into());
+        }
+        check_confidence_ratio(&twap_price_feed.twap, self.max_conf_ratio_bps.get().to())?;
+        Ok(twap_price_feed.twap.price)
+    }
+}
@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.0;
+
+// Initializes a receiver deployed without a proxy, the storage layout must match `PythReceiver`
+// in `lib.rs`. Receivers deployed behind a proxy are initialized by `initialize` instead.
+contract PythReceiver {
+    struct DataSource {
+        uint16 chainId;
+        bytes32 emitterAddress;
+    }
+
+    struct Price {
+        int64 price;
+        uint64 conf;
+        int32 expo;
+        uint256 publishTime;
+    }
+
+    struct PriceFeed {
+        bytes32 id;
+        Price price;
+        Price emaPrice;
+    }
+
+    bool private initialized;
+    address private wormhole;
+    uint256 private singleUpdateFeeInWei;
+    uint256 private validTimePeriodSeconds;
+    mapping(bytes32 => bool) private isValidDataSource;
+    mapping(bytes32 => PriceFeed) private priceFeeds;
+    DataSource[] private validDataSources;
+    DataSource private governanceDataSource;
+    uint64 private lastExecutedGovernanceSequence;
+    uint32 private governanceDataSourceIndex;
+    uint256 private transactionFeeInWei;
+
+    constructor(
+        address _wormhole,
+        uint256 _singleUpdateFeeInWei,
+        uint256 _validTimePeriodSeconds,
+        uint16[] memory dataSourceEmitterChainIds,
+        bytes32[] memory dataSourceEmitterAddresses,
+        uint16 governanceEmitterChainId,
+        bytes32 governanceEmitterAddress,
+        uint64 governanceInitialSequence
+    ) {
+        require(
+            dataSourceEmitterChainIds.length ==
+                dataSourceEmitterAddresses.length,
+            "mismatched data sources"
+        );
+        initialized = true;
+        wormhole = _wormhole;
+        singleUpdateFeeInWei = _singleUpdateFeeInWei;
+        validTimePeriodSeconds = _validTimePeriodSeconds;
+        for (uint i = 0; i < dataSourceEmitterChainIds.length; i++) {
+            DataSource memory dataSource = DataSource(
+                dataSourceEmitterChainIds[i],
+                dataSourceEmitterAddresses[i]
+            );
+            validDataSources.push(dataSource);
+            isValidDataSource[
+                keccak256(
+                    abi.encodePacked(
+                        dataSource.chainId,
+                        dataSource.emitterAddress
+                    )
+                )
+            ] = true;
+        }
+        governanceDataSource = DataSource(
+            governanceEmitterChainId,
+            governanceEmitterAddress
+        );
+        lastExecutedGovernanceSequence = governanceInitialSequence;
+    }
+}
@@ -0,0 +1,545 @@
+//! Governance instructions of the receiver, in the wire format of the Solidity receiver (see
+//! `PythGovernanceInstructions.sol`).
+
+use {
+    crate::{PythReceiver, VM},
+    alloc::vec::Vec,
+    alloy_primitives::{uint, Address, B256, U256, U32, U64},
+    alloy_sol_types::sol,
+    pyth_stylus::{
+        pyth::errors::{
+            Error, InsufficientFee, InvalidGovernanceDataSource, InvalidGovernanceMessage,
+            InvalidGovernanceTarget, InvalidWormholeAddressToSet, InvalidWormholeVaa,
+            OldGovernanceMessage,
+        },
+        utils::call_helper,
+    },
+    stylus_sdk::{
+        call::transfer_eth,
+        contract, evm,
+        storage::{StorageAddress, StorageType},
+    },
+};
+
+sol! {
+    /// A data source of price updates or governance instructions.
+    struct DataSource {
+        uint16 chainId;
+        bytes32 emitterAddress;
+    }
+
+    /// Emitted when the implementation of the proxy of the receiver is upgraded.
+    event ContractUpgraded(address oldImplementation, address newImplementation);
+
+    /// The ERC-1967 event emitted when the implementation of a proxy is upgraded.
+    event Upgraded(address indexed implementation);
+
+    /// Emitted when the governance data source is transferred.
+    event GovernanceDataSourceSet(
+        DataSource oldDataSource,
+        DataSource newDataSource,
+        uint64 initialSequence
+    );
+
+    /// Emitted when the data sources of price updates are replaced.
+    event DataSourcesSet(DataSource[] oldDataSources, DataSource[] newDataSources);
+
+    /// Emitted when the fee of each price update is updated.
+    event FeeSet(uint256 oldFee, uint256 newFee);
+
+    /// Emitted when the valid time period of prices is updated.
+    event ValidPeriodSet(uint256 oldValidPeriod, uint256 newValidPeriod);
+
+    /// Emitted when the Wormhole core contract is updated.
+    event WormholeAddressSet(address oldWormholeAddress, address newWormholeAddress);
+
+    /// Emitted when the fee of each update transaction is updated.
+    event TransactionFeeSet(uint256 oldFee, uint256 newFee);
+
+    /// Emitted when collected fees are withdrawn.
+    event FeeWithdrawn(address targetAddress, uint256 fee);
+
+    /// Function call selector: Identifies receiver implementations.
+    function pythUpgradableMagic() external pure returns (uint32);
+}
+
+/// Returned by `pythUpgradableMagic` of receiver implementations, the same value as the
+/// Solidity receiver.
+pub(crate) const PYTH_UPGRADABLE_MAGIC: u32 = 0x97a6f304;
+
+/// The ERC-1967 storage slot of the implementation of a proxy,
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`.
+const IMPLEMENTATION_SLOT: U256 =
+    uint!(0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc_U256);
+
+/// Magic number of governance instructions, `b"PTGM"`.
+const MAGIC: u32 = 0x5054474d;
+
+/// The governance module of the instructions executed by Pyth receivers.
+const MODULE_TARGET: u8 = 1;
+
+/// A governance action and its payload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum GovernanceAction {
+    UpgradeContract {
+        new_implementation: Address,
+    },
+    AuthorizeGovernanceDataSourceTransfer {
+        claim_vaa: Vec<u8>,
+    },
+    /// New data sources, as `(emitter_chain_id, emitter_address)` pairs.
+    SetDataSources {
+        data_sources: Vec<(u16, B256)>,
+    },
+    SetFee {
+        new_fee: U256,
+    },
+    SetValidPeriod {
+        new_valid_period: U256,
+    },
+    /// Only valid as the payload of the `claim_vaa` of `AuthorizeGovernanceDataSourceTransfer`.
+    RequestGovernanceDataSourceTransfer {
+        governance_data_source_index: u32,
+    },
+    SetWormholeAddress {
+        new_wormhole_address: Address,
+    },
+    /// A no-op on EVM chains.
+    SetFeeInToken,
+    SetTransactionFee {
+        new_fee: U256,
+    },
+    WithdrawFee {
+        target_address: Address,
+        fee: U256,
+    },
+}
+
+/// A governance instruction for the receivers of `target_chain_id`, or for all receivers if it
+/// is 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct GovernanceInstruction {
+    pub(crate) target_chain_id: u16,
+    pub(crate) action: GovernanceAction,
+}
+
+impl GovernanceInstruction {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader(data);
+        if reader.u32()? != MAGIC {
+            return Err(invalid_message());
+        }
+        if reader.u8()? != MODULE_TARGET {
+            return Err(invalid_target());
+        }
+        let action = reader.u8()?;
+        let target_chain_id = reader.u16()?;
+
+        let action = match action {
+            0 => GovernanceAction::UpgradeContract {
+                new_implementation: reader.address()?,
+            },
+            1 => GovernanceAction::AuthorizeGovernanceDataSourceTransfer {
+                claim_vaa: reader.rest().to_vec(),
+            },
+            2 => {
+                let len = reader.u8()?;
+                let mut data_sources = Vec::with_capacity(len.into());
+                for _ in 0..len {
+                    data_sources.push((reader.u16()?, reader.b256()?));
+                }
+                GovernanceAction::SetDataSources { data_sources }
+            }
+            3 => GovernanceAction::SetFee {
+                new_fee: reader.fee()?,
+            },
+            4 => GovernanceAction::SetValidPeriod {
+                new_valid_period: U256::from(reader.u64()?),
+            },
+            5 => GovernanceAction::RequestGovernanceDataSourceTransfer {
+                governance_data_source_index: reader.u32()?,
+            },
+            6 => GovernanceAction::SetWormholeAddress {
+                new_wormhole_address: reader.address()?,
+            },
+            7 => {
+                reader.rest();
+                GovernanceAction::SetFeeInToken
+            }
+            8 => GovernanceAction::SetTransactionFee {
+                new_fee: reader.fee()?,
+            },
+            9 => GovernanceAction::WithdrawFee {
+                target_address: reader.address()?,
+                fee: reader.fee()?,
+            },
+            _ => return Err(invalid_message()),
+        };
+        if !reader.0.is_empty() {
+            return Err(invalid_message());
+        }
+
+        Ok(GovernanceInstruction {
+            target_chain_id,
+            action,
+        })
+    }
+}
+
+impl PythReceiver {
+    /// Verifies the governance VAA `encoded_vm` and executes its instruction, as
+    /// `executeGovernanceInstruction` of the Solidity receiver.
+    pub(crate) fn execute_governance_vm(&mut self, encoded_vm: Vec<u8>) -> Result<(), Vec<u8>> {
+        let vm = self.verify_governance_vm(encoded_vm.clone())?;
+        let instruction = GovernanceInstruction::parse(&vm.payload)?;
+        self.check_target_chain_id(instruction.target_chain_id)?;
+
+        match instruction.action {
+            GovernanceAction::UpgradeContract { new_implementation } => {
+                if instruction.target_chain_id == 0 {
+                    return Err(invalid_target().into());
+                }
+                self.upgrade_contract(new_implementation)?;
+            }
+            GovernanceAction::AuthorizeGovernanceDataSourceTransfer { claim_vaa } => {
+                self.authorize_governance_data_source_transfer(claim_vaa)?;
+            }
+            GovernanceAction::SetDataSources { data_sources } => {
+                let old_data_sources = self.set_valid_data_sources(data_sources);
+                evm::log(DataSourcesSet {
+                    oldDataSources: old_data_sources.into_iter().map(data_source).collect(),
+                    newDataSources: self
+                        .valid_data_sources()
+                        .into_iter()
+                        .map(data_source)
+                        .collect(),
+                });
+            }
+            GovernanceAction::SetFee { new_fee } => {
+                let old_fee = self.single_update_fee_in_wei.get();
+                self.single_update_fee_in_wei.set(new_fee);
+                evm::log(FeeSet {
+                    oldFee: old_fee,
+                    newFee: new_fee,
+                });
+            }
+            GovernanceAction::SetValidPeriod { new_valid_period } => {
+                let old_valid_period = self.valid_time_period_seconds.get();
+                self.valid_time_period_seconds.set(new_valid_period);
+                evm::log(ValidPeriodSet {
+                    oldValidPeriod: old_valid_period,
+                    newValidPeriod: new_valid_period,
+                });
+            }
+            // Only valid as the claim VAA of `AuthorizeGovernanceDataSourceTransfer`.
+            GovernanceAction::RequestGovernanceDataSourceTransfer { .. } => {
+                return Err(invalid_message().into());
+            }
+            GovernanceAction::SetWormholeAddress {
+                new_wormhole_address,
+            } => {
+                if instruction.target_chain_id == 0 {
+                    return Err(invalid_target().into());
+                }
+                self.set_wormhole_address(new_wormhole_address, encoded_vm)?;
+            }
+            GovernanceAction::SetFeeInToken => {}
+            GovernanceAction::SetTransactionFee { new_fee } => {
+                let old_fee = self.transaction_fee_in_wei.get();
+                self.transaction_fee_in_wei.set(new_fee);
+                evm::log(TransactionFeeSet {
+                    oldFee: old_fee,
+                    newFee: new_fee,
+                });
+            }
+            GovernanceAction::WithdrawFee {
+                target_address,
+                fee,
+            } => {
+                if fee > contract::balance() {
+                    return Err(Error::InsufficientFee(InsufficientFee {}).into());
+                }
+                transfer_eth(target_address, fee)?;
+                evm::log(FeeWithdrawn {
+                    targetAddress: target_address,
+                    fee,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies that `encoded_vm` is emitted by the governance data source and that it is newer
+    /// than the last executed governance VAA, and marks it as executed.
+    fn verify_governance_vm(&mut self, encoded_vm: Vec<u8>) -> Result<VM, Vec<u8>> {
+        let result = self.parse_and_verify_vm(encoded_vm)?;
+        if !result.valid {
+            return Err(Error::InvalidWormholeVaa(InvalidWormholeVaa {}).into());
+        }
+        let vm = result.vm;
+        if !self.is_governance_data_source(vm.emitterChainId, vm.emitterAddress) {
+            return Err(Error::InvalidGovernanceDataSource(InvalidGovernanceDataSource {}).into());
+        }
+        if vm.sequence <= self.last_executed_governance_sequence.get().to::<u64>() {
+            return Err(Error::OldGovernanceMessage(OldGovernanceMessage {}).into());
+        }
+        self.last_executed_governance_sequence
+            .set(U64::from(vm.sequence));
+        Ok(vm)
+    }
+
+    fn is_governance_data_source(&self, chain_id: u16, emitter_address: B256) -> bool {
+        self.governance_data_source.get() == (chain_id, emitter_address)
+    }
+
+    /// Checks that an instruction targets this chain, or all chains if `target_chain_id` is 0.
+    fn check_target_chain_id(&mut self, target_chain_id: u16) -> Result<(), Vec<u8>> {
+        if target_chain_id != 0 && target_chain_id != self.wormhole_chain_id()? {
+            return Err(invalid_target().into());
+        }
+        Ok(())
+    }
+
+    /// Points the proxy of the receiver to `new_implementation`. The receiver must be called
+    /// through an ERC-1967 proxy.
+    fn upgrade_contract(&mut self, new_implementation: Address) -> Result<(), Vec<u8>> {
+        // Make sure that the new implementation is a receiver, which can be upgraded again.
+        let magic = call_helper::<pythUpgradableMagicCall>(self, new_implementation, ())?._0;
+        if magic != PYTH_UPGRADABLE_MAGIC {
+            return Err(invalid_message().into());
+        }
+
+        // SAFETY: the slot is reserved by ERC-1967 and isn't used by the storage of the receiver.
+        let mut implementation = unsafe { StorageAddress::new(IMPLEMENTATION_SLOT, 0) };
+        let old_implementation = implementation.get();
+        implementation.set(new_implementation);
+        evm::log(Upgraded {
+            implementation: new_implementation,
+        });
+        evm::log(ContractUpgraded {
+            oldImplementation: old_implementation,
+            newImplementation: new_implementation,
+        });
+        Ok(())
+    }
+
+    /// Transfers the governance to the emitter of `claim_vaa`, which must request it with a
+    /// governance data source index greater than the current one.
+    fn authorize_governance_data_source_transfer(
+        &mut self,
+        claim_vaa: Vec<u8>,
+    ) -> Result<(), Vec<u8>> {
+        let result = self.parse_and_verify_vm(claim_vaa)?;
+        if !result.valid {
+            return Err(Error::InvalidWormholeVaa(InvalidWormholeVaa {}).into());
+        }
+        let vm = result.vm;
+        let instruction = GovernanceInstruction::parse(&vm.payload)?;
+        self.check_target_chain_id(instruction.target_chain_id)?;
+        let GovernanceAction::RequestGovernanceDataSourceTransfer {
+            governance_data_source_index,
+        } = instruction.action
+        else {
+            return Err(invalid_message().into());
+        };
+
+        if self.governance_data_source_index.get().to::<u32>() >= governance_data_source_index {
+            return Err(Error::OldGovernanceMessage(OldGovernanceMessage {}).into());
+        }
+        self.governance_data_source_index
+            .set(U32::from(governance_data_source_index));
+
+        let old_governance_data_source = self.governance_data_source.get();
+        self.governance_data_source
+            .set(vm.emitterChainId, vm.emitterAddress);
+        // Older governance VAAs of the new governance data source can't be executed.
+        self.last_executed_governance_sequence
+            .set(U64::from(vm.sequence));
+
+        evm::log(GovernanceDataSourceSet {
+            oldDataSource: data_source(old_governance_data_source),
+            newDataSource: data_source((vm.emitterChainId, vm.emitterAddress)),
+            initialSequence: vm.sequence,
+        });
+        Ok(())
+    }
+
+    /// Sets the Wormhole core contract, which must be able to verify `encoded_vm`, the VAA of
+    /// the instruction setting it.
+    fn set_wormhole_address(
+        &mut self,
+        new_wormhole_address: Address,
+        encoded_vm: Vec<u8>,
+    ) -> Result<(), Vec<u8>> {
+        let old_wormhole_address = self.wormhole.get();
+        self.wormhole.set(new_wormhole_address);
+
+        let result = self.parse_and_verify_vm(encoded_vm)?;
+        let vm = result.vm;
+        if !result.valid || !self.is_governance_data_source(vm.emitterChainId, vm.emitterAddress) {
+            return Err(invalid_message().into());
+        }
+        let invalid_wormhole_address =
+            || Error::InvalidWormholeAddressToSet(InvalidWormholeAddressToSet {});
+        if vm.sequence != self.last_executed_governance_sequence.get().to::<u64>() {
+            return Err(invalid_wormhole_address().into());
+        }
+        // The target chain isn't checked, as the new Wormhole contract may use another chain ID.
+        match GovernanceInstruction::parse(&vm.payload)?.action {
+            GovernanceAction::SetWormholeAddress {
+                new_wormhole_address: parsed_wormhole_address,
+            } if parsed_wormhole_address == new_wormhole_address => {}
+            _ => return Err(invalid_wormhole_address().into()),
+        }
+
+        evm::log(WormholeAddressSet {
+            oldWormholeAddress: old_wormhole_address,
+            newWormholeAddress: new_wormhole_address,
+        });
+        Ok(())
+    }
+}
+
+fn data_source((chain_id, emitter_address): (u16, B256)) -> DataSource {
+    DataSource {
+        chainId: chain_id,
+        emitterAddress: emitter_address,
+    }
+}
+
+fn invalid_target() -> Error {
+    Error::InvalidGovernanceTarget(InvalidGovernanceTarget {})
+}
+
+fn invalid_message() -> Error {
+    Error::InvalidGovernanceMessage(InvalidGovernanceMessage {})
+}
+
+/// Reads big endian values from the front of a payload.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        if self.0.len() < N {
+            return Err(invalid_message());
+        }
+        let (bytes, rest) = self.0.split_at(N);
+        self.0 = rest;
+        Ok(bytes.try_into().expect("slice has N bytes"))
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        core::mem::take(&mut self.0)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(u8::from_be_bytes(self.bytes()?))
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_be_bytes(self.bytes()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_be_bytes(self.bytes()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_be_bytes(self.bytes()?))
+    }
+
+    fn address(&mut self) -> Result<Address, Error> {
+        Ok(Address::from(self.bytes::<20>()?))
+    }
+
+    fn b256(&mut self) -> Result<B256, Error> {
+        Ok(B256::from(self.bytes::<32>()?))
+    }
+
+    /// Reads a fee encoded as `(value, exponent)`, i.e. `value * 10^exponent`.
+    fn fee(&mut self) -> Result<U256, Error> {
+        let value = self.u64()?;
+        let expo = self.u64()?;
+        U256::from(10)
+            .checked_pow(U256::from(expo))
+            .and_then(|scale| scale.checked_mul(U256::from(value)))
+            .ok_or_else(invalid_message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{GovernanceAction, GovernanceInstruction, MAGIC},
+        alloc::vec::Vec,
+        alloy_primitives::{Address, B256, U256},
+    };
+
+    fn instruction(module: u8, action: u8, target_chain_id: u16, payload: &[u8]) -> Vec<u8> {
+        [
+            &MAGIC.to_be_bytes()[..],
+            &[module, action],
+            &target_chain_id.to_be_bytes(),
+            payload,
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn can_parse_set_data_sources() {
+        let payload = [
+            &[2u8][..],
+            &1u16.to_be_bytes(),
+            &[3; 32],
+            &26u16.to_be_bytes(),
+            &[4; 32],
+        ]
+        .concat();
+        assert_eq!(
+            GovernanceInstruction::parse(&instruction(1, 2, 7, &payload)).unwrap(),
+            GovernanceInstruction {
+                target_chain_id: 7,
+                action: GovernanceAction::SetDataSources {
+                    data_sources: vec![(1, B256::repeat_byte(3)), (26, B256::repeat_byte(4))],
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_withdraw_fee() {
+        let payload = [&[5u8; 20][..], &12u64.to_be_bytes(), &3u64.to_be_bytes()].concat();
+        assert_eq!(
+            GovernanceInstruction::parse(&instruction(1, 9, 0, &payload)).unwrap(),
+            GovernanceInstruction {
+                target_chain_id: 0,
+                action: GovernanceAction::WithdrawFee {
+                    target_address: Address::repeat_byte(5),
+                    fee: U256::from(12_000),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_instructions() {
+        let fee = [1u64.to_be_bytes(), 2u64.to_be_bytes()].concat();
+        // Wrong magic.
+        let mut data = instruction(1, 3, 0, &fee);
+        data[0] = 0;
+        assert!(GovernanceInstruction::parse(&data).is_err());
+        // Wrong module.
+        assert!(GovernanceInstruction::parse(&instruction(2, 3, 0, &fee)).is_err());
+        // Unknown action.
+        assert!(GovernanceInstruction::parse(&instruction(1, 10, 0, &fee)).is_err());
+        // Truncated and oversized payloads.
+        assert!(GovernanceInstruction::parse(&instruction(1, 3, 0, &fee[..15])).is_err());
+        assert!(
+            GovernanceInstruction::parse(&instruction(1, 3, 0, &[&fee[..], &[0]].concat()))
+                .is_err()
+        );
+        // Overflowing fee.
+        let fee = [u64::MAX.to_be_bytes(), 100u64.to_be_bytes()].concat();
+        assert!(GovernanceInstruction::parse(&instruction(1, 3, 0, &fee)).is_err());
+    }
+}
@@ -0,0 +1,1116 @@
+//! Pyth price feed receiver implemented in Stylus.
+//!
+//! Unlike [`pyth_stylus::pyth::pyth_contract::PythContract`], which forwards calls to a deployed
+//! Pyth contract, this contract verifies and stores price updates itself and implements the
+//! `IPyth` ABI of the Solidity receiver. Accumulator updates are parsed with `pythnet_sdk::wire`,
+//! their VAA is verified by the Wormhole core contract, and their messages are checked against
+//! the merkle root of the VAA with `pythnet_sdk::accumulators::merkle`.
+//!
+//! Like the Solidity receiver, the receiver is meant to be deployed behind an ERC-1967 proxy
+//! whose constructor calls `initialize`. It is then managed by the governance instructions of
+//! its governance data source, which can update its configuration, upgrade the implementation
+//! of the proxy and withdraw the collected fees. `src/constructor.sol` initializes a receiver
+//! deployed without a proxy, which can't be upgraded.
+#![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
+extern crate alloc;
+
+mod governance;
+mod wormhole;
+
+use {
+    alloc::vec::Vec,
+    alloy_primitives::{Address, B256, U16, U256, U64},
+    alloy_sol_types::sol,
+    byteorder::BigEndian,
+    pyth_stylus::pyth::{
+        errors::{
+            Error, InsufficientFee, InvalidArgument, InvalidUpdateData, InvalidUpdateDataSource,
+            InvalidWormholeVaa, NoFreshUpdate, PriceFeedNotFound, PriceFeedNotFoundWithinRange,
+            StalePrice,
+        },
+        events::PriceFeedUpdate,
+        types::{Price, PriceFeed, StoragePriceFeed},
+    },
+    pythnet_sdk::{
+        accumulators::merkle::MerkleRoot,
+        hashers::keccak256_160::Keccak160,
+        messages::Message,
+        wire::{
+            from_slice,
+            v1::{AccumulatorUpdateData, Proof, WormholeMessage, WormholePayload},
+        },
+    },
+    stylus_sdk::{
+        abi::Bytes as AbiBytes,
+        block, contract,
+        crypto::keccak,
+        evm, msg,
+        prelude::*,
+        storage::{
+            StorageAddress, StorageB256, StorageBool, StorageMap, StorageU16, StorageU256,
+            StorageU32, StorageU64, StorageVec,
+        },
+        types::AddressVM,
+    },
+};
+
+sol! {
+    /// A guardian signature of a VAA, as returned by the Wormhole core contract.
+    struct GuardianSignature {
+        bytes32 r;
+        bytes32 s;
+        uint8 v;
+        uint8 guardianIndex;
+    }
+
+    /// A parsed VAA, as returned by the Wormhole core contract.
+    struct VM {
+        uint8 version;
+        uint32 timestamp;
+        uint32 nonce;
+        uint16 emitterChainId;
+        bytes32 emitterAddress;
+        uint64 sequence;
+        uint8 consistencyLevel;
+        bytes payload;
+        uint32 guardianSetIndex;
+        GuardianSignature[] signatures;
+        bytes32 hash;
+    }
+
+    /// Function call selector: Parses a VAA and verifies its signatures against the current
+    /// guardian set of the Wormhole core contract.
+    function parseAndVerifyVM(bytes calldata encodedVM)
+        external
+        view
+        returns (VM memory vm, bool valid, string memory reason);
+
+    /// Function call selector: Returns the Wormhole chain ID of the chain of the Wormhole core
+    /// contract.
+    function chainId() external view returns (uint16);
+}
+
+/// ABI representation of a `Price`: `(price, conf, expo, publish_time)`.
+pub type PriceTuple = (i64, u64, i32, U256);
+
+/// ABI representation of a `PriceFeed`: `(id, price, ema_price)`.
+pub type PriceFeedTuple = (B256, PriceTuple, PriceTuple);
+
+/// A price feed parsed from an update, along with the publish time of the previous update of
+/// the feed.
+type ParsedPriceFeed = (PriceFeed, u64);
+
+/// A data source of price updates or governance instructions, identified by the Wormhole chain
+/// ID and emitter address of its VAAs.
+#[storage]
+pub struct StorageDataSource {
+    chain_id: StorageU16,
+    emitter_address: StorageB256,
+}
+
+impl StorageDataSource {
+    fn get(&self) -> (u16, B256) {
+        (self.chain_id.get().to(), self.emitter_address.get())
+    }
+
+    fn set(&mut self, chain_id: u16, emitter_address: B256) {
+        self.chain_id.set(U16::from(chain_id));
+        self.emitter_address.set(emitter_address);
+    }
+}
+
+/// The Pyth receiver contract.
+///
+/// The storage layout must match `src/constructor.sol`.
+#[entrypoint]
+#[storage]
+pub struct PythReceiver {
+    initialized: StorageBool,
+    wormhole: StorageAddress,
+    single_update_fee_in_wei: StorageU256,
+    valid_time_period_seconds: StorageU256,
+    /// Keyed by `data_source_key(emitter_chain_id, emitter_address)`.
+    is_valid_data_source: StorageMap<B256, StorageBool>,
+    price_feeds: StorageMap<B256, StoragePriceFeed>,
+    valid_data_sources: StorageVec<StorageDataSource>,
+    governance_data_source: StorageDataSource,
+    last_executed_governance_sequence: StorageU64,
+    /// Incremented by each transfer of the governance data source, so that the claim VAA of a
+    /// transfer can't be replayed.
+    governance_data_source_index: StorageU32,
+    transaction_fee_in_wei: StorageU256,
+}
+
+#[public]
+impl PythReceiver {
+    /// Initializes the receiver. Price updates are verified by the `wormhole` core contract and
+    /// must be emitted by one of the given data sources. Governance instructions must be emitted
+    /// by the governance data source, with a sequence greater than `governance_initial_sequence`.
+    ///
+    /// Can only be called while the contract is being deployed, i.e. from the constructor of
+    /// its proxy, so that it can't be front-run.
+    #[allow(clippy::too_many_arguments)]
+    fn initialize(
+        &mut self,
+        wormhole: Address,
+        single_update_fee_in_wei: U256,
+        valid_time_period_seconds: U256,
+        data_source_emitter_chain_ids: Vec<u16>,
+        data_source_emitter_addresses: Vec<B256>,
+        governance_emitter_chain_id: u16,
+        governance_emitter_address: B256,
+        governance_initial_sequence: u64,
+    ) -> Result<(), Vec<u8>> {
+        // A contract only gets its code at the end of its deployment.
+        if contract::address().has_code() {
+            return Err(Error::InvalidArgument(InvalidArgument {}).into());
+        }
+        self.init(
+            wormhole,
+            single_update_fee_in_wei,
+            valid_time_period_seconds,
+            data_source_emitter_chain_ids,
+            data_source_emitter_addresses,
+            governance_emitter_chain_id,
+            governance_emitter_address,
+            governance_initial_sequence,
+        )
+    }
+
+    fn wormhole(&self) -> Address {
+        self.wormhole.get()
+    }
+
+    fn single_update_fee_in_wei(&self) -> U256 {
+        self.single_update_fee_in_wei.get()
+    }
+
+    fn transaction_fee_in_wei(&self) -> U256 {
+        self.transaction_fee_in_wei.get()
+    }
+
+    /// Returns the data sources of price updates, as `(emitter_chain_id, emitter_address)`.
+    fn valid_data_sources(&self) -> Vec<(u16, B256)> {
+        (0..self.valid_data_sources.len())
+            .filter_map(|i| self.valid_data_sources.getter(i))
+            .map(|data_source| data_source.get())
+            .collect()
+    }
+
+    /// Returns the data source of governance instructions, as
+    /// `(emitter_chain_id, emitter_address)`.
+    fn governance_data_source(&self) -> (u16, B256) {
+        self.governance_data_source.get()
+    }
+
+    fn last_executed_governance_sequence(&self) -> u64 {
+        self.last_executed_governance_sequence.get().to()
+    }
+
+    fn governance_data_source_index(&self) -> u32 {
+        self.governance_data_source_index.get().to()
+    }
+
+    /// Identifies receiver implementations, which are the only valid targets of an upgrade.
+    fn pyth_upgradable_magic(&self) -> u32 {
+        governance::PYTH_UPGRADABLE_MAGIC
+    }
+
+    /// Executes the governance instruction of `encoded_vm`, a VAA emitted by the governance data
+    /// source.
+    fn execute_governance_instruction(&mut self, encoded_vm: AbiBytes) -> Result<(), Vec<u8>> {
+        self.execute_governance_vm(encoded_vm.0)
+    }
+
+    fn get_valid_time_period(&self) -> U256 {
+        self.valid_time_period_seconds.get()
+    }
+
+    fn get_price(&self, id: B256) -> Result<PriceTuple, Vec<u8>> {
+        self.get_price_no_older_than(id, self.valid_time_period_seconds.get())
+    }
+
+    fn get_ema_price(&self, id: B256) -> Result<PriceTuple, Vec<u8>> {
+        self.get_ema_price_no_older_than(id, self.valid_time_period_seconds.get())
+    }
+
+    fn get_price_unsafe(&self, id: B256) -> Result<PriceTuple, Vec<u8>> {
+        Ok(price_tuple(&self.stored_price_feed(id)?.price))
+    }
+
+    fn get_price_no_older_than(&self, id: B256, age: U256) -> Result<PriceTuple, Vec<u8>> {
+        let price = self.stored_price_feed(id)?.price;
+        check_age(&price, age)?;
+        Ok(price_tuple(&price))
+    }
+
+    fn get_ema_price_unsafe(&self, id: B256) -> Result<PriceTuple, Vec<u8>> {
+        Ok(price_tuple(&self.stored_price_feed(id)?.ema_price))
+    }
+
+    fn get_ema_price_no_older_than(&self, id: B256, age: U256) -> Result<PriceTuple, Vec<u8>> {
+        let ema_price = self.stored_price_feed(id)?.ema_price;
+        check_age(&ema_price, age)?;
+        Ok(price_tuple(&ema_price))
+    }
+
+    fn price_feed_exists(&self, id: B256) -> bool {
+        !self.price_feeds.getter(id).to_price_feed().id.is_zero()
+    }
+
+    fn query_price_feed(&self, id: B256) -> Result<PriceFeedTuple, Vec<u8>> {
+        Ok(price_feed_tuple(&self.stored_price_feed(id)?))
+    }
+
+    /// Returns the fee of `update_data`, which is charged for each price update it contains,
+    /// plus the transaction fee.
+    fn get_update_fee(&self, update_data: Vec<AbiBytes>) -> Result<U256, Vec<u8>> {
+        self.update_fee(&update_data)
+    }
+
+    #[payable]
+    fn update_price_feeds(&mut self, update_data: Vec<AbiBytes>) -> Result<(), Vec<u8>> {
+        self.check_fee(&update_data)?;
+        for data in &update_data {
+            for (price_feed, _) in self.parse_update(&data.0)? {
+                self.update_price_feed_if_new(price_feed);
+            }
+        }
+        Ok(())
+    }
+
+    /// Updates the price feeds only if one of the stored price feeds of `price_ids` is older
+    /// than the corresponding publish time in `publish_times`.
+    #[payable]
+    fn update_price_feeds_if_necessary(
+        &mut self,
+        update_data: Vec<AbiBytes>,
+        price_ids: Vec<B256>,
+        publish_times: Vec<u64>,
+    ) -> Result<(), Vec<u8>> {
+        if price_ids.len() != publish_times.len() {
+            return Err(Error::InvalidArgument(InvalidArgument {}).into());
+        }
+        for (id, publish_time) in price_ids.into_iter().zip(publish_times) {
+            let stored_price_feed = self.price_feeds.getter(id).to_price_feed();
+            if stored_price_feed.id.is_zero()
+                || stored_price_feed.price.publish_time < U256::from(publish_time)
+            {
+                return self.update_price_feeds(update_data);
+            }
+        }
+        Err(Error::NoFreshUpdate(NoFreshUpdate {}).into())
+    }
+
+    #[payable]
+    fn parse_price_feed_updates(
+        &mut self,
+        update_data: Vec<AbiBytes>,
+        price_ids: Vec<B256>,
+        min_publish_time: u64,
+        max_publish_time: u64,
+    ) -> Result<Vec<PriceFeedTuple>, Vec<u8>> {
+        self.parse_price_feed_updates_internal(
+            update_data,
+            price_ids,
+            min_publish_time,
+            max_publish_time,
+            false,
+        )
+    }
+
+    #[payable]
+    fn parse_price_feed_updates_unique(
+        &mut self,
+        update_data: Vec<AbiBytes>,
+        price_ids: Vec<B256>,
+        min_publish_time: u64,
+        max_publish_time: u64,
+    ) -> Result<Vec<PriceFeedTuple>, Vec<u8>> {
+        self.parse_price_feed_updates_internal(
+            update_data,
+            price_ids,
+            min_publish_time,
+            max_publish_time,
+            true,
+        )
+    }
+}
+
+impl PythReceiver {
+    #[allow(clippy::too_many_arguments)]
+    fn init(
+        &mut self,
+        wormhole: Address,
+        single_update_fee_in_wei: U256,
+        valid_time_period_seconds: U256,
+        data_source_emitter_chain_ids: Vec<u16>,
+        data_source_emitter_addresses: Vec<B256>,
+        governance_emitter_chain_id: u16,
+        governance_emitter_address: B256,
+        governance_initial_sequence: u64,
+    ) -> Result<(), Vec<u8>> {
+        if self.initialized.get()
+            || data_source_emitter_chain_ids.len() != data_source_emitter_addresses.len()
+        {
+            return Err(Error::InvalidArgument(InvalidArgument {}).into());
+        }
+        self.initialized.set(true);
+        self.wormhole.set(wormhole);
+        self.single_update_fee_in_wei.set(single_update_fee_in_wei);
+        self.valid_time_period_seconds
+            .set(valid_time_period_seconds);
+        self.set_valid_data_sources(
+            data_source_emitter_chain_ids
+                .into_iter()
+                .zip(data_source_emitter_addresses)
+                .collect(),
+        );
+        self.governance_data_source
+            .set(governance_emitter_chain_id, governance_emitter_address);
+        self.last_executed_governance_sequence
+            .set(U64::from(governance_initial_sequence));
+        Ok(())
+    }
+
+    /// Replaces the data sources of price updates and returns the previous ones.
+    fn set_valid_data_sources(&mut self, data_sources: Vec<(u16, B256)>) -> Vec<(u16, B256)> {
+        let old_data_sources = self.valid_data_sources();
+        for (chain_id, emitter_address) in &old_data_sources {
+            self.is_valid_data_source
+                .setter(data_source_key(*chain_id, *emitter_address))
+                .set(false);
+        }
+        while self.valid_data_sources.shrink().is_some() {}
+        for (chain_id, emitter_address) in data_sources {
+            self.valid_data_sources
+                .grow()
+                .set(chain_id, emitter_address);
+            self.is_valid_data_source
+                .setter(data_source_key(chain_id, emitter_address))
+                .set(true);
+        }
+        old_data_sources
+    }
+
+    fn stored_price_feed(&self, id: B256) -> Result<PriceFeed, Vec<u8>> {
+        let price_feed = self.price_feeds.getter(id).to_price_feed();
+        if price_feed.id.is_zero() {
+            return Err(Error::PriceFeedNotFound(PriceFeedNotFound {}).into());
+        }
+        Ok(price_feed)
+    }
+
+    fn update_fee(&self, update_data: &[AbiBytes]) -> Result<U256, Vec<u8>> {
+        let mut num_updates: u64 = 0;
+        for data in update_data {
+            let accumulator_update = AccumulatorUpdateData::try_from_slice(&data.0)
+                .map_err(|_| Error::InvalidUpdateData(InvalidUpdateData {}))?;
+            match accumulator_update.proof {
+                Proof::WormholeMerkle { updates, .. } => num_updates += updates.len() as u64,
+            }
+        }
+        Ok(
+            self.single_update_fee_in_wei.get() * U256::from(num_updates)
+                + self.transaction_fee_in_wei.get(),
+        )
+    }
+
+    fn check_fee(&self, update_data: &[AbiBytes]) -> Result<(), Vec<u8>> {
+        if msg::value() < self.update_fee(update_data)? {
+            return Err(Error::InsufficientFee(InsufficientFee {}).into());
+        }
+        Ok(())
+    }
+
+    /// Verifies an accumulator update and returns the price feeds it contains.
+    fn parse_update(&mut self, data: &[u8]) -> Result<Vec<ParsedPriceFeed>, Vec<u8>> {
+        let invalid_update_data = || Error::InvalidUpdateData(InvalidUpdateData {});
+        let accumulator_update =
+            AccumulatorUpdateData::try_from_slice(data).map_err(|_| invalid_update_data())?;
+        match accumulator_update.proof {
+            Proof::WormholeMerkle { vaa, updates } => {
+                let vm = self.verify_vaa(Vec::from(vaa))?;
+                let root = match WormholeMessage::try_from_bytes(vm.payload)
+                    .map_err(|_| invalid_update_data())?
+                    .payload
+                {
+                    WormholePayload::Merkle(merkle_root) => {
+                        MerkleRoot::<Keccak160>::new(merkle_root.root)
+                    }
+                };
+
+                let mut price_feeds = Vec::with_capacity(updates.len());
+                for update in updates {
+                    let message = Vec::from(update.message);
+                    if !root.check(update.proof, &message) {
+                        return Err(invalid_update_data().into());
+                    }
+                    // Only price feed messages are stored by this contract.
+                    if let Message::PriceFeedMessage(message) =
+                        from_slice::<BigEndian, Message>(&message)
+                            .map_err(|_| invalid_update_data())?
+                    {
+                        let publish_time = u64::try_from(message.publish_time)
+                            .map_err(|_| invalid_update_data())?;
+                        let prev_publish_time = u64::try_from(message.prev_publish_time)
+                            .map_err(|_| invalid_update_data())?;
+                        let price = |price, conf| Price {
+                            price,
+                            conf,
+                            expo: message.exponent,
+                            publish_time: U256::from(publish_time),
+                        };
+                        price_feeds.push((
+                            PriceFeed {
+                                id: B256::from(message.feed_id),
+                                price: price(message.price, message.conf),
+                                ema_price: price(message.ema_price, message.ema_conf),
+                            },
+                            prev_publish_time,
+                        ));
+                    }
+                }
+                Ok(price_feeds)
+            }
+        }
+    }
+
+    /// Verifies `vaa` with the Wormhole core contract and checks that it is emitted by a valid
+    /// data source.
+    fn verify_vaa(&mut self, vaa: Vec<u8>) -> Result<VM, Vec<u8>> {
+        let result = self.parse_and_verify_vm(vaa)?;
+        if !result.valid {
+            return Err(Error::InvalidWormholeVaa(InvalidWormholeVaa {}).into());
+        }
+        let key = data_source_key(result.vm.emitterChainId, result.vm.emitterAddress);
+        if !self.is_valid_data_source.get(key) {
+            return Err(Error::InvalidUpdateDataSource(InvalidUpdateDataSource {}).into());
+        }
+        Ok(result.vm)
+    }
+
+    fn update_price_feed_if_new(&mut self, price_feed: PriceFeed) {
+        let stored_publish_time = self
+            .price_feeds
+            .getter(price_feed.id)
+            .to_price_feed()
+            .price
+            .publish_time;
+        if stored_publish_time < price_feed.price.publish_time {
+            self.price_feeds.setter(price_feed.id).set(price_feed);
+            evm::log(PriceFeedUpdate {
+                id: price_feed.id,
+                publishTime: price_feed.price.publish_time.to(),
+                price: price_feed.price.price,
+                conf: price_feed.price.conf,
+            });
+        }
+    }
+
+    fn parse_price_feed_updates_internal(
+        &mut self,
+        update_data: Vec<AbiBytes>,
+        price_ids: Vec<B256>,
+        min_publish_time: u64,
+        max_publish_time: u64,
+        unique: bool,
+    ) -> Result<Vec<PriceFeedTuple>, Vec<u8>> {
+        self.check_fee(&update_data)?;
+        let mut price_feeds = Vec::new();
+        for data in &update_data {
+            price_feeds.extend(self.parse_update(&data.0)?);
+        }
+
+        price_ids
+            .iter()
+            .map(|id| {
+                price_feeds
+                    .iter()
+                    .find(|(price_feed, prev_publish_time)| {
+                        let publish_time = price_feed.price.publish_time;
+                        price_feed.id == *id
+                            && publish_time >= U256::from(min_publish_time)
+                            && publish_time <= U256::from(max_publish_time)
+                            && (!unique || *prev_publish_time < min_publish_time)
+                    })
+                    .map(|(price_feed, _)| price_feed_tuple(price_feed))
+                    .ok_or_else(|| {
+                        Error::PriceFeedNotFoundWithinRange(PriceFeedNotFoundWithinRange {}).into()
+                    })
+            })
+            .collect()
+    }
+}
+
+/// The key of a data source in `is_valid_data_source`, i.e.
+/// `keccak256(abi.encodePacked(emitter_chain_id, emitter_address))`.
+fn data_source_key(emitter_chain_id: u16, emitter_address: B256) -> B256 {
+    keccak(
+        [
+            &emitter_chain_id.to_be_bytes()[..],
+            emitter_address.as_slice(),
+        ]
+        .concat(),
+    )
+}
+
+/// Checks that `price` was published at most `age` seconds away from the current block time.
+fn check_age(price: &Price, age: U256) -> Result<(), Vec<u8>> {
+    let current_time = U256::from(block::timestamp());
+    let publish_time = price.publish_time;
+    let diff = if current_time > publish_time {
+        current_time - publish_time
+    } else {
+        publish_time - current_time
+    };
+    if diff > age {
+        return Err(Error::StalePrice(StalePrice {}).into());
+    }
+    Ok(())
+}
+
+fn price_tuple(price: &Price) -> PriceTuple {
+    (price.price, price.conf, price.expo, price.publish_time)
+}
+
+fn price_feed_tuple(price_feed: &PriceFeed) -> PriceFeedTuple {
+    (
+        price_feed.id,
+        price_tuple(&price_feed.price),
+        price_tuple(&price_feed.ema_price),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{data_source_key, PythReceiver},
+        alloc::vec,
+        alloc::vec::Vec,
+        alloy_primitives::{Address, B256, U256},
+        byteorder::BigEndian,
+        pyth_stylus::pyth::errors::{
+            Error, InsufficientFee, InvalidGovernanceDataSource, InvalidGovernanceTarget,
+            InvalidUpdateData, InvalidUpdateDataSource, NoFreshUpdate, OldGovernanceMessage,
+            PriceFeedNotFoundWithinRange,
+        },
+        pythnet_sdk::{
+            messages::{Message, PriceFeedMessage},
+            test_utils::{
+                create_accumulator_message, create_dummy_feed_id, create_dummy_price_feed_message,
+                create_dummy_price_feed_message_with_feed_id, create_vaa_from_payload, DataSource,
+                DEFAULT_CHAIN_ID, DEFAULT_DATA_SOURCE, DEFAULT_GOVERNANCE_SOURCE,
+                SECONDARY_GOVERNANCE_SOURCE, WRONG_CHAIN_ID, WRONG_SOURCE,
+            },
+            wire::{
+                to_vec,
+                v1::{AccumulatorUpdateData, Proof},
+                PrefixedVec,
+            },
+        },
+        stylus_sdk::abi::Bytes,
+    };
+
+    const SINGLE_UPDATE_FEE: u64 = 1000;
+    const VALID_TIME_PERIOD: u64 = 60;
+    const GOVERNANCE_INITIAL_SEQUENCE: u64 = 1;
+
+    fn initialize_with_fee(contract: &mut PythReceiver, single_update_fee: u64) {
+        contract
+            .init(
+                Address::repeat_byte(1),
+                U256::from(single_update_fee),
+                U256::from(VALID_TIME_PERIOD),
+                vec![DEFAULT_DATA_SOURCE.chain.into()],
+                vec![B256::from(DEFAULT_DATA_SOURCE.address.0)],
+                DEFAULT_GOVERNANCE_SOURCE.chain.into(),
+                B256::from(DEFAULT_GOVERNANCE_SOURCE.address.0),
+                GOVERNANCE_INITIAL_SEQUENCE,
+            )
+            .unwrap();
+    }
+
+    fn initialize(contract: &mut PythReceiver) {
+        initialize_with_fee(contract, SINGLE_UPDATE_FEE);
+    }
+
+    fn error(error: Error) -> Vec<u8> {
+        error.into()
+    }
+
+    fn price_feed_message(message: &Message) -> &PriceFeedMessage {
+        match message {
+            Message::PriceFeedMessage(message) => message,
+            _ => panic!("not a price feed message"),
+        }
+    }
+
+    fn id(message: &Message) -> B256 {
+        B256::from(price_feed_message(message).feed_id)
+    }
+
+    /// An update of all of `feeds`, emitted by `DEFAULT_DATA_SOURCE`.
+    fn update_data(feeds: &[&Message]) -> Vec<Bytes> {
+        vec![Bytes::from(create_accumulator_message(
+            feeds, feeds, false, false, None,
+        ))]
+    }
+
+    fn assert_stored(contract: &PythReceiver, message: &Message) {
+        let message = price_feed_message(message);
+        let publish_time = U256::from(message.publish_time);
+        assert_eq!(
+            contract.get_price_unsafe(B256::from(message.feed_id)),
+            Ok((message.price, message.conf, message.exponent, publish_time))
+        );
+        assert_eq!(
+            contract.get_ema_price_unsafe(B256::from(message.feed_id)),
+            Ok((
+                message.ema_price,
+                message.ema_conf,
+                message.exponent,
+                publish_time
+            ))
+        );
+    }
+
+    /// A governance VAA emitted by `source` with the given instruction.
+    fn governance_vaa(
+        source: &DataSource,
+        sequence: u64,
+        action: u8,
+        target_chain_id: u16,
+        payload: &[u8],
+    ) -> Bytes {
+        let instruction = [
+            &b"PTGM"[..],
+            &[1, action],
+            &target_chain_id.to_be_bytes(),
+            payload,
+        ]
+        .concat();
+        let vaa = create_vaa_from_payload(&instruction, source.address, source.chain, sequence);
+        Bytes::from(serde_wormhole::to_vec(&vaa).unwrap())
+    }
+
+    #[motsu::test]
+    fn can_initialize(contract: PythReceiver) {
+        initialize(contract);
+        assert_eq!(
+            contract.get_valid_time_period(),
+            U256::from(VALID_TIME_PERIOD)
+        );
+        let chain_id = DEFAULT_DATA_SOURCE.chain.into();
+        let emitter_address = B256::from(DEFAULT_DATA_SOURCE.address.0);
+        assert!(contract
+            .is_valid_data_source
+            .get(data_source_key(chain_id, emitter_address)));
+        assert!(!contract
+            .is_valid_data_source
+            .get(data_source_key(chain_id + 1, emitter_address)));
+        assert_eq!(
+            contract.valid_data_sources(),
+            vec![(chain_id, emitter_address)]
+        );
+        assert_eq!(
+            contract.governance_data_source(),
+            (
+                DEFAULT_GOVERNANCE_SOURCE.chain.into(),
+                B256::from(DEFAULT_GOVERNANCE_SOURCE.address.0)
+            )
+        );
+        assert_eq!(
+            contract.last_executed_governance_sequence(),
+            GOVERNANCE_INITIAL_SEQUENCE
+        );
+    }
+
+    #[motsu::test]
+    fn cannot_initialize_twice(contract: PythReceiver) {
+        initialize(contract);
+        let result = contract.init(
+            Address::repeat_byte(1),
+            U256::from(SINGLE_UPDATE_FEE),
+            U256::from(VALID_TIME_PERIOD),
+            vec![],
+            vec![],
+            DEFAULT_GOVERNANCE_SOURCE.chain.into(),
+            B256::from(DEFAULT_GOVERNANCE_SOURCE.address.0),
+            GOVERNANCE_INITIAL_SEQUENCE,
+        );
+        assert!(result.is_err());
+    }
+
+    #[motsu::test]
+    fn cannot_initialize_with_mismatched_data_sources(contract: PythReceiver) {
+        let result = contract.init(
+            Address::repeat_byte(1),
+            U256::from(SINGLE_UPDATE_FEE),
+            U256::from(VALID_TIME_PERIOD),
+            vec![1, 2],
+            vec![B256::repeat_byte(2)],
+            DEFAULT_GOVERNANCE_SOURCE.chain.into(),
+            B256::from(DEFAULT_GOVERNANCE_SOURCE.address.0),
+            GOVERNANCE_INITIAL_SEQUENCE,
+        );
+        assert!(result.is_err());
+    }
+
+    #[motsu::test]
+    fn can_get_update_fee(contract: PythReceiver) {
+        initialize(contract);
+        let feed1 = create_dummy_price_feed_message(100);
+        let feed2 = create_dummy_price_feed_message(200);
+        let data1 = create_accumulator_message(&[&feed1, &feed2], &[&feed1], false, false, None);
+        let data2 =
+            create_accumulator_message(&[&feed1, &feed2], &[&feed1, &feed2], false, false, None);
+        let fee = contract
+            .get_update_fee(vec![Bytes::from(data1), Bytes::from(data2)])
+            .unwrap();
+        assert_eq!(fee, U256::from(3 * SINGLE_UPDATE_FEE));
+    }
+
+    #[motsu::test]
+    fn get_update_fee_rejects_invalid_data(contract: PythReceiver) {
+        initialize(contract);
+        assert!(contract
+            .get_update_fee(vec![Bytes::from(vec![1, 2, 3])])
+            .is_err());
+    }
+
+    #[motsu::test]
+    fn query_missing_price_feed_fails(contract: PythReceiver) {
+        initialize(contract);
+        let id = B256::repeat_byte(3);
+        assert!(!contract.price_feed_exists(id));
+        assert!(contract.query_price_feed(id).is_err());
+        assert!(contract.get_price_unsafe(id).is_err());
+    }
+
+    #[motsu::test]
+    fn can_update_price_feeds(contract: PythReceiver) {
+        initialize_with_fee(contract, 0);
+        let feed1 = create_dummy_price_feed_message(100);
+        let feed2 = create_dummy_price_feed_message(200);
+        contract
+            .update_price_feeds(update_data(&[&feed1, &feed2]))
+            .unwrap();
+        assert!(contract.price_feed_exists(id(&feed1)));
+        assert_stored(contract, &feed1);
+        assert_stored(contract, &feed2);
+
+        // Older updates are ignored.
+        let newer_feed1 =
+            create_dummy_price_feed_message_with_feed_id(150, create_dummy_feed_id(100));
+        let older_feed2 =
+            create_dummy_price_feed_message_with_feed_id(50, create_dummy_feed_id(200));
+        contract
+            .update_price_feeds(update_data(&[&newer_feed1, &older_feed2]))
+            .unwrap();
+        assert_stored(contract, &newer_feed1);
+        assert_stored(contract, &feed2);
+    }
+
+    #[motsu::test]
+    fn can_parse_price_feed_updates(contract: PythReceiver) {
+        initialize_with_fee(contract, 0);
+        let feed1 = create_dummy_price_feed_message(100);
+        let feed2 = create_dummy_price_feed_message(200);
+        let data = update_data(&[&feed1, &feed2]);
+
+        let price_feeds = contract
+            .parse_price_feed_updates(data.clone(), vec![id(&feed2), id(&feed1)], 0, 200)
+            .unwrap();
+        assert_eq!(price_feeds.len(), 2);
+        assert_eq!(price_feeds[0].0, id(&feed2));
+        assert_eq!(price_feeds[0].1 .0, 200);
+        assert_eq!(price_feeds[1].0, id(&feed1));
+        assert_eq!(price_feeds[1].1 .0, 100);
+        // Parsed price feeds aren't stored.
+        assert!(!contract.price_feed_exists(id(&feed1)));
+
+        assert_eq!(
+            contract.parse_price_feed_updates(data, vec![id(&feed1)], 101, 200),
+            Err(error(Error::PriceFeedNotFoundWithinRange(
+                PriceFeedNotFoundWithinRange {}
+            )))
+        );
+    }
+
+    #[motsu::test]
+    fn can_update_price_feeds_if_necessary(contract: PythReceiver) {
+        initialize_with_fee(contract, 0);
+        let feed = create_dummy_price_feed_message(100);
+        contract
+            .update_price_feeds_if_necessary(update_data(&[&feed]), vec![id(&feed)], vec![100])
+            .unwrap();
+        assert_stored(contract, &feed);
+
+        let newer_feed =
+            create_dummy_price_feed_message_with_feed_id(150, create_dummy_feed_id(100));
+        assert_eq!(
+            contract.update_price_feeds_if_necessary(
+                update_data(&[&newer_feed]),
+                vec![id(&feed)],
+                vec![100]
+            ),
+            Err(error(Error::NoFreshUpdate(NoFreshUpdate {})))
+        );
+        assert_stored(contract, &feed);
+
+        contract
+            .update_price_feeds_if_necessary(
+                update_data(&[&newer_feed]),
+                vec![id(&feed)],
+                vec![101],
+            )
+            .unwrap();
+        assert_stored(contract, &newer_feed);
+    }
+
+    #[motsu::test]
+    fn rejects_bad_merkle_proof(contract: PythReceiver) {
+        initialize_with_fee(contract, 0);
+        let feed1 = create_dummy_price_feed_message(100);
+        let feed2 = create_dummy_price_feed_message(200);
+        let data = create_accumulator_message(&[&feed1, &feed2], &[&feed1], false, false, None);
+
+        // Replace the message of the update, keeping the proof of `feed1`.
+        let mut accumulator_update = AccumulatorUpdateData::try_from_slice(&data).unwrap();
+        let Proof::WormholeMerkle { updates, .. } = &mut accumulator_update.proof;
+        updates[0].message = PrefixedVec::from(to_vec::<_, BigEndian>(&feed2).unwrap());
+        let data = to_vec::<_, BigEndian>(&accumulator_update).unwrap();
+
+        assert_eq!(
+            contract.update_price_feeds(vec![Bytes::from(data)]),
+            Err(error(Error::InvalidUpdateData(InvalidUpdateData {})))
+        );
+        assert!(!contract.price_feed_exists(id(&feed2)));
+    }
+
+    #[motsu::test]
+    fn rejects_untrusted_data_source(contract: PythReceiver) {
+        initialize_with_fee(contract, 0);
+        let feed = create_dummy_price_feed_message(100);
+        let data = create_accumulator_message(&[&feed], &[&feed], false, false, Some(WRONG_SOURCE));
+        assert_eq!(
+            contract.update_price_feeds(vec![Bytes::from(data)]),
+            Err(error(Error::InvalidUpdateDataSource(
+                InvalidUpdateDataSource {}
+            )))
+        );
+        assert!(!contract.price_feed_exists(id(&feed)));
+    }
+
+    #[motsu::test]
+    fn requires_update_fee(contract: PythReceiver) {
+        initialize(contract);
+        let feed = create_dummy_price_feed_message(100);
+        let insufficient_fee = Err(error(Error::InsufficientFee(InsufficientFee {})));
+        assert_eq!(
+            contract.update_price_feeds(update_data(&[&feed])),
+            insufficient_fee
+        );
+        assert_eq!(
+            contract
+                .parse_price_feed_updates(update_data(&[&feed]), vec![id(&feed)], 0, 100)
+                .map(|_| ()),
+            insufficient_fee
+        );
+        assert!(!contract.price_feed_exists(id(&feed)));
+    }
+
+    #[motsu::test]
+    fn can_execute_governance_instructions(contract: PythReceiver) {
+        initialize(contract);
+        let chain_id = u16::from(DEFAULT_CHAIN_ID);
+
+        // SetFee: 5 * 10^3.
+        let payload = [5u64.to_be_bytes(), 3u64.to_be_bytes()].concat();
+        contract
+            .execute_governance_instruction(governance_vaa(
+                &DEFAULT_GOVERNANCE_SOURCE,
+                2,
+                3,
+                chain_id,
+                &payload,
+            ))
+            .unwrap();
+        assert_eq!(contract.single_update_fee_in_wei(), U256::from(5000));
+        assert_eq!(contract.last_executed_governance_sequence(), 2);
+
+        // SetValidPeriod, for all chains.
+        contract
+            .execute_governance_instruction(governance_vaa(
+                &DEFAULT_GOVERNANCE_SOURCE,
+                3,
+                4,
+                0,
+                &30u64.to_be_bytes(),
+            ))
+            .unwrap();
+        assert_eq!(contract.get_valid_time_period(), U256::from(30));
+
+        // SetTransactionFee: 7 * 10^0.
+        let payload = [7u64.to_be_bytes(), 0u64.to_be_bytes()].concat();
+        contract
+            .execute_governance_instruction(governance_vaa(
+                &DEFAULT_GOVERNANCE_SOURCE,
+                4,
+                8,
+                chain_id,
+                &payload,
+            ))
+            .unwrap();
+        assert_eq!(contract.transaction_fee_in_wei(), U256::from(7));
+
+        // SetWormholeAddress.
+        contract
+            .execute_governance_instruction(governance_vaa(
+                &DEFAULT_GOVERNANCE_SOURCE,
+                5,
+                6,
+                chain_id,
+                &[9; 20],
+            ))
+            .unwrap();
+        assert_eq!(contract.wormhole(), Address::repeat_byte(9));
+    }
+
+    #[motsu::test]
+    fn can_set_data_sources(contract: PythReceiver) {
+        initialize_with_fee(contract, 0);
+        let new_data_source = (
+            u16::from(WRONG_SOURCE.chain),
+            B256::from(WRONG_SOURCE.address.0),
+        );
+        let payload = [
+            &[1u8][..],
+            &new_data_source.0.to_be_bytes(),
+            new_data_source.1.as_slice(),
+        ]
+        .concat();
+        contract
+            .execute_governance_instruction(governance_vaa(
+                &DEFAULT_GOVERNANCE_SOURCE,
+                2,
+                2,
+                0,
+                &payload,
+            ))
+            .unwrap();
+        assert_eq!(contract.valid_data_sources(), vec![new_data_source]);
+
+        let feed = create_dummy_price_feed_message(100);
+        assert_eq!(
+            contract.update_price_feeds(update_data(&[&feed])),
+            Err(error(Error::InvalidUpdateDataSource(
+                InvalidUpdateDataSource {}
+            )))
+        );
+        let data = create_accumulator_message(&[&feed], &[&feed], false, false, Some(WRONG_SOURCE));
+        contract
+            .update_price_feeds(vec![Bytes::from(data)])
+            .unwrap();
+        assert_stored(contract, &feed);
+    }
+
+    #[motsu::test]
+    fn can_transfer_governance_data_source(contract: PythReceiver) {
+        initialize(contract);
+        let claim_vaa = governance_vaa(&SECONDARY_GOVERNANCE_SOURCE, 10, 5, 0, &1u32.to_be_bytes());
+        contract
+            .execute_governance_instruction(governance_vaa(
+                &DEFAULT_GOVERNANCE_SOURCE,
+                2,
+                1,
+                0,
+                &claim_vaa.0,
+            ))
+            .unwrap();
+        assert_eq!(
+            contract.governance_data_source(),
+            (
+                SECONDARY_GOVERNANCE_SOURCE.chain.into(),
+                B256::from(SECONDARY_GOVERNANCE_SOURCE.address.0)
+            )
+        );
+        assert_eq!(contract.governance_data_source_index(), 1);
+        assert_eq!(contract.last_executed_governance_sequence(), 10);
+
+        // The previous governance data source can't execute instructions anymore.
+        assert_eq!(
+            contract.execute_governance_instruction(governance_vaa(
+                &DEFAULT_GOVERNANCE_SOURCE,
+                11,
+                4,
+                0,
+                &30u64.to_be_bytes(),
+            )),
+            Err(error(Error::InvalidGovernanceDataSource(
+                InvalidGovernanceDataSource {}
+            )))
+        );
+        contract
+            .execute_governance_instruction(governance_vaa(
+                &SECONDARY_GOVERNANCE_SOURCE,
+                11,
+                4,
+                0,
+                &30u64.to_be_bytes(),
+            ))
+            .unwrap();
+        assert_eq!(contract.get_valid_time_period(), U256::from(30));
+    }
+
+    #[motsu::test]
+    fn rejects_invalid_governance_instructions(contract: PythReceiver) {
+        initialize(contract);
+        let set_valid_period = |source: &DataSource, sequence: u64, target_chain_id: u16| {
+            governance_vaa(source, sequence, 4, target_chain_id, &30u64.to_be_bytes())
+        };
+
+        assert_eq!(
+            contract.execute_governance_instruction(set_valid_period(&WRONG_SOURCE, 2, 0)),
+            Err(error(Error::InvalidGovernanceDataSource(
+                InvalidGovernanceDataSource {}
+            )))
+        );
+        assert_eq!(
+            contract.execute_governance_instruction(set_valid_period(
+                &DEFAULT_GOVERNANCE_SOURCE,
+                GOVERNANCE_INITIAL_SEQUENCE,
+                0
+            )),
+            Err(error(Error::OldGovernanceMessage(OldGovernanceMessage {})))
+        );
+        assert_eq!(
+            contract.execute_governance_instruction(set_valid_period(
+                &DEFAULT_GOVERNANCE_SOURCE,
+                2,
+                WRONG_CHAIN_ID.into()
+            )),
+            Err(error(Error::InvalidGovernanceTarget(
+                InvalidGovernanceTarget {}
+            )))
+        );
+        // Upgrades must target a chain.
+        assert_eq!(
+            contract.execute_governance_instruction(governance_vaa(
+                &DEFAULT_GOVERNANCE_SOURCE,
+                3,
+                0,
+                0,
+                &[9; 20],
+            )),
+            Err(error(Error::InvalidGovernanceTarget(
+                InvalidGovernanceTarget {}
+            )))
+        );
+        assert_eq!(
+            contract.get_valid_time_period(),
+            U256::from(VALID_TIME_PERIOD)
+        );
+    }
+}
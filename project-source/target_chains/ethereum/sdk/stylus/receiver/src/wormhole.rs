@@ -0,0 +1,77 @@
+//! Calls to the Wormhole core contract.
+//!
+//! Contracts can't be called in unit tests, so the core contract is replaced there by a mock
+//! which parses VAAs without verifying their signatures.
+
+#[cfg(not(test))]
+mod core {
+    use {
+        crate::{chainIdCall, parseAndVerifyVMCall, parseAndVerifyVMReturn, PythReceiver},
+        alloc::vec::Vec,
+        alloy_primitives::Bytes,
+        pyth_stylus::utils::call_helper,
+    };
+
+    impl PythReceiver {
+        /// Parses `encoded_vm` and verifies its signatures with the Wormhole core contract.
+        pub(crate) fn parse_and_verify_vm(
+            &mut self,
+            encoded_vm: Vec<u8>,
+        ) -> Result<parseAndVerifyVMReturn, Vec<u8>> {
+            let wormhole = self.wormhole.get();
+            call_helper::<parseAndVerifyVMCall>(self, wormhole, (Bytes::from(encoded_vm),))
+        }
+
+        /// Returns the Wormhole chain ID of this chain, as configured in the Wormhole core
+        /// contract.
+        pub(crate) fn wormhole_chain_id(&mut self) -> Result<u16, Vec<u8>> {
+            let wormhole = self.wormhole.get();
+            Ok(call_helper::<chainIdCall>(self, wormhole, ())?._0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod mock {
+    use {
+        crate::{parseAndVerifyVMReturn, PythReceiver, VM},
+        alloc::{string::String, vec::Vec},
+        alloy_primitives::{Bytes, B256},
+        pythnet_sdk::test_utils::DEFAULT_CHAIN_ID,
+        serde_wormhole::RawMessage,
+        wormhole_sdk::Vaa,
+    };
+
+    impl PythReceiver {
+        /// Returns the parsed VAA of `encoded_vm` as valid, without verifying its signatures.
+        pub(crate) fn parse_and_verify_vm(
+            &mut self,
+            encoded_vm: Vec<u8>,
+        ) -> Result<parseAndVerifyVMReturn, Vec<u8>> {
+            let vaa: Vaa<&RawMessage> =
+                serde_wormhole::from_slice(&encoded_vm).map_err(|_| b"invalid VM".to_vec())?;
+            Ok(parseAndVerifyVMReturn {
+                vm: VM {
+                    version: vaa.version,
+                    timestamp: vaa.timestamp,
+                    nonce: vaa.nonce,
+                    emitterChainId: vaa.emitter_chain.into(),
+                    emitterAddress: B256::from(vaa.emitter_address.0),
+                    sequence: vaa.sequence,
+                    consistencyLevel: vaa.consistency_level,
+                    payload: Bytes::from(vaa.payload.to_vec()),
+                    guardianSetIndex: vaa.guardian_set_index,
+                    signatures: Vec::new(),
+                    hash: B256::ZERO,
+                },
+                valid: true,
+                reason: String::new(),
+            })
+        }
+
+        /// Returns `DEFAULT_CHAIN_ID`, the chain of the VAAs of `pythnet_sdk::test_utils`.
+        pub(crate) fn wormhole_chain_id(&mut self) -> Result<u16, Vec<u8>> {
+            Ok(DEFAULT_CHAIN_ID.into())
+        }
+    }
+}
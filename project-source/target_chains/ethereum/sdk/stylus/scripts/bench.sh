@@ -60,6 +60,7 @@ for CRATE_NAME in $(get_example_crate_names)
 do
   opt_wasm "$CRATE_NAME"
 done
+opt_wasm "pyth-receiver-stylus"
 
 export RPC_URL=http://localhost:8547
 
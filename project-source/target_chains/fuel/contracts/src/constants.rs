@@ -23,6 +23,9 @@ pub const WORMHOLE_GOVERNANCE_DATA_SOURCE: DataSource = DataSource {
 
 pub const DUMMY_CHAIN_ID: u16 = 1;
 
+// the contract converts publish times from UNIX to TAI64 by adding this offset
+pub const TAI64_DIFFERENCE: u64 = 4611686018427387904;
+
 pub const BETA_5_URL: &str = "beta-5.fuel.network";
 pub const BETA_5_PYTH_CONTRACT_ID: &str =
     "0xe69daeb9fcf4c536c0fe402403b4b9e9822cc8b1f296e5d754be12cc384554c5";
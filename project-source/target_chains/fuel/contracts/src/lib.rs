@@ -1,2 +1,3 @@
 pub mod constants;
 pub mod pyth_utils;
+pub mod test_vaas;
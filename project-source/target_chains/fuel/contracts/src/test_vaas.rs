@@ -0,0 +1,148 @@
+//! Generation of price updates, governance VAAs and guardian set upgrade VAAs signed by test
+//! guardian sets, so that tests can run offline with arbitrary data instead of relying on
+//! updates fetched from Hermes and VAAs signed by the mainnet guardians.
+//!
+//! The data is generated by the shared `pyth-test-vectors` crate, this module only converts
+//! from and to the Fuel types.
+use crate::{constants::WORMHOLE_GOVERNANCE_DATA_SOURCE, pyth_utils::DataSource};
+use fuels::types::{Bits256, Bytes};
+use pyth_test_vectors::{governance::GuardianSetUpgrade, price_updates, GuardianSet};
+use pythnet_sdk::{messages::Message, test_utils::DataSource as WormholeDataSource};
+use wormhole_sdk::{Address, Chain};
+
+/// A set of guardians whose secret keys are known, used to sign VAAs.
+pub struct TestGuardianSet {
+    guardian_set: GuardianSet,
+}
+
+impl TestGuardianSet {
+    /// The guardians of `pythnet_sdk::test_utils::dummy_guardians`, with the index `set_index`.
+    pub fn dummy(set_index: u32) -> Self {
+        TestGuardianSet {
+            guardian_set: GuardianSet::test(set_index),
+        }
+    }
+
+    /// A set of `num_guardians` guardians whose keys are derived from `seed`.
+    /// Different seeds give disjoint guardian sets, and seed 0 gives the dummy guardians.
+    pub fn generate(set_index: u32, num_guardians: u8, seed: u8) -> Self {
+        TestGuardianSet {
+            guardian_set: GuardianSet::generate(set_index, num_guardians, seed),
+        }
+    }
+
+    pub fn set_index(&self) -> u32 {
+        self.guardian_set.set_index
+    }
+
+    /// The addresses of the guardians as expected by the contract, i.e. left-padded with zeros.
+    pub fn addresses(&self) -> Vec<Bits256> {
+        self.guardian_set
+            .addresses()
+            .iter()
+            .map(|address| {
+                let mut full_address = [0u8; 32];
+                full_address[12..].copy_from_slice(address);
+                Bits256(full_address)
+            })
+            .collect()
+    }
+
+    /// Creates a VAA containing `payload` emitted by `emitter`, signed by all the guardians.
+    pub fn sign_vaa(&self, payload: &[u8], emitter: &DataSource, sequence: u64) -> Bytes {
+        Bytes(
+            self.guardian_set
+                .sign_vaa(&wormhole_data_source(emitter), sequence, payload),
+        )
+    }
+}
+
+fn wormhole_data_source(data_source: &DataSource) -> WormholeDataSource {
+    WormholeDataSource {
+        address: Address(data_source.emitter_address.0),
+        chain: Chain::from(data_source.chain_id),
+    }
+}
+
+/// Creates a price feed message whose EMA price is equal to its price.
+/// The previous publish time is one second before `publish_time`.
+pub fn create_price_feed_message(
+    feed_id: Bits256,
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+) -> Message {
+    price_updates::create_price_feed_message(feed_id.0, price, conf, exponent, publish_time)
+}
+
+/// Creates a TWAP message. The previous publish time is one second before `publish_time`.
+pub fn create_twap_message(
+    feed_id: Bits256,
+    cumulative_price: i128,
+    cumulative_conf: u128,
+    num_down_slots: u64,
+    exponent: i32,
+    publish_time: i64,
+    publish_slot: u64,
+) -> Message {
+    price_updates::create_twap_message(
+        feed_id.0,
+        cumulative_price,
+        cumulative_conf,
+        num_down_slots,
+        exponent,
+        publish_time,
+        publish_slot,
+    )
+}
+
+/// Creates an accumulator update of `messages`, emitted by `emitter` and signed by all the
+/// guardians of `guardian_set`.
+///
+/// If `corrupt_proofs` is set, the signed merkle root doesn't match the messages, so that the
+/// merkle proofs of all the messages are invalid.
+pub fn create_accumulator_update(
+    guardian_set: &TestGuardianSet,
+    emitter: &DataSource,
+    messages: &[Message],
+    corrupt_proofs: bool,
+) -> Bytes {
+    let signers: Vec<usize> = (0..guardian_set.guardian_set.secrets.len()).collect();
+    Bytes(price_updates::create_accumulator_update(
+        &guardian_set.guardian_set,
+        &signers,
+        &wormhole_data_source(emitter),
+        messages,
+        corrupt_proofs,
+        false,
+    ))
+}
+
+/// Creates a VAA containing a governance instruction `payload`, emitted by
+/// `governance_data_source` and signed by `guardian_set`.
+pub fn create_governance_vaa(
+    guardian_set: &TestGuardianSet,
+    governance_data_source: &DataSource,
+    sequence: u64,
+    payload: &[u8],
+) -> Bytes {
+    guardian_set.sign_vaa(payload, governance_data_source, sequence)
+}
+
+/// Creates a VAA upgrading the guardian set from `current_guardian_set` to `new_guardian_set`,
+/// emitted by `WORMHOLE_GOVERNANCE_DATA_SOURCE` and signed by `current_guardian_set`.
+pub fn create_guardian_set_upgrade_vaa(
+    current_guardian_set: &TestGuardianSet,
+    new_guardian_set: &TestGuardianSet,
+    sequence: u64,
+) -> Bytes {
+    let payload = GuardianSetUpgrade {
+        // Chain ID 0 targets all chains.
+        chain_id: 0,
+        new_set_index: new_guardian_set.set_index(),
+        guardians: new_guardian_set.guardian_set.addresses(),
+    }
+    .serialize();
+    current_guardian_set.sign_vaa(&payload, &WORMHOLE_GOVERNANCE_DATA_SOURCE, sequence)
+}
@@ -2,7 +2,7 @@ use crate::utils::interface::{
     pyth_core::{update_fee, update_price_feeds},
     pyth_init::constructor,
 };
-use crate::utils::setup::setup_environment;
+use crate::utils::setup::{setup_environment, setup_environment_with_guardian_set};
 use pyth_sdk::{
     constants::{
         DEFAULT_SINGLE_UPDATE_FEE, DEFAULT_VALID_TIME_PERIOD, DUMMY_CHAIN_ID,
@@ -126,3 +126,86 @@ mod success {
         );
     }
 }
+
+mod failure {
+    use fuels::{
+        accounts::ViewOnlyAccount,
+        types::{
+            errors::{transaction::Reason, Error},
+            Bits256,
+        },
+    };
+    use pyth_sdk::test_vaas::{
+        create_accumulator_update, create_price_feed_message, TestGuardianSet,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn price_no_older_than_fails_for_outdated_price() {
+        let guardian_set = TestGuardianSet::dummy(0);
+        let (_oracle_contract_id, deployer) = setup_environment_with_guardian_set(&guardian_set)
+            .await
+            .unwrap();
+
+        // Publish a price older than the valid time period, relative to the latest block
+        let current_time = deployer
+            .wallet
+            .provider()
+            .unwrap()
+            .latest_block_time()
+            .await
+            .unwrap()
+            .unwrap()
+            .timestamp();
+        let publish_time = current_time - 2 * DEFAULT_VALID_TIME_PERIOD as i64;
+        let feed_id = Bits256([1; 32]);
+        let update_data = vec![create_accumulator_update(
+            &guardian_set,
+            &default_data_sources()[0],
+            &[create_price_feed_message(
+                feed_id,
+                100_000,
+                100,
+                -5,
+                publish_time,
+            )],
+            false,
+        )];
+
+        let fee = update_fee(&deployer.instance, update_data.clone())
+            .await
+            .value;
+
+        update_price_feeds(&deployer.instance, fee, update_data).await;
+
+        // Manually call the function to test the panic
+        let result = deployer
+            .instance
+            .methods()
+            .price_no_older_than(DEFAULT_VALID_TIME_PERIOD, feed_id)
+            .call()
+            .await;
+
+        let is_error = result.is_err();
+
+        assert!(is_error);
+
+        let error = result.unwrap_err();
+
+        if let Error::Transaction(Reason::Reverted {
+            reason,
+            revert_id: _,
+            receipts: _,
+        }) = error
+        {
+            assert_eq!(
+                reason, "OutdatedPrice",
+                "Expected OutdatedPrice error, got: {}",
+                reason
+            );
+        } else {
+            panic!("Expected RevertTransactionError, got another error type.");
+        }
+    }
+}
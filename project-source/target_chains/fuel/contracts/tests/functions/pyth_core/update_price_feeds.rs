@@ -1,20 +1,24 @@
 use crate::utils::interface::{
-    pyth_core::{update_fee, update_price_feeds},
+    pyth_core::{price_unsafe, update_fee, update_price_feeds},
     pyth_info::price_feed_exists,
     pyth_init::constructor,
 };
-use crate::utils::setup::setup_environment;
+use crate::utils::setup::{setup_environment, setup_environment_with_guardian_set};
 
+use fuels::types::Bits256;
 use pyth_sdk::{
     constants::{
         DEFAULT_SINGLE_UPDATE_FEE, DEFAULT_VALID_TIME_PERIOD, DUMMY_CHAIN_ID,
-        GOVERNANCE_DATA_SOURCE, WORMHOLE_GOVERNANCE_DATA_SOURCE,
+        GOVERNANCE_DATA_SOURCE, TAI64_DIFFERENCE, WORMHOLE_GOVERNANCE_DATA_SOURCE,
     },
     pyth_utils::{
         default_data_sources, default_price_feed_ids, guardian_set_upgrade_3_addresses,
         test_accumulator_update_data_bytes, test_batch_update_data_bytes,
         test_corrupted_proof_accumulator_update_data_bytes,
     },
+    test_vaas::{
+        create_accumulator_update, create_price_feed_message, create_twap_message, TestGuardianSet,
+    },
 };
 
 mod success {
@@ -125,6 +129,70 @@ mod success {
             (true, true)
         );
     }
+
+    #[tokio::test]
+    async fn updates_price_feeds_for_generated_accumulator_update() {
+        let guardian_set = TestGuardianSet::dummy(0);
+        let (_oracle_contract_id, deployer) = setup_environment_with_guardian_set(&guardian_set)
+            .await
+            .unwrap();
+
+        let feed_ids = [Bits256([1; 32]), Bits256([2; 32]), Bits256([3; 32])];
+        let messages = [
+            create_price_feed_message(feed_ids[0], 100_000, 100, -5, 1_700_000_000),
+            create_price_feed_message(feed_ids[1], 42, 1, 0, 1_700_000_001),
+            create_price_feed_message(feed_ids[2], 2_000_000_000, 3_000_000, -8, 1_700_000_002),
+        ];
+        let update_data = vec![create_accumulator_update(
+            &guardian_set,
+            &default_data_sources()[0],
+            &messages,
+            false,
+        )];
+
+        let fee = update_fee(&deployer.instance, update_data.clone())
+            .await
+            .value;
+        assert_eq!(fee, DEFAULT_SINGLE_UPDATE_FEE * 3);
+
+        update_price_feeds(&deployer.instance, fee, update_data).await;
+
+        let price = price_unsafe(&deployer.instance, feed_ids[0]).await.value;
+        assert_eq!(
+            (
+                price.price,
+                price.confidence,
+                price.exponent,
+                price.publish_time
+            ),
+            (100_000, 100, 5, 1_700_000_000 + TAI64_DIFFERENCE)
+        );
+        let price = price_unsafe(&deployer.instance, feed_ids[1]).await.value;
+        assert_eq!(
+            (
+                price.price,
+                price.confidence,
+                price.exponent,
+                price.publish_time
+            ),
+            (42, 1, 0, 1_700_000_001 + TAI64_DIFFERENCE)
+        );
+        let price = price_unsafe(&deployer.instance, feed_ids[2]).await.value;
+        assert_eq!(
+            (
+                price.price,
+                price.confidence,
+                price.exponent,
+                price.publish_time
+            ),
+            (
+                2_000_000_000,
+                3_000_000,
+                8,
+                1_700_000_002 + TAI64_DIFFERENCE
+            )
+        );
+    }
 }
 
 mod failure {
@@ -200,4 +268,119 @@ mod failure {
             panic!("Expected RevertTransactionError, got another error type.");
         }
     }
+
+    #[tokio::test]
+    async fn updates_price_feeds_for_generated_accumulator_update_fail_merkle_proof_verification() {
+        let guardian_set = TestGuardianSet::dummy(0);
+        let (_oracle_contract_id, deployer) = setup_environment_with_guardian_set(&guardian_set)
+            .await
+            .unwrap();
+
+        let update_data = vec![create_accumulator_update(
+            &guardian_set,
+            &default_data_sources()[0],
+            &[create_price_feed_message(
+                Bits256([1; 32]),
+                100_000,
+                100,
+                -5,
+                1_700_000_000,
+            )],
+            true,
+        )];
+
+        let fee = update_fee(&deployer.instance, update_data.clone())
+            .await
+            .value;
+
+        // Manually call the function to test the panic
+        let result = deployer
+            .instance
+            .methods()
+            .update_price_feeds(update_data)
+            .call_params(CallParameters::default().with_amount(fee))
+            .unwrap()
+            .call()
+            .await;
+
+        let is_error = result.is_err();
+
+        assert!(is_error);
+
+        let error = result.unwrap_err();
+
+        if let Error::Transaction(Reason::Reverted {
+            reason,
+            revert_id: _,
+            receipts: _,
+        }) = error
+        {
+            assert_eq!(
+                reason, "InvalidProof",
+                "Expected InvalidProof error, got: {}",
+                reason
+            );
+        } else {
+            panic!("Expected RevertTransactionError, got another error type.");
+        }
+    }
+
+    #[tokio::test]
+    async fn updates_price_feeds_for_twap_message_fail_message_type() {
+        let guardian_set = TestGuardianSet::dummy(0);
+        let (_oracle_contract_id, deployer) = setup_environment_with_guardian_set(&guardian_set)
+            .await
+            .unwrap();
+
+        // The contract only supports price feed messages
+        let update_data = vec![create_accumulator_update(
+            &guardian_set,
+            &default_data_sources()[0],
+            &[create_twap_message(
+                Bits256([1; 32]),
+                1_000_000,
+                1_000,
+                0,
+                -5,
+                1_700_000_000,
+                100,
+            )],
+            false,
+        )];
+
+        let fee = update_fee(&deployer.instance, update_data.clone())
+            .await
+            .value;
+
+        // Manually call the function to test the panic
+        let result = deployer
+            .instance
+            .methods()
+            .update_price_feeds(update_data)
+            .call_params(CallParameters::default().with_amount(fee))
+            .unwrap()
+            .call()
+            .await;
+
+        let is_error = result.is_err();
+
+        assert!(is_error);
+
+        let error = result.unwrap_err();
+
+        if let Error::Transaction(Reason::Reverted {
+            reason,
+            revert_id: _,
+            receipts: _,
+        }) = error
+        {
+            assert_eq!(
+                reason, "IncorrectMessageType",
+                "Expected IncorrectMessageType error, got: {}",
+                reason
+            );
+        } else {
+            panic!("Expected RevertTransactionError, got another error type.");
+        }
+    }
 }
@@ -2,7 +2,7 @@ use crate::utils::interface::{
     pyth_init::constructor,
     wormhole_guardians::{current_guardian_set_index, guardian_set, submit_new_guardian_set},
 };
-use crate::utils::setup::setup_environment;
+use crate::utils::setup::{setup_environment, setup_environment_with_guardian_set};
 
 use fuels::types::Bytes;
 use pyth_sdk::{
@@ -13,6 +13,7 @@ use pyth_sdk::{
     pyth_utils::{
         default_data_sources, guardian_set_upgrade_3_addresses, guardian_set_upgrade_4_addresses,
     },
+    test_vaas::{create_guardian_set_upgrade_vaa, TestGuardianSet},
 };
 
 mod success {
@@ -67,4 +68,32 @@ mod success {
             "Guardian set keys do not match expected values."
         );
     }
+
+    #[tokio::test]
+    async fn upgrade_guardian_set_with_generated_vaa() {
+        let current_test_guardian_set = TestGuardianSet::dummy(0);
+        let new_test_guardian_set = TestGuardianSet::generate(1, 5, 1);
+        let (_oracle_contract_id, deployer) =
+            setup_environment_with_guardian_set(&current_test_guardian_set)
+                .await
+                .unwrap();
+
+        submit_new_guardian_set(
+            &deployer.instance,
+            create_guardian_set_upgrade_vaa(&current_test_guardian_set, &new_test_guardian_set, 1),
+        )
+        .await;
+
+        let guardian_set_index = current_guardian_set_index(&deployer.instance).await.value;
+        assert_eq!(guardian_set_index, 1);
+
+        let current_guardian_set = guardian_set(&deployer.instance, guardian_set_index)
+            .await
+            .value;
+        assert_eq!(
+            current_guardian_set.keys,
+            new_test_guardian_set.addresses(),
+            "Guardian set keys do not match expected values."
+        );
+    }
 }
@@ -1,8 +1,16 @@
+use crate::utils::interface::pyth_init::constructor;
 use fuels::{
     test_helpers::{launch_custom_provider_and_get_wallets, WalletsConfig},
     types::{errors::Error, ContractId},
 };
-use pyth_sdk::pyth_utils::Pyth;
+use pyth_sdk::{
+    constants::{
+        DEFAULT_SINGLE_UPDATE_FEE, DEFAULT_VALID_TIME_PERIOD, DUMMY_CHAIN_ID,
+        GOVERNANCE_DATA_SOURCE, WORMHOLE_GOVERNANCE_DATA_SOURCE,
+    },
+    pyth_utils::{default_data_sources, Pyth},
+    test_vaas::TestGuardianSet,
+};
 
 pub(crate) async fn setup_environment() -> Result<(ContractId, Pyth), Error> {
     // Launch a local network and deploy the contract
@@ -25,3 +33,26 @@ pub(crate) async fn setup_environment() -> Result<(ContractId, Pyth), Error> {
 
     Ok((pyth.instance.contract_id().into(), pyth))
 }
+
+/// Deploys the contract and initializes it with the default configuration and `guardian_set`,
+/// so that it accepts the VAAs generated by `pyth_sdk::test_vaas` for it.
+pub(crate) async fn setup_environment_with_guardian_set(
+    guardian_set: &TestGuardianSet,
+) -> Result<(ContractId, Pyth), Error> {
+    let (oracle_contract_id, deployer) = setup_environment().await?;
+
+    constructor(
+        &deployer.instance,
+        default_data_sources(),
+        GOVERNANCE_DATA_SOURCE,
+        WORMHOLE_GOVERNANCE_DATA_SOURCE,
+        DEFAULT_SINGLE_UPDATE_FEE,
+        DEFAULT_VALID_TIME_PERIOD,
+        guardian_set.addresses(),
+        guardian_set.set_index(),
+        DUMMY_CHAIN_ID,
+    )
+    .await;
+
+    Ok((oracle_contract_id, deployer))
+}
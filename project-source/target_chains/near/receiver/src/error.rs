@@ -66,6 +66,15 @@ pub enum Error {
 
     #[error("Invalid wormhole message.")]
     InvalidWormholeMessage,
+
+    #[error("TWAP message is not the first update for its timestamp.")]
+    InvalidTwapMessage,
+
+    #[error("TWAP window is empty.")]
+    InvalidTwapWindow,
+
+    #[error("A price feed was not found within the requested publish time range.")]
+    PriceFeedNotFoundWithinRange,
 }
 
 /// Convert IO errors into Payload errors, the only I/O we do is parsing with `Cursor` so this is a
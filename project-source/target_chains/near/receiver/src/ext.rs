@@ -4,7 +4,7 @@
 use {
     crate::{
         error::Error,
-        state::{Price, PriceIdentifier, Source},
+        state::{Price, PriceFeed, PriceIdentifier, Source, TwapPrice},
     },
     near_sdk::{ext_contract, json_types::U128},
     std::collections::HashMap,
@@ -26,6 +26,16 @@ pub trait Pyth {
     // price feed endpoint, and should be fed in as base64.
     #[handle_result]
     fn update_price_feeds(&mut self, data: String) -> Result<(), Error>;
+
+    // Resolves to the price feeds of `price_ids` published within the given time range, without
+    // storing them. The update fee must be attached.
+    fn parse_price_feed_updates(
+        &mut self,
+        data: String,
+        price_ids: Vec<PriceIdentifier>,
+        min_publish_time: i64,
+        max_publish_time: i64,
+    ) -> Vec<PriceFeed>;
     fn get_update_fee_estimate(&self, vaa: String) -> U128;
     fn get_sources(&self) -> Vec<Source>;
     fn get_stale_threshold(&self) -> u64;
@@ -63,4 +73,17 @@ pub trait Pyth {
         &self,
         price_ids: Vec<PriceIdentifier>,
     ) -> HashMap<PriceIdentifier, Option<Price>>;
+    fn get_twap(&self, price_id: PriceIdentifier, window: u64) -> Option<TwapPrice>;
+    fn get_twap_unsafe(&self, price_id: PriceIdentifier, window: u64) -> Option<TwapPrice>;
+    fn get_twap_no_older_than(
+        &self,
+        price_id: PriceIdentifier,
+        window: u64,
+        age: u64,
+    ) -> Option<TwapPrice>;
+    fn list_twaps(
+        &self,
+        price_ids: Vec<PriceIdentifier>,
+        window: u64,
+    ) -> HashMap<PriceIdentifier, Option<TwapPrice>>;
 }
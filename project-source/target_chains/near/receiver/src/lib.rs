@@ -5,7 +5,7 @@ use {
     ext::ext_wormhole,
     near_sdk::{
         borsh::{BorshDeserialize, BorshSerialize},
-        collections::{UnorderedMap, UnorderedSet},
+        collections::{LookupMap, UnorderedMap, UnorderedSet},
         env, is_promise_success,
         json_types::U128,
         log, near_bindgen, AccountId, BorshStorageKey, Duration, Gas, NearToken, PanicOnDefault,
@@ -25,7 +25,9 @@ use {
         },
     },
     serde_wormhole::RawMessage,
-    state::{Price, PriceFeed, PriceIdentifier, Source, Vaa},
+    state::{
+        Price, PriceFeed, PriceIdentifier, Source, TwapPrice, TwapSnapshot, TwapSnapshots, Vaa,
+    },
     std::{
         collections::HashMap,
         io::{Cursor, Read},
@@ -44,6 +46,7 @@ pub mod tests;
 enum StorageKeys {
     Source,
     Prices,
+    Twaps,
 }
 
 /// Alias to document time unit Pyth expects data to be in.
@@ -328,38 +331,22 @@ impl Pyth {
 
         let mut count_updates = 0;
         let bytes = &*hex::decode(data.clone()).map_err(|_| Error::InvalidHex)?;
-        let cursor = &mut Cursor::new(bytes);
-        let update_data =
-            AccumulatorUpdateData::try_from_slice(&cursor.clone().into_inner()).unwrap();
-
-        match update_data.proof {
-            Proof::WormholeMerkle { vaa, updates } => {
-                let vaa: wormhole_sdk::Vaa<&RawMessage> =
-                    serde_wormhole::from_slice(vaa.as_ref()).unwrap();
-                let message = WormholeMessage::try_from_bytes(vaa.payload)
-                    .map_err(|_| Error::InvalidWormholeMessage)?;
-                let root: MerkleRoot<Keccak160> = MerkleRoot::new(match message.payload {
-                    WormholePayload::Merkle(merkle_root) => merkle_root.root,
-                });
-
-                for update in updates {
-                    let message_vec = Vec::from(update.message);
-                    if !root.check(update.proof, &message_vec) {
-                        return Err(Error::InvalidMerkleProof)?;
+        for msg in Self::verified_accumulator_messages(bytes)? {
+            match msg {
+                Message::PriceFeedMessage(price_feed_message) => {
+                    if self.update_price_feed_if_new(PriceFeed::from(&price_feed_message)) {
+                        count_updates += 1;
                     }
-
-                    let msg = from_slice::<byteorder::BE, Message>(&message_vec)
-                        .map_err(|_| Error::InvalidAccumulatorMessage)?;
-
-                    match msg {
-                        Message::PriceFeedMessage(price_feed_message) => {
-                            if self.update_price_feed_if_new(PriceFeed::from(&price_feed_message)) {
-                                count_updates += 1;
-                            }
-                        }
-                        _ => return Err(Error::InvalidAccumulatorMessageType)?,
+                }
+                Message::TwapMessage(twap_message) => {
+                    if self.update_twap_feed_if_new(
+                        PriceIdentifier(twap_message.feed_id),
+                        TwapSnapshot::from(&twap_message),
+                    ) {
+                        count_updates += 1;
                     }
                 }
+                _ => return Err(Error::InvalidAccumulatorMessageType)?,
             }
         }
 
@@ -393,6 +380,109 @@ impl Pyth {
         )
     }
 
+    /// Instruction for parsing the price feeds of `price_ids` from an accumulator update without
+    /// storing them, similar to `parsePriceFeedUpdates` on EVM chains.
+    ///
+    /// The update is verified through Wormhole like in `update_price_feeds`, and the returned
+    /// promise resolves to the price feeds in the order of `price_ids`. For each price id, the
+    /// first price feed in the update with a publish time in
+    /// `[min_publish_time, max_publish_time]` is returned, which allows settling at a specific
+    /// historical time. The promise fails if any of the price ids is missing from that range.
+    ///
+    /// The update fee of each update in `data` must be attached. It is charged when the call is
+    /// made and any remaining deposit is refunded immediately, as no storage is used.
+    #[payable]
+    #[handle_result]
+    pub fn parse_price_feed_updates(
+        &mut self,
+        data: String,
+        price_ids: Vec<PriceIdentifier>,
+        min_publish_time: i64,
+        max_publish_time: i64,
+    ) -> Result<Promise, Error> {
+        let bytes = &*hex::decode(data.clone()).map_err(|_| Error::InvalidHex)?;
+        let mut header = [0u8; 4];
+        Cursor::new(bytes).read_exact(&mut header)?;
+
+        // Only accumulator updates contain the full price feeds and their publish times.
+        ensure!(
+            &header == PYTHNET_ACCUMULATOR_UPDATE_MAGIC,
+            Error::InvalidAccumulatorMessage
+        );
+        let update_data =
+            AccumulatorUpdateData::try_from_slice(bytes).map_err(|_| Error::InvalidPayload)?;
+
+        match update_data.proof {
+            Proof::WormholeMerkle { vaa, updates } => {
+                let fee = self
+                    .update_fee
+                    .checked_mul(updates.len() as u128)
+                    .ok_or(Error::ArithmeticOverflow)?;
+                let refund = env::attached_deposit()
+                    .checked_sub(fee)
+                    .ok_or(Error::InsufficientDeposit)?;
+
+                self.verify_encoded_vaa_source(vaa.as_ref())?;
+                if !refund.is_zero() {
+                    Promise::new(env::predecessor_account_id()).transfer(refund);
+                }
+
+                Ok(ext_wormhole::ext(self.wormhole.clone())
+                    .with_static_gas(Gas::from_gas(30_000_000_000_000))
+                    .verify_vaa(hex::encode(vaa.as_ref()))
+                    .then(
+                        Self::ext(env::current_account_id())
+                            .with_static_gas(Gas::from_gas(30_000_000_000_000))
+                            .parse_wormhole_merkle_callback(
+                                data,
+                                price_ids,
+                                min_publish_time,
+                                max_publish_time,
+                            ),
+                    ))
+            }
+        }
+    }
+
+    #[private]
+    #[handle_result]
+    pub fn parse_wormhole_merkle_callback(
+        &self,
+        data: String,
+        price_ids: Vec<PriceIdentifier>,
+        min_publish_time: i64,
+        max_publish_time: i64,
+        #[callback_result] _result: Result<u32, near_sdk::PromiseError>,
+    ) -> Result<Vec<PriceFeed>, Error> {
+        if !is_promise_success() {
+            return Err(Error::VaaVerificationFailed);
+        }
+
+        let bytes = &*hex::decode(data).map_err(|_| Error::InvalidHex)?;
+        let mut price_feeds: HashMap<PriceIdentifier, PriceFeed> = HashMap::new();
+        for msg in Self::verified_accumulator_messages(bytes)? {
+            // Other message types can be part of the same update, they are simply not returned.
+            if let Message::PriceFeedMessage(price_feed_message) = msg {
+                let price_feed = PriceFeed::from(&price_feed_message);
+                if (min_publish_time..=max_publish_time).contains(&price_feed.price.publish_time)
+                    && price_ids.contains(&price_feed.id)
+                    && !price_feeds.contains_key(&price_feed.id)
+                {
+                    price_feeds.insert(PriceIdentifier(price_feed.id.0), price_feed);
+                }
+            }
+        }
+
+        price_ids
+            .iter()
+            .map(|price_id| {
+                price_feeds
+                    .remove(price_id)
+                    .ok_or(Error::PriceFeedNotFoundWithinRange)
+            })
+            .collect()
+    }
+
     /// Return the deposit required to update a price feed. This is the upper limit for an update
     /// call and any remaining deposit not consumed for storage will be refunded.
     #[allow(unused_variables)]
@@ -616,6 +706,65 @@ impl Pyth {
             })
             .collect()
     }
+
+    /// Get the TWAP of the given price identifier over the last `window` seconds, if the end of
+    /// the window is no older than the stale price threshold.
+    ///
+    /// The TWAP starts at the most recent TWAP update published at least `window` seconds before
+    /// the latest one, out of the last `MAX_TWAP_SNAPSHOTS` updates of the feed. The window is
+    /// longer than requested when no update was published exactly at its start, so callers should
+    /// check the `start_time` of the returned TWAP.
+    pub fn get_twap(&self, price_id: PriceIdentifier, window: Seconds) -> Option<TwapPrice> {
+        self.get_twap_no_older_than(price_id, window, self.stale_threshold)
+    }
+
+    /// Get the TWAP of the given price identifier over the last `window` seconds before its latest
+    /// TWAP update, selected as in `get_twap`.
+    ///
+    /// WARNING:
+    ///
+    /// the end of the window is arbitrary. Users of this function should check the `start_time`
+    /// and `publish_time` of the returned TWAP to ensure that it is useful for their application.
+    /// The checked get_twap() function should be used in preference to this.
+    pub fn get_twap_unsafe(&self, price_id: PriceIdentifier, window: Seconds) -> Option<TwapPrice> {
+        Self::twaps()
+            .get(&price_id)
+            .and_then(|snapshots| snapshots.window(window))
+            .and_then(|feed| feed.calculate_twap().ok())
+    }
+
+    /// Get the TWAP of the given price identifier over the last `window` seconds, if the end of
+    /// the window is no older than the given age.
+    pub fn get_twap_no_older_than(
+        &self,
+        price_id: PriceIdentifier,
+        window: Seconds,
+        age: Seconds,
+    ) -> Option<TwapPrice> {
+        self.get_twap_unsafe(price_id, window).filter(|twap| {
+            let block_timestamp = env::block_timestamp() / 1_000_000_000;
+            u64::abs_diff(block_timestamp, twap.publish_time.try_into().unwrap()) <= age
+        })
+    }
+
+    /// Batch version of `get_twap`.
+    pub fn list_twaps(
+        &self,
+        price_ids: Vec<PriceIdentifier>,
+        window: Seconds,
+    ) -> HashMap<PriceIdentifier, Option<TwapPrice>> {
+        price_ids
+            .into_iter()
+            .map(|price_id| {
+                let twap = self.get_twap_no_older_than(
+                    PriceIdentifier(price_id.0),
+                    window,
+                    self.stale_threshold,
+                );
+                (price_id, twap)
+            })
+            .collect()
+    }
 }
 
 /// This second `impl Pyth` block contains only private methods that are called internally that
@@ -658,6 +807,73 @@ impl Pyth {
         }
     }
 
+    /// Verify the merkle proofs of an accumulator update against the root of its VAA and return
+    /// its messages. The VAA itself must already have been verified by Wormhole.
+    fn verified_accumulator_messages(bytes: &[u8]) -> Result<Vec<Message>, Error> {
+        let update_data =
+            AccumulatorUpdateData::try_from_slice(bytes).map_err(|_| Error::InvalidPayload)?;
+
+        match update_data.proof {
+            Proof::WormholeMerkle { vaa, updates } => {
+                let vaa: wormhole_sdk::Vaa<&RawMessage> =
+                    serde_wormhole::from_slice(vaa.as_ref()).map_err(|_| Error::InvalidVaa)?;
+                let message = WormholeMessage::try_from_bytes(vaa.payload)
+                    .map_err(|_| Error::InvalidWormholeMessage)?;
+                let root: MerkleRoot<Keccak160> = MerkleRoot::new(match message.payload {
+                    WormholePayload::Merkle(merkle_root) => merkle_root.root,
+                });
+
+                updates
+                    .into_iter()
+                    .map(|update| {
+                        let message_vec = Vec::from(update.message);
+                        if !root.check(update.proof, &message_vec) {
+                            return Err(Error::InvalidMerkleProof);
+                        }
+                        from_slice::<byteorder::BE, Message>(&message_vec)
+                            .map_err(|_| Error::InvalidAccumulatorMessage)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The TWAP snapshots of the price feeds.
+    ///
+    /// A `LookupMap` doesn't hold any state besides its storage prefix, so it is recreated on
+    /// access instead of being stored in `Pyth`. This avoids a state migration.
+    fn twaps() -> LookupMap<PriceIdentifier, TwapSnapshots> {
+        LookupMap::new(StorageKeys::Twaps)
+    }
+
+    /// Adds `snapshot` to the TWAP snapshots of a price feed if it's newer than the latest one,
+    /// evicting the oldest snapshot when `MAX_TWAP_SNAPSHOTS` are stored. The return value
+    /// indicates whether the update was performed or not.
+    fn update_twap_feed_if_new(
+        &mut self,
+        price_id: PriceIdentifier,
+        snapshot: TwapSnapshot,
+    ) -> bool {
+        // Only the first update of each timestamp is stored, as TWAPs are otherwise not
+        // deterministic. Other updates are skipped like outdated ones, so that they don't prevent
+        // the rest of the accumulator update from being applied.
+        if snapshot.prev_publish_time >= snapshot.publish_time {
+            return false;
+        }
+
+        let mut twaps = Self::twaps();
+        let mut snapshots = twaps.get(&price_id).unwrap_or_default();
+        if snapshots
+            .latest()
+            .map_or(false, |latest| snapshot.publish_time <= latest.publish_time)
+        {
+            return false;
+        }
+        snapshots.push(snapshot);
+        twaps.insert(&price_id, &snapshots);
+        true
+    }
+
     /// Checks storage usage invariants and additionally refunds the caller if they overpay. This
     /// method can optionally charge a fee to the caller which is removed from their deposit during
     /// refund.
@@ -1,11 +1,12 @@
 use {
+    crate::{ensure, error::Error},
     near_sdk::{
         borsh::{BorshDeserialize, BorshSerialize},
         json_types::{I64, U64},
         serde::{Deserialize, Serialize},
     },
     pythnet_sdk::legacy::PriceAttestation,
-    pythnet_sdk::messages::PriceFeedMessage,
+    pythnet_sdk::messages::{PriceFeedMessage, TwapMessage},
     schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema},
     wormhole_sdk::Chain as WormholeChain,
 };
@@ -164,6 +165,167 @@ impl From<&PriceFeedMessage> for PriceFeed {
     }
 }
 
+/// The cumulative values of a price feed at a given time, as published in a `TwapMessage`.
+///
+/// TWAPs are computed from the difference between two snapshots, so the contract only needs to
+/// store the cumulative values rather than the individual prices of the window.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq, Eq)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct TwapSnapshot {
+    pub cumulative_price: i128,
+    pub cumulative_conf: u128,
+    pub num_down_slots: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub publish_slot: u64,
+}
+
+impl From<&TwapMessage> for TwapSnapshot {
+    fn from(twap_message: &TwapMessage) -> Self {
+        Self {
+            cumulative_price: twap_message.cumulative_price,
+            cumulative_conf: twap_message.cumulative_conf,
+            num_down_slots: twap_message.num_down_slots,
+            expo: twap_message.exponent,
+            publish_time: twap_message.publish_time,
+            prev_publish_time: twap_message.prev_publish_time,
+            publish_slot: twap_message.publish_slot,
+        }
+    }
+}
+
+/// Maximum number of `TwapSnapshot`s stored for a price feed.
+pub const MAX_TWAP_SNAPSHOTS: usize = 16;
+
+/// The most recent `TwapSnapshot`s received for a price feed, ordered by publish time. Only the
+/// last `MAX_TWAP_SNAPSHOTS` snapshots are kept, which bounds both the storage used by a feed and
+/// the windows over which its TWAP can be computed.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Default, PartialEq, Eq)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct TwapSnapshots {
+    pub snapshots: Vec<TwapSnapshot>,
+}
+
+impl TwapSnapshots {
+    pub fn latest(&self) -> Option<&TwapSnapshot> {
+        self.snapshots.last()
+    }
+
+    /// Appends a snapshot newer than the latest one, evicting the oldest snapshot when full.
+    pub fn push(&mut self, snapshot: TwapSnapshot) {
+        debug_assert!(self
+            .latest()
+            .map_or(true, |latest| latest.publish_time < snapshot.publish_time));
+        if self.snapshots.len() >= MAX_TWAP_SNAPSHOTS {
+            self.snapshots.remove(0);
+        }
+        self.snapshots.push(snapshot);
+    }
+
+    /// Returns the window ending at the latest snapshot and starting at the most recent snapshot
+    /// published at least `window` seconds earlier, if any. The returned window may be longer than
+    /// requested when no snapshot was published exactly `window` seconds before the latest one.
+    pub fn window(&self, window: u64) -> Option<TwapFeed> {
+        let end = self.latest()?;
+        let start_time = end.publish_time.checked_sub(i64::try_from(window).ok()?)?;
+        let start = self
+            .snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.publish_time <= start_time)?;
+        Some(TwapFeed {
+            start: start.clone(),
+            end: end.clone(),
+        })
+    }
+}
+
+/// A window between two `TwapSnapshot`s of a price feed. The TWAP of the window is the time
+/// weighted average price between `start` and `end`.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq, Eq)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct TwapFeed {
+    pub start: TwapSnapshot,
+    pub end: TwapSnapshot,
+}
+
+/// A time weighted average price over the window from `start_time` to `publish_time`.
+///
+/// `down_slots_ratio` is the ratio of slots in the window in which the feed had no valid price,
+/// as an integer between 0 and 1_000_000. A high ratio means that the TWAP is computed from few
+/// prices and should be used with care.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(abi, derive(JsonSchema))]
+pub struct TwapPrice {
+    pub price: I64,
+    /// Confidence interval around the price
+    pub conf: U64,
+    /// The exponent
+    pub expo: i32,
+    /// Unix timestamp of the end of the window
+    pub publish_time: i64,
+    /// Unix timestamp of the start of the window
+    pub start_time: i64,
+    /// Ratio of down slots in the window, scaled by 1_000_000
+    pub down_slots_ratio: u32,
+}
+
+impl TwapFeed {
+    /// Calculate the TWAP over the window of the feed. This follows the calculation done by
+    /// Hermes: both snapshots must be the first update for their timestamp so that the TWAP is
+    /// deterministic, and the averages are taken over the number of slots in the window.
+    pub fn calculate_twap(&self) -> Result<TwapPrice, Error> {
+        let (start, end) = (&self.start, &self.end);
+        ensure!(
+            end.publish_slot > start.publish_slot,
+            Error::InvalidTwapWindow
+        );
+        ensure!(
+            start.prev_publish_time < start.publish_time,
+            Error::InvalidTwapMessage
+        );
+        ensure!(
+            end.prev_publish_time < end.publish_time,
+            Error::InvalidTwapMessage
+        );
+
+        let slot_diff = end.publish_slot - start.publish_slot;
+        let price_diff = end
+            .cumulative_price
+            .checked_sub(start.cumulative_price)
+            .ok_or(Error::ArithmeticOverflow)?;
+        let conf_diff = end
+            .cumulative_conf
+            .checked_sub(start.cumulative_conf)
+            .ok_or(Error::ArithmeticOverflow)?;
+        let down_slots_diff = end
+            .num_down_slots
+            .checked_sub(start.num_down_slots)
+            .ok_or(Error::ArithmeticOverflow)?;
+
+        // Divide before casting to keep the precision of the cumulative values.
+        let price = i64::try_from(price_diff / i128::from(slot_diff))
+            .map_err(|_| Error::ArithmeticOverflow)?;
+        let conf = u64::try_from(conf_diff / u128::from(slot_diff))
+            .map_err(|_| Error::ArithmeticOverflow)?;
+        let down_slots_ratio =
+            u32::try_from(u128::from(down_slots_diff) * 1_000_000 / u128::from(slot_diff))
+                .map_err(|_| Error::ArithmeticOverflow)?;
+
+        Ok(TwapPrice {
+            price: price.into(),
+            conf: conf.into(),
+            expo: end.expo,
+            publish_time: end.publish_time,
+            start_time: start.publish_time,
+            down_slots_ratio,
+        })
+    }
+}
+
 /// A wrapper around a 16bit chain identifier. We can't use Chain from the Wormhole SDK as it does
 /// not provide borsh serialization but we can re-wrap it here relying on the validation from
 /// `wormhole::Chain`.
@@ -2,7 +2,11 @@
 #[allow(clippy::module_inception)]
 mod tests {
     use {
-        crate::{state::Source, Pyth},
+        crate::{
+            error::Error,
+            state::{Source, TwapFeed, TwapPrice, TwapSnapshot, TwapSnapshots, MAX_TWAP_SNAPSHOTS},
+            Pyth,
+        },
         near_sdk::{test_utils::VMContextBuilder, testing_env, VMContext},
     };
 
@@ -31,4 +35,117 @@ mod tests {
         assert_eq!(contract.sources.len(), 1);
         assert_eq!(contract.prices.len(), 0);
     }
+
+    fn create_twap_snapshot(
+        cumulative_price: i128,
+        cumulative_conf: u128,
+        num_down_slots: u64,
+        publish_time: i64,
+        publish_slot: u64,
+    ) -> TwapSnapshot {
+        TwapSnapshot {
+            cumulative_price,
+            cumulative_conf,
+            num_down_slots,
+            expo: -8,
+            publish_time,
+            prev_publish_time: publish_time - 1,
+            publish_slot,
+        }
+    }
+
+    #[test]
+    fn test_calculate_twap() {
+        let feed = TwapFeed {
+            start: create_twap_snapshot(100_000, 10_000, 10, 100, 1_000),
+            end: create_twap_snapshot(500_000, 50_000, 110, 160, 1_400),
+        };
+        assert_eq!(
+            feed.calculate_twap().unwrap(),
+            TwapPrice {
+                price: 1_000.into(),
+                conf: 100.into(),
+                expo: -8,
+                publish_time: 160,
+                start_time: 100,
+                // 100 down slots out of 400 slots
+                down_slots_ratio: 250_000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_calculate_twap_invalid_window() {
+        let snapshot = create_twap_snapshot(100_000, 10_000, 10, 100, 1_000);
+        let feed = TwapFeed {
+            start: snapshot.clone(),
+            end: snapshot.clone(),
+        };
+        assert!(matches!(
+            feed.calculate_twap(),
+            Err(Error::InvalidTwapWindow)
+        ));
+
+        // Only the first update of a timestamp can be used in a TWAP.
+        let mut end = create_twap_snapshot(500_000, 50_000, 110, 160, 1_400);
+        end.prev_publish_time = end.publish_time;
+        let feed = TwapFeed {
+            start: snapshot,
+            end,
+        };
+        assert!(matches!(
+            feed.calculate_twap(),
+            Err(Error::InvalidTwapMessage)
+        ));
+    }
+
+    #[test]
+    fn test_twap_snapshots_window() {
+        let mut snapshots = TwapSnapshots::default();
+        assert_eq!(snapshots.window(60), None);
+
+        for (publish_time, publish_slot) in [(100, 1_000), (130, 1_200), (160, 1_400)] {
+            snapshots.push(create_twap_snapshot(0, 0, 0, publish_time, publish_slot));
+        }
+
+        // The start of the window is the most recent snapshot at least `window` seconds older
+        // than the latest one.
+        let window = |window| {
+            snapshots
+                .window(window)
+                .map(|feed| (feed.start.publish_time, feed.end.publish_time))
+        };
+        assert_eq!(window(0), Some((160, 160)));
+        assert_eq!(window(30), Some((130, 160)));
+        assert_eq!(window(45), Some((100, 160)));
+        assert_eq!(window(60), Some((100, 160)));
+        assert_eq!(window(61), None);
+        assert_eq!(window(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_twap_snapshots_capacity() {
+        let mut snapshots = TwapSnapshots::default();
+        for i in 0..MAX_TWAP_SNAPSHOTS as i64 + 2 {
+            snapshots.push(create_twap_snapshot(0, 0, 0, 100 + i, 1_000 + i as u64));
+        }
+        assert_eq!(snapshots.snapshots.len(), MAX_TWAP_SNAPSHOTS);
+        assert_eq!(snapshots.snapshots[0].publish_time, 102);
+        assert_eq!(
+            snapshots.latest().unwrap().publish_time,
+            101 + MAX_TWAP_SNAPSHOTS as i64
+        );
+    }
+
+    #[test]
+    fn test_calculate_twap_overflow() {
+        let feed = TwapFeed {
+            start: create_twap_snapshot(0, 0, 0, 100, 1_000),
+            end: create_twap_snapshot(i128::MAX, 0, 0, 101, 1_001),
+        };
+        assert!(matches!(
+            feed.calculate_twap(),
+            Err(Error::ArithmeticOverflow)
+        ));
+    }
 }
@@ -3,11 +3,10 @@ use {
     near_workspaces::types::{Gas, NearToken},
     pyth_near::{
         governance::{GovernanceAction, GovernanceInstruction, GovernanceModule},
-        state::{Chain, Price, PriceIdentifier, Source},
-    },
-    pythnet_sdk::legacy::{
-        BatchPriceAttestation, Identifier, PriceAttestation, PriceStatus,
+        state::{Chain, Price, PriceFeed, PriceIdentifier, Source, TwapPrice},
     },
+    pythnet_sdk::legacy::{BatchPriceAttestation, Identifier, PriceAttestation, PriceStatus},
+    pythnet_sdk::messages::{Message, TwapMessage},
     pythnet_sdk::test_utils::{
         create_accumulator_message, create_dummy_price_feed_message, create_vaa_from_payload,
         DEFAULT_DATA_SOURCE, DEFAULT_GOVERNANCE_SOURCE, DEFAULT_VALID_TIME_PERIOD,
@@ -921,6 +920,188 @@ async fn test_accumulator_updates() {
     );
 }
 
+#[tokio::test]
+async fn test_twap_updates() {
+    let (_, contract, _) = initialize_chain().await;
+
+    let mut feed_id = [0; 32];
+    feed_id[0] = 100;
+    let create_twap_message = |cumulative_price, num_down_slots, publish_time, publish_slot| {
+        Message::TwapMessage(TwapMessage {
+            feed_id,
+            cumulative_price,
+            cumulative_conf: 4_000,
+            num_down_slots,
+            exponent: -8,
+            publish_time,
+            prev_publish_time: publish_time - 1,
+            publish_slot,
+        })
+    };
+
+    let twap_contract = &contract;
+    let get_twap = |window: u64| async move {
+        serde_json::from_slice::<Option<TwapPrice>>(
+            &twap_contract
+                .view("get_twap_unsafe")
+                .args_json(json!({ "price_id": PriceIdentifier(feed_id), "window": window }))
+                .await
+                .unwrap()
+                .result,
+        )
+        .unwrap()
+    };
+
+    // A TWAP message that isn't the first update for its timestamp is skipped without failing
+    // the rest of the update.
+    let mut not_first_update = create_twap_message(900_000, 110, 190, 1_600);
+    if let Message::TwapMessage(twap_message) = &mut not_first_update {
+        twap_message.prev_publish_time = twap_message.publish_time;
+    }
+
+    // The first update only sets the start of the window, so there is no TWAP yet.
+    for (twap_messages, expected) in [
+        (vec![create_twap_message(100_000, 10, 100, 1_000)], None),
+        (
+            vec![
+                create_twap_message(500_000, 110, 160, 1_400),
+                not_first_update,
+            ],
+            Some(TwapPrice {
+                price: 1_000.into(),
+                conf: 0.into(),
+                expo: -8,
+                publish_time: 160,
+                start_time: 100,
+                down_slots_ratio: 250_000,
+            }),
+        ),
+    ] {
+        let twap_messages = twap_messages.iter().collect::<Vec<_>>();
+        let message =
+            create_accumulator_message(&twap_messages, &twap_messages, false, false, None);
+        assert!(contract
+            .call("update_price_feeds")
+            .gas(Gas::from_gas(300_000_000_000_000))
+            .deposit(NearToken::from_yoctonear(300_000_000_000_000_000_000_000))
+            .args_json(json!({
+                "data": hex::encode(message),
+            }))
+            .transact_async()
+            .await
+            .expect("Failed to submit VAA")
+            .await
+            .unwrap()
+            .failures()
+            .is_empty());
+
+        assert_eq!(get_twap(60).await, expected);
+    }
+
+    // Older snapshots are kept, so the TWAP can start at any of them.
+    assert_eq!(get_twap(30).await.map(|twap| twap.start_time), Some(100));
+    assert_eq!(get_twap(61).await, None);
+
+    // The window matches, but the TWAP is stale.
+    assert_eq!(
+        serde_json::from_slice::<Option<TwapPrice>>(
+            &contract
+                .view("get_twap")
+                .args_json(json!({ "price_id": PriceIdentifier(feed_id), "window": 60 }))
+                .await
+                .unwrap()
+                .result,
+        )
+        .unwrap(),
+        None,
+    );
+}
+
+#[tokio::test]
+async fn test_parse_price_feed_updates() {
+    let (_, contract, _) = initialize_chain().await;
+
+    let feed_1 = create_dummy_price_feed_message(100);
+    let feed_2 = create_dummy_price_feed_message(200);
+    let message = hex::encode(create_accumulator_message(
+        &[&feed_1, &feed_2],
+        &[&feed_1, &feed_2],
+        false,
+        false,
+        None,
+    ));
+
+    let mut identifier_1 = [0; 32];
+    identifier_1[0] = 100;
+    let mut identifier_2 = [0; 32];
+    identifier_2[0] = 200;
+
+    // Feeds are returned in the requested order.
+    let price_feeds = contract
+        .call("parse_price_feed_updates")
+        .gas(Gas::from_gas(300_000_000_000_000))
+        .deposit(NearToken::from_yoctonear(2))
+        .args_json(json!({
+            "data": message,
+            "price_ids": [PriceIdentifier(identifier_2), PriceIdentifier(identifier_1)],
+            "min_publish_time": 100,
+            "max_publish_time": 200,
+        }))
+        .transact()
+        .await
+        .expect("Failed to parse price feed updates")
+        .json::<Vec<PriceFeed>>()
+        .unwrap();
+    assert_eq!(price_feeds.len(), 2);
+    assert!(price_feeds[0].id == PriceIdentifier(identifier_2));
+    assert_eq!(price_feeds[0].price.publish_time, 200);
+    assert!(price_feeds[1].id == PriceIdentifier(identifier_1));
+    assert_eq!(price_feeds[1].price.publish_time, 100);
+
+    // Nothing is stored.
+    assert!(!serde_json::from_slice::<bool>(
+        &contract
+            .view("price_feed_exists")
+            .args_json(json!({ "price_identifier": PriceIdentifier(identifier_1) }))
+            .await
+            .unwrap()
+            .result
+    )
+    .unwrap());
+
+    // Fails when a feed is outside of the publish time range.
+    assert!(!contract
+        .call("parse_price_feed_updates")
+        .gas(Gas::from_gas(300_000_000_000_000))
+        .deposit(NearToken::from_yoctonear(2))
+        .args_json(json!({
+            "data": message,
+            "price_ids": [PriceIdentifier(identifier_1), PriceIdentifier(identifier_2)],
+            "min_publish_time": 150,
+            "max_publish_time": 200,
+        }))
+        .transact()
+        .await
+        .unwrap()
+        .is_success());
+
+    // Fails when the update fee isn't attached.
+    assert!(!contract
+        .call("parse_price_feed_updates")
+        .gas(Gas::from_gas(300_000_000_000_000))
+        .deposit(NearToken::from_yoctonear(1))
+        .args_json(json!({
+            "data": message,
+            "price_ids": [PriceIdentifier(identifier_1)],
+            "min_publish_time": 100,
+            "max_publish_time": 200,
+        }))
+        .transact()
+        .await
+        .unwrap()
+        .is_success());
+}
+
 #[tokio::test]
 async fn test_sdk_compat() {
     let price = pyth_sdk::Price {
@@ -37,6 +37,13 @@ pub enum Action {
         #[clap(short = 'p', long, help = "Payload from Hermes")]
         payload: String,
     },
+    #[clap(
+        about = "Post all the price updates of a payload from Hermes to Solana in a single instruction"
+    )]
+    PostPriceUpdatesBatch {
+        #[clap(short = 'p', long, help = "Payload from Hermes")]
+        payload: String,
+    },
     #[clap(about = "Post a price update from Hermes to Solana in one transaction")]
     PostPriceUpdateAtomic {
         #[clap(short = 'p', long, help = "Payload from Hermes")]
@@ -63,6 +70,55 @@ pub enum Action {
         )]
         end_payload: String,
     },
+    #[clap(
+        about = "Keep push oracle price feed accounts up to date with the price updates streamed by Hermes"
+    )]
+    Push {
+        #[clap(
+            long,
+            default_value = "https://hermes.pyth.network",
+            help = "Endpoint of Hermes"
+        )]
+        hermes_url: String,
+        #[clap(
+            short = 'i',
+            long = "feed-id",
+            required = true,
+            help = "Hex encoded id of a price feed to push, can be repeated"
+        )]
+        feed_ids: Vec<String>,
+        #[clap(
+            short = 's',
+            long,
+            default_value = "0",
+            help = "Shard of the price feed accounts"
+        )]
+        shard_id: u16,
+        #[clap(
+            long,
+            default_value = "50",
+            help = "Push a price feed when its price moved by at least this many basis points since its last on-chain update"
+        )]
+        price_deviation_bps: u64,
+        #[clap(
+            long,
+            default_value = "60",
+            help = "Push a price feed when its last on-chain update is at least this many seconds old"
+        )]
+        heartbeat_secs: u64,
+        #[clap(
+            long,
+            default_value = "50",
+            help = "Percentile of the recent prioritization fees of the price feed accounts to bid"
+        )]
+        priority_fee_percentile: u8,
+        #[clap(
+            long,
+            default_value = "1000000",
+            help = "Maximum priority fee to bid, in micro-lamports per compute unit"
+        )]
+        max_priority_fee: u64,
+    },
     #[clap(
         about = "Initialize a wormhole receiver contract by sequentially replaying the guardian set updates"
     )]
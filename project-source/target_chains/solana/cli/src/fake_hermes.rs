@@ -0,0 +1,81 @@
+//! A local stand-in for Hermes to test the push daemon without network access.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// Serves `accumulator_updates` as server-sent events on every request and closes the connection
+/// afterwards. The paths of the requests are recorded so tests can inspect the subscriptions.
+pub struct FakeHermes {
+    port: u16,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeHermes {
+    pub fn start(accumulator_updates: Vec<Vec<u8>>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let thread_requests = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                // Errors only mean that the client went away
+                let _ = serve(stream, &accumulator_updates, &thread_requests);
+            }
+        });
+
+        Self { port, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn serve(
+    mut stream: TcpStream,
+    accumulator_updates: &[Vec<u8>],
+    requests: &Mutex<Vec<String>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the headers, the requests of the client don't have a body
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header == "\r\n" {
+            break;
+        }
+    }
+    requests.lock().unwrap().push(
+        request_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or_default()
+            .to_string(),
+    );
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n"
+    )?;
+    for accumulator_update in accumulator_updates {
+        write!(
+            stream,
+            ": keep-alive\n\ndata: {{\"binary\":{{\"encoding\":\"base64\",\"data\":[\"{}\"]}}}}\n\n",
+            base64::encode(accumulator_update)
+        )?;
+    }
+    stream.flush()
+}
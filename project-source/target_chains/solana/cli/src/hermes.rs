@@ -0,0 +1,199 @@
+use {
+    anyhow::{anyhow, Result},
+    pythnet_sdk::messages::FeedId,
+    serde::Deserialize,
+    std::{
+        collections::VecDeque,
+        io::{BufRead, BufReader, Lines},
+        sync::mpsc::{self, Receiver},
+        thread,
+        time::Duration,
+    },
+};
+
+/// How long to wait before reconnecting to Hermes after the price update stream ended or failed
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Deserialize)]
+struct PriceUpdateEvent {
+    binary: BinaryPriceUpdate,
+}
+
+#[derive(Deserialize)]
+struct BinaryPriceUpdate {
+    encoding: String,
+    data: Vec<String>,
+}
+
+/// A client for the server-sent events price update stream of Hermes
+pub struct HermesClient {
+    client: reqwest::blocking::Client,
+    url: String,
+}
+
+impl HermesClient {
+    pub fn new(url: &str) -> Result<Self> {
+        // The stream is long-lived so it mustn't be subject to the default request timeout
+        let client = reqwest::blocking::Client::builder().timeout(None).build()?;
+        Ok(Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Subscribe to the price updates of `feed_ids`. Each item of the stream is an accumulator
+    /// update containing the latest price update of every feed.
+    pub fn stream_price_updates(&self, feed_ids: &[FeedId]) -> Result<PriceUpdateStream> {
+        let mut query: Vec<(&str, String)> = feed_ids
+            .iter()
+            .map(|feed_id| ("ids[]", hex::encode(feed_id)))
+            .collect();
+        query.push(("encoding", "base64".to_string()));
+        query.push(("parsed", "false".to_string()));
+
+        let response = self
+            .client
+            .get(format!("{}/v2/updates/price/stream", self.url))
+            .query(&query)
+            .send()?
+            .error_for_status()?;
+
+        Ok(PriceUpdateStream {
+            lines: BufReader::new(response).lines(),
+            pending: VecDeque::new(),
+        })
+    }
+}
+
+/// The accumulator updates streamed by Hermes, in the order they were received
+pub struct PriceUpdateStream {
+    lines: Lines<BufReader<reqwest::blocking::Response>>,
+    pending: VecDeque<Vec<u8>>,
+}
+
+impl Iterator for PriceUpdateStream {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(accumulator_update) = self.pending.pop_front() {
+                return Some(Ok(accumulator_update));
+            }
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+            // Ignore the other fields of the events as well as the keep-alive comments
+            if let Some(data) = line.strip_prefix("data:") {
+                match parse_price_update_event(data.trim()) {
+                    Ok(accumulator_updates) => self.pending.extend(accumulator_updates),
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+        }
+    }
+}
+
+fn parse_price_update_event(data: &str) -> Result<Vec<Vec<u8>>> {
+    let event: PriceUpdateEvent = serde_json::from_str(data)?;
+    if event.binary.encoding != "base64" {
+        return Err(anyhow!(
+            "Unexpected price update encoding: {}",
+            event.binary.encoding
+        ));
+    }
+    event
+        .binary
+        .data
+        .iter()
+        .map(|data| Ok(base64::decode(data)?))
+        .collect()
+}
+
+/// Subscribe to the price updates of `feed_ids` in a background thread that reconnects to Hermes
+/// whenever the stream ends or fails. The thread stops once the receiver is dropped.
+pub fn spawn_price_update_subscription(
+    hermes_client: HermesClient,
+    feed_ids: Vec<FeedId>,
+) -> Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || loop {
+        match hermes_client.stream_price_updates(&feed_ids) {
+            Ok(stream) => {
+                for accumulator_update in stream {
+                    match accumulator_update {
+                        Ok(accumulator_update) => {
+                            if sender.send(accumulator_update).is_err() {
+                                return;
+                            }
+                        }
+                        Err(err) => {
+                            println!("Hermes stream error: {err:?}");
+                            break;
+                        }
+                    }
+                }
+                println!("Hermes stream ended, reconnecting");
+            }
+            Err(err) => println!("Failed to connect to Hermes: {err:?}"),
+        }
+        thread::sleep(RECONNECT_DELAY);
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::fake_hermes::FakeHermes,
+        pythnet_sdk::test_utils::{
+            create_accumulator_message, create_dummy_feed_id,
+            create_dummy_price_feed_message_with_feed_id,
+        },
+    };
+
+    #[test]
+    fn test_stream_price_updates() {
+        let feed_id = create_dummy_feed_id(100);
+        let feed = create_dummy_price_feed_message_with_feed_id(100, feed_id);
+        let accumulator_updates = vec![
+            create_accumulator_message(&[&feed], &[&feed], false, false, None),
+            create_accumulator_message(&[&feed], &[&feed], false, false, None),
+        ];
+        let fake_hermes = FakeHermes::start(accumulator_updates.clone());
+
+        let stream = HermesClient::new(&fake_hermes.url())
+            .unwrap()
+            .stream_price_updates(&[feed_id])
+            .unwrap();
+        assert_eq!(
+            stream.collect::<Result<Vec<_>>>().unwrap(),
+            accumulator_updates
+        );
+
+        let requests = fake_hermes.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("/v2/updates/price/stream?"));
+        assert!(requests[0].contains(&hex::encode(feed_id)));
+        assert!(requests[0].contains("encoding=base64"));
+    }
+
+    #[test]
+    fn test_price_update_subscription_reconnects() {
+        let feed_id = create_dummy_feed_id(100);
+        let feed = create_dummy_price_feed_message_with_feed_id(100, feed_id);
+        let accumulator_update = create_accumulator_message(&[&feed], &[&feed], false, false, None);
+        let fake_hermes = FakeHermes::start(vec![accumulator_update.clone()]);
+
+        let receiver = spawn_price_update_subscription(
+            HermesClient::new(&fake_hermes.url()).unwrap(),
+            vec![feed_id],
+        );
+        // The fake Hermes closes the stream once it served its updates, so the second update comes
+        // from a new connection
+        assert_eq!(receiver.recv().unwrap(), accumulator_update);
+        assert_eq!(receiver.recv().unwrap(), accumulator_update);
+        assert!(fake_hermes.requests().len() >= 2);
+    }
+}
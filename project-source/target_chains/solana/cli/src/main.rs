@@ -1,18 +1,23 @@
 #![deny(warnings)]
 
 pub mod cli;
+#[cfg(test)]
+mod fake_hermes;
+pub mod hermes;
+pub mod push;
 
 use {
     anchor_client::anchor_lang::{InstructionData, ToAccountMetas},
-    anyhow::Result,
+    anyhow::{anyhow, Result},
     borsh::BorshDeserialize,
     clap::Parser,
     cli::{Action, Cli},
+    push::{process_push, PriorityFeeBidder, PushConfig, UpdateRules},
     pyth_solana_receiver::sdk::{
         deserialize_accumulator_update_data, get_random_treasury_id, VAA_SPLIT_INDEX,
     },
     pyth_solana_receiver_sdk::config::DataSource,
-    pythnet_sdk::wire::v1::MerklePriceUpdate,
+    pythnet_sdk::{messages::FeedId, wire::v1::MerklePriceUpdate},
     serde_wormhole::RawMessage,
     solana_client::{rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig},
     solana_sdk::{
@@ -45,6 +50,11 @@ const UPGRADE_GUARDIAN_SET_VAA_2 : &str = "01000000010d0012e6b39c6da90c5dfd3c228
 const UPGRADE_GUARDIAN_SET_VAA_3 : &str = "01000000020d00ce45474d9e1b1e7790a2d210871e195db53a70ffd6f237cfe70e2686a32859ac43c84a332267a8ef66f59719cf91cc8df0101fd7c36aa1878d5139241660edc0010375cc906156ae530786661c0cd9aef444747bc3d8d5aa84cac6a6d2933d4e1a031cffa30383d4af8131e929d9f203f460b07309a647d6cd32ab1cc7724089392c000452305156cfc90343128f97e499311b5cae174f488ff22fbc09591991a0a73d8e6af3afb8a5968441d3ab8437836407481739e9850ad5c95e6acfcc871e951bc30105a7956eefc23e7c945a1966d5ddbe9e4be376c2f54e45e3d5da88c2f8692510c7429b1ea860ae94d929bd97e84923a18187e777aa3db419813a80deb84cc8d22b00061b2a4f3d2666608e0aa96737689e3ba5793810ff3a52ff28ad57d8efb20967735dc5537a2e43ef10f583d144c12a1606542c207f5b79af08c38656d3ac40713301086b62c8e130af3411b3c0d91b5b50dcb01ed5f293963f901fc36e7b0e50114dce203373b32eb45971cef8288e5d928d0ed51cd86e2a3006b0af6a65c396c009080009e93ab4d2c8228901a5f4525934000b2c26d1dc679a05e47fdf0ff3231d98fbc207103159ff4116df2832eea69b38275283434e6cd4a4af04d25fa7a82990b707010aa643f4cf615dfff06ffd65830f7f6cf6512dabc3690d5d9e210fdc712842dc2708b8b2c22e224c99280cd25e5e8bfb40e3d1c55b8c41774e287c1e2c352aecfc010b89c1e85faa20a30601964ccc6a79c0ae53cfd26fb10863db37783428cd91390a163346558239db3cd9d420cfe423a0df84c84399790e2e308011b4b63e6b8015010ca31dcb564ac81a053a268d8090e72097f94f366711d0c5d13815af1ec7d47e662e2d1bde22678113d15963da100b668ba26c0c325970d07114b83c5698f46097010dc9fda39c0d592d9ed92cd22b5425cc6b37430e236f02d0d1f8a2ef45a00bde26223c0a6eb363c8b25fd3bf57234a1d9364976cefb8360e755a267cbbb674b39501108db01e444ab1003dd8b6c96f8eb77958b40ba7a85fefecf32ad00b7a47c0ae7524216262495977e09c0989dd50f280c21453d3756843608eacd17f4fdfe47600001261025228ef5af837cb060bcd986fcfa84ccef75b3fa100468cfd24e7fadf99163938f3b841a33496c2706d0208faab088bd155b2e20fd74c625bb1cc8c43677a0163c53c409e0c5dfa000100000000000000000000000000000000000000000000000000000000000000046c5a054d7833d1e42000000000000000000000000000000000000000000000000000000000436f7265020000000000031358cc3ae5c097b213ce3c81979e1b9f9570746aa5ff6cb952589bde862c25ef4392132fb9d4a42157114de8460193bdf3a2fcf81f86a09765f4762fd1107a0086b32d7a0977926a205131d8731d39cbeb8c82b2fd82faed2711d59af0f2499d16e726f6b211b39756c042441be6d8650b69b54ebe715e234354ce5b4d348fb74b958e8966e2ec3dbd4958a7cd15e7caf07c4e3dc8e7c469f92c8cd88fb8005a2074a3bf913953d695260d88bc1aa25a4eee363ef0000ac0076727b35fbea2dac28fee5ccb0fea768eaf45ced136b9d9e24903464ae889f5c8a723fc14f93124b7c738843cbb89e864c862c38cddcccf95d2cc37a4dc036a8d232b48f62cdd4731412f4890da798f6896a3331f64b48c12d1d57fd9cbe7081171aa1be1d36cafe3867910f99c09e347899c19c38192b6e7387ccd768277c17dab1b7a5027c0b3cf178e21ad2e77ae06711549cfbb1f9c7a9d8096e85e1487f35515d02a92753504a8d75471b9f49edb6fbebc898f403e4773e95feb15e80c9a99c8348d";
 const UPGRADE_GUARDIAN_SET_VAA_4 : &str = "01000000030d03d4a37a6ff4361d91714730831e9d49785f61624c8f348a9c6c1d82bc1d98cadc5e936338204445c6250bb4928f3f3e165ad47ca03a5d63111168a2de4576856301049a5df10464ea4e1961589fd30fc18d1970a7a2ffaad617e56a0f7777f25275253af7d10a0f0f2494dc6e99fc80e444ab9ebbbee252ded2d5dcb50cbf7a54bb5a01055f4603b553b9ba9e224f9c55c7bca3da00abb10abd19e0081aecd3b352be061a70f79f5f388ebe5190838ef3cd13a2f22459c9a94206883b739c90b40d5d74640006a8fade3997f650a36e46bceb1f609edff201ab32362266f166c5c7da713f6a19590c20b68ed3f0119cb24813c727560ede086b3d610c2d7a1efa66f655bad90900080f5e495a75ea52241c59d145c616bfac01e57182ad8d784cbcc9862ed3afb60c0983ccbc690553961ffcf115a0c917367daada8e60be2cbb8b8008bac6341a8c010935ab11e0eea28b87a1edc5ccce3f1fac25f75b5f640fe6b0673a7cd74513c9dc01c544216cf364cc9993b09fda612e0cd1ced9c00fb668b872a16a64ebb55d27010ab2bc39617a2396e7defa24cd7c22f42dc31f3c42ffcd9d1472b02df8468a4d0563911e8fb6a4b5b0ce0bd505daa53779b08ff660967b31f246126ed7f6f29a7e000bdb6d3fd7b33bdc9ac3992916eb4aacb97e7e21d19649e7fa28d2dd6e337937e4274516a96c13ac7a8895da9f91948ea3a09c25f44b982c62ce8842b58e20c8a9000d3d1b19c8bb000856b6610b9d28abde6c35cb7705c6ca5db711f7be96d60eed9d72cfa402a6bfe8bf0496dbc7af35796fc768da51a067b95941b3712dce8ae1e7010ec80085033157fd1a5628fc0c56267469a86f0e5a66d7dede1ad4ce74ecc3dff95b60307a39c3bfbeedc915075070da30d0395def9635130584f709b3885e1bdc0010fc480eb9ee715a2d151b23722b48b42581d7f4001fc1696c75425040bfc1ffc5394fe418adb2b64bd3dc692efda4cc408163677dbe233b16bcdabb853a20843301118ee9e115e1a0c981f19d0772b850e666591322da742a9a12cce9f52a5665bd474abdd59c580016bee8aae67fdf39b315be2528d12eec3a652910e03cc4c6fa3801129d0d1e2e429e969918ec163d16a7a5b2c6729aa44af5dccad07d25d19891556a79b574f42d9adbd9e2a9ae5a6b8750331d2fccb328dd94c3bf8791ee1bfe85aa00661e99781981faea00010000000000000000000000000000000000000000000000000000000000000004fd4c6c55ec8dfd342000000000000000000000000000000000000000000000000000000000436f726502000000000004135893b5a76c3f739645648885bdccc06cd70a3cd3ff6cb952589bde862c25ef4392132fb9d4a42157114de8460193bdf3a2fcf81f86a09765f4762fd1107a0086b32d7a0977926a205131d8731d39cbeb8c82b2fd82faed2711d59af0f2499d16e726f6b211b39756c042441be6d8650b69b54ebe715e234354ce5b4d348fb74b958e8966e2ec3dbd4958a7cd15e7caf07c4e3dc8e7c469f92c8cd88fb8005a2074a3bf913953d695260d88bc1aa25a4eee363ef0000ac0076727b35fbea2dac28fee5ccb0fea768eaf45ced136b9d9e24903464ae889f5c8a723fc14f93124b7c738843cbb89e864c862c38cddcccf95d2cc37a4dc036a8d232b48f62cdd4731412f4890da798f6896a3331f64b48c12d1d57fd9cbe7081171aa1be1d36cafe3867910f99c09e347899c19c38192b6e7387ccd768277c17dab1b7a5027c0b3cf178e21ad2e77ae06711549cfbb1f9c7a9d8096e85e1487f35515d02a92753504a8d75471b9f49edb6fbebc898f403e4773e95feb15e80c9a99c8348d";
 const GUARDIAN_EXPIRATION_TIME: u32 = 86400;
+/// Compute budget of each price update posted by a `post_updates_batch` instruction
+const POST_UPDATE_COMPUTE_BUDGET: u32 = 200_000;
+/// Number of new price update accounts of a `post_updates_batch` instruction that fit in a transaction,
+/// each of them adds its signature, its pubkey and its merkle proof to the transaction
+const POST_UPDATES_BATCH_PER_TRANSACTION: usize = 2;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -72,6 +82,22 @@ fn main() -> Result<()> {
                 &merkle_price_updates[0],
             )?;
         }
+        Action::PostPriceUpdatesBatch { payload } => {
+            let rpc_client = RpcClient::new(url);
+            let payer =
+                read_keypair_file(&*shellexpand::tilde(&keypair)).expect("Keypair not found");
+
+            let payload_bytes: Vec<u8> = base64::decode(payload)?;
+            let (vaa, merkle_price_updates) = deserialize_accumulator_update_data(payload_bytes)?;
+
+            process_write_encoded_vaa_and_post_price_updates_batch(
+                &rpc_client,
+                &vaa,
+                wormhole,
+                &payer,
+                &merkle_price_updates,
+            )?;
+        }
         Action::PostPriceUpdateAtomic {
             payload,
             n_signatures,
@@ -118,6 +144,46 @@ fn main() -> Result<()> {
                 &end_merkle_price_updates[0],
             )?;
         }
+        Action::Push {
+            hermes_url,
+            feed_ids,
+            shard_id,
+            price_deviation_bps,
+            heartbeat_secs,
+            priority_fee_percentile,
+            max_priority_fee,
+        } => {
+            let rpc_client = RpcClient::new(url);
+            let payer =
+                read_keypair_file(&*shellexpand::tilde(&keypair)).expect("Keypair not found");
+
+            let feed_ids = feed_ids
+                .iter()
+                .map(|feed_id| {
+                    FeedId::try_from(hex::decode(feed_id.trim_start_matches("0x"))?)
+                        .map_err(|_| anyhow!("Invalid feed id: {feed_id}"))
+                })
+                .collect::<Result<Vec<FeedId>>>()?;
+
+            process_push(
+                &rpc_client,
+                wormhole,
+                &payer,
+                PushConfig {
+                    hermes_url,
+                    feed_ids,
+                    shard_id,
+                    update_rules: UpdateRules {
+                        price_deviation_bps,
+                        heartbeat_secs,
+                    },
+                    priority_fee_bidder: PriorityFeeBidder::new(
+                        priority_fee_percentile,
+                        max_priority_fee,
+                    ),
+                },
+            )?;
+        }
         Action::InitializeWormholeReceiver {} => {
             let rpc_client = RpcClient::new(url);
             let payer =
@@ -438,6 +504,88 @@ pub fn process_write_encoded_vaa_and_post_price_update(
     Ok(price_update_keypair.pubkey())
 }
 
+/// This function posts a VAA using the new way of interacting with wormhole and then posts all the
+/// price updates of the VAA with `post_updates_batch` instructions. Returns the price update
+/// account pubkeys in the same order as the price updates.
+///
+/// The operation is split up into at least 3 transactions:
+/// 1. Creates and initializes the VAA account and writes its first part
+/// 2. Writes the remaining data of the VAA and verifies it
+/// 3. Posts the price updates against the verified VAA, `POST_UPDATES_BATCH_PER_TRANSACTION` per transaction
+pub fn process_write_encoded_vaa_and_post_price_updates_batch(
+    rpc_client: &RpcClient,
+    vaa: &[u8],
+    wormhole: Pubkey,
+    payer: &Keypair,
+    merkle_price_updates: &[MerklePriceUpdate],
+) -> Result<Vec<Pubkey>> {
+    let encoded_vaa_keypair = Keypair::new();
+
+    // Transaction 1: Create and initialize VAA
+    let init_instructions = init_encoded_vaa_and_write_initial_data_ixs(
+        &payer.pubkey(),
+        vaa,
+        &wormhole,
+        &encoded_vaa_keypair,
+    )?;
+    process_transaction(
+        rpc_client,
+        init_instructions,
+        &vec![payer, &encoded_vaa_keypair],
+    )?;
+
+    // Transaction 2: Write remaining VAA data and verify VAA
+    let mut verify_instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(600_000)];
+    verify_instructions.extend(write_remaining_data_and_verify_vaa_ixs(
+        &payer.pubkey(),
+        vaa,
+        &encoded_vaa_keypair.pubkey(),
+        wormhole,
+    )?);
+    process_transaction(rpc_client, verify_instructions, &vec![payer])?;
+
+    // Transactions 3+: Post the price updates, all the chunks use the same verified VAA
+    let price_update_keypairs: Vec<Keypair> = merkle_price_updates
+        .iter()
+        .map(|_| Keypair::new())
+        .collect();
+
+    for (update_chunk, keypair_chunk) in merkle_price_updates
+        .chunks(POST_UPDATES_BATCH_PER_TRANSACTION)
+        .zip(price_update_keypairs.chunks(POST_UPDATES_BATCH_PER_TRANSACTION))
+    {
+        let price_update_accounts: Vec<(Pubkey, bool)> = keypair_chunk
+            .iter()
+            .map(|price_update_keypair| (price_update_keypair.pubkey(), true))
+            .collect();
+        let post_instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(
+                POST_UPDATE_COMPUTE_BUDGET * update_chunk.len() as u32,
+            ),
+            pyth_solana_receiver::instruction::PostUpdatesBatch::populate(
+                payer.pubkey(),
+                payer.pubkey(),
+                encoded_vaa_keypair.pubkey(),
+                &price_update_accounts,
+                update_chunk.to_vec(),
+                get_random_treasury_id(),
+            ),
+        ];
+        let mut signers = vec![payer];
+        signers.extend(keypair_chunk.iter());
+        process_transaction(rpc_client, post_instructions, &signers)?;
+    }
+
+    let price_update_pubkeys: Vec<Pubkey> = price_update_keypairs
+        .iter()
+        .map(|price_update_keypair| price_update_keypair.pubkey())
+        .collect();
+    for price_update_pubkey in &price_update_pubkeys {
+        println!("Price update posted to account: {}", price_update_pubkey);
+    }
+    Ok(price_update_pubkeys)
+}
+
 /// This function verifies start & end VAAs from Hermes via Wormhole to produce encoded VAAs,
 /// and then posts a TWAP update using the encoded VAAs. Returns the TwapUpdate account pubkey.
 ///
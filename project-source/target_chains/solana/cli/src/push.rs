@@ -0,0 +1,455 @@
+use {
+    crate::{
+        hermes::{spawn_price_update_subscription, HermesClient},
+        init_encoded_vaa_and_write_initial_data_ixs, process_transaction,
+        write_remaining_data_and_verify_vaa_ixs,
+    },
+    anchor_client::anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas},
+    anyhow::Result,
+    pyth_solana_receiver::sdk::{deserialize_accumulator_update_data, get_random_treasury_id},
+    pyth_solana_receiver_sdk::price_update::PriceUpdateV2,
+    pythnet_sdk::{
+        messages::{FeedId, Message, PriceFeedMessage},
+        wire::{from_slice, v1::MerklePriceUpdate},
+    },
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
+        signature::Keypair, signer::Signer,
+    },
+    std::collections::HashSet,
+};
+
+/// Compute budget of each price feed updated by a `pyth_push_oracle::update_price_feeds` instruction
+const UPDATE_PRICE_FEED_COMPUTE_BUDGET: u32 = 55_000;
+/// Compute budget of a wormhole `close_encoded_vaa` instruction
+const CLOSE_ENCODED_VAA_COMPUTE_BUDGET: u32 = 30_000;
+/// Number of price updates of an `update_price_feeds` instruction that fit in a transaction alongside a `close_encoded_vaa`
+const UPDATE_PRICE_FEEDS_PER_TRANSACTION: usize = 2;
+/// Number of `close_encoded_vaa` instructions per transaction when reclaiming rent
+const CLOSE_ENCODED_VAAS_PER_TRANSACTION: usize = 10;
+
+/// When a price feed account should be updated with a new price
+#[derive(Clone, Copy, Debug)]
+pub struct UpdateRules {
+    /// Update when the price moved by at least this many basis points since the on-chain price
+    pub price_deviation_bps: u64,
+    /// Update when the on-chain price is at least this many seconds older than the new price
+    pub heartbeat_secs: u64,
+}
+
+impl UpdateRules {
+    /// Whether the on-chain price `current` should be replaced by `update`.
+    /// A price feed account that doesn't exist yet is always updated.
+    pub fn should_update(
+        &self,
+        current: Option<&PriceFeedMessage>,
+        update: &PriceFeedMessage,
+    ) -> bool {
+        let Some(current) = current else {
+            return true;
+        };
+        if update.publish_time <= current.publish_time {
+            return false;
+        }
+        if update.publish_time.abs_diff(current.publish_time) >= self.heartbeat_secs
+            || update.exponent != current.exponent
+        {
+            return true;
+        }
+        let deviation = (i128::from(update.price) - i128::from(current.price)).unsigned_abs();
+        deviation * 10_000
+            >= u128::from(self.price_deviation_bps) * u128::from(current.price.unsigned_abs())
+    }
+}
+
+/// Bids a percentile of the recent prioritization fees, doubled after each failed push and capped
+/// at a maximum
+#[derive(Debug)]
+pub struct PriorityFeeBidder {
+    percentile: u8,
+    max_priority_fee: u64,
+    consecutive_failures: u32,
+}
+
+impl PriorityFeeBidder {
+    pub fn new(percentile: u8, max_priority_fee: u64) -> Self {
+        Self {
+            percentile: percentile.min(100),
+            max_priority_fee,
+            consecutive_failures: 0,
+        }
+    }
+
+    /// The priority fee in micro-lamports per compute unit given the recent prioritization fees
+    pub fn bid(&self, recent_fees: &[u64]) -> u64 {
+        let mut recent_fees = recent_fees.to_vec();
+        recent_fees.sort_unstable();
+        let base_fee = recent_fees
+            .get((recent_fees.len().saturating_sub(1)) * usize::from(self.percentile) / 100)
+            .copied()
+            .unwrap_or(0);
+        let fee = if self.consecutive_failures == 0 {
+            base_fee
+        } else {
+            base_fee
+                .max(1)
+                .saturating_mul(2u64.saturating_pow(self.consecutive_failures))
+        };
+        fee.min(self.max_priority_fee)
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+    }
+
+    pub fn record_failure(&mut self) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+    }
+}
+
+pub struct PushConfig {
+    pub hermes_url: String,
+    pub feed_ids: Vec<FeedId>,
+    pub shard_id: u16,
+    pub update_rules: UpdateRules,
+    pub priority_fee_bidder: PriorityFeeBidder,
+}
+
+/// Keep the push oracle price feed accounts of `config.feed_ids` up to date with the price updates
+/// streamed by Hermes. This function only returns if the Hermes subscription stops.
+///
+/// Only the most recent price update is considered whenever the previous push is done, so a slow
+/// push doesn't make the daemon fall behind. The rent of the encoded VAAs left behind by failed
+/// pushes is reclaimed after every failure. Only the encoded VAAs created by this run are closed, as
+/// the payer may be shared with other processes whose encoded VAAs are still in use.
+pub fn process_push(
+    rpc_client: &RpcClient,
+    wormhole: Pubkey,
+    payer: &Keypair,
+    mut config: PushConfig,
+) -> Result<()> {
+    let mut open_encoded_vaas: HashSet<Pubkey> = HashSet::new();
+
+    let price_updates = spawn_price_update_subscription(
+        HermesClient::new(&config.hermes_url)?,
+        config.feed_ids.clone(),
+    );
+    while let Ok(accumulator_update) = price_updates.recv() {
+        let accumulator_update = price_updates
+            .try_iter()
+            .last()
+            .unwrap_or(accumulator_update);
+
+        match push_accumulator_update(
+            rpc_client,
+            wormhole,
+            payer,
+            &config,
+            accumulator_update,
+            &mut open_encoded_vaas,
+        ) {
+            Ok(()) => config.priority_fee_bidder.record_success(),
+            Err(err) => {
+                println!("Failed to push price updates: {err:?}");
+                config.priority_fee_bidder.record_failure();
+                if let Err(err) =
+                    reclaim_encoded_vaa_rent(rpc_client, wormhole, payer, &mut open_encoded_vaas)
+                {
+                    println!("Failed to reclaim encoded VAA rent: {err:?}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Push the price updates of `accumulator_update` whose price feed accounts need to be updated
+/// according to the update rules
+fn push_accumulator_update(
+    rpc_client: &RpcClient,
+    wormhole: Pubkey,
+    payer: &Keypair,
+    config: &PushConfig,
+    accumulator_update: Vec<u8>,
+    open_encoded_vaas: &mut HashSet<Pubkey>,
+) -> Result<()> {
+    let (vaa, merkle_price_updates) = deserialize_accumulator_update_data(accumulator_update)?;
+
+    let price_feed_addresses: Vec<Pubkey> = config
+        .feed_ids
+        .iter()
+        .map(|feed_id| pyth_push_oracle::sdk::get_price_feed_address(config.shard_id, *feed_id))
+        .collect();
+    let price_feed_accounts = rpc_client.get_multiple_accounts(&price_feed_addresses)?;
+
+    let mut updates_to_push: Vec<(FeedId, MerklePriceUpdate)> = vec![];
+    for merkle_price_update in merkle_price_updates {
+        let Message::PriceFeedMessage(update) =
+            from_slice::<byteorder::BigEndian, Message>(merkle_price_update.message.as_ref())?
+        else {
+            continue;
+        };
+        let Some(index) = config
+            .feed_ids
+            .iter()
+            .position(|feed_id| *feed_id == update.feed_id)
+        else {
+            continue;
+        };
+        let current = price_feed_accounts[index]
+            .as_ref()
+            .map(|account| PriceUpdateV2::try_deserialize(&mut account.data.as_slice()))
+            .transpose()?
+            .map(|price_update| price_update.price_message);
+
+        if config.update_rules.should_update(current.as_ref(), &update) {
+            updates_to_push.push((update.feed_id, merkle_price_update));
+        }
+    }
+    if updates_to_push.is_empty() {
+        return Ok(());
+    }
+
+    let recent_fees: Vec<u64> = rpc_client
+        .get_recent_prioritization_fees(&price_feed_addresses)?
+        .iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+    let priority_fee = config.priority_fee_bidder.bid(&recent_fees);
+
+    process_write_encoded_vaa_and_update_price_feeds(
+        rpc_client,
+        &vaa,
+        wormhole,
+        payer,
+        config.shard_id,
+        &updates_to_push,
+        priority_fee,
+        open_encoded_vaas,
+    )
+}
+
+/// This function posts a VAA using the new way of interacting with wormhole, updates the push oracle
+/// price feed accounts with the price updates of the VAA and closes the encoded VAA to recover its
+/// rent. Every transaction bids `priority_fee` micro-lamports per compute unit.
+///
+/// The encoded VAA account is added to `open_encoded_vaas` until it is closed, so that its rent can
+/// be reclaimed if one of the transactions fails.
+///
+/// The operation is split up into at least 3 transactions:
+/// 1. Creates and initializes the VAA account and writes its first part
+/// 2. Writes the remaining data of the VAA and verifies it
+/// 3. Updates the price feeds, a few at a time with a single instruction, and closes the VAA account
+///    in the last transaction
+#[allow(clippy::too_many_arguments)]
+pub fn process_write_encoded_vaa_and_update_price_feeds(
+    rpc_client: &RpcClient,
+    vaa: &[u8],
+    wormhole: Pubkey,
+    payer: &Keypair,
+    shard_id: u16,
+    updates: &[(FeedId, MerklePriceUpdate)],
+    priority_fee: u64,
+    open_encoded_vaas: &mut HashSet<Pubkey>,
+) -> Result<()> {
+    let encoded_vaa_keypair = Keypair::new();
+    open_encoded_vaas.insert(encoded_vaa_keypair.pubkey());
+
+    // Transaction 1: Create and initialize VAA
+    let mut init_instructions = vec![ComputeBudgetInstruction::set_compute_unit_price(
+        priority_fee,
+    )];
+    init_instructions.extend(init_encoded_vaa_and_write_initial_data_ixs(
+        &payer.pubkey(),
+        vaa,
+        &wormhole,
+        &encoded_vaa_keypair,
+    )?);
+    process_transaction(
+        rpc_client,
+        init_instructions,
+        &vec![payer, &encoded_vaa_keypair],
+    )?;
+
+    // Transaction 2: Write remaining VAA data and verify VAA
+    let mut verify_instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(600_000),
+        ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+    ];
+    verify_instructions.extend(write_remaining_data_and_verify_vaa_ixs(
+        &payer.pubkey(),
+        vaa,
+        &encoded_vaa_keypair.pubkey(),
+        wormhole,
+    )?);
+    process_transaction(rpc_client, verify_instructions, &vec![payer])?;
+
+    // Transaction 3+: Update the price feeds and close the VAA
+    let update_chunks: Vec<&[(FeedId, MerklePriceUpdate)]> =
+        updates.chunks(UPDATE_PRICE_FEEDS_PER_TRANSACTION).collect();
+    for (i, update_chunk) in update_chunks.iter().enumerate() {
+        let is_last_chunk = i + 1 == update_chunks.len();
+        let compute_unit_limit = UPDATE_PRICE_FEED_COMPUTE_BUDGET * update_chunk.len() as u32
+            + if is_last_chunk {
+                CLOSE_ENCODED_VAA_COMPUTE_BUDGET
+            } else {
+                0
+            };
+
+        let (feed_ids, merkle_price_updates): (Vec<FeedId>, Vec<MerklePriceUpdate>) =
+            update_chunk.iter().cloned().unzip();
+        let mut update_instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit),
+            ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+            pyth_push_oracle::instruction::UpdatePriceFeeds::populate(
+                payer.pubkey(),
+                encoded_vaa_keypair.pubkey(),
+                shard_id,
+                feed_ids,
+                get_random_treasury_id(),
+                merkle_price_updates,
+            ),
+        ];
+        if is_last_chunk {
+            update_instructions.push(close_encoded_vaa_ix(
+                &payer.pubkey(),
+                &encoded_vaa_keypair.pubkey(),
+                wormhole,
+            ));
+        }
+        process_transaction(rpc_client, update_instructions, &vec![payer])?;
+    }
+    open_encoded_vaas.remove(&encoded_vaa_keypair.pubkey());
+
+    for (feed_id, _) in updates {
+        println!(
+            "Price feed updated: {}",
+            pyth_push_oracle::sdk::get_price_feed_address(shard_id, *feed_id)
+        );
+    }
+    Ok(())
+}
+
+/// Close the encoded VAA accounts of `open_encoded_vaas` that still exist to recover their rent.
+/// The accounts that are closed or were never created are removed from `open_encoded_vaas`.
+pub fn reclaim_encoded_vaa_rent(
+    rpc_client: &RpcClient,
+    wormhole: Pubkey,
+    payer: &Keypair,
+    open_encoded_vaas: &mut HashSet<Pubkey>,
+) -> Result<()> {
+    let candidates: Vec<Pubkey> = open_encoded_vaas.iter().copied().collect();
+    let accounts = rpc_client.get_multiple_accounts(&candidates)?;
+    let encoded_vaas: Vec<Pubkey> = candidates
+        .iter()
+        .zip(accounts)
+        .filter_map(|(encoded_vaa, account)| {
+            if account.is_none() {
+                open_encoded_vaas.remove(encoded_vaa);
+            }
+            account.map(|_| *encoded_vaa)
+        })
+        .collect();
+
+    for encoded_vaa_chunk in encoded_vaas.chunks(CLOSE_ENCODED_VAAS_PER_TRANSACTION) {
+        let mut close_instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            CLOSE_ENCODED_VAA_COMPUTE_BUDGET * encoded_vaa_chunk.len() as u32,
+        )];
+        close_instructions.extend(
+            encoded_vaa_chunk
+                .iter()
+                .map(|encoded_vaa| close_encoded_vaa_ix(&payer.pubkey(), encoded_vaa, wormhole)),
+        );
+        process_transaction(rpc_client, close_instructions, &vec![payer])?;
+        for encoded_vaa in encoded_vaa_chunk {
+            open_encoded_vaas.remove(encoded_vaa);
+        }
+    }
+    if !encoded_vaas.is_empty() {
+        println!("Closed {} encoded VAA accounts", encoded_vaas.len());
+    }
+    Ok(())
+}
+
+/// Creates an instruction to close an encoded VAA account, recovering the rent
+pub fn close_encoded_vaa_ix(payer: &Pubkey, encoded_vaa: &Pubkey, wormhole: Pubkey) -> Instruction {
+    Instruction {
+        program_id: wormhole,
+        accounts: wormhole_core_bridge_solana::accounts::CloseEncodedVaa {
+            write_authority: *payer,
+            encoded_vaa: *encoded_vaa,
+        }
+        .to_account_metas(None),
+        data: wormhole_core_bridge_solana::instruction::CloseEncodedVaa {}.data(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_feed_message(price: i64, exponent: i32, publish_time: i64) -> PriceFeedMessage {
+        PriceFeedMessage {
+            feed_id: [1; 32],
+            price,
+            conf: 1,
+            exponent,
+            publish_time,
+            prev_publish_time: publish_time - 1,
+            ema_price: price,
+            ema_conf: 1,
+        }
+    }
+
+    #[test]
+    fn test_should_update() {
+        let update_rules = UpdateRules {
+            price_deviation_bps: 100,
+            heartbeat_secs: 60,
+        };
+        let current = price_feed_message(10_000, -2, 100);
+
+        // the account doesn't exist yet
+        assert!(update_rules.should_update(None, &current));
+        // the update isn't newer than the on-chain price
+        assert!(!update_rules.should_update(Some(&current), &price_feed_message(20_000, -2, 100)));
+        assert!(!update_rules.should_update(Some(&current), &price_feed_message(20_000, -2, 99)));
+        // the price didn't move enough
+        assert!(!update_rules.should_update(Some(&current), &price_feed_message(10_099, -2, 101)));
+        assert!(!update_rules.should_update(Some(&current), &price_feed_message(9_901, -2, 101)));
+        // the price moved by 1%
+        assert!(update_rules.should_update(Some(&current), &price_feed_message(10_100, -2, 101)));
+        assert!(update_rules.should_update(Some(&current), &price_feed_message(9_900, -2, 101)));
+        // the heartbeat is due
+        assert!(!update_rules.should_update(Some(&current), &price_feed_message(10_000, -2, 159)));
+        assert!(update_rules.should_update(Some(&current), &price_feed_message(10_000, -2, 160)));
+        // the prices can't be compared
+        assert!(update_rules.should_update(Some(&current), &price_feed_message(10_000, -3, 101)));
+    }
+
+    #[test]
+    fn test_priority_fee_bidder() {
+        let mut bidder = PriorityFeeBidder::new(50, 1_000);
+
+        assert_eq!(bidder.bid(&[]), 0);
+        assert_eq!(bidder.bid(&[30, 10, 20]), 20);
+        assert_eq!(bidder.bid(&[30, 10, 20, 2_000]), 20);
+
+        bidder.record_failure();
+        assert_eq!(bidder.bid(&[]), 2);
+        assert_eq!(bidder.bid(&[30, 10, 20]), 40);
+        bidder.record_failure();
+        assert_eq!(bidder.bid(&[30, 10, 20]), 80);
+        for _ in 0..100 {
+            bidder.record_failure();
+        }
+        assert_eq!(bidder.bid(&[30, 10, 20]), 1_000);
+
+        bidder.record_success();
+        assert_eq!(bidder.bid(&[30, 10, 20]), 20);
+
+        assert_eq!(PriorityFeeBidder::new(100, 1_000).bid(&[30, 10, 20]), 30);
+        assert_eq!(PriorityFeeBidder::new(0, 1_000).bid(&[30, 10, 20]), 10);
+    }
+}
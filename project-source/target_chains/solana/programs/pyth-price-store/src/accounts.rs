@@ -3,6 +3,7 @@ use {bytemuck::from_bytes, std::mem::size_of};
 pub mod buffer;
 pub mod config;
 pub mod errors;
+pub mod history;
 pub mod publisher_config;
 
 fn format(data: &[u8]) -> Option<u32> {
@@ -7,6 +7,9 @@ use {
 /// Account Magic to avoid Account Confusiong
 const FORMAT: u32 = 2848712303;
 
+/// The highest trading status known to the oracle (unknown, trading, halted, auction, ignored).
+pub const MAX_TRADING_STATUS: u32 = 4;
+
 /// A publisher's buffer account. This account acts as the buffer for storing prices sent
 /// by publishers. It tracks the slot in which it is collecting in order
 /// to allow the validator to stay in sync.
@@ -75,6 +78,29 @@ impl BufferedPrice {
     }
 }
 
+/// Verifies the values of submitted prices. Returns the prices if they're all valid:
+/// each price must have a feed index, a known trading status and a feed index that
+/// isn't used by another price of the submission.
+/// This is more expensive than `update`, so it's only used when the prices are also
+/// recorded in the publisher's history.
+pub fn validate_prices(new_prices: &[u8]) -> Result<&[BufferedPrice], PublisherPriceError> {
+    if new_prices.len() % size_of::<BufferedPrice>() != 0 {
+        return Err(PublisherPriceError);
+    }
+    let prices: &[BufferedPrice] = cast_slice(new_prices);
+    for (i, price) in prices.iter().enumerate() {
+        if price.feed_index() == 0
+            || price.trading_status() > MAX_TRADING_STATUS
+            || prices[..i]
+                .iter()
+                .any(|other| other.feed_index() == price.feed_index())
+        {
+            return Err(PublisherPriceError);
+        }
+    }
+    Ok(prices)
+}
+
 /// Verifies the account magic.
 pub fn format_matches(data: &[u8]) -> bool {
     super::format(data).map_or(false, |f| f == FORMAT)
@@ -205,3 +231,20 @@ fn test_extend_clears_old_prices() {
     }
     assert_eq!(read(&buf).unwrap().1.len(), 3);
 }
+
+#[test]
+fn test_validate_prices() {
+    let prices = [
+        BufferedPrice::new(1, 2, 200, 3).unwrap(),
+        BufferedPrice::new(2, 3, 300, 4).unwrap(),
+    ];
+    assert_eq!(validate_prices(cast_slice(&prices)).unwrap(), &prices);
+    assert!(validate_prices(&cast_slice(&prices)[1..]).is_err());
+    assert!(validate_prices(&[0; 20]).is_err());
+
+    let unknown_trading_status = BufferedPrice::new(3, MAX_TRADING_STATUS + 1, 300, 4).unwrap();
+    assert!(validate_prices(cast_slice(&[prices[0], unknown_trading_status])).is_err());
+
+    let duplicate_feed_index = BufferedPrice::new(1, 1, 250, 4).unwrap();
+    assert!(validate_prices(cast_slice(&[prices[0], prices[1], duplicate_feed_index])).is_err());
+}
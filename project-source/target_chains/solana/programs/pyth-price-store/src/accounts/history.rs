@@ -0,0 +1,212 @@
+use {
+    super::{
+        buffer::BufferedPrice,
+        errors::{ExtendError, ReadAccountError},
+    },
+    bytemuck::{cast_slice, cast_slice_mut, from_bytes, from_bytes_mut, Pod, Zeroable},
+    std::mem::size_of,
+};
+
+/// Account Magic to avoid Account Confusiong
+const FORMAT: u32 = 3413584217;
+
+/// A publisher's optional history account. This account acts as a ring buffer
+/// of the last prices submitted by the publisher, together with the slot
+/// in which they were submitted, so that publisher behavior can be audited.
+/// The buffer holds a fixed number of entries, not of slots: it covers fewer slots
+/// when the publisher submits many prices per slot.
+/// Like the buffer account, it's not a PDA so that it can hold a long history,
+/// but it's owned by the publisher program.
+/// The account's data is represented by a `HistoryHeader` followed by
+/// `capacity` `HistoryEntry`s, where the capacity is determined by the account size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Zeroable, Pod)]
+#[repr(C, packed)]
+pub struct HistoryHeader {
+    /// Account magic to avoid account confusion.
+    pub format: u32,
+    /// The publisher this history is associated with.
+    pub publisher: [u8; 32],
+    /// The index of the entry that will be overwritten by the next submitted price.
+    pub next_index: u32,
+    /// The number of entries currently stored in the account.
+    /// Never exceeds the capacity of the account.
+    pub num_entries: u32,
+}
+
+impl HistoryHeader {
+    pub fn new(publisher: [u8; 32]) -> Self {
+        HistoryHeader {
+            format: FORMAT,
+            publisher,
+            next_index: 0,
+            num_entries: 0,
+        }
+    }
+}
+
+/// A price submitted by the publisher in `slot`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Zeroable, Pod)]
+#[repr(C, packed)]
+pub struct HistoryEntry {
+    pub slot: u64,
+    pub price: BufferedPrice,
+}
+
+/// Verifies the account magic.
+pub fn format_matches(data: &[u8]) -> bool {
+    super::format(data).map_or(false, |f| f == FORMAT)
+}
+
+/// Returns the account size required to hold the specified number of entries.
+pub fn size(capacity: usize) -> usize {
+    size_of::<HistoryHeader>() + capacity * size_of::<HistoryEntry>()
+}
+
+fn split(data: &[u8]) -> Result<(&HistoryHeader, &[HistoryEntry]), ReadAccountError> {
+    if data.len() < size_of::<HistoryHeader>() {
+        return Err(ReadAccountError::DataTooShort);
+    }
+    let (header, entries) = data.split_at(size_of::<HistoryHeader>());
+    let header: &HistoryHeader = from_bytes(header);
+    if header.format != FORMAT {
+        return Err(ReadAccountError::FormatMismatch);
+    }
+    let capacity = entries.len() / size_of::<HistoryEntry>();
+    let entries: &[HistoryEntry] = cast_slice(&entries[..capacity * size_of::<HistoryEntry>()]);
+    let num_entries: usize = header.num_entries.try_into().unwrap();
+    let next_index: usize = header.next_index.try_into().unwrap();
+    if num_entries > capacity || (capacity > 0 && next_index >= capacity) {
+        return Err(ReadAccountError::InvalidNumPrices);
+    }
+    Ok((header, entries))
+}
+
+/// Verifies the account size and header. Returns the header and the stored entries,
+/// oldest first, as two slices: the entries of the second slice are more recent
+/// than the entries of the first one.
+pub fn read(
+    data: &[u8],
+) -> Result<(&HistoryHeader, &[HistoryEntry], &[HistoryEntry]), ReadAccountError> {
+    let (header, entries) = split(data)?;
+    let num_entries: usize = header.num_entries.try_into().unwrap();
+    let next_index: usize = header.next_index.try_into().unwrap();
+    if num_entries < entries.len() {
+        // The ring buffer hasn't wrapped around yet.
+        Ok((header, &entries[..num_entries], &[]))
+    } else {
+        let (newest, oldest) = entries.split_at(next_index);
+        Ok((header, oldest, newest))
+    }
+}
+
+/// Returns up to `limit` of the most recent entries, oldest first.
+pub fn latest(data: &[u8], limit: usize) -> Result<Vec<HistoryEntry>, ReadAccountError> {
+    let (_, older, newer) = read(data)?;
+    let skip = (older.len() + newer.len()).saturating_sub(limit);
+    Ok(older.iter().chain(newer).skip(skip).copied().collect())
+}
+
+/// Verifies the account size and header. Returns the header and the entries space.
+pub fn read_mut(
+    data: &mut [u8],
+) -> Result<(&mut HistoryHeader, &mut [HistoryEntry]), ReadAccountError> {
+    split(data)?;
+    let (header, entries) = data.split_at_mut(size_of::<HistoryHeader>());
+    let capacity = entries.len() / size_of::<HistoryEntry>();
+    Ok((
+        from_bytes_mut(header),
+        cast_slice_mut(&mut entries[..capacity * size_of::<HistoryEntry>()]),
+    ))
+}
+
+/// Initializes the history. The account must be able to hold at least one entry.
+pub fn create(
+    data: &mut [u8],
+    publisher: [u8; 32],
+) -> Result<&mut HistoryHeader, ReadAccountError> {
+    if data.len() < size(1) {
+        return Err(ReadAccountError::DataTooShort);
+    }
+    let header: &mut HistoryHeader = from_bytes_mut(&mut data[..size_of::<HistoryHeader>()]);
+    if header.format != 0 {
+        return Err(ReadAccountError::AlreadyInitialized);
+    }
+    *header = HistoryHeader::new(publisher);
+    Ok(header)
+}
+
+/// Appends an entry to the history for each price submitted in `slot`, overwriting the oldest
+/// entries if the history is full. Only the last `capacity` entries are kept, whichever slots
+/// they were submitted in.
+pub fn append_entries(
+    header: &mut HistoryHeader,
+    entries: &mut [HistoryEntry],
+    slot: u64,
+    new_prices: &[BufferedPrice],
+) -> Result<(), ExtendError> {
+    if entries.is_empty() {
+        return Err(ExtendError::NotEnoughSpace);
+    }
+    let mut next_index: usize = header.next_index.try_into().unwrap();
+    let mut num_entries: usize = header.num_entries.try_into().unwrap();
+    for price in new_prices {
+        entries[next_index] = HistoryEntry {
+            slot,
+            price: *price,
+        };
+        next_index = (next_index + 1) % entries.len();
+        num_entries = (num_entries + 1).min(entries.len());
+    }
+    header.next_index = next_index.try_into().expect("unexpected overflow");
+    header.num_entries = num_entries.try_into().expect("unexpected overflow");
+    Ok(())
+}
+
+#[test]
+fn test_entry_size() {
+    assert_eq!(size(0), 44);
+    assert_eq!(size(1000), 28044);
+}
+
+#[test]
+fn test_append_wraps_around() {
+    let mut buf = vec![0u8; size(3)];
+    create(&mut buf, Default::default()).unwrap();
+    assert!(latest(&buf, 10).unwrap().is_empty());
+
+    let prices: Vec<_> = (1..=5)
+        .map(|i| BufferedPrice::new(i, 1, i.into(), 1).unwrap())
+        .collect();
+    {
+        let (header, entries) = read_mut(&mut buf).unwrap();
+        append_entries(header, entries, 1, &prices[..2]).unwrap();
+    }
+    let history = latest(&buf, 10).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].price, prices[0]);
+    assert_eq!(history[1].price, prices[1]);
+
+    {
+        let (header, entries) = read_mut(&mut buf).unwrap();
+        append_entries(header, entries, 2, &prices[2..]).unwrap();
+    }
+    let history = latest(&buf, 10).unwrap();
+    assert_eq!(
+        history.iter().map(|e| e.price).collect::<Vec<_>>(),
+        &prices[2..]
+    );
+    assert_eq!(
+        history.iter().map(|e| e.slot).collect::<Vec<_>>(),
+        vec![2, 2, 2]
+    );
+    assert_eq!(latest(&buf, 1).unwrap()[0].price, prices[4]);
+}
+
+#[test]
+fn test_create_requires_space() {
+    let mut buf = vec![0u8; size(0)];
+    assert!(matches!(
+        create(&mut buf, Default::default()),
+        Err(ReadAccountError::DataTooShort)
+    ));
+}
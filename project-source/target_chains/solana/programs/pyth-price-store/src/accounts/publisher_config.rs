@@ -26,6 +26,10 @@ pub struct PublisherConfig {
 
 pub const SIZE: usize = size_of::<PublisherConfig>();
 
+/// Size of a publisher config followed by the key of the publisher's history account.
+/// Publisher configs are only extended to this size when their history is initialized.
+pub const SIZE_WITH_HISTORY: usize = SIZE + 32;
+
 pub fn read(data: &[u8]) -> Result<&PublisherConfig, ReadAccountError> {
     if data.len() < size_of::<PublisherConfig>() {
         return Err(ReadAccountError::DataTooShort);
@@ -54,3 +58,50 @@ pub fn create(
     data.buffer_account = buffer_account;
     Ok(data)
 }
+
+/// Returns the key of the publisher's history account, stored right after the `PublisherConfig`,
+/// if the history has been initialized.
+pub fn history_account(data: &[u8]) -> Option<[u8; 32]> {
+    let history_account: [u8; 32] = data.get(SIZE..SIZE_WITH_HISTORY)?.try_into().ok()?;
+    if history_account == [0; 32] {
+        None
+    } else {
+        Some(history_account)
+    }
+}
+
+/// Stores the key of the publisher's history account. The account data must have been
+/// extended to `SIZE_WITH_HISTORY`. The history account can only be set once.
+pub fn set_history_account(
+    data: &mut [u8],
+    history_account: [u8; 32],
+) -> Result<(), ReadAccountError> {
+    read(data)?;
+    let stored = data
+        .get_mut(SIZE..SIZE_WITH_HISTORY)
+        .ok_or(ReadAccountError::DataTooShort)?;
+    if stored.iter().any(|byte| *byte != 0) {
+        return Err(ReadAccountError::AlreadyInitialized);
+    }
+    stored.copy_from_slice(&history_account);
+    Ok(())
+}
+
+#[test]
+fn test_history_account() {
+    let mut buf = vec![0u8; SIZE_WITH_HISTORY];
+    create(&mut buf, [1; 32], [2; 32]).unwrap();
+    assert_eq!(history_account(&buf), None);
+    assert_eq!(history_account(&buf[..SIZE]), None);
+
+    set_history_account(&mut buf, [3; 32]).unwrap();
+    assert_eq!(history_account(&buf), Some([3; 32]));
+    assert!(matches!(
+        set_history_account(&mut buf, [4; 32]),
+        Err(ReadAccountError::AlreadyInitialized)
+    ));
+    assert!(matches!(
+        set_history_account(&mut buf[..SIZE], [4; 32]),
+        Err(ReadAccountError::DataTooShort)
+    ));
+}
@@ -7,6 +7,10 @@ pub const CONFIG_SEED: &str = "CONFIG";
 /// write their updates into.
 pub const PUBLISHER_CONFIG_SEED: &str = "PUBLISHER_CONFIG";
 
+/// Maximum number of history entries returned by `GetPublisherHistory`,
+/// limited by the size of the return data.
+pub const MAX_HISTORY_ENTRIES_PER_READ: u32 = 36;
+
 #[repr(u8)]
 pub enum Instruction {
     // key[0] payer     [signer writable]
@@ -16,6 +20,7 @@ pub enum Instruction {
     // key[0] publisher        [signer writable]
     // key[1] publisher_config []
     // key[2] buffer           [writable]
+    // key[3] history          [writable] (required if the publisher has a history account)
     SubmitPrices,
     // key[0] autority         [signer writable]
     // key[1] config           []
@@ -23,6 +28,14 @@ pub enum Instruction {
     // key[3] buffer           [writable]
     // key[4] system           []
     InitializePublisher,
+    // key[0] autority         [signer writable]
+    // key[1] config           []
+    // key[2] publisher_config [writable]
+    // key[3] history          [writable]
+    // key[4] system           []
+    InitializePublisherHistory,
+    // key[0] history          []
+    GetPublisherHistory,
 }
 
 #[cfg(feature = "solana-program")]
@@ -38,6 +51,8 @@ impl Instruction {
             0 => Instruction::Initialize,
             1 => Instruction::SubmitPrices,
             2 => Instruction::InitializePublisher,
+            3 => Instruction::InitializePublisherHistory,
+            4 => Instruction::GetPublisherHistory,
             _ => return Err(solana_program::program_error::ProgramError::InvalidInstructionData),
         };
         Ok((instruction, payload))
@@ -71,3 +86,21 @@ pub struct SubmitPricesArgsHeader {
     /// PDA bump of the publisher config account.
     pub publisher_config_bump: u8,
 }
+
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+#[repr(C, packed)]
+pub struct InitializePublisherHistoryArgs {
+    /// PDA bump of the config account.
+    pub config_bump: u8,
+    /// PDA bump of the publisher config account.
+    pub publisher_config_bump: u8,
+    /// The publisher whose submissions will be recorded.
+    pub publisher: [u8; 32],
+}
+
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+#[repr(C, packed)]
+pub struct GetPublisherHistoryArgs {
+    /// Maximum number of entries to return, capped by `MAX_HISTORY_ENTRIES_PER_READ`.
+    pub limit: u32,
+}
@@ -1,17 +1,22 @@
+mod get_publisher_history;
 mod initialize;
 mod initialize_publisher;
+mod initialize_publisher_history;
 mod submit_prices;
 
 use {
     crate::{
         ensure,
         instruction::{
-            InitializeArgs, InitializePublisherArgs, Instruction, SubmitPricesArgsHeader,
+            GetPublisherHistoryArgs, InitializeArgs, InitializePublisherArgs,
+            InitializePublisherHistoryArgs, Instruction, SubmitPricesArgsHeader,
         },
     },
     bytemuck::try_from_bytes,
+    get_publisher_history::get_publisher_history,
     initialize::initialize,
     initialize_publisher::initialize_publisher,
+    initialize_publisher_history::initialize_publisher_history,
     solana_program::{
         account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
         pubkey::Pubkey,
@@ -48,5 +53,15 @@ pub fn process_instruction(
                 try_from_bytes(payload).map_err(|_| ProgramError::InvalidInstructionData)?;
             initialize_publisher(program_id, accounts, args)
         }
+        Instruction::InitializePublisherHistory => {
+            let args: &InitializePublisherHistoryArgs =
+                try_from_bytes(payload).map_err(|_| ProgramError::InvalidInstructionData)?;
+            initialize_publisher_history(program_id, accounts, args)
+        }
+        Instruction::GetPublisherHistory => {
+            let args: &GetPublisherHistoryArgs =
+                try_from_bytes(payload).map_err(|_| ProgramError::InvalidInstructionData)?;
+            get_publisher_history(program_id, accounts, args)
+        }
     }
 }
@@ -0,0 +1,34 @@
+use {
+    crate::{
+        accounts::history,
+        ensure,
+        instruction::{GetPublisherHistoryArgs, MAX_HISTORY_ENTRIES_PER_READ},
+    },
+    bytemuck::cast_slice,
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program::set_return_data,
+        program_error::ProgramError, pubkey::Pubkey,
+    },
+};
+
+/// Returns the most recent entries of a publisher's history account, oldest first,
+/// as the return data of the instruction. The entries are encoded as consecutive
+/// `HistoryEntry`s. At most `MAX_HISTORY_ENTRIES_PER_READ` entries are returned.
+/// See `Instruction` for the list of required accounts.
+/// The history account must be an initialized history account owned by the program.
+/// Off-chain clients can read the account directly with `accounts::history::latest`.
+pub fn get_publisher_history(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: &GetPublisherHistoryArgs,
+) -> ProgramResult {
+    let history = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    ensure!(ProgramError::IllegalOwner, history.owner == program_id);
+    let limit = args.limit.min(MAX_HISTORY_ENTRIES_PER_READ);
+    let entries = history::latest(
+        &history.data.borrow(),
+        limit.try_into().expect("unexpected overflow"),
+    )?;
+    set_return_data(cast_slice(&entries));
+    Ok(())
+}
@@ -0,0 +1,362 @@
+use {
+    crate::{
+        accounts::{history, publisher_config},
+        ensure,
+        instruction::InitializePublisherHistoryArgs,
+        validate::{
+            validate_authority, validate_config, validate_history,
+            validate_publisher_config_for_access, validate_system,
+        },
+    },
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program::invoke,
+        program_error::ProgramError, program_memory::sol_memcmp, pubkey::Pubkey, rent::Rent,
+        system_instruction, sysvar::Sysvar,
+    },
+};
+
+/// Verifies and initializes the history account of an initialized publisher,
+/// and stores the history key in the publisher config account.
+/// See `Instruction` for the list of required accounts.
+/// The config account must be an initialized PDA account with an expected seed.
+/// The authority account that signed the instruction
+/// must match the authority key stored in the config account.
+/// The publisher config account must be an initialized PDA account with an expected seed.
+/// It's extended to hold the history key, the authority pays for the additional rent.
+/// The history account must be an existing, zero-filled account owned by the program
+/// that can hold at least one entry. Its size determines how many entries it retains.
+/// Note: a publisher can only have one history account. Once it's set, `SubmitPrices`
+/// requires it, so every submission of the publisher is recorded.
+pub fn initialize_publisher_history(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: &InitializePublisherHistoryArgs,
+) -> ProgramResult {
+    let mut accounts = accounts.iter();
+    let first_account = accounts.next();
+    let config = validate_config(accounts.next(), args.config_bump, program_id, false)?;
+    let authority = validate_authority(first_account, config)?;
+    let publisher_config = validate_publisher_config_for_access(
+        accounts.next(),
+        args.publisher_config_bump,
+        &args.publisher.into(),
+        program_id,
+    )?;
+    ensure!(ProgramError::InvalidArgument, publisher_config.is_writable);
+    let history = validate_history(accounts.next(), program_id)?;
+    let system = validate_system(accounts.next())?;
+
+    {
+        let publisher_config_data = publisher_config.data.borrow();
+        let publisher_config = publisher_config::read(*publisher_config_data)?;
+        ensure!(
+            ProgramError::InvalidArgument,
+            sol_memcmp(&args.publisher, &publisher_config.publisher, 32) == 0
+        );
+    }
+
+    // Publisher configs are created without room for the history key.
+    let rent = Rent::get()?;
+    if publisher_config.data_len() < publisher_config::SIZE_WITH_HISTORY {
+        let lamports = rent
+            .minimum_balance(publisher_config::SIZE_WITH_HISTORY)
+            .saturating_sub(publisher_config.lamports());
+        if lamports > 0 {
+            invoke(
+                &system_instruction::transfer(authority.key, publisher_config.key, lamports),
+                &[authority.clone(), publisher_config.clone(), system.clone()],
+            )?;
+        }
+        publisher_config.realloc(publisher_config::SIZE_WITH_HISTORY, true)?;
+    }
+    publisher_config::set_history_account(
+        *publisher_config.data.borrow_mut(),
+        history.key.to_bytes(),
+    )?;
+
+    let mut history_data = history.data.borrow_mut();
+    ensure!(
+        ProgramError::AccountNotRentExempt,
+        history.lamports() >= rent.minimum_balance(history_data.len())
+    );
+    history::create(*history_data, args.publisher)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{
+            accounts::{self, buffer::BufferedPrice, history::HistoryEntry},
+            instruction::{CONFIG_SEED, PUBLISHER_CONFIG_SEED},
+        },
+        bytemuck::cast_slice,
+        solana_program::{
+            instruction::{AccountMeta, Instruction},
+            pubkey::Pubkey,
+            system_program,
+        },
+        solana_program_test::*,
+        solana_sdk::{
+            hash::Hash,
+            rent::Rent,
+            signature::{Keypair, Signer},
+            transaction::Transaction,
+        },
+    };
+
+    async fn create_program_account(
+        banks_client: &mut BanksClient,
+        authority: &Keypair,
+        recent_blockhash: Hash,
+        seed: &str,
+        space: usize,
+        id: &Pubkey,
+    ) -> Pubkey {
+        let key = Pubkey::create_with_seed(&authority.pubkey(), seed, id).unwrap();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                solana_program::system_instruction::create_account_with_seed(
+                    &authority.pubkey(),
+                    &key,
+                    &authority.pubkey(),
+                    seed,
+                    Rent::default().minimum_balance(space),
+                    space as u64,
+                    id,
+                ),
+            ],
+            Some(&authority.pubkey()),
+        );
+        transaction.sign(&[authority], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
+        key
+    }
+
+    #[tokio::test]
+    async fn test_initialize_history_and_publish() {
+        let id = Pubkey::new_unique();
+        let (mut banks_client, authority, recent_blockhash) = ProgramTest::new(
+            "publishers",
+            id,
+            processor!(crate::processor::process_instruction),
+        )
+        .start()
+        .await;
+
+        // Setup Accounts
+        let (config, config_bump) = Pubkey::find_program_address(&[CONFIG_SEED.as_bytes()], &id);
+        let publisher = Keypair::new();
+        let (publisher_config, publisher_config_bump) = Pubkey::find_program_address(
+            &[
+                PUBLISHER_CONFIG_SEED.as_bytes(),
+                &publisher.pubkey().to_bytes(),
+            ],
+            &id,
+        );
+        let buffer_key = create_program_account(
+            &mut banks_client,
+            &authority,
+            recent_blockhash,
+            "buffer",
+            accounts::buffer::size(100),
+            &id,
+        )
+        .await;
+        let history_key = create_program_account(
+            &mut banks_client,
+            &authority,
+            recent_blockhash,
+            "history",
+            accounts::history::size(3),
+            &id,
+        )
+        .await;
+
+        // Initialize the program and the publisher, and topup the publisher account.
+        let mut initialize_data = vec![
+            crate::instruction::Instruction::Initialize as u8,
+            config_bump,
+        ];
+        initialize_data.extend_from_slice(&authority.pubkey().to_bytes());
+        let mut initialize_publisher_data = vec![
+            crate::instruction::Instruction::InitializePublisher as u8,
+            config_bump,
+            publisher_config_bump,
+        ];
+        initialize_publisher_data.extend_from_slice(&publisher.pubkey().to_bytes());
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                Instruction {
+                    program_id: id,
+                    data: initialize_data,
+                    accounts: vec![
+                        AccountMeta::new_readonly(authority.pubkey(), true),
+                        AccountMeta::new(config, false),
+                        AccountMeta::new_readonly(system_program::id(), false),
+                    ],
+                },
+                Instruction {
+                    program_id: id,
+                    data: initialize_publisher_data,
+                    accounts: vec![
+                        AccountMeta::new(authority.pubkey(), true),
+                        AccountMeta::new_readonly(config, false),
+                        AccountMeta::new(publisher_config, false),
+                        AccountMeta::new(buffer_key, false),
+                        AccountMeta::new_readonly(system_program::id(), false),
+                    ],
+                },
+                solana_program::system_instruction::transfer(
+                    &authority.pubkey(),
+                    &publisher.pubkey(),
+                    1_000_000_000,
+                ),
+            ],
+            Some(&authority.pubkey()),
+        );
+        transaction.sign(&[&authority], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        // Initialize the publisher's history.
+        let mut data = vec![
+            crate::instruction::Instruction::InitializePublisherHistory as u8,
+            config_bump,
+            publisher_config_bump,
+        ];
+        data.extend_from_slice(&publisher.pubkey().to_bytes());
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction {
+                program_id: id,
+                data,
+                accounts: vec![
+                    AccountMeta::new(authority.pubkey(), true),
+                    AccountMeta::new_readonly(config, false),
+                    AccountMeta::new(publisher_config, false),
+                    AccountMeta::new(history_key, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+            }],
+            Some(&authority.pubkey()),
+        );
+        transaction.sign(&[&authority], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        {
+            let history = banks_client
+                .get_account(history_key)
+                .await
+                .unwrap()
+                .unwrap();
+            let (header, older, newer) = accounts::history::read(&history.data).unwrap();
+            assert_eq!(&header.publisher, &publisher.pubkey().to_bytes());
+            assert!(older.is_empty() && newer.is_empty());
+
+            let publisher_config = banks_client
+                .get_account(publisher_config)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                accounts::publisher_config::history_account(&publisher_config.data),
+                Some(history_key.to_bytes())
+            );
+        }
+
+        let submit_prices = |prices: &[BufferedPrice], with_history: bool| {
+            let mut data = vec![
+                crate::instruction::Instruction::SubmitPrices as u8,
+                publisher_config_bump,
+            ];
+            data.extend_from_slice(cast_slice(prices));
+            let mut transaction = Transaction::new_with_payer(
+                &[Instruction {
+                    program_id: id,
+                    data,
+                    accounts: [
+                        AccountMeta::new(publisher.pubkey(), true),
+                        AccountMeta::new_readonly(publisher_config, false),
+                        AccountMeta::new(buffer_key, false),
+                        AccountMeta::new(history_key, false),
+                    ][..if with_history { 4 } else { 3 }]
+                        .to_vec(),
+                }],
+                Some(&publisher.pubkey()),
+            );
+            transaction.sign(&[&publisher], recent_blockhash);
+            transaction
+        };
+
+        // Invalid prices are rejected when the history is recorded.
+        let mut invalid_price = BufferedPrice::new(1, 2, 200, 3).unwrap();
+        invalid_price.trading_status_and_feed_index = 0;
+        assert!(banks_client
+            .process_transaction(submit_prices(&[invalid_price], true))
+            .await
+            .is_err());
+
+        // The history account is required once it's initialized.
+        let price = BufferedPrice::new(1, 2, 200, 3).unwrap();
+        assert!(banks_client
+            .process_transaction(submit_prices(&[price], false))
+            .await
+            .is_err());
+
+        // The history only retains the most recent entries.
+        let prices = [
+            BufferedPrice::new(1, 2, 200, 3).unwrap(),
+            BufferedPrice::new(2, 3, 300, 4).unwrap(),
+            BufferedPrice::new(3, 3, 400, 5).unwrap(),
+            BufferedPrice::new(4, 3, 500, 6).unwrap(),
+        ];
+        banks_client
+            .process_transaction(submit_prices(&prices, true))
+            .await
+            .unwrap();
+
+        {
+            let history = banks_client
+                .get_account(history_key)
+                .await
+                .unwrap()
+                .unwrap();
+            let entries = accounts::history::latest(&history.data, 10).unwrap();
+            assert_eq!(
+                entries.iter().map(|entry| entry.price).collect::<Vec<_>>(),
+                &prices[1..]
+            );
+            assert!(entries.iter().all(|entry| { entry.slot } != 0));
+
+            let buffer = banks_client.get_account(buffer_key).await.unwrap().unwrap();
+            assert_eq!(accounts::buffer::read(&buffer.data).unwrap().1, &prices[..]);
+        }
+
+        // The same entries are returned by `GetPublisherHistory`, limited to the requested number.
+        for (limit, expected) in [(10, &prices[1..]), (2, &prices[2..])] {
+            let mut data = vec![crate::instruction::Instruction::GetPublisherHistory as u8];
+            data.extend_from_slice(&u32::to_le_bytes(limit));
+            let mut transaction = Transaction::new_with_payer(
+                &[Instruction {
+                    program_id: id,
+                    data,
+                    accounts: vec![AccountMeta::new_readonly(history_key, false)],
+                }],
+                Some(&authority.pubkey()),
+            );
+            transaction.sign(&[&authority], recent_blockhash);
+            let simulation = banks_client
+                .simulate_transaction(transaction)
+                .await
+                .unwrap();
+            assert!(simulation.result.unwrap().is_ok());
+            let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+            assert_eq!(return_data.program_id, id);
+            let entries: &[HistoryEntry] = cast_slice(&return_data.data);
+            assert_eq!(
+                entries.iter().map(|entry| entry.price).collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+}
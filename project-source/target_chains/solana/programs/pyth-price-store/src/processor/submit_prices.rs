@@ -1,9 +1,12 @@
 use {
     crate::{
-        accounts::{buffer, publisher_config},
+        accounts::{buffer, history, publisher_config},
         ensure,
         instruction::SubmitPricesArgsHeader,
-        validate::{validate_buffer, validate_publisher, validate_publisher_config_for_access},
+        validate::{
+            validate_buffer, validate_history, validate_publisher,
+            validate_publisher_config_for_access,
+        },
     },
     solana_program::{
         account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
@@ -19,6 +22,9 @@ use {
 /// The publisher config account must be an initialized PDA account with an expected seed
 /// (depending on the publisher account that signed the instruction).
 /// The buffer account must match the buffer key stored in the publisher config account.
+/// If the publisher has a history account, it must be passed and match the history key
+/// stored in the publisher config account. The prices are then validated and appended
+/// to the history.
 pub fn submit_prices(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -36,6 +42,17 @@ pub fn submit_prices(
     let buffer = validate_buffer(accounts.next(), program_id)?;
 
     let publisher_config_data = publisher_config.data.borrow();
+    let history = match publisher_config::history_account(*publisher_config_data) {
+        Some(history_account) => {
+            let history = validate_history(accounts.next(), program_id)?;
+            ensure!(
+                ProgramError::InvalidArgument,
+                sol_memcmp(&history.key.to_bytes(), &history_account, 32) == 0
+            );
+            Some(history)
+        }
+        None => None,
+    };
     let publisher_config = publisher_config::read(*publisher_config_data)?;
     // Required to ensure that `find_program_address` returned the same account as
     // `create_program_address` in `initialize_publisher`.
@@ -51,7 +68,20 @@ pub fn submit_prices(
     // Access and update PublisherPrices account with new data.
     let mut buffer_data = buffer.data.borrow_mut();
     let (header, prices) = buffer::read_mut(*buffer_data)?;
-    buffer::update(header, prices, Clock::get()?.slot, prices_data)?;
+    let slot = Clock::get()?.slot;
+    buffer::update(header, prices, slot, prices_data)?;
+
+    if let Some(history) = history {
+        let new_prices = buffer::validate_prices(prices_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        let mut history_data = history.data.borrow_mut();
+        let (header, entries) = history::read_mut(*history_data)?;
+        ensure!(
+            ProgramError::InvalidArgument,
+            sol_memcmp(&publisher.key.to_bytes(), &header.publisher, 32) == 0
+        );
+        history::append_entries(header, entries, slot, new_prices)?;
+    }
 
     Ok(())
 }
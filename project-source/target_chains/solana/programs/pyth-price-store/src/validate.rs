@@ -137,3 +137,13 @@ pub fn validate_buffer<'a, 'b>(
     ensure!(ProgramError::IllegalOwner, buffer.owner == program_id);
     Ok(buffer)
 }
+
+pub fn validate_history<'a, 'b>(
+    account: Option<&'b AccountInfo<'a>>,
+    program_id: &Pubkey,
+) -> Result<&'b AccountInfo<'a>, ProgramError> {
+    let history = account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+    ensure!(ProgramError::InvalidArgument, history.is_writable);
+    ensure!(ProgramError::IllegalOwner, history.owner == program_id);
+    Ok(history)
+}
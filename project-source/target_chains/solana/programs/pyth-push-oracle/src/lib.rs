@@ -4,12 +4,14 @@
 use {
     anchor_lang::prelude::*,
     pyth_solana_receiver_sdk::{
-        cpi::accounts::PostUpdate, price_update::PriceUpdateV2, program::PythSolanaReceiver,
-        PostUpdateParams, PYTH_PUSH_ORACLE_ID,
+        cpi::accounts::{PostTwapUpdate, PostUpdate},
+        price_update::{PriceUpdateV2, TwapUpdate},
+        program::PythSolanaReceiver,
+        PostTwapUpdateParams, PostUpdateParams, PYTH_PUSH_ORACLE_ID,
     },
     pythnet_sdk::{
         messages::{FeedId, Message},
-        wire::from_slice,
+        wire::{from_slice, v1::MerklePriceUpdate},
     },
 };
 
@@ -17,16 +19,24 @@ pub mod sdk;
 
 pub const ID: Pubkey = PYTH_PUSH_ORACLE_ID;
 
+pub const TWAP_SEED: &str = "twap";
+
 #[error_code]
 pub enum PushOracleError {
     #[msg("Updates must be monotonically increasing")]
     UpdatesNotMonotonic,
     #[msg("Trying to update price feed with the wrong feed id")]
     PriceFeedMessageMismatch,
-    #[msg("The message in the update must be a PriceFeedMessage")]
+    #[msg(
+        "The message in the update must be a PriceFeedMessage, or a TwapMessage for TWAP updates"
+    )]
     UnsupportedMessageType,
     #[msg("Could not deserialize the message in the update")]
     DeserializeMessageFailed,
+    #[msg("The number of price feed accounts or feed ids doesn't match the number of updates")]
+    PriceFeedAccountsMismatch,
+    #[msg("The price feed account doesn't match the PDA derivation")]
+    InvalidPriceFeedAccount,
 }
 #[program]
 pub mod pyth_push_oracle {
@@ -49,53 +59,136 @@ pub mod pyth_push_oracle {
             write_authority: ctx.accounts.price_feed_account.to_account_info().clone(),
         };
 
+        post_price_feed_update(
+            cpi_program,
+            cpi_accounts,
+            params,
+            shard_id,
+            feed_id,
+            *ctx.bumps.get("price_feed_account").unwrap(),
+        )
+    }
+
+    /// Update several price feeds of the same shard in one instruction.
+    /// The price feed accounts are passed as the remaining accounts, in the same order as `feed_ids` and `merkle_price_updates`.
+    /// Like in `update_price_feed`, pushing a stale price to a price feed succeeds without changing it.
+    pub fn update_price_feeds<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdatePriceFeeds<'info>>,
+        merkle_price_updates: Vec<MerklePriceUpdate>,
+        treasury_id: u8,
+        shard_id: u16,
+        feed_ids: Vec<FeedId>,
+    ) -> Result<()> {
+        let price_feed_accounts = ctx.remaining_accounts;
+        require!(
+            price_feed_accounts.len() == merkle_price_updates.len()
+                && feed_ids.len() == merkle_price_updates.len(),
+            PushOracleError::PriceFeedAccountsMismatch
+        );
+
+        for ((price_feed_account, merkle_price_update), feed_id) in price_feed_accounts
+            .iter()
+            .zip(merkle_price_updates)
+            .zip(feed_ids)
+        {
+            let (expected_price_feed_account, bump) =
+                Pubkey::find_program_address(&[&shard_id.to_le_bytes(), feed_id.as_ref()], &ID);
+            require_keys_eq!(
+                expected_price_feed_account,
+                price_feed_account.key(),
+                PushOracleError::InvalidPriceFeedAccount
+            );
+
+            let cpi_program = ctx.accounts.pyth_solana_receiver.to_account_info().clone();
+            let cpi_accounts = PostUpdate {
+                payer: ctx.accounts.payer.to_account_info().clone(),
+                encoded_vaa: ctx.accounts.encoded_vaa.to_account_info().clone(),
+                config: ctx.accounts.config.to_account_info().clone(),
+                treasury: ctx.accounts.treasury.to_account_info().clone(),
+                price_update_account: price_feed_account.clone(),
+                system_program: ctx.accounts.system_program.to_account_info().clone(),
+                write_authority: price_feed_account.clone(),
+            };
+
+            post_price_feed_update(
+                cpi_program,
+                cpi_accounts,
+                PostUpdateParams {
+                    merkle_price_update,
+                    treasury_id,
+                },
+                shard_id,
+                feed_id,
+                bump,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Update the TWAP of a price feed over the window between the start and end updates.
+    /// Pushing a TWAP whose window doesn't end after the window of the stored TWAP succeeds without changing it.
+    pub fn update_twap_feed(
+        ctx: Context<UpdateTwapFeed>,
+        params: PostTwapUpdateParams,
+        shard_id: u16,
+        feed_id: FeedId,
+    ) -> Result<()> {
+        let cpi_program = ctx.accounts.pyth_solana_receiver.to_account_info().clone();
+        let cpi_accounts = PostTwapUpdate {
+            payer: ctx.accounts.payer.to_account_info().clone(),
+            start_encoded_vaa: ctx.accounts.start_encoded_vaa.to_account_info().clone(),
+            end_encoded_vaa: ctx.accounts.end_encoded_vaa.to_account_info().clone(),
+            config: ctx.accounts.config.to_account_info().clone(),
+            treasury: ctx.accounts.treasury.to_account_info().clone(),
+            twap_update_account: ctx.accounts.twap_feed_account.to_account_info().clone(),
+            system_program: ctx.accounts.system_program.to_account_info().clone(),
+            write_authority: ctx.accounts.twap_feed_account.to_account_info().clone(),
+        };
+
         let seeds = &[
+            TWAP_SEED.as_bytes(),
             &shard_id.to_le_bytes(),
             feed_id.as_ref(),
-            &[*ctx.bumps.get("price_feed_account").unwrap()],
+            &[*ctx.bumps.get("twap_feed_account").unwrap()],
         ];
         let signer_seeds = &[&seeds[..]];
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
-        // Get the timestamp of the price currently stored in the price feed account.
-        let current_timestamp = {
-            if ctx.accounts.price_feed_account.data_is_empty() {
+        // Get the end time of the TWAP currently stored in the TWAP feed account.
+        let current_end_time = {
+            if ctx.accounts.twap_feed_account.data_is_empty() {
                 0
             } else {
-                let price_feed_account_data = ctx.accounts.price_feed_account.try_borrow_data()?;
-                let price_feed_account =
-                    PriceUpdateV2::try_deserialize(&mut &price_feed_account_data[..])?;
-                price_feed_account.price_message.publish_time
+                let twap_feed_account_data = ctx.accounts.twap_feed_account.try_borrow_data()?;
+                let twap_feed_account =
+                    TwapUpdate::try_deserialize(&mut &twap_feed_account_data[..])?;
+                twap_feed_account.twap.end_time
             }
         };
 
-        // Get the timestamp of the price in the arguments (that we are trying to put in the account).
-        // It is a little annoying that we have to redundantly deserialize the message here, but
-        // it is required to make txs pushing stale prices succeed w/o updating the on-chain price.
-        //
-        // Note that we don't do any validity checks on the proof etc. here. If the caller passes an
-        // invalid message with a newer timestamp, the validity checks will be performed by pyth_solana_receiver.
+        // Get the end time of the TWAP in the arguments. The validity checks of the messages are
+        // performed by pyth_solana_receiver.
         let message =
-            from_slice::<byteorder::BE, Message>(params.merkle_price_update.message.as_ref())
+            from_slice::<byteorder::BE, Message>(params.end_merkle_price_update.message.as_ref())
                 .map_err(|_| PushOracleError::DeserializeMessageFailed)?;
-        let next_timestamp = match message {
-            Message::PriceFeedMessage(price_feed_message) => price_feed_message.publish_time,
-            Message::TwapMessage(_) | Message::PublisherStakeCapsMessage(_) => {
+        let next_end_time = match message {
+            Message::TwapMessage(twap_message) => twap_message.publish_time,
+            Message::PriceFeedMessage(_) | Message::PublisherStakeCapsMessage(_) => {
                 return err!(PushOracleError::UnsupportedMessageType);
             }
         };
 
-        // Only update the price feed if the message contains a newer price. Pushing a stale price
+        // Only update the TWAP feed if the message contains a newer TWAP. Pushing a stale TWAP
         // suceeds without changing the on-chain state.
-        if next_timestamp > current_timestamp {
-            pyth_solana_receiver_sdk::cpi::post_update(cpi_context, params)?;
+        if next_end_time > current_end_time {
+            pyth_solana_receiver_sdk::cpi::post_twap_update(cpi_context, params)?;
             {
-                let price_feed_account_data = ctx.accounts.price_feed_account.try_borrow_data()?;
-                let price_feed_account =
-                    PriceUpdateV2::try_deserialize(&mut &price_feed_account_data[..])?;
+                let twap_feed_account_data = ctx.accounts.twap_feed_account.try_borrow_data()?;
+                let twap_feed_account =
+                    TwapUpdate::try_deserialize(&mut &twap_feed_account_data[..])?;
 
                 require!(
-                    price_feed_account.price_message.feed_id == feed_id,
+                    twap_feed_account.twap.feed_id == feed_id,
                     PushOracleError::PriceFeedMessageMismatch
                 );
             }
@@ -104,6 +197,65 @@ pub mod pyth_push_oracle {
     }
 }
 
+fn post_price_feed_update<'info>(
+    cpi_program: AccountInfo<'info>,
+    cpi_accounts: PostUpdate<'info>,
+    params: PostUpdateParams,
+    shard_id: u16,
+    feed_id: FeedId,
+    bump: u8,
+) -> Result<()> {
+    let price_feed_account = cpi_accounts.price_update_account.clone();
+
+    let seeds = &[&shard_id.to_le_bytes(), feed_id.as_ref(), &[bump]];
+    let signer_seeds = &[&seeds[..]];
+    let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+    // Get the timestamp of the price currently stored in the price feed account.
+    let current_timestamp = {
+        if price_feed_account.data_is_empty() {
+            0
+        } else {
+            let price_feed_account_data = price_feed_account.try_borrow_data()?;
+            let price_feed_account =
+                PriceUpdateV2::try_deserialize(&mut &price_feed_account_data[..])?;
+            price_feed_account.price_message.publish_time
+        }
+    };
+
+    // Get the timestamp of the price in the arguments (that we are trying to put in the account).
+    // It is a little annoying that we have to redundantly deserialize the message here, but
+    // it is required to make txs pushing stale prices succeed w/o updating the on-chain price.
+    //
+    // Note that we don't do any validity checks on the proof etc. here. If the caller passes an
+    // invalid message with a newer timestamp, the validity checks will be performed by pyth_solana_receiver.
+    let message = from_slice::<byteorder::BE, Message>(params.merkle_price_update.message.as_ref())
+        .map_err(|_| PushOracleError::DeserializeMessageFailed)?;
+    let next_timestamp = match message {
+        Message::PriceFeedMessage(price_feed_message) => price_feed_message.publish_time,
+        Message::TwapMessage(_) | Message::PublisherStakeCapsMessage(_) => {
+            return err!(PushOracleError::UnsupportedMessageType);
+        }
+    };
+
+    // Only update the price feed if the message contains a newer price. Pushing a stale price
+    // suceeds without changing the on-chain state.
+    if next_timestamp > current_timestamp {
+        pyth_solana_receiver_sdk::cpi::post_update(cpi_context, params)?;
+        {
+            let price_feed_account_data = price_feed_account.try_borrow_data()?;
+            let price_feed_account =
+                PriceUpdateV2::try_deserialize(&mut &price_feed_account_data[..])?;
+
+            require!(
+                price_feed_account.price_message.feed_id == feed_id,
+                PushOracleError::PriceFeedMessageMismatch
+            );
+        }
+    }
+    Ok(())
+}
+
 #[derive(Accounts)]
 #[instruction(params : PostUpdateParams, shard_id : u16, feed_id : FeedId)]
 pub struct UpdatePriceFeed<'info> {
@@ -122,3 +274,39 @@ pub struct UpdatePriceFeed<'info> {
     pub price_feed_account: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
 }
+
+#[derive(Accounts)]
+pub struct UpdatePriceFeeds<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub pyth_solana_receiver: Program<'info, PythSolanaReceiver>,
+    /// CHECK: Checked by CPI into the Pyth Solana Receiver
+    pub encoded_vaa: AccountInfo<'info>,
+    /// CHECK: Checked by CPI into the Pyth Solana Receiver
+    pub config: AccountInfo<'info>,
+    /// CHECK: Checked by CPI into the Pyth Solana Receiver
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(params : PostTwapUpdateParams, shard_id : u16, feed_id : FeedId)]
+pub struct UpdateTwapFeed<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub pyth_solana_receiver: Program<'info, PythSolanaReceiver>,
+    /// CHECK: Checked by CPI into the Pyth Solana Receiver
+    pub start_encoded_vaa: AccountInfo<'info>,
+    /// CHECK: Checked by CPI into the Pyth Solana Receiver
+    pub end_encoded_vaa: AccountInfo<'info>,
+    /// CHECK: Checked by CPI into the Pyth Solana Receiver
+    pub config: AccountInfo<'info>,
+    /// CHECK: Checked by CPI into the Pyth Solana Receiver
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: This account's seeds are checked
+    #[account(mut, seeds = [TWAP_SEED.as_ref(), &shard_id.to_le_bytes(), &feed_id], bump)]
+    pub twap_feed_account: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
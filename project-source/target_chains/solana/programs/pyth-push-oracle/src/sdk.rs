@@ -1,15 +1,27 @@
 use {
-    crate::{accounts, instruction, PostUpdateParams, ID},
+    crate::{accounts, instruction, PostTwapUpdateParams, PostUpdateParams, ID, TWAP_SEED},
     anchor_lang::{prelude::*, system_program, InstructionData},
     pyth_solana_receiver_sdk::pda::{get_config_address, get_treasury_address},
     pythnet_sdk::{messages::FeedId, wire::v1::MerklePriceUpdate},
-    solana_program::instruction::Instruction,
+    solana_program::instruction::{AccountMeta, Instruction},
 };
 
 pub fn get_price_feed_address(shard_id: u16, feed_id: FeedId) -> Pubkey {
     Pubkey::find_program_address(&[&shard_id.to_le_bytes(), feed_id.as_ref()], &ID).0
 }
 
+pub fn get_twap_feed_address(shard_id: u16, feed_id: FeedId) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            TWAP_SEED.as_ref(),
+            &shard_id.to_le_bytes(),
+            feed_id.as_ref(),
+        ],
+        &ID,
+    )
+    .0
+}
+
 impl accounts::UpdatePriceFeed {
     pub fn populate(
         payer: Pubkey,
@@ -57,3 +69,107 @@ impl instruction::UpdatePriceFeed {
         }
     }
 }
+
+impl accounts::UpdatePriceFeeds {
+    pub fn populate(payer: Pubkey, encoded_vaa: Pubkey, treasury_id: u8) -> Self {
+        accounts::UpdatePriceFeeds {
+            payer,
+            encoded_vaa,
+            config: get_config_address(),
+            treasury: get_treasury_address(treasury_id),
+            pyth_solana_receiver: pyth_solana_receiver_sdk::ID,
+            system_program: system_program::ID,
+        }
+    }
+}
+
+impl instruction::UpdatePriceFeeds {
+    pub fn populate(
+        payer: Pubkey,
+        encoded_vaa: Pubkey,
+        shard_id: u16,
+        feed_ids: Vec<FeedId>,
+        treasury_id: u8,
+        merkle_price_updates: Vec<MerklePriceUpdate>,
+    ) -> Instruction {
+        let mut update_price_feeds_accounts =
+            accounts::UpdatePriceFeeds::populate(payer, encoded_vaa, treasury_id)
+                .to_account_metas(None);
+        update_price_feeds_accounts.extend(
+            feed_ids
+                .iter()
+                .map(|feed_id| AccountMeta::new(get_price_feed_address(shard_id, *feed_id), false)),
+        );
+        Instruction {
+            program_id: ID,
+            accounts: update_price_feeds_accounts,
+            data: instruction::UpdatePriceFeeds {
+                merkle_price_updates,
+                treasury_id,
+                shard_id,
+                feed_ids,
+            }
+            .data(),
+        }
+    }
+}
+
+impl accounts::UpdateTwapFeed {
+    pub fn populate(
+        payer: Pubkey,
+        start_encoded_vaa: Pubkey,
+        end_encoded_vaa: Pubkey,
+        shard_id: u16,
+        feed_id: FeedId,
+        treasury_id: u8,
+    ) -> Self {
+        accounts::UpdateTwapFeed {
+            payer,
+            start_encoded_vaa,
+            end_encoded_vaa,
+            config: get_config_address(),
+            treasury: get_treasury_address(treasury_id),
+            twap_feed_account: get_twap_feed_address(shard_id, feed_id),
+            pyth_solana_receiver: pyth_solana_receiver_sdk::ID,
+            system_program: system_program::ID,
+        }
+    }
+}
+
+impl instruction::UpdateTwapFeed {
+    #[allow(clippy::too_many_arguments)]
+    pub fn populate(
+        payer: Pubkey,
+        start_encoded_vaa: Pubkey,
+        end_encoded_vaa: Pubkey,
+        shard_id: u16,
+        feed_id: FeedId,
+        treasury_id: u8,
+        start_merkle_price_update: MerklePriceUpdate,
+        end_merkle_price_update: MerklePriceUpdate,
+    ) -> Instruction {
+        let update_twap_feed_accounts = accounts::UpdateTwapFeed::populate(
+            payer,
+            start_encoded_vaa,
+            end_encoded_vaa,
+            shard_id,
+            feed_id,
+            treasury_id,
+        )
+        .to_account_metas(None);
+        Instruction {
+            program_id: ID,
+            accounts: update_twap_feed_accounts,
+            data: instruction::UpdateTwapFeed {
+                params: PostTwapUpdateParams {
+                    start_merkle_price_update,
+                    end_merkle_price_update,
+                    treasury_id,
+                },
+                shard_id,
+                feed_id,
+            }
+            .data(),
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use {
+    common_test_utils::{
+        assert_treasury_balance, setup_pyth_receiver, ProgramTestFixtures, WrongSetupOption,
+    },
+    program_simulator::into_transaction_error,
+    pyth_push_oracle::{instruction::UpdateTwapFeed, sdk::get_twap_feed_address, PushOracleError},
+    pyth_solana_receiver::sdk::{deserialize_accumulator_update_data, DEFAULT_TREASURY_ID},
+    pyth_solana_receiver_sdk::price_update::TwapUpdate,
+    pythnet_sdk::{
+        messages::{Message, TwapMessage},
+        test_utils::{create_accumulator_message, create_dummy_price_feed_message},
+    },
+    solana_sdk::{rent::Rent, signer::Signer},
+};
+
+const DEFAULT_SHARD: u16 = 0;
+
+fn create_twap_message(
+    cumulative_price: i128,
+    num_down_slots: u64,
+    publish_time: i64,
+    publish_slot: u64,
+) -> Message {
+    Message::TwapMessage(TwapMessage {
+        feed_id: [1; 32],
+        cumulative_price,
+        cumulative_conf: cumulative_price as u128,
+        num_down_slots,
+        exponent: -8,
+        publish_time,
+        prev_publish_time: publish_time - 1,
+        publish_slot,
+    })
+}
+
+#[tokio::test]
+async fn test_update_twap_feed() {
+    let feed_id = [1; 32];
+
+    // cumul_price=100 at slot=100, cumul_price=500 at slot=500 and cumul_price=1300 at slot=900
+    let start_msg = create_twap_message(100, 10, 100, 100);
+    let end_msg = create_twap_message(500, 110, 500, 500);
+    let later_end_msg = create_twap_message(1300, 110, 900, 900);
+    let price_feed_msg = create_dummy_price_feed_message(100);
+
+    let start_message =
+        create_accumulator_message(&[&start_msg], &[&start_msg], false, false, None);
+    let end_message = create_accumulator_message(
+        &[&end_msg, &price_feed_msg],
+        &[&end_msg, &price_feed_msg],
+        false,
+        false,
+        None,
+    );
+    let later_end_message =
+        create_accumulator_message(&[&later_end_msg], &[&later_end_msg], false, false, None);
+    let (start_vaa, start_merkle_price_updates) =
+        deserialize_accumulator_update_data(start_message).unwrap();
+    let (end_vaa, end_merkle_price_updates) =
+        deserialize_accumulator_update_data(end_message).unwrap();
+    let (later_end_vaa, later_end_merkle_price_updates) =
+        deserialize_accumulator_update_data(later_end_message).unwrap();
+
+    let ProgramTestFixtures {
+        mut program_simulator,
+        encoded_vaa_addresses,
+        governance_authority: _,
+    } = setup_pyth_receiver(
+        vec![
+            serde_wormhole::from_slice(&start_vaa).unwrap(),
+            serde_wormhole::from_slice(&end_vaa).unwrap(),
+            serde_wormhole::from_slice(&later_end_vaa).unwrap(),
+        ],
+        WrongSetupOption::None,
+    )
+    .await;
+
+    assert_treasury_balance(&mut program_simulator, 0, DEFAULT_TREASURY_ID).await;
+
+    let poster = program_simulator.get_funded_keypair().await.unwrap();
+
+    // post the TWAP over [100, 500]
+    program_simulator
+        .process_ix_with_default_compute_limit(
+            UpdateTwapFeed::populate(
+                poster.pubkey(),
+                encoded_vaa_addresses[0],
+                encoded_vaa_addresses[1],
+                DEFAULT_SHARD,
+                feed_id,
+                DEFAULT_TREASURY_ID,
+                start_merkle_price_updates[0].clone(),
+                end_merkle_price_updates[0].clone(),
+            ),
+            &vec![&poster],
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_treasury_balance(
+        &mut program_simulator,
+        Rent::default().minimum_balance(0),
+        DEFAULT_TREASURY_ID,
+    )
+    .await;
+
+    let twap_feed_account = program_simulator
+        .get_anchor_account_data::<TwapUpdate>(get_twap_feed_address(DEFAULT_SHARD, feed_id))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        twap_feed_account.write_authority,
+        get_twap_feed_address(DEFAULT_SHARD, feed_id)
+    );
+    assert_eq!(twap_feed_account.twap.feed_id, feed_id);
+    assert_eq!(twap_feed_account.twap.start_time, 100);
+    assert_eq!(twap_feed_account.twap.end_time, 500);
+    assert_eq!(twap_feed_account.twap.price, 1); // (500-100)/(500-100)
+    assert_eq!(twap_feed_account.twap.exponent, -8);
+    assert_eq!(twap_feed_account.twap.down_slots_ratio, 250_000); // 100 down slots out of 400
+
+    // post the same TWAP again. The tx succeeds w/o updating on-chain account state.
+    program_simulator
+        .process_ix_with_default_compute_limit(
+            UpdateTwapFeed::populate(
+                poster.pubkey(),
+                encoded_vaa_addresses[0],
+                encoded_vaa_addresses[1],
+                DEFAULT_SHARD,
+                feed_id,
+                DEFAULT_TREASURY_ID,
+                start_merkle_price_updates[0].clone(),
+                end_merkle_price_updates[0].clone(),
+            ),
+            &vec![&poster],
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_treasury_balance(
+        &mut program_simulator,
+        Rent::default().minimum_balance(0),
+        DEFAULT_TREASURY_ID,
+    )
+    .await;
+
+    // post the TWAP over [100, 900]
+    program_simulator
+        .process_ix_with_default_compute_limit(
+            UpdateTwapFeed::populate(
+                poster.pubkey(),
+                encoded_vaa_addresses[0],
+                encoded_vaa_addresses[2],
+                DEFAULT_SHARD,
+                feed_id,
+                DEFAULT_TREASURY_ID,
+                start_merkle_price_updates[0].clone(),
+                later_end_merkle_price_updates[0].clone(),
+            ),
+            &vec![&poster],
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_treasury_balance(
+        &mut program_simulator,
+        Rent::default().minimum_balance(0) + 1,
+        DEFAULT_TREASURY_ID,
+    )
+    .await;
+
+    let twap_feed_account = program_simulator
+        .get_anchor_account_data::<TwapUpdate>(get_twap_feed_address(DEFAULT_SHARD, feed_id))
+        .await
+        .unwrap();
+
+    assert_eq!(twap_feed_account.twap.start_time, 100);
+    assert_eq!(twap_feed_account.twap.end_time, 900);
+    assert_eq!(twap_feed_account.twap.price, 1); // (1300-100)/(900-100) rounded down
+    assert_eq!(twap_feed_account.twap.down_slots_ratio, 125_000); // 100 down slots out of 800
+
+    // try to post a price feed message as the end of the TWAP window
+    assert_eq!(
+        program_simulator
+            .process_ix_with_default_compute_limit(
+                UpdateTwapFeed::populate(
+                    poster.pubkey(),
+                    encoded_vaa_addresses[0],
+                    encoded_vaa_addresses[1],
+                    DEFAULT_SHARD,
+                    feed_id,
+                    DEFAULT_TREASURY_ID,
+                    start_merkle_price_updates[0].clone(),
+                    end_merkle_price_updates[1].clone(),
+                ),
+                &vec![&poster],
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        into_transaction_error(PushOracleError::UnsupportedMessageType)
+    );
+}
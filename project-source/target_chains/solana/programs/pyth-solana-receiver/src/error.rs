@@ -65,4 +65,24 @@ pub enum ReceiverError {
     NonexistentGovernanceAuthorityTransferRequest,
     #[msg("The minimum number of signatures should be at least 1")]
     ZeroMinimumSignatures,
+    // Batch post errors (from post_updates_batch)
+    #[msg("The number of price update accounts doesn't match the number of price updates")]
+    PriceUpdateAccountsMismatch,
+    #[msg("A new price update account must sign the transaction")]
+    PriceUpdateAccountNotSigner,
+    #[msg("Overflow in the update fee calculation")]
+    UpdateFeeOverflow,
+    // Governance VAA errors (from execute_governance_instruction)
+    #[msg("An error occurred when deserializing the governance instruction")]
+    InvalidGovernanceMessage,
+    #[msg("The governance VAA wasn't emitted by the governance data source")]
+    InvalidGovernanceDataSource,
+    #[msg("The governance instruction targets another chain")]
+    InvalidGovernanceTargetChain,
+    #[msg("The governance VAA or governance data source transfer request was already executed")]
+    GovernanceMessageReplayed,
+    #[msg("This governance action can't be executed on this chain")]
+    UnsupportedGovernanceAction,
+    #[msg("The claim VAA account is missing or doesn't match the claim VAA of the governance instruction")]
+    ClaimVaaMismatch,
 }
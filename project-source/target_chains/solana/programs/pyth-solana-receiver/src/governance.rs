@@ -0,0 +1,271 @@
+//! Pyth governance instructions, as emitted by the Pyth governance data source in governance VAAs.
+//! The payloads are the same as the ones parsed by the receivers of the other target chains.
+
+use {
+    crate::error::ReceiverError,
+    anchor_lang::prelude::*,
+    byteorder::{BigEndian, ReadBytesExt, WriteBytesExt},
+    pyth_solana_receiver_sdk::config::DataSource,
+    std::io::{Read, Write},
+};
+
+/// Magic header of the governance instructions.
+pub const GOVERNANCE_MAGIC: &[u8; 4] = b"PTGM";
+
+/// The target chain id of the governance instructions that apply to every chain.
+pub const ALL_CHAINS_TARGET_CHAIN_ID: u16 = 0;
+
+/// The type of contract that can accept a governance instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GovernanceModule {
+    /// The PythNet executor contract
+    Executor = 0,
+    /// A target chain contract (like this one!)
+    Target = 1,
+}
+
+/// The actions that can be executed through governance.
+///
+/// Note that the order of the enum cannot be changed, as the integer representation of
+/// each variant must match the other target chains.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GovernanceAction {
+    /// Upgrade the program to the buffer account `buffer`. Not supported on Solana, where the
+    /// program upgrade authority is used instead.
+    UpgradeContract { buffer: Pubkey }, // 0
+    /// The second step of a governance data source transfer. `claim_vaa` is a VAA from the new
+    /// governance data source containing a `RequestGovernanceDataSourceTransfer` action.
+    AuthorizeGovernanceDataSourceTransfer { claim_vaa: Vec<u8> }, // 1
+    /// Set the valid data sources of the price updates.
+    SetDataSources { data_sources: Vec<DataSource> }, // 2
+    /// Set the fee of a single price update to `val * 10^expo` lamports.
+    SetFee { val: u64, expo: u64 }, // 3
+    /// Set the valid period of the prices. Not supported on Solana, where consumers choose the
+    /// maximum age of the prices they read.
+    SetValidPeriod { valid_seconds: u64 }, // 4
+    /// The first step of a governance data source transfer, emitted by the new governance data
+    /// source. `governance_data_source_index` must increase with each transfer to prevent replays.
+    RequestGovernanceDataSourceTransfer { governance_data_source_index: u32 }, // 5
+    /// Set the address of the wormhole program.
+    SetWormholeAddress { address: Pubkey }, // 6
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GovernanceInstruction {
+    pub module: GovernanceModule,
+    pub action: GovernanceAction,
+    pub target_chain_id: u16,
+}
+
+impl GovernanceInstruction {
+    /// Parse a governance instruction from the payload of a governance VAA.
+    /// Only instructions for the `Target` module that use the whole payload are accepted.
+    pub fn deserialize(payload: &[u8]) -> Result<Self> {
+        Self::try_deserialize(payload)
+            .ok_or_else(|| error!(ReceiverError::InvalidGovernanceMessage))
+    }
+
+    fn try_deserialize(mut bytes: &[u8]) -> Option<Self> {
+        let mut magic = [0u8; 4];
+        bytes.read_exact(&mut magic).ok()?;
+        if &magic != GOVERNANCE_MAGIC {
+            return None;
+        }
+
+        let module = match bytes.read_u8().ok()? {
+            1 => GovernanceModule::Target,
+            _ => return None,
+        };
+        let action_type = bytes.read_u8().ok()?;
+        let target_chain_id = bytes.read_u16::<BigEndian>().ok()?;
+
+        let action = match action_type {
+            0 => GovernanceAction::UpgradeContract {
+                buffer: read_pubkey(&mut bytes)?,
+            },
+            1 => {
+                let mut claim_vaa = vec![];
+                bytes.read_to_end(&mut claim_vaa).ok()?;
+                GovernanceAction::AuthorizeGovernanceDataSourceTransfer { claim_vaa }
+            }
+            2 => {
+                let num_data_sources = bytes.read_u8().ok()?;
+                let mut data_sources = Vec::with_capacity(num_data_sources.into());
+                for _ in 0..num_data_sources {
+                    let chain = bytes.read_u16::<BigEndian>().ok()?;
+                    let emitter = read_pubkey(&mut bytes)?;
+                    data_sources.push(DataSource { chain, emitter });
+                }
+                GovernanceAction::SetDataSources { data_sources }
+            }
+            3 => GovernanceAction::SetFee {
+                val: bytes.read_u64::<BigEndian>().ok()?,
+                expo: bytes.read_u64::<BigEndian>().ok()?,
+            },
+            4 => GovernanceAction::SetValidPeriod {
+                valid_seconds: bytes.read_u64::<BigEndian>().ok()?,
+            },
+            5 => GovernanceAction::RequestGovernanceDataSourceTransfer {
+                governance_data_source_index: bytes.read_u32::<BigEndian>().ok()?,
+            },
+            6 => GovernanceAction::SetWormholeAddress {
+                address: read_pubkey(&mut bytes)?,
+            },
+            _ => return None,
+        };
+
+        // Make sure that this program knows how to interpret every byte of the payload
+        if !bytes.is_empty() {
+            return None;
+        }
+
+        Some(GovernanceInstruction {
+            module,
+            action,
+            target_chain_id,
+        })
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = GOVERNANCE_MAGIC.to_vec();
+        buf.push(self.module as u8);
+
+        // Writing to a Vec can't fail
+        match &self.action {
+            GovernanceAction::UpgradeContract { buffer } => {
+                buf.push(0);
+                buf.write_u16::<BigEndian>(self.target_chain_id).unwrap();
+                buf.write_all(buffer.as_ref()).unwrap();
+            }
+            GovernanceAction::AuthorizeGovernanceDataSourceTransfer { claim_vaa } => {
+                buf.push(1);
+                buf.write_u16::<BigEndian>(self.target_chain_id).unwrap();
+                buf.write_all(claim_vaa).unwrap();
+            }
+            GovernanceAction::SetDataSources { data_sources } => {
+                buf.push(2);
+                buf.write_u16::<BigEndian>(self.target_chain_id).unwrap();
+                buf.push(u8::try_from(data_sources.len()).expect("Too many data sources"));
+                for data_source in data_sources {
+                    buf.write_u16::<BigEndian>(data_source.chain).unwrap();
+                    buf.write_all(data_source.emitter.as_ref()).unwrap();
+                }
+            }
+            GovernanceAction::SetFee { val, expo } => {
+                buf.push(3);
+                buf.write_u16::<BigEndian>(self.target_chain_id).unwrap();
+                buf.write_u64::<BigEndian>(*val).unwrap();
+                buf.write_u64::<BigEndian>(*expo).unwrap();
+            }
+            GovernanceAction::SetValidPeriod { valid_seconds } => {
+                buf.push(4);
+                buf.write_u16::<BigEndian>(self.target_chain_id).unwrap();
+                buf.write_u64::<BigEndian>(*valid_seconds).unwrap();
+            }
+            GovernanceAction::RequestGovernanceDataSourceTransfer {
+                governance_data_source_index,
+            } => {
+                buf.push(5);
+                buf.write_u16::<BigEndian>(self.target_chain_id).unwrap();
+                buf.write_u32::<BigEndian>(*governance_data_source_index)
+                    .unwrap();
+            }
+            GovernanceAction::SetWormholeAddress { address } => {
+                buf.push(6);
+                buf.write_u16::<BigEndian>(self.target_chain_id).unwrap();
+                buf.write_all(address.as_ref()).unwrap();
+            }
+        }
+
+        buf
+    }
+}
+
+fn read_pubkey(bytes: &mut &[u8]) -> Option<Pubkey> {
+    let mut pubkey = [0u8; 32];
+    bytes.read_exact(&mut pubkey).ok()?;
+    Some(Pubkey::from(pubkey))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_governance_serialize_matches_deserialize() {
+        let instructions = vec![
+            GovernanceAction::UpgradeContract {
+                buffer: Pubkey::new_unique(),
+            },
+            GovernanceAction::AuthorizeGovernanceDataSourceTransfer {
+                claim_vaa: vec![1, 2, 3],
+            },
+            GovernanceAction::SetDataSources {
+                data_sources: vec![
+                    DataSource {
+                        chain: 1,
+                        emitter: Pubkey::new_unique(),
+                    },
+                    DataSource {
+                        chain: 2,
+                        emitter: Pubkey::new_unique(),
+                    },
+                ],
+            },
+            GovernanceAction::SetFee { val: 5, expo: 3 },
+            GovernanceAction::SetValidPeriod { valid_seconds: 60 },
+            GovernanceAction::RequestGovernanceDataSourceTransfer {
+                governance_data_source_index: 4,
+            },
+            GovernanceAction::SetWormholeAddress {
+                address: Pubkey::new_unique(),
+            },
+        ]
+        .into_iter()
+        .map(|action| GovernanceInstruction {
+            module: GovernanceModule::Target,
+            action,
+            target_chain_id: 1,
+        });
+
+        for instruction in instructions {
+            assert_eq!(
+                GovernanceInstruction::deserialize(&instruction.serialize()).unwrap(),
+                instruction
+            );
+        }
+    }
+
+    #[test]
+    fn test_payload_wrong_size() {
+        let instruction = GovernanceInstruction {
+            module: GovernanceModule::Target,
+            action: GovernanceAction::SetFee { val: 100, expo: 2 },
+            target_chain_id: 1,
+        };
+        let mut payload = instruction.serialize();
+
+        assert!(GovernanceInstruction::deserialize(&payload[..payload.len() - 1]).is_err());
+        payload.push(0);
+        assert!(GovernanceInstruction::deserialize(&payload).is_err());
+    }
+
+    #[test]
+    fn test_wrong_header() {
+        let instruction = GovernanceInstruction {
+            module: GovernanceModule::Executor,
+            action: GovernanceAction::SetFee { val: 100, expo: 2 },
+            target_chain_id: 1,
+        };
+        let mut payload = instruction.serialize();
+        // Executor instructions are not for this program
+        assert!(GovernanceInstruction::deserialize(&payload).is_err());
+
+        payload[4] = GovernanceModule::Target as u8;
+        assert!(GovernanceInstruction::deserialize(&payload).is_ok());
+
+        payload[0] = b'X';
+        assert!(GovernanceInstruction::deserialize(&payload).is_err());
+    }
+}
@@ -3,13 +3,16 @@
 
 pub use pythnet_sdk::wire::v1::MerklePriceUpdate;
 use {
-    crate::error::ReceiverError,
+    crate::{
+        error::ReceiverError,
+        governance::{GovernanceAction, GovernanceInstruction, ALL_CHAINS_TARGET_CHAIN_ID},
+    },
     anchor_lang::prelude::*,
     pyth_solana_receiver_sdk::{
-        config::{Config, DataSource},
-        pda::{CONFIG_SEED, TREASURY_SEED},
+        config::{Config, DataSource, GovernanceConfig},
+        pda::{CONFIG_SEED, GOVERNANCE_CONFIG_SEED, TREASURY_SEED},
         price_update::{PriceUpdateV2, TwapUpdate, VerificationLevel},
-        PostTwapUpdateParams, PostUpdateAtomicParams, PostUpdateParams,
+        PostTwapUpdateParams, PostUpdateAtomicParams, PostUpdateParams, PostUpdatesBatchParams,
     },
     pythnet_sdk::{
         accumulators::merkle::MerkleRoot,
@@ -32,6 +35,7 @@ use {
 };
 
 pub mod error;
+pub mod governance;
 pub mod sdk;
 
 declare_id!(pyth_solana_receiver_sdk::ID);
@@ -104,6 +108,95 @@ pub mod pyth_solana_receiver {
         Ok(())
     }
 
+    /// Enable the governance through Pyth governance VAAs emitted by `governance_data_source`.
+    /// `target_chain_id` is the wormhole chain id of the chain this program is deployed on.
+    /// The governance authority keeps its powers, this only adds `execute_governance_instruction`.
+    pub fn initialize_governance_config(
+        ctx: Context<InitializeGovernanceConfig>,
+        governance_data_source: DataSource,
+        target_chain_id: u16,
+    ) -> Result<()> {
+        let governance_config = &mut ctx.accounts.governance_config;
+        **governance_config = GovernanceConfig {
+            governance_data_source,
+            target_chain_id,
+            last_executed_governance_sequence: 0,
+            governance_data_source_index: 0,
+        };
+        Ok(())
+    }
+
+    /// Execute a governance instruction using an encoded_vaa account emitted by the governance data source.
+    /// This should be called after the client has already verified the Vaa via the Wormhole contract.
+    ///
+    /// Governance VAAs are executed in increasing sequence order, a VAA can't be executed if a VAA with a higher sequence has already been executed.
+    /// `AuthorizeGovernanceDataSourceTransfer` instructions also require the encoded_vaa account of the claim VAA they contain as `claim_encoded_vaa`.
+    ///
+    /// Only the actions shared with the receivers of the other target chains are supported.
+    /// `set_minimum_signatures` and the governance authority transfer have no such action, so they still require a signature of the governance authority.
+    pub fn execute_governance_instruction(
+        ctx: Context<ExecuteGovernanceInstruction>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let governance_config = &mut ctx.accounts.governance_config;
+        let encoded_vaa = VaaAccount::load(&ctx.accounts.encoded_vaa)?; // IMPORTANT: This line checks that the encoded_vaa has ProcessingStatus::Verified. This check is critical otherwise the program could be tricked into accepting unverified VAAs.
+
+        let (emitter_chain, emitter_address, sequence) = encoded_vaa.try_emitter_info()?;
+        require!(
+            governance_config.governance_data_source
+                == DataSource {
+                    chain: emitter_chain,
+                    emitter: Pubkey::from(emitter_address),
+                },
+            ReceiverError::InvalidGovernanceDataSource
+        );
+        require_gt!(
+            sequence,
+            governance_config.last_executed_governance_sequence,
+            ReceiverError::GovernanceMessageReplayed
+        );
+
+        let instruction = GovernanceInstruction::deserialize(encoded_vaa.try_payload()?.as_ref())?;
+        verify_governance_target_chain(governance_config, &instruction)?;
+        governance_config.last_executed_governance_sequence = sequence;
+
+        match instruction.action {
+            GovernanceAction::SetDataSources { data_sources } => {
+                config.valid_data_sources = data_sources;
+            }
+            GovernanceAction::SetFee { val, expo } => {
+                config.single_update_fee_in_lamports = u32::try_from(expo)
+                    .ok()
+                    .and_then(|expo| 10u64.checked_pow(expo))
+                    .and_then(|multiplier| multiplier.checked_mul(val))
+                    .ok_or(ReceiverError::UpdateFeeOverflow)?;
+            }
+            GovernanceAction::SetWormholeAddress { address } => {
+                config.wormhole = address;
+            }
+            GovernanceAction::AuthorizeGovernanceDataSourceTransfer { claim_vaa } => {
+                let claim_encoded_vaa = ctx
+                    .accounts
+                    .claim_encoded_vaa
+                    .as_ref()
+                    .ok_or(ReceiverError::ClaimVaaMismatch)?;
+                authorize_governance_data_source_transfer(
+                    governance_config,
+                    &claim_vaa,
+                    claim_encoded_vaa,
+                )?;
+            }
+            GovernanceAction::RequestGovernanceDataSourceTransfer { .. } => {
+                // This action is only valid inside of an AuthorizeGovernanceDataSourceTransfer
+                return err!(ReceiverError::InvalidGovernanceMessage);
+            }
+            GovernanceAction::UpgradeContract { .. } | GovernanceAction::SetValidPeriod { .. } => {
+                return err!(ReceiverError::UnsupportedGovernanceAction);
+            }
+        }
+        Ok(())
+    }
+
     /// Post a price update using a VAA and a MerklePriceUpdate.
     /// This function allows you to post a price update in a single transaction.
     /// Compared to `post_update`, it only checks whatever signatures are present in the provided VAA and doesn't fail if the number of signatures is lower than the Wormhole quorum of two thirds of the guardians.
@@ -232,6 +325,70 @@ pub mod pyth_solana_receiver {
         Ok(())
     }
 
+    /// Post several price updates using a single encoded_vaa account and MerklePriceUpdates calldata.
+    /// The price update accounts are passed as the remaining accounts, in the same order as `params.merkle_price_updates`.
+    /// Compared to calling `post_update` once per price update, the encoded VAA, its data source and its merkle root are only checked once.
+    ///
+    /// New price update accounts are created by this instruction and must sign the transaction, like in `post_update`.
+    /// Existing price update accounts must have `write_authority` as their write authority.
+    pub fn post_updates_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, PostUpdatesBatch<'info>>,
+        params: PostUpdatesBatchParams,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let payer: &Signer<'_> = &ctx.accounts.payer;
+        let write_authority: &Signer<'_> = &ctx.accounts.write_authority;
+        let encoded_vaa = VaaAccount::load(&ctx.accounts.encoded_vaa)?; // IMPORTANT: This line checks that the encoded_vaa has ProcessingStatus::Verified. This check is critical otherwise the program could be tricked into accepting unverified VAAs.
+        let treasury: &AccountInfo<'_> = &ctx.accounts.treasury;
+        let price_update_accounts = ctx.remaining_accounts;
+
+        require_eq!(
+            price_update_accounts.len(),
+            params.merkle_price_updates.len(),
+            ReceiverError::PriceUpdateAccountsMismatch
+        );
+
+        let vaa_components = VaaComponents {
+            verification_level: VerificationLevel::Full,
+            emitter_address: encoded_vaa.try_emitter_address()?,
+            emitter_chain: encoded_vaa.try_emitter_chain()?,
+        };
+
+        let num_updates = u64::try_from(params.merkle_price_updates.len())
+            .map_err(|_| ReceiverError::UpdateFeeOverflow)?;
+        pay_update_fees(config, treasury, payer, num_updates)?;
+        verify_vaa_data_source(config, &vaa_components)?;
+        let root = parse_merkle_root(encoded_vaa.try_payload()?.as_ref())?;
+        let posted_slot = Clock::get()?.slot;
+
+        for (price_update_account_info, merkle_price_update) in price_update_accounts
+            .iter()
+            .zip(params.merkle_price_updates.iter())
+        {
+            let message = verify_merkle_proof_with_root(&root, merkle_price_update)?;
+            let price_feed_message = match message {
+                Message::PriceFeedMessage(price_feed_message) => price_feed_message,
+                Message::TwapMessage(_) | Message::PublisherStakeCapsMessage(_) => {
+                    return err!(ReceiverError::UnsupportedMessageType);
+                }
+            };
+
+            let mut price_update_account = load_or_create_price_update_account(
+                payer,
+                write_authority,
+                price_update_account_info,
+                &ctx.accounts.system_program,
+            )?;
+            price_update_account.write_authority = write_authority.key();
+            price_update_account.verification_level = vaa_components.verification_level;
+            price_update_account.price_message = price_feed_message;
+            price_update_account.posted_slot = posted_slot;
+            price_update_account.exit(&crate::ID)?;
+        }
+
+        Ok(())
+    }
+
     /// Post a TWAP (time weighted average price) update for a given time window.
     /// This should be called after the client has already verified the VAAs via the Wormhole contract.
     /// Check out target_chains/solana/cli/src/main.rs for an example of how to do this.
@@ -320,6 +477,35 @@ pub struct AcceptGovernanceAuthorityTransfer<'info> {
     pub config: Account<'info, Config>,
 }
 
+#[derive(Accounts)]
+pub struct InitializeGovernanceConfig<'info> {
+    #[account(mut, constraint =
+        payer.key() == config.governance_authority @
+        ReceiverError::GovernanceAuthorityMismatch
+    )]
+    pub payer: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump)]
+    pub config: Account<'info, Config>,
+    #[account(init, space = GovernanceConfig::LEN, payer = payer, seeds = [GOVERNANCE_CONFIG_SEED.as_ref()], bump)]
+    pub governance_config: Account<'info, GovernanceConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteGovernanceInstruction<'info> {
+    pub payer: Signer<'info>,
+    #[account(owner = config.wormhole @ ReceiverError::WrongVaaOwner)]
+    /// CHECK: We aren't deserializing the VAA here but later with VaaAccount::load, which is the recommended way
+    pub encoded_vaa: AccountInfo<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_ref()], bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [GOVERNANCE_CONFIG_SEED.as_ref()], bump)]
+    pub governance_config: Account<'info, GovernanceConfig>,
+    #[account(owner = config.wormhole @ ReceiverError::WrongVaaOwner)]
+    /// CHECK: We aren't deserializing the VAA here but later with VaaAccount::load, which is the recommended way
+    pub claim_encoded_vaa: Option<AccountInfo<'info>>,
+}
+
 #[derive(Accounts)]
 #[instruction(params: PostUpdateParams)]
 pub struct PostUpdate<'info> {
@@ -341,6 +527,23 @@ pub struct PostUpdate<'info> {
     pub write_authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(params: PostUpdatesBatchParams)]
+pub struct PostUpdatesBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(owner = config.wormhole @ ReceiverError::WrongVaaOwner)]
+    /// CHECK: We aren't deserializing the VAA here but later with VaaAccount::load, which is the recommended way
+    pub encoded_vaa: AccountInfo<'info>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: This is just a PDA controlled by the program. There is currently no way to withdraw funds from it.
+    #[account(mut, seeds = [TREASURY_SEED.as_ref(), &[params.treasury_id]], bump)]
+    pub treasury: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub write_authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(params: PostTwapUpdateParams)]
 pub struct PostTwapUpdate<'info> {
@@ -440,6 +643,48 @@ struct VaaComponents {
     emitter_chain: u16,
 }
 
+/// Load a price update account passed in the remaining accounts, creating it if it doesn't exist yet.
+/// This does the same checks as the `init_if_needed` constraint of `PostUpdate::price_update_account`.
+fn load_or_create_price_update_account<'info>(
+    payer: &Signer<'info>,
+    write_authority: &Signer<'info>,
+    price_update_account_info: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<Account<'info, PriceUpdateV2>> {
+    if *price_update_account_info.owner == system_program.key() {
+        require!(
+            price_update_account_info.is_signer,
+            ReceiverError::PriceUpdateAccountNotSigner
+        );
+        let create_account_instruction = system_instruction::create_account(
+            payer.key,
+            price_update_account_info.key,
+            Rent::get()?.minimum_balance(PriceUpdateV2::LEN),
+            PriceUpdateV2::LEN as u64,
+            &crate::ID,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &create_account_instruction,
+            &[
+                payer.to_account_info(),
+                price_update_account_info.clone(),
+                system_program.to_account_info(),
+            ],
+        )?;
+        // The account was just created so its data is zeroed and it doesn't have a discriminator yet,
+        // the discriminator is written when the account is serialized on exit.
+        Account::try_from_unchecked(price_update_account_info)
+    } else {
+        let price_update_account = Account::<PriceUpdateV2>::try_from(price_update_account_info)?;
+        require_keys_eq!(
+            price_update_account.write_authority,
+            write_authority.key(),
+            ReceiverError::WrongWriteAuthority
+        );
+        Ok(price_update_account)
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn post_price_update_from_vaa<'info>(
     config: &Account<'info, Config>,
@@ -595,13 +840,25 @@ fn pay_single_update_fee<'info>(
     treasury: &AccountInfo<'info>,
     payer: &Signer<'info>,
 ) -> Result<()> {
+    pay_update_fees(config, treasury, payer, 1)
+}
+
+fn pay_update_fees<'info>(
+    config: &Account<'info, Config>,
+    treasury: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    num_updates: u64,
+) -> Result<()> {
+    let update_fees = config
+        .single_update_fee_in_lamports
+        .checked_mul(num_updates)
+        .ok_or(ReceiverError::UpdateFeeOverflow)?;
+
     // Handle treasury payment
     let amount_to_pay = if treasury.lamports() == 0 {
-        Rent::get()?
-            .minimum_balance(0)
-            .max(config.single_update_fee_in_lamports)
+        Rent::get()?.minimum_balance(0).max(update_fees)
     } else {
-        config.single_update_fee_in_lamports
+        update_fees
     };
 
     if payer.lamports()
@@ -655,12 +912,22 @@ fn verify_guardian_signature(
 }
 
 fn verify_merkle_proof(vaa_payload: &[u8], price_update: &MerklePriceUpdate) -> Result<Message> {
+    let root = parse_merkle_root(vaa_payload)?;
+    verify_merkle_proof_with_root(&root, price_update)
+}
+
+fn parse_merkle_root(vaa_payload: &[u8]) -> Result<MerkleRoot<Keccak160>> {
     let wormhole_message = WormholeMessage::try_from_bytes(vaa_payload)
         .map_err(|_| ReceiverError::InvalidWormholeMessage)?;
-    let root: MerkleRoot<Keccak160> = MerkleRoot::new(match wormhole_message.payload {
+    Ok(MerkleRoot::new(match wormhole_message.payload {
         WormholePayload::Merkle(merkle_root) => merkle_root.root,
-    });
+    }))
+}
 
+fn verify_merkle_proof_with_root(
+    root: &MerkleRoot<Keccak160>,
+    price_update: &MerklePriceUpdate,
+) -> Result<Message> {
     if !root.check(price_update.proof.clone(), price_update.message.as_ref()) {
         return err!(ReceiverError::InvalidPriceUpdate);
     }
@@ -684,6 +951,59 @@ fn verify_vaa_data_source(
     Ok(())
 }
 
+fn verify_governance_target_chain(
+    governance_config: &GovernanceConfig,
+    instruction: &GovernanceInstruction,
+) -> Result<()> {
+    require!(
+        instruction.target_chain_id == governance_config.target_chain_id
+            || instruction.target_chain_id == ALL_CHAINS_TARGET_CHAIN_ID,
+        ReceiverError::InvalidGovernanceTargetChain
+    );
+    Ok(())
+}
+
+/// Transfer the governance to the emitter of `claim_vaa`, which must contain a `RequestGovernanceDataSourceTransfer` instruction.
+/// The claim VAA is verified through `claim_encoded_vaa`, the encoded_vaa account of the same VAA.
+fn authorize_governance_data_source_transfer(
+    governance_config: &mut GovernanceConfig,
+    claim_vaa: &[u8],
+    claim_encoded_vaa: &AccountInfo<'_>,
+) -> Result<()> {
+    let claim_encoded_vaa = VaaAccount::load(claim_encoded_vaa)?; // IMPORTANT: This line checks that the encoded_vaa has ProcessingStatus::Verified. This check is critical otherwise the program could be tricked into accepting unverified VAAs.
+    let claim_vaa = Vaa::parse(claim_vaa).map_err(|_| ReceiverError::DeserializeVaaFailed)?;
+    let claim_vaa_digest = keccak::hash(keccak::hash(claim_vaa.body().as_ref()).as_ref());
+    require!(
+        claim_vaa_digest == claim_encoded_vaa.try_digest()?,
+        ReceiverError::ClaimVaaMismatch
+    );
+
+    let claim_instruction =
+        GovernanceInstruction::deserialize(claim_encoded_vaa.try_payload()?.as_ref())?;
+    verify_governance_target_chain(governance_config, &claim_instruction)?;
+    let GovernanceAction::RequestGovernanceDataSourceTransfer {
+        governance_data_source_index,
+    } = claim_instruction.action
+    else {
+        return err!(ReceiverError::InvalidGovernanceMessage);
+    };
+    require_gt!(
+        governance_data_source_index,
+        governance_config.governance_data_source_index,
+        ReceiverError::GovernanceMessageReplayed
+    );
+
+    let (emitter_chain, emitter_address, sequence) = claim_encoded_vaa.try_emitter_info()?;
+    governance_config.governance_data_source = DataSource {
+        chain: emitter_chain,
+        emitter: Pubkey::from(emitter_address),
+    };
+    governance_config.governance_data_source_index = governance_data_source_index;
+    // The next governance VAA of the new governance data source must come after the claim VAA
+    governance_config.last_executed_governance_sequence = sequence;
+    Ok(())
+}
+
 #[cfg(test)]
 /// Unit tests for the core TWAP calculation logic in `calculate_twap` and `validate_twap_messages`
 /// This test module is here because these functions are private and can't
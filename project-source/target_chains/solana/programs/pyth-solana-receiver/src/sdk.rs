@@ -3,12 +3,12 @@ use {
     anchor_lang::{prelude::*, system_program, InstructionData},
     pyth_solana_receiver_sdk::{
         config::{Config, DataSource},
-        pda::{get_config_address, get_treasury_address},
-        PostTwapUpdateParams, PostUpdateAtomicParams, PostUpdateParams,
+        pda::{get_config_address, get_governance_config_address, get_treasury_address},
+        PostTwapUpdateParams, PostUpdateAtomicParams, PostUpdateParams, PostUpdatesBatchParams,
     },
     pythnet_sdk::wire::v1::{AccumulatorUpdateData, MerklePriceUpdate, Proof},
     rand::Rng,
-    solana_program::instruction::Instruction,
+    solana_program::instruction::{AccountMeta, Instruction},
     wormhole_core_bridge_solana::state::GuardianSet,
 };
 
@@ -80,6 +80,26 @@ impl accounts::PostUpdate {
     }
 }
 
+impl accounts::PostUpdatesBatch {
+    pub fn populate(
+        payer: Pubkey,
+        write_authority: Pubkey,
+        encoded_vaa: Pubkey,
+        treasury_id: u8,
+    ) -> Self {
+        let config = get_config_address();
+        let treasury = get_treasury_address(treasury_id);
+        accounts::PostUpdatesBatch {
+            payer,
+            encoded_vaa,
+            config,
+            treasury,
+            system_program: system_program::ID,
+            write_authority,
+        }
+    }
+}
+
 impl accounts::PostTwapUpdate {
     pub fn populate(
         payer: Pubkey,
@@ -118,6 +138,29 @@ impl accounts::AcceptGovernanceAuthorityTransfer {
     }
 }
 
+impl accounts::InitializeGovernanceConfig {
+    pub fn populate(payer: Pubkey) -> Self {
+        accounts::InitializeGovernanceConfig {
+            payer,
+            config: get_config_address(),
+            governance_config: get_governance_config_address(),
+            system_program: system_program::ID,
+        }
+    }
+}
+
+impl accounts::ExecuteGovernanceInstruction {
+    pub fn populate(payer: Pubkey, encoded_vaa: Pubkey, claim_encoded_vaa: Option<Pubkey>) -> Self {
+        accounts::ExecuteGovernanceInstruction {
+            payer,
+            encoded_vaa,
+            config: get_config_address(),
+            governance_config: get_governance_config_address(),
+            claim_encoded_vaa,
+        }
+    }
+}
+
 impl accounts::ReclaimRent {
     pub fn populate(payer: Pubkey, price_update_account: Pubkey) -> Self {
         let _config = get_config_address();
@@ -169,6 +212,39 @@ impl instruction::PostUpdate {
     }
 }
 
+impl instruction::PostUpdatesBatch {
+    /// `price_update_accounts` pairs each price update account with whether it signs the transaction.
+    /// Accounts that don't exist yet must sign so they can be created by the instruction,
+    /// existing accounts don't need to.
+    /// `price_update_accounts` and `merkle_price_updates` must have the same length.
+    pub fn populate(
+        payer: Pubkey,
+        write_authority: Pubkey,
+        encoded_vaa: Pubkey,
+        price_update_accounts: &[(Pubkey, bool)],
+        merkle_price_updates: Vec<MerklePriceUpdate>,
+        treasury_id: u8,
+    ) -> Instruction {
+        let mut post_updates_batch_accounts =
+            accounts::PostUpdatesBatch::populate(payer, write_authority, encoded_vaa, treasury_id)
+                .to_account_metas(None);
+        post_updates_batch_accounts.extend(price_update_accounts.iter().map(
+            |(price_update_account, is_signer)| AccountMeta::new(*price_update_account, *is_signer),
+        ));
+        Instruction {
+            program_id: ID,
+            accounts: post_updates_batch_accounts,
+            data: instruction::PostUpdatesBatch {
+                params: PostUpdatesBatchParams {
+                    merkle_price_updates,
+                    treasury_id,
+                },
+            }
+            .data(),
+        }
+    }
+}
+
 impl instruction::PostUpdateAtomic {
     #[allow(clippy::too_many_arguments)]
     pub fn populate(
@@ -327,6 +403,44 @@ impl instruction::AcceptGovernanceAuthorityTransfer {
     }
 }
 
+impl instruction::InitializeGovernanceConfig {
+    pub fn populate(
+        payer: Pubkey,
+        governance_data_source: DataSource,
+        target_chain_id: u16,
+    ) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts::InitializeGovernanceConfig::populate(payer).to_account_metas(None),
+            data: instruction::InitializeGovernanceConfig {
+                governance_data_source,
+                target_chain_id,
+            }
+            .data(),
+        }
+    }
+}
+
+impl instruction::ExecuteGovernanceInstruction {
+    /// `claim_encoded_vaa` is only needed to execute `AuthorizeGovernanceDataSourceTransfer` instructions
+    pub fn populate(
+        payer: Pubkey,
+        encoded_vaa: Pubkey,
+        claim_encoded_vaa: Option<Pubkey>,
+    ) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts::ExecuteGovernanceInstruction::populate(
+                payer,
+                encoded_vaa,
+                claim_encoded_vaa,
+            )
+            .to_account_metas(None),
+            data: instruction::ExecuteGovernanceInstruction {}.data(),
+        }
+    }
+}
+
 impl instruction::ReclaimRent {
     pub fn populate(payer: Pubkey, price_update_account: Pubkey) -> Instruction {
         let governance_accounts =
@@ -0,0 +1,352 @@
+use {
+    common_test_utils::{setup_pyth_receiver, ProgramTestFixtures, WrongSetupOption},
+    program_simulator::into_transaction_error,
+    pyth_solana_receiver::{
+        error::ReceiverError,
+        governance::{GovernanceAction, GovernanceInstruction, GovernanceModule},
+        instruction::{ExecuteGovernanceInstruction, InitializeGovernanceConfig},
+    },
+    pyth_solana_receiver_sdk::{
+        config::{Config, DataSource, GovernanceConfig},
+        pda::{get_config_address, get_governance_config_address},
+    },
+    pythnet_sdk::test_utils::{
+        create_vaa_from_payload, DataSource as WormholeDataSource, DEFAULT_GOVERNANCE_SOURCE,
+        SECONDARY_DATA_SOURCE, SECONDARY_GOVERNANCE_SOURCE, WRONG_SOURCE,
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::signer::Signer,
+    wormhole_sdk::Chain,
+};
+
+fn create_governance_vaa(
+    action: GovernanceAction,
+    target_chain: Chain,
+    emitter: &WormholeDataSource,
+    sequence: u64,
+) -> Vec<u8> {
+    let instruction = GovernanceInstruction {
+        module: GovernanceModule::Target,
+        action,
+        target_chain_id: target_chain.into(),
+    };
+    serde_wormhole::to_vec(&create_vaa_from_payload(
+        &instruction.serialize(),
+        emitter.address,
+        emitter.chain,
+        sequence,
+    ))
+    .unwrap()
+}
+
+fn to_data_source(emitter: &WormholeDataSource) -> DataSource {
+    DataSource {
+        chain: emitter.chain.into(),
+        emitter: Pubkey::from(emitter.address.0),
+    }
+}
+
+#[tokio::test]
+async fn test_governance_vaa() {
+    let new_wormhole = Pubkey::new_unique();
+    let claim_vaa = create_governance_vaa(
+        GovernanceAction::RequestGovernanceDataSourceTransfer {
+            governance_data_source_index: 1,
+        },
+        Chain::Solana,
+        &SECONDARY_GOVERNANCE_SOURCE,
+        10,
+    );
+    let vaas = vec![
+        create_governance_vaa(
+            GovernanceAction::SetFee { val: 2, expo: 3 },
+            Chain::Solana,
+            &DEFAULT_GOVERNANCE_SOURCE,
+            1,
+        ),
+        create_governance_vaa(
+            GovernanceAction::SetDataSources {
+                data_sources: vec![to_data_source(&SECONDARY_DATA_SOURCE)],
+            },
+            Chain::Any,
+            &DEFAULT_GOVERNANCE_SOURCE,
+            2,
+        ),
+        create_governance_vaa(
+            GovernanceAction::SetFee { val: 3, expo: 3 },
+            Chain::Solana,
+            &WRONG_SOURCE,
+            3,
+        ),
+        create_governance_vaa(
+            GovernanceAction::SetFee { val: 3, expo: 3 },
+            Chain::Ethereum,
+            &DEFAULT_GOVERNANCE_SOURCE,
+            3,
+        ),
+        create_governance_vaa(
+            GovernanceAction::SetValidPeriod { valid_seconds: 60 },
+            Chain::Solana,
+            &DEFAULT_GOVERNANCE_SOURCE,
+            4,
+        ),
+        claim_vaa.clone(),
+        create_governance_vaa(
+            GovernanceAction::AuthorizeGovernanceDataSourceTransfer { claim_vaa },
+            Chain::Solana,
+            &DEFAULT_GOVERNANCE_SOURCE,
+            5,
+        ),
+        create_governance_vaa(
+            GovernanceAction::SetWormholeAddress {
+                address: new_wormhole,
+            },
+            Chain::Solana,
+            &SECONDARY_GOVERNANCE_SOURCE,
+            11,
+        ),
+        create_governance_vaa(
+            GovernanceAction::SetFee { val: 3, expo: 3 },
+            Chain::Solana,
+            &DEFAULT_GOVERNANCE_SOURCE,
+            6,
+        ),
+    ];
+
+    let ProgramTestFixtures {
+        mut program_simulator,
+        encoded_vaa_addresses,
+        governance_authority,
+    } = setup_pyth_receiver(
+        vaas.iter()
+            .map(|vaa| serde_wormhole::from_slice(vaa).unwrap())
+            .collect(),
+        WrongSetupOption::None,
+    )
+    .await;
+
+    let executor = program_simulator.get_funded_keypair().await.unwrap();
+
+    // only the governance authority can enable the governance through VAAs
+    assert_eq!(
+        program_simulator
+            .process_ix_with_default_compute_limit(
+                InitializeGovernanceConfig::populate(
+                    executor.pubkey(),
+                    to_data_source(&DEFAULT_GOVERNANCE_SOURCE),
+                    Chain::Solana.into(),
+                ),
+                &vec![&executor],
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        into_transaction_error(ReceiverError::GovernanceAuthorityMismatch)
+    );
+
+    program_simulator
+        .process_ix_with_default_compute_limit(
+            InitializeGovernanceConfig::populate(
+                governance_authority.pubkey(),
+                to_data_source(&DEFAULT_GOVERNANCE_SOURCE),
+                Chain::Solana.into(),
+            ),
+            &vec![&governance_authority],
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        program_simulator
+            .get_anchor_account_data::<GovernanceConfig>(get_governance_config_address())
+            .await
+            .unwrap(),
+        GovernanceConfig {
+            governance_data_source: to_data_source(&DEFAULT_GOVERNANCE_SOURCE),
+            target_chain_id: Chain::Solana.into(),
+            last_executed_governance_sequence: 0,
+            governance_data_source_index: 0,
+        }
+    );
+
+    // set the fee
+    program_simulator
+        .process_ix_with_default_compute_limit(
+            ExecuteGovernanceInstruction::populate(
+                executor.pubkey(),
+                encoded_vaa_addresses[0],
+                None,
+            ),
+            &vec![&executor],
+            None,
+        )
+        .await
+        .unwrap();
+
+    let config = program_simulator
+        .get_anchor_account_data::<Config>(get_config_address())
+        .await
+        .unwrap();
+    assert_eq!(config.single_update_fee_in_lamports, 2000);
+
+    // the same VAA can't be executed twice
+    assert_eq!(
+        program_simulator
+            .process_ix_with_default_compute_limit(
+                ExecuteGovernanceInstruction::populate(
+                    executor.pubkey(),
+                    encoded_vaa_addresses[0],
+                    None,
+                ),
+                &vec![&executor],
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        into_transaction_error(ReceiverError::GovernanceMessageReplayed)
+    );
+
+    // set the data sources with an instruction for all chains
+    program_simulator
+        .process_ix_with_default_compute_limit(
+            ExecuteGovernanceInstruction::populate(
+                executor.pubkey(),
+                encoded_vaa_addresses[1],
+                None,
+            ),
+            &vec![&executor],
+            None,
+        )
+        .await
+        .unwrap();
+
+    let config = program_simulator
+        .get_anchor_account_data::<Config>(get_config_address())
+        .await
+        .unwrap();
+    assert_eq!(
+        config.valid_data_sources,
+        vec![to_data_source(&SECONDARY_DATA_SOURCE)]
+    );
+
+    // wrong emitter, wrong target chain and unsupported action
+    for (encoded_vaa_address, error) in [
+        (
+            encoded_vaa_addresses[2],
+            ReceiverError::InvalidGovernanceDataSource,
+        ),
+        (
+            encoded_vaa_addresses[3],
+            ReceiverError::InvalidGovernanceTargetChain,
+        ),
+        (
+            encoded_vaa_addresses[4],
+            ReceiverError::UnsupportedGovernanceAction,
+        ),
+    ] {
+        assert_eq!(
+            program_simulator
+                .process_ix_with_default_compute_limit(
+                    ExecuteGovernanceInstruction::populate(
+                        executor.pubkey(),
+                        encoded_vaa_address,
+                        None,
+                    ),
+                    &vec![&executor],
+                    None,
+                )
+                .await
+                .unwrap_err()
+                .unwrap(),
+            into_transaction_error(error)
+        );
+    }
+
+    // the governance data source transfer needs the encoded VAA of the claim VAA
+    for claim_encoded_vaa in [None, Some(encoded_vaa_addresses[0])] {
+        assert_eq!(
+            program_simulator
+                .process_ix_with_default_compute_limit(
+                    ExecuteGovernanceInstruction::populate(
+                        executor.pubkey(),
+                        encoded_vaa_addresses[6],
+                        claim_encoded_vaa,
+                    ),
+                    &vec![&executor],
+                    None,
+                )
+                .await
+                .unwrap_err()
+                .unwrap(),
+            into_transaction_error(ReceiverError::ClaimVaaMismatch)
+        );
+    }
+
+    program_simulator
+        .process_ix_with_default_compute_limit(
+            ExecuteGovernanceInstruction::populate(
+                executor.pubkey(),
+                encoded_vaa_addresses[6],
+                Some(encoded_vaa_addresses[5]),
+            ),
+            &vec![&executor],
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        program_simulator
+            .get_anchor_account_data::<GovernanceConfig>(get_governance_config_address())
+            .await
+            .unwrap(),
+        GovernanceConfig {
+            governance_data_source: to_data_source(&SECONDARY_GOVERNANCE_SOURCE),
+            target_chain_id: Chain::Solana.into(),
+            last_executed_governance_sequence: 10,
+            governance_data_source_index: 1,
+        }
+    );
+
+    // the previous governance data source can't govern anymore
+    assert_eq!(
+        program_simulator
+            .process_ix_with_default_compute_limit(
+                ExecuteGovernanceInstruction::populate(
+                    executor.pubkey(),
+                    encoded_vaa_addresses[8],
+                    None,
+                ),
+                &vec![&executor],
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        into_transaction_error(ReceiverError::InvalidGovernanceDataSource)
+    );
+
+    // the new governance data source can
+    program_simulator
+        .process_ix_with_default_compute_limit(
+            ExecuteGovernanceInstruction::populate(
+                executor.pubkey(),
+                encoded_vaa_addresses[7],
+                None,
+            ),
+            &vec![&executor],
+            None,
+        )
+        .await
+        .unwrap();
+
+    let config = program_simulator
+        .get_anchor_account_data::<Config>(get_config_address())
+        .await
+        .unwrap();
+    assert_eq!(config.wormhole, new_wormhole);
+    assert_eq!(config.single_update_fee_in_lamports, 2000);
+}
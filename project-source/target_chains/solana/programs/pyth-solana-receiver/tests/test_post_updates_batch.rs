@@ -0,0 +1,249 @@
+use {
+    common_test_utils::{
+        assert_treasury_balance, setup_pyth_receiver, ProgramTestFixtures, WrongSetupOption,
+    },
+    program_simulator::into_transaction_error,
+    pyth_solana_receiver::{
+        error::ReceiverError,
+        instruction::PostUpdatesBatch,
+        sdk::{deserialize_accumulator_update_data, DEFAULT_TREASURY_ID},
+    },
+    pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel},
+    pythnet_sdk::{
+        messages::Message,
+        test_utils::{create_accumulator_message, create_dummy_price_feed_message},
+    },
+    solana_sdk::{rent::Rent, signature::Keypair, signer::Signer},
+};
+
+#[tokio::test]
+async fn test_post_updates_batch() {
+    let feed_1 = create_dummy_price_feed_message(100);
+    let feed_2 = create_dummy_price_feed_message(200);
+    let message =
+        create_accumulator_message(&[&feed_1, &feed_2], &[&feed_1, &feed_2], false, false, None);
+    let (vaa, merkle_price_updates) = deserialize_accumulator_update_data(message).unwrap();
+
+    let ProgramTestFixtures {
+        mut program_simulator,
+        encoded_vaa_addresses,
+        governance_authority: _,
+    } = setup_pyth_receiver(
+        vec![serde_wormhole::from_slice(&vaa).unwrap()],
+        WrongSetupOption::None,
+    )
+    .await;
+
+    assert_treasury_balance(&mut program_simulator, 0, DEFAULT_TREASURY_ID).await;
+
+    let poster = program_simulator.get_funded_keypair().await.unwrap();
+    let price_update_keypair_1 = Keypair::new();
+    let price_update_keypair_2 = Keypair::new();
+
+    // post both updates to new accounts
+    program_simulator
+        .process_ix_with_default_compute_limit(
+            PostUpdatesBatch::populate(
+                poster.pubkey(),
+                poster.pubkey(),
+                encoded_vaa_addresses[0],
+                &[
+                    (price_update_keypair_1.pubkey(), true),
+                    (price_update_keypair_2.pubkey(), true),
+                ],
+                merkle_price_updates.clone(),
+                DEFAULT_TREASURY_ID,
+            ),
+            &vec![&poster, &price_update_keypair_1, &price_update_keypair_2],
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_treasury_balance(
+        &mut program_simulator,
+        Rent::default().minimum_balance(0),
+        DEFAULT_TREASURY_ID,
+    )
+    .await;
+
+    for (price_update_keypair, feed) in [
+        (&price_update_keypair_1, &feed_1),
+        (&price_update_keypair_2, &feed_2),
+    ] {
+        let price_update_account = program_simulator
+            .get_anchor_account_data::<PriceUpdateV2>(price_update_keypair.pubkey())
+            .await
+            .unwrap();
+
+        assert_eq!(price_update_account.write_authority, poster.pubkey());
+        assert_eq!(
+            price_update_account.verification_level,
+            VerificationLevel::Full
+        );
+        assert_eq!(
+            Message::PriceFeedMessage(price_update_account.price_message),
+            *feed
+        );
+        assert_eq!(
+            price_update_account.posted_slot,
+            program_simulator.get_clock().await.unwrap().slot
+        );
+    }
+
+    // post the updates again to the existing accounts, swapped, the existing accounts don't need to sign
+    program_simulator
+        .process_ix_with_default_compute_limit(
+            PostUpdatesBatch::populate(
+                poster.pubkey(),
+                poster.pubkey(),
+                encoded_vaa_addresses[0],
+                &[
+                    (price_update_keypair_1.pubkey(), false),
+                    (price_update_keypair_2.pubkey(), false),
+                ],
+                vec![
+                    merkle_price_updates[1].clone(),
+                    merkle_price_updates[0].clone(),
+                ],
+                DEFAULT_TREASURY_ID,
+            ),
+            &vec![&poster],
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_treasury_balance(
+        &mut program_simulator,
+        Rent::default().minimum_balance(0) + 2,
+        DEFAULT_TREASURY_ID,
+    )
+    .await;
+
+    for (price_update_keypair, feed) in [
+        (&price_update_keypair_1, &feed_2),
+        (&price_update_keypair_2, &feed_1),
+    ] {
+        let price_update_account = program_simulator
+            .get_anchor_account_data::<PriceUpdateV2>(price_update_keypair.pubkey())
+            .await
+            .unwrap();
+
+        assert_eq!(price_update_account.write_authority, poster.pubkey());
+        assert_eq!(
+            Message::PriceFeedMessage(price_update_account.price_message),
+            *feed
+        );
+    }
+
+    // This poster doesn't have the write authority
+    let poster_2 = program_simulator.get_funded_keypair().await.unwrap();
+    assert_eq!(
+        program_simulator
+            .process_ix_with_default_compute_limit(
+                PostUpdatesBatch::populate(
+                    poster_2.pubkey(),
+                    poster_2.pubkey(),
+                    encoded_vaa_addresses[0],
+                    &[(price_update_keypair_1.pubkey(), false)],
+                    vec![merkle_price_updates[0].clone()],
+                    DEFAULT_TREASURY_ID,
+                ),
+                &vec![&poster_2],
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        into_transaction_error(ReceiverError::WrongWriteAuthority)
+    );
+}
+
+#[tokio::test]
+async fn test_post_updates_batch_wrong_number_of_accounts() {
+    let feed_1 = create_dummy_price_feed_message(100);
+    let feed_2 = create_dummy_price_feed_message(200);
+    let message =
+        create_accumulator_message(&[&feed_1, &feed_2], &[&feed_1, &feed_2], false, false, None);
+    let (vaa, merkle_price_updates) = deserialize_accumulator_update_data(message).unwrap();
+
+    let ProgramTestFixtures {
+        mut program_simulator,
+        encoded_vaa_addresses,
+        governance_authority: _,
+    } = setup_pyth_receiver(
+        vec![serde_wormhole::from_slice(&vaa).unwrap()],
+        WrongSetupOption::None,
+    )
+    .await;
+
+    let poster = program_simulator.get_funded_keypair().await.unwrap();
+    let price_update_keypair = Keypair::new();
+
+    assert_eq!(
+        program_simulator
+            .process_ix_with_default_compute_limit(
+                PostUpdatesBatch::populate(
+                    poster.pubkey(),
+                    poster.pubkey(),
+                    encoded_vaa_addresses[0],
+                    &[(price_update_keypair.pubkey(), true)],
+                    merkle_price_updates,
+                    DEFAULT_TREASURY_ID,
+                ),
+                &vec![&poster, &price_update_keypair],
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        into_transaction_error(ReceiverError::PriceUpdateAccountsMismatch)
+    );
+}
+
+#[tokio::test]
+async fn test_post_updates_batch_wrong_setup() {
+    let feed_1 = create_dummy_price_feed_message(100);
+    let feed_2 = create_dummy_price_feed_message(200);
+    let message =
+        create_accumulator_message(&[&feed_1, &feed_2], &[&feed_1, &feed_2], false, false, None);
+    let (vaa, merkle_price_updates) = deserialize_accumulator_update_data(message).unwrap();
+
+    let ProgramTestFixtures {
+        mut program_simulator,
+        encoded_vaa_addresses,
+        governance_authority: _,
+    } = setup_pyth_receiver(
+        vec![serde_wormhole::from_slice(&vaa).unwrap()],
+        WrongSetupOption::UnverifiedEncodedVaa,
+    )
+    .await;
+
+    let poster = program_simulator.get_funded_keypair().await.unwrap();
+    let price_update_keypair_1 = Keypair::new();
+    let price_update_keypair_2 = Keypair::new();
+
+    assert_eq!(
+        program_simulator
+            .process_ix_with_default_compute_limit(
+                PostUpdatesBatch::populate(
+                    poster.pubkey(),
+                    poster.pubkey(),
+                    encoded_vaa_addresses[0],
+                    &[
+                        (price_update_keypair_1.pubkey(), true),
+                        (price_update_keypair_2.pubkey(), true),
+                    ],
+                    merkle_price_updates,
+                    DEFAULT_TREASURY_ID,
+                ),
+                &vec![&poster, &price_update_keypair_1, &price_update_keypair_2],
+                None,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+        into_transaction_error(wormhole_core_bridge_solana::error::CoreBridgeError::UnverifiedVaa)
+    );
+}
@@ -0,0 +1,523 @@
+//! Helpers to combine the prices of several `PriceUpdateV2` accounts.
+//!
+//! Prices are first read with [`get_prices_no_older_than`], which applies the same staleness and
+//! verification checks as [`PriceUpdateV2::get_price_no_older_than_with_custom_verification_level`]
+//! to every account, and then combined with [`get_median_price`], [`get_min_price`],
+//! [`get_max_price`], [`get_confidence_weighted_price`] or [`get_cross_rate`].
+//!
+//! # Example
+//! ```
+//! use pyth_solana_receiver_sdk::aggregation::{get_median_price, get_prices_no_older_than};
+//! use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2, VerificationLevel};
+//! use anchor_lang::prelude::*;
+//!
+//! const MAXIMUM_AGE : u64 = 30;
+//! const USDC_FEED_ID: &str = "0xeaa020c61cc479712813461ce153894a96a6c00b21ed0cfc2798d1f9a9e9c94a"; // USDC/USD
+//! const USDT_FEED_ID: &str = "0x2b89b9dc8fdf9f34709a5b106b472f0f39bb6ca9ce04b0fd7f2e971688e2e53b"; // USDT/USD
+//!
+//! #[derive(Accounts)]
+//! pub struct ReadPriceAccounts<'info> {
+//!     pub usdc_price_update: Account<'info, PriceUpdateV2>,
+//!     pub usdt_price_update: Account<'info, PriceUpdateV2>,
+//! }
+//!
+//! pub fn read_price_accounts(ctx : Context<ReadPriceAccounts>) -> Result<()> {
+//!     let prices = get_prices_no_older_than(
+//!         &Clock::get()?,
+//!         MAXIMUM_AGE,
+//!         &[
+//!             (&*ctx.accounts.usdc_price_update, &get_feed_id_from_hex(USDC_FEED_ID)?),
+//!             (&*ctx.accounts.usdt_price_update, &get_feed_id_from_hex(USDT_FEED_ID)?),
+//!         ],
+//!         VerificationLevel::Full,
+//!     )?;
+//!     let stablecoin_price = get_median_price(&prices)?;
+//!     Ok(())
+//! }
+//! ```
+
+use {
+    crate::{
+        check,
+        error::GetPriceError,
+        price_update::{FeedId, Price, PriceUpdateV2, VerificationLevel},
+    },
+    anchor_lang::prelude::Clock,
+};
+
+/// The weight of a price with a confidence of 1 in [`get_confidence_weighted_price`].
+const CONFIDENCE_WEIGHT_SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// The denominator of deviations expressed in basis points.
+const BASIS_POINTS: u128 = 10_000;
+
+/// Get the prices of several `PriceUpdateV2` accounts, each for its given `FeedId`, no older than
+/// `maximum_age` and with at least the given verification level.
+///
+/// Fails with the error of the first account that doesn't pass the checks.
+pub fn get_prices_no_older_than(
+    clock: &Clock,
+    maximum_age: u64,
+    price_updates: &[(&PriceUpdateV2, &FeedId)],
+    verification_level: VerificationLevel,
+) -> std::result::Result<Vec<Price>, GetPriceError> {
+    price_updates
+        .iter()
+        .map(|(price_update, feed_id)| {
+            price_update.get_price_no_older_than_with_custom_verification_level(
+                clock,
+                maximum_age,
+                feed_id,
+                verification_level,
+            )
+        })
+        .collect()
+}
+
+impl Price {
+    /// Express the price and its confidence with another exponent.
+    /// Increasing the exponent truncates the digits that can't be represented anymore.
+    pub fn scale_to_exponent(&self, exponent: i32) -> std::result::Result<Price, GetPriceError> {
+        let (price, conf) = if exponent >= self.exponent {
+            let divisor = pow10(exponent.abs_diff(self.exponent))?;
+            (
+                i128::from(self.price) / divisor,
+                i128::from(self.conf) / divisor,
+            )
+        } else {
+            let multiplier = pow10(exponent.abs_diff(self.exponent))?;
+            (
+                i128::from(self.price)
+                    .checked_mul(multiplier)
+                    .ok_or(GetPriceError::PriceOverflow)?,
+                i128::from(self.conf)
+                    .checked_mul(multiplier)
+                    .ok_or(GetPriceError::PriceOverflow)?,
+            )
+        };
+        Ok(Price {
+            price: i64::try_from(price).map_err(|_| GetPriceError::PriceOverflow)?,
+            conf: u64::try_from(conf).map_err(|_| GetPriceError::PriceOverflow)?,
+            exponent,
+            publish_time: self.publish_time,
+        })
+    }
+}
+
+/// Get the median of several prices.
+///
+/// With an odd number of prices the median price is returned as is. With an even number of
+/// prices the two middle prices are averaged, with the smallest of their exponents and the oldest
+/// of their publish times.
+pub fn get_median_price(prices: &[Price]) -> std::result::Result<Price, GetPriceError> {
+    check!(!prices.is_empty(), GetPriceError::EmptyPrices);
+    let mut sorted_prices = normalize_prices(prices)?
+        .into_iter()
+        .zip(prices.iter())
+        .collect::<Vec<_>>();
+    sorted_prices.sort_by_key(|(normalized_price, _)| normalized_price.price);
+
+    let middle = sorted_prices.len() / 2;
+    if sorted_prices.len() % 2 == 1 {
+        return Ok(*sorted_prices[middle].1);
+    }
+
+    let (lower, _) = sorted_prices[middle - 1];
+    let (upper, _) = sorted_prices[middle];
+    Ok(Price {
+        price: i64::try_from((i128::from(lower.price) + i128::from(upper.price)) / 2)
+            .map_err(|_| GetPriceError::PriceOverflow)?,
+        conf: u64::try_from((u128::from(lower.conf) + u128::from(upper.conf)) / 2)
+            .map_err(|_| GetPriceError::PriceOverflow)?,
+        exponent: lower.exponent,
+        publish_time: lower.publish_time.min(upper.publish_time),
+    })
+}
+
+/// Get the lowest of several prices, as is.
+pub fn get_min_price(prices: &[Price]) -> std::result::Result<Price, GetPriceError> {
+    let normalized_prices = normalize_prices(prices)?;
+    normalized_prices
+        .iter()
+        .zip(prices.iter())
+        .min_by_key(|(normalized_price, _)| normalized_price.price)
+        .map(|(_, price)| *price)
+        .ok_or(GetPriceError::EmptyPrices)
+}
+
+/// Get the highest of several prices, as is.
+pub fn get_max_price(prices: &[Price]) -> std::result::Result<Price, GetPriceError> {
+    let normalized_prices = normalize_prices(prices)?;
+    normalized_prices
+        .iter()
+        .zip(prices.iter())
+        .max_by_key(|(normalized_price, _)| normalized_price.price)
+        .map(|(_, price)| *price)
+        .ok_or(GetPriceError::EmptyPrices)
+}
+
+/// Get the average of several prices weighted by the inverse of their confidence, so the most
+/// precise prices weigh the most. The confidence of the result is the weighted average of the
+/// confidences.
+///
+/// The result has the smallest exponent of the prices and the oldest of their publish times.
+/// A price with a confidence of 0 is weighted as if its confidence was 1.
+pub fn get_confidence_weighted_price(
+    prices: &[Price],
+) -> std::result::Result<Price, GetPriceError> {
+    check!(!prices.is_empty(), GetPriceError::EmptyPrices);
+    let normalized_prices = normalize_prices(prices)?;
+
+    let mut weighted_price_sum: i128 = 0;
+    let mut weighted_conf_sum: u128 = 0;
+    let mut weight_sum: u128 = 0;
+    for price in &normalized_prices {
+        let weight = CONFIDENCE_WEIGHT_SCALE / u128::from(price.conf.max(1));
+        weighted_price_sum = i128::try_from(weight)
+            .ok()
+            .and_then(|weight| weight.checked_mul(i128::from(price.price)))
+            .and_then(|weighted_price| weighted_price_sum.checked_add(weighted_price))
+            .ok_or(GetPriceError::PriceOverflow)?;
+        weighted_conf_sum = weight
+            .checked_mul(u128::from(price.conf))
+            .and_then(|weighted_conf| weighted_conf_sum.checked_add(weighted_conf))
+            .ok_or(GetPriceError::PriceOverflow)?;
+        weight_sum = weight_sum
+            .checked_add(weight)
+            .ok_or(GetPriceError::PriceOverflow)?;
+    }
+
+    let price = weighted_price_sum
+        / i128::try_from(weight_sum).map_err(|_| GetPriceError::PriceOverflow)?;
+    let conf = weighted_conf_sum / weight_sum;
+    Ok(Price {
+        price: i64::try_from(price).map_err(|_| GetPriceError::PriceOverflow)?,
+        conf: u64::try_from(conf).map_err(|_| GetPriceError::PriceOverflow)?,
+        exponent: normalized_prices[0].exponent,
+        publish_time: oldest_publish_time(prices),
+    })
+}
+
+/// Get the price of `base` in units of `quote` with the given exponent, for example the price of
+/// A/B from the prices of A/USD and B/USD.
+///
+/// The relative confidences of the two prices are added up, and the result has the oldest of their
+/// publish times. Fails if the quote price isn't positive or if the result doesn't fit with the
+/// given exponent.
+pub fn get_cross_rate(
+    base: &Price,
+    quote: &Price,
+    exponent: i32,
+) -> std::result::Result<Price, GetPriceError> {
+    check!(quote.price > 0, GetPriceError::NonPositiveQuotePrice);
+
+    // base / quote = (base.price / quote.price) * 10^(base.exponent - quote.exponent), so the price
+    // with the given exponent is base.price * 10^shift / quote.price.
+    let shift = i64::from(base.exponent) - i64::from(quote.exponent) - i64::from(exponent);
+    let scale =
+        pow10(u32::try_from(shift.unsigned_abs()).map_err(|_| GetPriceError::PriceOverflow)?)?;
+    let (numerator_scale, denominator_scale) = if shift >= 0 { (scale, 1) } else { (1, scale) };
+    let denominator = i128::from(quote.price)
+        .checked_mul(denominator_scale)
+        .ok_or(GetPriceError::PriceOverflow)?;
+
+    let price = i128::from(base.price)
+        .checked_mul(numerator_scale)
+        .ok_or(GetPriceError::PriceOverflow)?
+        / denominator;
+
+    // conf = |price| * (base.conf / |base.price| + quote.conf / quote.price)
+    //      = base.conf * 10^shift / quote.price + |price| * quote.conf / quote.price
+    let base_conf = i128::from(base.conf)
+        .checked_mul(numerator_scale)
+        .ok_or(GetPriceError::PriceOverflow)?
+        / denominator;
+    let quote_conf = price
+        .abs()
+        .checked_mul(i128::from(quote.conf))
+        .ok_or(GetPriceError::PriceOverflow)?
+        / i128::from(quote.price);
+    let conf = base_conf
+        .checked_add(quote_conf)
+        .ok_or(GetPriceError::PriceOverflow)?;
+
+    Ok(Price {
+        price: i64::try_from(price).map_err(|_| GetPriceError::PriceOverflow)?,
+        conf: u64::try_from(conf).map_err(|_| GetPriceError::PriceOverflow)?,
+        exponent,
+        publish_time: base.publish_time.min(quote.publish_time),
+    })
+}
+
+impl PriceUpdateV2 {
+    /// Get a `Price` from a `PriceUpdateV2` account for a given `FeedId` no older than `maximum_age` with customizable verification level,
+    /// and check that it deviates from the EMA price by at most `max_deviation_bps` basis points of the EMA price.
+    ///
+    /// A price far from its EMA usually means the market is moving fast or is being manipulated, so programs that can wait for the price to settle
+    /// can use this to reject it.
+    pub fn get_price_no_older_than_with_max_ema_deviation(
+        &self,
+        clock: &Clock,
+        maximum_age: u64,
+        feed_id: &FeedId,
+        verification_level: VerificationLevel,
+        max_deviation_bps: u64,
+    ) -> std::result::Result<Price, GetPriceError> {
+        let price = self.get_price_no_older_than_with_custom_verification_level(
+            clock,
+            maximum_age,
+            feed_id,
+            verification_level,
+        )?;
+
+        // The price and the EMA price have the same exponent.
+        let ema_price = self.price_message.ema_price;
+        let deviation = u128::from(price.price.abs_diff(ema_price));
+        check!(
+            deviation * BASIS_POINTS
+                <= u128::from(max_deviation_bps) * u128::from(ema_price.unsigned_abs()),
+            GetPriceError::EmaDeviationTooLarge
+        );
+        Ok(price)
+    }
+}
+
+/// Express the prices with the smallest of their exponents so they can be compared.
+fn normalize_prices(prices: &[Price]) -> std::result::Result<Vec<Price>, GetPriceError> {
+    let exponent = prices
+        .iter()
+        .map(|price| price.exponent)
+        .min()
+        .ok_or(GetPriceError::EmptyPrices)?;
+    prices
+        .iter()
+        .map(|price| price.scale_to_exponent(exponent))
+        .collect()
+}
+
+fn oldest_publish_time(prices: &[Price]) -> i64 {
+    prices
+        .iter()
+        .map(|price| price.publish_time)
+        .min()
+        .unwrap_or_default()
+}
+
+fn pow10(exponent: u32) -> std::result::Result<i128, GetPriceError> {
+    10i128
+        .checked_pow(exponent)
+        .ok_or(GetPriceError::PriceOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, pythnet_sdk::messages::PriceFeedMessage, solana_program::pubkey::Pubkey};
+
+    fn price(price: i64, conf: u64, exponent: i32, publish_time: i64) -> Price {
+        Price {
+            price,
+            conf,
+            exponent,
+            publish_time,
+        }
+    }
+
+    fn price_update(
+        feed_id: FeedId,
+        price: i64,
+        ema_price: i64,
+        verification_level: VerificationLevel,
+    ) -> PriceUpdateV2 {
+        PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level,
+            price_message: PriceFeedMessage {
+                feed_id,
+                ema_conf: 0,
+                ema_price,
+                price,
+                conf: 2,
+                exponent: -8,
+                prev_publish_time: 899,
+                publish_time: 900,
+            },
+            posted_slot: 0,
+        }
+    }
+
+    #[test]
+    fn scale_to_exponent() {
+        assert_eq!(
+            price(12345, 67, -2, 10).scale_to_exponent(-4),
+            Ok(price(1234500, 6700, -4, 10))
+        );
+        assert_eq!(
+            price(12345, 67, -2, 10).scale_to_exponent(0),
+            Ok(price(123, 0, 0, 10))
+        );
+        assert_eq!(
+            price(i64::MAX, 0, 0, 10).scale_to_exponent(-1),
+            Err(GetPriceError::PriceOverflow)
+        );
+    }
+
+    #[test]
+    fn get_prices() {
+        let feed_id = [0; 32];
+        let mock_clock = Clock {
+            unix_timestamp: 1000,
+            ..Default::default()
+        };
+        let fully_verified = price_update(feed_id, 100, 100, VerificationLevel::Full);
+        let partially_verified = price_update(
+            feed_id,
+            200,
+            200,
+            VerificationLevel::Partial { num_signatures: 5 },
+        );
+
+        assert_eq!(
+            get_prices_no_older_than(
+                &mock_clock,
+                100,
+                &[(&fully_verified, &feed_id), (&fully_verified, &feed_id)],
+                VerificationLevel::Full
+            ),
+            Ok(vec![price(100, 2, -8, 900), price(100, 2, -8, 900)])
+        );
+        assert_eq!(
+            get_prices_no_older_than(
+                &mock_clock,
+                100,
+                &[(&fully_verified, &feed_id), (&partially_verified, &feed_id)],
+                VerificationLevel::Full
+            ),
+            Err(GetPriceError::InsufficientVerificationLevel)
+        );
+        assert_eq!(
+            get_prices_no_older_than(
+                &mock_clock,
+                10,
+                &[(&fully_verified, &feed_id)],
+                VerificationLevel::Full
+            ),
+            Err(GetPriceError::PriceTooOld)
+        );
+        assert_eq!(
+            get_prices_no_older_than(
+                &mock_clock,
+                100,
+                &[(&fully_verified, &[1; 32])],
+                VerificationLevel::Full
+            ),
+            Err(GetPriceError::MismatchedFeedId)
+        );
+    }
+
+    #[test]
+    fn median_min_max() {
+        let prices = [
+            price(300, 3, -2, 10),
+            price(1, 1, 0, 20),     // 100 with exponent -2
+            price(2000, 2, -3, 30), // 200 with exponent -2
+        ];
+        assert_eq!(get_median_price(&prices), Ok(prices[2]));
+        assert_eq!(get_min_price(&prices), Ok(prices[1]));
+        assert_eq!(get_max_price(&prices), Ok(prices[0]));
+
+        // The average of 300 +- 3 and 100 +- 100 with exponent -2.
+        assert_eq!(get_median_price(&prices[..2]), Ok(price(200, 51, -2, 10)));
+
+        assert_eq!(get_median_price(&[]), Err(GetPriceError::EmptyPrices));
+        assert_eq!(get_min_price(&[]), Err(GetPriceError::EmptyPrices));
+        assert_eq!(get_max_price(&[]), Err(GetPriceError::EmptyPrices));
+    }
+
+    #[test]
+    fn confidence_weighted() {
+        // 1000 +- 10 and 200 +- 30 with exponent -2, the first price is 3 times more precise.
+        let prices = [price(1000, 10, -2, 10), price(20, 3, -1, 20)];
+        // (1000 * 3 + 200 * 1) / 4 = 800 and (10 * 3 + 30 * 1) / 4 = 15, minus rounding errors.
+        assert_eq!(
+            get_confidence_weighted_price(&prices),
+            Ok(price(800, 14, -2, 10))
+        );
+
+        assert_eq!(
+            get_confidence_weighted_price(&[]),
+            Err(GetPriceError::EmptyPrices)
+        );
+    }
+
+    #[test]
+    fn cross_rate() {
+        // ETH/USD = 3000 +- 3 and BTC/USD = 60000 +- 60
+        let eth_usd = price(300_000_000_000, 300_000_000, -8, 100);
+        let btc_usd = price(6_000_000_000_000, 6_000_000_000, -8, 99);
+
+        assert_eq!(
+            get_cross_rate(&eth_usd, &btc_usd, -8),
+            Ok(price(5_000_000, 10_000, -8, 99))
+        );
+        assert_eq!(
+            get_cross_rate(&btc_usd, &eth_usd, -4),
+            Ok(price(200_000, 400, -4, 99))
+        );
+        // Different input exponents: BTC/USD = 60000 +- 60 with exponent -2.
+        assert_eq!(
+            get_cross_rate(&eth_usd, &price(6_000_000, 6_000, -2, 99), -8),
+            Ok(price(5_000_000, 10_000, -8, 99))
+        );
+
+        assert_eq!(
+            get_cross_rate(&eth_usd, &price(0, 0, -8, 99), -8),
+            Err(GetPriceError::NonPositiveQuotePrice)
+        );
+        assert_eq!(
+            get_cross_rate(&eth_usd, &btc_usd, -30),
+            Err(GetPriceError::PriceOverflow)
+        );
+    }
+
+    #[test]
+    fn ema_deviation() {
+        let feed_id = [0; 32];
+        let mock_clock = Clock {
+            unix_timestamp: 1000,
+            ..Default::default()
+        };
+        // The price deviates from the EMA price by 5%.
+        let price_update = price_update(feed_id, 10_500, 10_000, VerificationLevel::Full);
+
+        assert_eq!(
+            price_update.get_price_no_older_than_with_max_ema_deviation(
+                &mock_clock,
+                100,
+                &feed_id,
+                VerificationLevel::Full,
+                500
+            ),
+            Ok(price(10_500, 2, -8, 900))
+        );
+        assert_eq!(
+            price_update.get_price_no_older_than_with_max_ema_deviation(
+                &mock_clock,
+                100,
+                &feed_id,
+                VerificationLevel::Full,
+                499
+            ),
+            Err(GetPriceError::EmaDeviationTooLarge)
+        );
+        assert_eq!(
+            price_update.get_price_no_older_than_with_max_ema_deviation(
+                &mock_clock,
+                10,
+                &feed_id,
+                VerificationLevel::Full,
+                500
+            ),
+            Err(GetPriceError::PriceTooOld)
+        );
+    }
+}
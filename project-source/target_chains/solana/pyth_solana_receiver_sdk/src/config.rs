@@ -21,11 +21,26 @@ impl Config {
     pub const LEN: usize = 370; // This is two times the current size of a Config account with 2 data sources, to leave space for more fields
 }
 
+/// The state of the governance through Pyth governance VAAs. Once this account is initialized, the
+/// fields of `Config` can also be changed by executing governance VAAs from `governance_data_source`.
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct GovernanceConfig {
+    pub governance_data_source: DataSource, // The emitter of the governance VAAs
+    pub target_chain_id: u16, // The wormhole chain id of this chain, governance instructions must target it or all chains (0)
+    pub last_executed_governance_sequence: u64, // Governance VAAs must have a higher sequence to be executed, to prevent replays
+    pub governance_data_source_index: u32, // Governance data source transfer requests must have a higher index to be executed, to prevent replays
+}
+
+impl GovernanceConfig {
+    pub const LEN: usize = 112; // This is two times the current size of a GovernanceConfig account, to leave space for more fields
+}
+
 #[cfg(test)]
 pub mod tests {
     use {
         super::DataSource,
-        crate::config::Config,
+        crate::config::{Config, GovernanceConfig},
         anchor_lang::{AnchorSerialize, Discriminator},
         solana_program::pubkey::Pubkey,
     };
@@ -58,4 +73,27 @@ pub mod tests {
             Config::discriminator().len() + test_config.try_to_vec().unwrap().len() <= Config::LEN
         );
     }
+
+    #[test]
+    fn check_governance_config_size() {
+        let test_governance_config = GovernanceConfig {
+            governance_data_source: DataSource {
+                chain: 1,
+                emitter: Pubkey::new_unique(),
+            },
+            target_chain_id: 1,
+            last_executed_governance_sequence: 0,
+            governance_data_source_index: 0,
+        };
+
+        assert_eq!(
+            test_governance_config.try_to_vec().unwrap().len(),
+            2 + 32 + 2 + 8 + 4
+        );
+        assert!(
+            GovernanceConfig::discriminator().len()
+                + test_governance_config.try_to_vec().unwrap().len()
+                <= GovernanceConfig::LEN
+        );
+    }
 }
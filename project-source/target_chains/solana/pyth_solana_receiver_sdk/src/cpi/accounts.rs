@@ -170,3 +170,97 @@ impl<'info> anchor_lang::ToAccountInfos<'info> for PostUpdate<'info> {
         account_infos
     }
 }
+
+pub struct PostTwapUpdate<'info> {
+    pub payer: anchor_lang::solana_program::account_info::AccountInfo<'info>,
+    pub start_encoded_vaa: anchor_lang::solana_program::account_info::AccountInfo<'info>,
+    pub end_encoded_vaa: anchor_lang::solana_program::account_info::AccountInfo<'info>,
+    pub config: anchor_lang::solana_program::account_info::AccountInfo<'info>,
+    pub treasury: anchor_lang::solana_program::account_info::AccountInfo<'info>,
+    ///The constraint is such that either the price_update_account is uninitialized or the write_authority is the write_authority.
+    ///Pubkey::default() is the SystemProgram on Solana and it can't sign so it's impossible that price_update_account.write_authority == Pubkey::default() once the account is initialized
+    pub twap_update_account: anchor_lang::solana_program::account_info::AccountInfo<'info>,
+    pub system_program: anchor_lang::solana_program::account_info::AccountInfo<'info>,
+    pub write_authority: anchor_lang::solana_program::account_info::AccountInfo<'info>,
+}
+#[automatically_derived]
+impl<'info> anchor_lang::ToAccountMetas for PostTwapUpdate<'info> {
+    fn to_account_metas(
+        &self,
+        is_signer: Option<bool>,
+    ) -> Vec<anchor_lang::solana_program::instruction::AccountMeta> {
+        let mut account_metas = vec![];
+        account_metas.push(anchor_lang::solana_program::instruction::AccountMeta::new(
+            anchor_lang::Key::key(&self.payer),
+            true,
+        ));
+        account_metas.push(
+            anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                anchor_lang::Key::key(&self.start_encoded_vaa),
+                false,
+            ),
+        );
+        account_metas.push(
+            anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                anchor_lang::Key::key(&self.end_encoded_vaa),
+                false,
+            ),
+        );
+        account_metas.push(
+            anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                anchor_lang::Key::key(&self.config),
+                false,
+            ),
+        );
+        account_metas.push(anchor_lang::solana_program::instruction::AccountMeta::new(
+            anchor_lang::Key::key(&self.treasury),
+            false,
+        ));
+        account_metas.push(anchor_lang::solana_program::instruction::AccountMeta::new(
+            anchor_lang::Key::key(&self.twap_update_account),
+            true,
+        ));
+        account_metas.push(
+            anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                anchor_lang::Key::key(&self.system_program),
+                false,
+            ),
+        );
+        account_metas.push(
+            anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
+                anchor_lang::Key::key(&self.write_authority),
+                true,
+            ),
+        );
+        account_metas
+    }
+}
+#[automatically_derived]
+impl<'info> anchor_lang::ToAccountInfos<'info> for PostTwapUpdate<'info> {
+    fn to_account_infos(
+        &self,
+    ) -> Vec<anchor_lang::solana_program::account_info::AccountInfo<'info>> {
+        let mut account_infos = vec![];
+        account_infos.extend(anchor_lang::ToAccountInfos::to_account_infos(&self.payer));
+        account_infos.extend(anchor_lang::ToAccountInfos::to_account_infos(
+            &self.start_encoded_vaa,
+        ));
+        account_infos.extend(anchor_lang::ToAccountInfos::to_account_infos(
+            &self.end_encoded_vaa,
+        ));
+        account_infos.extend(anchor_lang::ToAccountInfos::to_account_infos(&self.config));
+        account_infos.extend(anchor_lang::ToAccountInfos::to_account_infos(
+            &self.treasury,
+        ));
+        account_infos.extend(anchor_lang::ToAccountInfos::to_account_infos(
+            &self.twap_update_account,
+        ));
+        account_infos.extend(anchor_lang::ToAccountInfos::to_account_infos(
+            &self.system_program,
+        ));
+        account_infos.extend(anchor_lang::ToAccountInfos::to_account_infos(
+            &self.write_authority,
+        ));
+        account_infos
+    }
+}
@@ -1,6 +1,6 @@
 use {
-    self::accounts::{PostUpdate, PostUpdateAtomic},
-    crate::{PostUpdateAtomicParams, PostUpdateParams},
+    self::accounts::{PostTwapUpdate, PostUpdate, PostUpdateAtomic},
+    crate::{PostTwapUpdateParams, PostUpdateAtomicParams, PostUpdateParams},
     anchor_lang::prelude::*,
 };
 
@@ -49,3 +49,25 @@ pub fn post_update_atomic<'info>(
     anchor_lang::solana_program::program::invoke_signed(&ix, &acc_infos, ctx.signer_seeds)
         .map_or_else(|e| Err(Into::into(e)), |_| Ok(()))
 }
+
+// This implementation comes from the expanded macros of programs/pyth-solana-receiver/src/lib.rs
+pub fn post_twap_update<'info>(
+    ctx: anchor_lang::context::CpiContext<'_, '_, '_, 'info, PostTwapUpdate<'info>>,
+    params: PostTwapUpdateParams,
+) -> anchor_lang::Result<()> {
+    let ix = {
+        let mut ix_data = AnchorSerialize::try_to_vec(&params)
+            .map_err(|_| anchor_lang::error::ErrorCode::InstructionDidNotSerialize)?;
+        let mut data = [232, 176, 212, 105, 69, 121, 18, 30].to_vec();
+        data.append(&mut ix_data);
+        let accounts = ctx.to_account_metas(None);
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: crate::ID,
+            accounts,
+            data,
+        }
+    };
+    let acc_infos = ctx.to_account_infos();
+    anchor_lang::solana_program::program::invoke_signed(&ix, &acc_infos, ctx.signer_seeds)
+        .map_or_else(|e| Err(Into::into(e)), |_| Ok(()))
+}
@@ -15,6 +15,14 @@ pub enum GetPriceError {
     FeedIdMustBe32Bytes,
     #[msg("Feed id contains non-hex characters")]
     FeedIdNonHexCharacter,
+    #[msg("At least one price is required")]
+    EmptyPrices,
+    #[msg("Overflow when normalizing or combining prices")]
+    PriceOverflow,
+    #[msg("The quote price must be positive")]
+    NonPositiveQuotePrice,
+    #[msg("The price deviates from the EMA price by more than the maximum deviation")]
+    EmaDeviationTooLarge,
 }
 
 #[macro_export]
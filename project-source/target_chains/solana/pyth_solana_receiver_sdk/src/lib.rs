@@ -8,6 +8,7 @@ use {
     solana_program::{pubkey, pubkey::Pubkey},
 };
 
+pub mod aggregation;
 pub mod config;
 pub mod cpi;
 pub mod error;
@@ -25,6 +26,12 @@ pub struct PostUpdateParams {
     pub treasury_id: u8,
 }
 
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
+pub struct PostUpdatesBatchParams {
+    pub merkle_price_updates: Vec<MerklePriceUpdate>,
+    pub treasury_id: u8,
+}
+
 #[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
 pub struct PostUpdateAtomicParams {
     pub vaa: Vec<u8>,
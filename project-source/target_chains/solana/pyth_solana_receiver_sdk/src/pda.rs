@@ -2,6 +2,7 @@ use {crate::ID, anchor_lang::prelude::*};
 
 pub const CONFIG_SEED: &str = "config";
 pub const TREASURY_SEED: &str = "treasury";
+pub const GOVERNANCE_CONFIG_SEED: &str = "governance_config";
 
 // There is one treasury for each u8 value
 // This is to load balance the write load
@@ -12,3 +13,7 @@ pub fn get_treasury_address(treasury_id: u8) -> Pubkey {
 pub fn get_config_address() -> Pubkey {
     Pubkey::find_program_address(&[CONFIG_SEED.as_ref()], &ID).0
 }
+
+pub fn get_governance_config_address() -> Pubkey {
+    Pubkey::find_program_address(&[GOVERNANCE_CONFIG_SEED.as_ref()], &ID).0
+}
@@ -1,13 +1,54 @@
-use std::{path::Path, process::Command, str};
+use std::{fmt::Display, path::Path, process::Command, str};
 
 use base64::Engine;
 use libsecp256k1::SecretKey;
 use primitive_types::U256;
-use test_vaas::{
-    locate_vaa_in_price_update, print_as_cairo_fn, re_sign_price_update, serialize_vaa, u256_to_be,
-    DataSource, EthAddress, GuardianSet, GuardianSetUpgrade,
+use pyth_test_vectors::{
+    format::to_cairo_fn,
+    governance::{GovernanceAction, GovernanceInstruction, GuardianSetUpgrade},
+    price_updates::{accumulator_update_vaa, re_sign_accumulator_update},
+    GuardianSet,
 };
-use wormhole_vaas::{PayloadKind, VaaBody};
+use pythnet_sdk::test_utils::DataSource;
+use serde_wormhole::RawMessage;
+use wormhole_sdk::{vaa::Body, Address, Chain};
+
+/// The target chain ID of the Pyth governance instructions.
+const TARGET_CHAIN_ID: u16 = 60051;
+
+/// Prints data in the format suitable for embedding in tests.
+fn print_as_cairo_fn(data: &[u8], name: impl Display, comment: impl Display) {
+    println!();
+    print!(
+        "{}",
+        to_cairo_fn(&name.to_string(), &comment.to_string(), data)
+    );
+}
+
+fn u256_to_be(value: U256) -> [u8; 32] {
+    let mut buf = [0; 32];
+    value.to_big_endian(&mut buf);
+    buf
+}
+
+/// Creates the body of a VAA emitted by `emitter_address` (left-padded with zeros) on
+/// `emitter_chain`.
+fn vaa_body(
+    emitter_chain: u16,
+    emitter_address: u16,
+    sequence: u64,
+    payload: Vec<u8>,
+) -> Body<Box<RawMessage>> {
+    Body {
+        timestamp: 1,
+        nonce: 2,
+        emitter_chain: Chain::from(emitter_chain),
+        emitter_address: Address(u256_to_be(emitter_address.into())),
+        sequence,
+        consistency_level: 6,
+        payload: <Box<RawMessage>>::from(payload),
+    }
+}
 
 fn main() {
     println!("// Generated with generate_test_data.rs, do not edit\n");
@@ -22,9 +63,8 @@ fn main() {
         "good_update1",
         "A random update pulled from Hermes.",
     );
-    let indexes = locate_vaa_in_price_update(&good_update1);
     print_as_cairo_fn(
-        &good_update1[indexes.pos_before_vaa..indexes.pos_after_vaa],
+        &accumulator_update_vaa(&good_update1),
         "good_vm1",
         "A wormhole VAA from a random update pulled from Hermes.",
     );
@@ -66,12 +106,12 @@ fn main() {
     // Generated with `generate_keypair.rs`.
     let secret1 = "047f10198517025e9bf2f6d09ebb650826b35397f01ca2a64a38348cae653f86";
     let address1_hex = "686b9ea8e3237110eaaba1f1b7467559a3273819";
-    let address1 = EthAddress(hex::decode(address1_hex).unwrap());
+    let address1: [u8; 20] = hex::decode(address1_hex).unwrap().try_into().unwrap();
     println!("\npub const TEST_GUARDIAN_ADDRESS1: felt252 = 0x{address1_hex};");
 
     let secret2 = "a95d32e5e2b9464b3f49a0f7ef2ede3ff17585836b253b96c832a86d2b5614cb";
     let address2_hex = "363598f080a817e633fc2d8f2b92e6e637f8b449";
-    let address2 = EthAddress(hex::decode(address2_hex).unwrap());
+    let address2: [u8; 20] = hex::decode(address2_hex).unwrap().try_into().unwrap();
     println!("pub const TEST_GUARDIAN_ADDRESS2: felt252 = 0x{address2_hex};");
 
     let guardians = GuardianSet {
@@ -79,25 +119,18 @@ fn main() {
         secrets: vec![SecretKey::parse_slice(&hex::decode(secret1).unwrap()).unwrap()],
     };
 
-    let empty_set_upgrade = serialize_vaa(
-        guardians.sign_vaa(
-            &[0],
-            VaaBody {
-                timestamp: 1,
-                nonce: 2,
-                emitter_chain: 1,
-                emitter_address: u256_to_be(4.into()).into(),
-                sequence: 5.try_into().unwrap(),
-                consistency_level: 6,
-                payload: PayloadKind::Binary(
-                    GuardianSetUpgrade {
-                        chain_id: 60051,
-                        set_index: 1,
-                        guardians: Vec::new(),
-                    }
-                    .serialize(),
-                ),
-            },
+    let empty_set_upgrade = guardians.sign_body(
+        &[0],
+        vaa_body(
+            1,
+            4,
+            5,
+            GuardianSetUpgrade {
+                chain_id: TARGET_CHAIN_ID,
+                new_set_index: 1,
+                guardians: Vec::new(),
+            }
+            .serialize(),
         ),
     );
     print_as_cairo_fn(
@@ -106,25 +139,18 @@ fn main() {
         "An invalid wormhole guardian set upgrade instruction containing no new guardians.",
     );
 
-    let wrong_emitter_upgrade = serialize_vaa(
-        guardians.sign_vaa(
-            &[0],
-            VaaBody {
-                timestamp: 1,
-                nonce: 2,
-                emitter_chain: 1,
-                emitter_address: u256_to_be(5.into()).into(),
-                sequence: 5.try_into().unwrap(),
-                consistency_level: 6,
-                payload: PayloadKind::Binary(
-                    GuardianSetUpgrade {
-                        chain_id: 60051,
-                        set_index: 1,
-                        guardians: vec![address1.clone()],
-                    }
-                    .serialize(),
-                ),
-            },
+    let wrong_emitter_upgrade = guardians.sign_body(
+        &[0],
+        vaa_body(
+            1,
+            5,
+            5,
+            GuardianSetUpgrade {
+                chain_id: TARGET_CHAIN_ID,
+                new_set_index: 1,
+                guardians: vec![address1],
+            }
+            .serialize(),
         ),
     );
     print_as_cairo_fn(
@@ -133,25 +159,18 @@ fn main() {
         "A wormhole guardian set upgrade instruction with emitter not expected by the test.",
     );
 
-    let wrong_index_upgrade = serialize_vaa(
-        guardians.sign_vaa(
-            &[0],
-            VaaBody {
-                timestamp: 1,
-                nonce: 2,
-                emitter_chain: 1,
-                emitter_address: u256_to_be(4.into()).into(),
-                sequence: 5.try_into().unwrap(),
-                consistency_level: 6,
-                payload: PayloadKind::Binary(
-                    GuardianSetUpgrade {
-                        chain_id: 0,
-                        set_index: 3,
-                        guardians: vec![address1.clone()],
-                    }
-                    .serialize(),
-                ),
-            },
+    let wrong_index_upgrade = guardians.sign_body(
+        &[0],
+        vaa_body(
+            1,
+            4,
+            5,
+            GuardianSetUpgrade {
+                chain_id: 0,
+                new_set_index: 3,
+                guardians: vec![address1],
+            }
+            .serialize(),
         ),
     );
     print_as_cairo_fn(
@@ -160,25 +179,18 @@ fn main() {
         "A wormhole guardian set upgrade instruction with set index = 3 not expected by the test.",
     );
 
-    let upgrade_to_test2 = serialize_vaa(
-        guardians.sign_vaa(
-            &[0],
-            VaaBody {
-                timestamp: 1,
-                nonce: 2,
-                emitter_chain: 1,
-                emitter_address: u256_to_be(4.into()).into(),
-                sequence: 5.try_into().unwrap(),
-                consistency_level: 6,
-                payload: PayloadKind::Binary(
-                    GuardianSetUpgrade {
-                        chain_id: 0,
-                        set_index: 1,
-                        guardians: vec![address2],
-                    }
-                    .serialize(),
-                ),
-            },
+    let upgrade_to_test2 = guardians.sign_body(
+        &[0],
+        vaa_body(
+            1,
+            4,
+            5,
+            GuardianSetUpgrade {
+                chain_id: 0,
+                new_set_index: 1,
+                guardians: vec![address2],
+            }
+            .serialize(),
         ),
     );
     print_as_cairo_fn(
@@ -188,147 +200,110 @@ fn main() {
         test guardian #2 as the new guardian set.",
     );
 
-    // Pyth governance payload bytes are copied from
-    // `governance/xc_admin/packages/xc_admin_common/src/__tests__/GovernancePayload.test.ts`
-    let pyth_set_fee_payload = vec![
-        80, 84, 71, 77, 1, 3, 234, 147, 0, 0, 0, 0, 0, 0, 0, 42, 0, 0, 0, 0, 0, 0, 0, 2,
-    ];
-    let pyth_set_fee = serialize_vaa(guardians.sign_vaa(
-        &[0],
-        VaaBody {
-            timestamp: 1,
-            nonce: 2,
-            emitter_chain: 1,
-            emitter_address: u256_to_be(41.into()).into(),
-            sequence: 1.try_into().unwrap(),
-            consistency_level: 6,
-            payload: PayloadKind::Binary(pyth_set_fee_payload.clone()),
-        },
-    ));
+    let pyth_set_fee_payload = GovernanceInstruction {
+        action: GovernanceAction::SetFee { value: 42, expo: 2 },
+        target_chain_id: TARGET_CHAIN_ID,
+    }
+    .serialize();
+    let pyth_set_fee = guardians.sign_body(&[0], vaa_body(1, 41, 1, pyth_set_fee_payload.clone()));
     print_as_cairo_fn(
         &pyth_set_fee,
         "pyth_set_fee",
         "A Pyth governance instruction to set fee signed by the test guardian #1.",
     );
 
-    let pyth_set_fee_in_token_payload = vec![
-        80, 84, 71, 77, 1, 7, 234, 147, 0, 0, 0, 0, 0, 0, 0, 42, 0, 0, 0, 0, 0, 0, 0, 2, 32, 4,
-        157, 54, 87, 13, 78, 70, 244, 142, 153, 103, 75, 211, 252, 200, 70, 68, 221, 214, 185, 111,
-        124, 116, 27, 21, 98, 184, 47, 158, 0, 77, 199,
-    ];
-    let pyth_set_fee_in_token = serialize_vaa(guardians.sign_vaa(
-        &[0],
-        VaaBody {
-            timestamp: 1,
-            nonce: 2,
-            emitter_chain: 1,
-            emitter_address: u256_to_be(41.into()).into(),
-            sequence: 1.try_into().unwrap(),
-            consistency_level: 6,
-            payload: PayloadKind::Binary(pyth_set_fee_in_token_payload.clone()),
+    let pyth_set_fee_in_token_payload = GovernanceInstruction {
+        action: GovernanceAction::SetFeeInToken {
+            value: 42,
+            expo: 2,
+            token: hex::decode("049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7")
+                .unwrap(),
         },
-    ));
+        target_chain_id: TARGET_CHAIN_ID,
+    }
+    .serialize();
+    let pyth_set_fee_in_token =
+        guardians.sign_body(&[0], vaa_body(1, 41, 1, pyth_set_fee_in_token_payload));
     print_as_cairo_fn(
         &pyth_set_fee_in_token,
         "pyth_set_fee_in_token",
         "A Pyth governance instruction to set fee signed by the test guardian #1.",
     );
 
-    let pyth_set_data_sources = serialize_vaa(guardians.sign_vaa(
-        &[0],
-        VaaBody {
-            timestamp: 1,
-            nonce: 2,
-            emitter_chain: 1,
-            emitter_address: u256_to_be(41.into()).into(),
-            sequence: 1.try_into().unwrap(),
-            consistency_level: 6,
-            payload: PayloadKind::Binary(vec![
-                80, 84, 71, 77, 1, 2, 234, 147, 2, 0, 1, 107, 177, 69, 9, 166, 18, 240, 31, 187,
-                196, 207, 254, 235, 212, 187, 251, 73, 42, 134, 223, 113, 126, 190, 146, 235, 109,
-                244, 50, 163, 240, 10, 37, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 45,
-            ]),
+    let pyth_set_data_sources_payload = GovernanceInstruction {
+        action: GovernanceAction::SetDataSources {
+            data_sources: vec![
+                (
+                    1,
+                    hex::decode("6bb14509a612f01fbbc4cffeebd4bbfb492a86df717ebe92eb6df432a3f00a25")
+                        .unwrap()
+                        .try_into()
+                        .unwrap(),
+                ),
+                (3, u256_to_be(301.into())),
+            ],
         },
-    ));
+        target_chain_id: TARGET_CHAIN_ID,
+    }
+    .serialize();
+    let pyth_set_data_sources =
+        guardians.sign_body(&[0], vaa_body(1, 41, 1, pyth_set_data_sources_payload));
     print_as_cairo_fn(
         &pyth_set_data_sources,
         "pyth_set_data_sources",
         "A Pyth governance instruction to set data sources signed by the test guardian #1.",
     );
 
-    let pyth_set_wormhole = serialize_vaa(guardians.sign_vaa(
-        &[0],
-        VaaBody {
-            timestamp: 1,
-            nonce: 2,
-            emitter_chain: 1,
-            emitter_address: u256_to_be(41.into()).into(),
-            sequence: 1.try_into().unwrap(),
-            consistency_level: 6,
-            payload: PayloadKind::Binary(vec![
-                80, 84, 71, 77, 1, 6, 234, 147, 5, 3, 63, 6, 213, 196, 123, 204, 231, 150, 14, 167,
-                3, 176, 74, 11, 246, 75, 243, 63, 111, 46, 181, 97, 52, 150, 218, 116, 117, 34,
-                217, 194,
-            ]),
+    let pyth_set_wormhole_payload = GovernanceInstruction {
+        action: GovernanceAction::SetWormholeAddress {
+            address: hex::decode(
+                "05033f06d5c47bcce7960ea703b04a0bf64bf33f6f2eb5613496da747522d9c2",
+            )
+            .unwrap(),
         },
-    ));
+        target_chain_id: TARGET_CHAIN_ID,
+    }
+    .serialize();
+    let pyth_set_wormhole =
+        guardians.sign_body(&[0], vaa_body(1, 41, 1, pyth_set_wormhole_payload));
     print_as_cairo_fn(
         &pyth_set_wormhole,
         "pyth_set_wormhole",
         "A Pyth governance instruction to set wormhole address signed by the test guardian #1.",
     );
 
-    let pyth_request_transfer = serialize_vaa(guardians.sign_vaa(
-        &[0],
-        VaaBody {
-            timestamp: 1,
-            nonce: 2,
-            emitter_chain: 2,
-            emitter_address: u256_to_be(43.into()).into(),
-            sequence: 1.try_into().unwrap(),
-            consistency_level: 6,
-            payload: PayloadKind::Binary(vec![80, 84, 71, 77, 1, 5, 234, 147, 0, 0, 0, 1]),
+    let pyth_request_transfer_payload = GovernanceInstruction {
+        action: GovernanceAction::RequestGovernanceDataSourceTransfer {
+            governance_data_source_index: 1,
         },
-    ));
+        target_chain_id: TARGET_CHAIN_ID,
+    }
+    .serialize();
+    let pyth_request_transfer =
+        guardians.sign_body(&[0], vaa_body(2, 43, 1, pyth_request_transfer_payload));
     print_as_cairo_fn(
         &pyth_request_transfer,
         "pyth_request_transfer",
         "A Pyth governance instruction to request governance data source transfer signed by the test guardian #1.",
     );
 
-    let mut pyth_auth_transfer_payload = vec![80, 84, 71, 77, 1, 1, 234, 147];
-    pyth_auth_transfer_payload.extend_from_slice(&pyth_request_transfer);
-    let pyth_auth_transfer = serialize_vaa(guardians.sign_vaa(
-        &[0],
-        VaaBody {
-            timestamp: 1,
-            nonce: 2,
-            emitter_chain: 1,
-            emitter_address: u256_to_be(41.into()).into(),
-            sequence: 1.try_into().unwrap(),
-            consistency_level: 6,
-            payload: PayloadKind::Binary(pyth_auth_transfer_payload),
+    let pyth_auth_transfer_payload = GovernanceInstruction {
+        action: GovernanceAction::AuthorizeGovernanceDataSourceTransfer {
+            claim_vaa: pyth_request_transfer,
         },
-    ));
+        target_chain_id: TARGET_CHAIN_ID,
+    }
+    .serialize();
+    let pyth_auth_transfer =
+        guardians.sign_body(&[0], vaa_body(1, 41, 1, pyth_auth_transfer_payload));
     print_as_cairo_fn(
         &pyth_auth_transfer,
         "pyth_auth_transfer",
         "A Pyth governance instruction to authorize governance data source transfer signed by the test guardian #1.",
     );
 
-    let pyth_set_fee_alt_emitter = serialize_vaa(guardians.sign_vaa(
-        &[0],
-        VaaBody {
-            timestamp: 1,
-            nonce: 2,
-            emitter_chain: 2,
-            emitter_address: u256_to_be(43.into()).into(),
-            sequence: 2.try_into().unwrap(),
-            consistency_level: 6,
-            payload: PayloadKind::Binary(pyth_set_fee_payload.clone()),
-        },
-    ));
+    let pyth_set_fee_alt_emitter =
+        guardians.sign_body(&[0], vaa_body(2, 43, 2, pyth_set_fee_payload));
     print_as_cairo_fn(
         &pyth_set_fee_alt_emitter,
         "pyth_set_fee_alt_emitter",
@@ -357,20 +332,14 @@ fn main() {
         ("invalid_hash", 505.into()),
     ];
     for (name, hash) in upgrade_hashes {
-        let mut pyth_upgrade_payload = vec![80, 84, 71, 77, 1, 0, 234, 147];
-        pyth_upgrade_payload.extend_from_slice(&u256_to_be(hash));
-        let pyth_upgrade = serialize_vaa(guardians.sign_vaa(
-            &[0],
-            VaaBody {
-                timestamp: 1,
-                nonce: 2,
-                emitter_chain: 1,
-                emitter_address: u256_to_be(41.into()).into(),
-                sequence: 1.try_into().unwrap(),
-                consistency_level: 6,
-                payload: PayloadKind::Binary(pyth_upgrade_payload),
+        let pyth_upgrade_payload = GovernanceInstruction {
+            action: GovernanceAction::UpgradeContract {
+                payload: u256_to_be(hash).to_vec(),
             },
-        ));
+            target_chain_id: TARGET_CHAIN_ID,
+        }
+        .serialize();
+        let pyth_upgrade = guardians.sign_body(&[0], vaa_body(1, 41, 1, pyth_upgrade_payload));
         print_as_cairo_fn(
             &pyth_upgrade,
             format!("pyth_upgrade_{name}"),
@@ -389,7 +358,7 @@ fn main() {
         "504e41550100000003b801000000040d0078c1c39cc9036ff5b77ab57320c9063446902ca4d8a093eac4f483b58897fdea6ace38344d5b1beb85830c4c6ce6a1069587fe465d90b04baa3e7b31d9d4bf1401019bd155786ca5ba0b0892d6deb4652ee4807e524dde320ef786ae87005bffd319343d4c5fb73a1a37bc070b7b5701efdf5a1d30758aa6501abaa3538a971c1a6e0102e6ac44fc5c5475a791df7b6894993308fc73b760f6386cca37181504b4c981c1427cf9075b396b014d28471dc9a127097e43f7aa3d84253de55f28fbdc0c1e6400035e5570e94a512303d5e6a2fcad70e894a49422a9aae0f33d3b3c817a3a481f3b0e83d84052a1adc88bb5c4e9f243c1cac659aef7f91afd3c8d8345a26632b7480104a605e54eb1aadf738cfa6738e7f8e33cc8c7de75a095165302e3ab531eb5320c09e43454a0fbd6ae73465fd5d74933eba5500c089d65af9cb82ad171f63fae44000625c6f3ec9d903240d136ea1f401f815e559eee6ae0e1b64ccb458fc1e988b8b57ea91188d806a331b47a63de6a2669fb3efc683f5a3a59b5ad56523b46593f0a0107f0fea4b0113f2890ea9214a5cf306c44d6c6c411b26683d9ef29a001a1ec7f211e5a30aab07feddc488c2b7dfeccddd03a95786571899a5b329e813d997b5ded010b6a8542ebe7bfd20893ae6fcba2d9e1c10ffeada79f5bc98fa12cc81b773916780d1c48110065ece3647763c78e995447d691b298630fce719ba5a9bf06b667af000c776d09592a76644442801fd127ed93cc2eef0c7091a15befca05b5896acb19b376bf3499a30b2bf1e4728024fb38ab907e719523e7b61119d93e90686a94550e000ddf0fbecc9dba2b7487ed62bdd78a099aa20c587bfd8a73ac1e7ef344e7cfe5fb4ba92c20f174ac66ecd64b6cf994bf34b6d6cee6582bbaaf14b6a870078de96b000e19479568bb774e1a0b4b985c00e7747c70067daa96ab44008c86784b8b0ee6c729d0632003f9343730a452f359029a0961a260003d8d75245f0c8aa3db23023f010f1ebefdcd36a2fd6b72ce8fabe9e9e16ede44ef2b1c5179a4e44c0ca3a7b24cf03dcee54d90b28fd21dd649581616348565ad30d53fffc75a804bc25b29a85efb0112ca92c3b2c08afc49c7fc49c6678735563c6b3b5fc199ff70c951adb94dfaec0659d0543073a69ab1a8dd46be673af96fafd0c65d83ebfa274239dc7c383ac4c9016644908400000000001ae101faedac5851e32b9b23b5f9411a8c2bac4aae3ed4dd7b811dd1a72ea4aa710000000003611fb601415557560000000000086b3d38000027102c9a35a044ed7becf6fb4d92621d48a930c5b0c501005500e62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43000005b687eb22a900000000d0458627fffffff800000000664490830000000066449083000005ae1e43acc000000000bccb05040adf16c33ea64586472be0d01955ca53f009516b22cad85d8fa0255b15c4f0381b21c92f9861976eeb6fb48d0916e7b5d9c84c96f2f55b8ddee8292b5f737aa48b71bcf5cde935ad180852b00eb9689e4ce50ac2aaa85a2aa42c0d9573b8cc46f9518279e5f5681cd7449ac980c550e409242667480c8e9f3c9ea6a43d7d5a0f1b1969a81e3bf9fa0ea613fb4ef01ab0d7d1e587223a510e17061f9a29a8f52c64fb219556a265c5026844792da87ff3bc59b913bc16a56bd50680b342bd86a51640f9161ebcaa17ca",
     ];
     for (index, item) in data.into_iter().enumerate() {
-        let data = re_sign_price_update(&hex::decode(item).unwrap(), &guardians, None);
+        let data = re_sign_accumulator_update(&hex::decode(item).unwrap(), &guardians, &[0], None);
         print_as_cairo_fn(
             &data,
             format!("test_price_update{}", index + 1),
@@ -397,12 +366,13 @@ fn main() {
         );
     }
 
-    let test_update2_alt_emitter = re_sign_price_update(
+    let test_update2_alt_emitter = re_sign_accumulator_update(
         &hex::decode(data[1]).unwrap(),
         &guardians,
-        Some(DataSource {
-            emitter_chain_id: 3,
-            emitter_address: u256_to_be(301.into()).into(),
+        &[0],
+        Some(&DataSource {
+            address: Address(u256_to_be(301.into())),
+            chain: Chain::from(3),
         }),
     );
     print_as_cairo_fn(
@@ -411,7 +381,8 @@ fn main() {
         "An update pulled from Hermes and re-signed by the test guardian #1 with another emitter address.",
     );
 
-    let test_update2_set2 = re_sign_price_update(&hex::decode(data[1]).unwrap(), &guardians2, None);
+    let test_update2_set2 =
+        re_sign_accumulator_update(&hex::decode(data[1]).unwrap(), &guardians2, &[0], None);
     print_as_cairo_fn(
         &test_update2_set2,
         "test_update2_set2",
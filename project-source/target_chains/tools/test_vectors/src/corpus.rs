@@ -0,0 +1,572 @@
+use {
+    crate::{
+        governance::{
+            data_source_argument, GovernanceAction, GovernanceInstruction, GuardianSetUpgrade,
+        },
+        guardians::GuardianSet,
+        price_updates::{
+            create_accumulator_update, create_batch_attestation, create_price_attestation,
+            create_price_feed_message, create_twap_message,
+        },
+    },
+    pythnet_sdk::{
+        messages::{FeedId, Message},
+        test_utils::{
+            DataSource, DEFAULT_DATA_SOURCE, DEFAULT_GOVERNANCE_SOURCE, SECONDARY_DATA_SOURCE,
+            SECONDARY_GOVERNANCE_SOURCE, WRONG_SOURCE,
+        },
+    },
+    wormhole_sdk::{Address, Chain},
+};
+
+/// The emitter of Wormhole core governance instructions, such as guardian set upgrades.
+pub const WORMHOLE_GOVERNANCE_SOURCE: DataSource = DataSource {
+    address: Address({
+        let mut address = [0u8; 32];
+        address[31] = 4;
+        address
+    }),
+    chain: Chain::Solana,
+};
+
+/// The publish time of the prices of the corpus, except the stale ones.
+pub const PUBLISH_TIME: i64 = 1_700_000_000;
+
+/// The publish time of the stale prices of the corpus.
+pub const STALE_PUBLISH_TIME: i64 = 1;
+
+/// The feed IDs of the prices of the corpus.
+pub const FEED_IDS: [FeedId; 3] = [[1; 32], [2; 32], [3; 32]];
+
+/// The group of a test vector, which determines how receivers consume it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    PriceUpdate,
+    GovernanceInstruction,
+    GuardianSetUpgrade,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::PriceUpdate => "price_update",
+            Kind::GovernanceInstruction => "governance_instruction",
+            Kind::GuardianSetUpgrade => "guardian_set_upgrade",
+        }
+    }
+}
+
+pub struct TestVector {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub kind: Kind,
+    /// Whether the vector is well-formed and authorized, i.e. whether a receiver configured as
+    /// described by `Corpus` must accept it. Receivers may still reject the chain-specific
+    /// arguments of valid governance instructions, such as the upgrade of `UpgradeContract`.
+    pub valid: bool,
+    pub data: Vec<u8>,
+}
+
+/// The canonical test vectors, along with the configuration that receivers must be
+/// initialized with to consume them.
+///
+/// Receivers are expected to be initialized with the guardian set `guardian_set`, the data
+/// source `data_source`, the governance data source `governance_data_source` and to accept
+/// guardian set upgrades from `WORMHOLE_GOVERNANCE_SOURCE`. Governance instructions target all
+/// chains and have increasing sequence numbers, so they can be executed in order.
+pub struct Corpus {
+    pub guardian_set: GuardianSet,
+    /// The guardian set that `guardian_set_upgrade` upgrades to.
+    pub next_guardian_set: GuardianSet,
+    pub data_source: DataSource,
+    pub governance_data_source: DataSource,
+    pub vectors: Vec<TestVector>,
+}
+
+impl Corpus {
+    pub fn new() -> Self {
+        let guardian_set = GuardianSet::test(0);
+        let next_guardian_set = GuardianSet::generate(1, 5, 1);
+        let all_signers: Vec<usize> = (0..guardian_set.secrets.len()).collect();
+        let below_quorum: Vec<usize> = (0..guardian_set.quorum() - 1).collect();
+
+        let price_feeds = vec![
+            create_price_feed_message(FEED_IDS[0], 100_000_000, 50_000, -8, PUBLISH_TIME),
+            create_price_feed_message(FEED_IDS[1], 2_000_000, 1_000, -5, PUBLISH_TIME),
+            create_price_feed_message(FEED_IDS[2], -42, 3, 0, PUBLISH_TIME),
+        ];
+        let price_update = |signers: &[usize],
+                            emitter: &DataSource,
+                            messages: &[Message],
+                            corrupt_proofs: bool,
+                            corrupt_wormhole_message: bool| {
+            create_accumulator_update(
+                &guardian_set,
+                signers,
+                emitter,
+                messages,
+                corrupt_proofs,
+                corrupt_wormhole_message,
+            )
+        };
+
+        let mut vectors = vec![
+            TestVector {
+                name: "accumulator_update",
+                description: "An accumulator update of the prices of `FEED_IDS`.",
+                kind: Kind::PriceUpdate,
+                valid: true,
+                data: price_update(
+                    &all_signers,
+                    &DEFAULT_DATA_SOURCE,
+                    &price_feeds,
+                    false,
+                    false,
+                ),
+            },
+            TestVector {
+                name: "accumulator_update_quorum",
+                description: "An accumulator update of the price of `FEED_IDS[0]` signed by the \
+                    minimum number of guardians.",
+                kind: Kind::PriceUpdate,
+                valid: true,
+                data: price_update(
+                    &(0..guardian_set.quorum()).collect::<Vec<_>>(),
+                    &DEFAULT_DATA_SOURCE,
+                    &price_feeds[..1],
+                    false,
+                    false,
+                ),
+            },
+            TestVector {
+                name: "accumulator_update_stale",
+                description: "An accumulator update of the price of `FEED_IDS[0]` published at \
+                    `STALE_PUBLISH_TIME`.",
+                kind: Kind::PriceUpdate,
+                valid: true,
+                data: price_update(
+                    &all_signers,
+                    &DEFAULT_DATA_SOURCE,
+                    &[create_price_feed_message(
+                        FEED_IDS[0],
+                        100_000_000,
+                        50_000,
+                        -8,
+                        STALE_PUBLISH_TIME,
+                    )],
+                    false,
+                    false,
+                ),
+            },
+            TestVector {
+                name: "accumulator_update_twap",
+                description: "An accumulator update of the price and the TWAP message of \
+                    `FEED_IDS[0]`. Receivers that don't support TWAPs may reject it.",
+                kind: Kind::PriceUpdate,
+                valid: true,
+                data: price_update(
+                    &all_signers,
+                    &DEFAULT_DATA_SOURCE,
+                    &[
+                        price_feeds[0].clone(),
+                        create_twap_message(
+                            FEED_IDS[0],
+                            100_000_000_000,
+                            50_000_000,
+                            0,
+                            -8,
+                            PUBLISH_TIME,
+                            1000,
+                        ),
+                    ],
+                    false,
+                    false,
+                ),
+            },
+            TestVector {
+                name: "accumulator_update_corrupted_proof",
+                description: "An accumulator update whose merkle proofs don't match the signed \
+                    merkle root.",
+                kind: Kind::PriceUpdate,
+                valid: false,
+                data: price_update(
+                    &all_signers,
+                    &DEFAULT_DATA_SOURCE,
+                    &price_feeds,
+                    true,
+                    false,
+                ),
+            },
+            TestVector {
+                name: "accumulator_update_corrupted_wormhole_message",
+                description: "An accumulator update whose signed wormhole message has an invalid \
+                    magic.",
+                kind: Kind::PriceUpdate,
+                valid: false,
+                data: price_update(
+                    &all_signers,
+                    &DEFAULT_DATA_SOURCE,
+                    &price_feeds,
+                    false,
+                    true,
+                ),
+            },
+            TestVector {
+                name: "accumulator_update_wrong_emitter",
+                description: "An accumulator update emitted by an unknown data source.",
+                kind: Kind::PriceUpdate,
+                valid: false,
+                data: price_update(&all_signers, &WRONG_SOURCE, &price_feeds, false, false),
+            },
+            TestVector {
+                name: "accumulator_update_below_quorum",
+                description: "An accumulator update signed by one guardian less than the \
+                    quorum.",
+                kind: Kind::PriceUpdate,
+                valid: false,
+                data: price_update(
+                    &below_quorum,
+                    &DEFAULT_DATA_SOURCE,
+                    &price_feeds,
+                    false,
+                    false,
+                ),
+            },
+            TestVector {
+                name: "accumulator_update_next_guardian_set",
+                description: "An accumulator update signed by the guardian set that \
+                    `guardian_set_upgrade` upgrades to.",
+                kind: Kind::PriceUpdate,
+                valid: false,
+                data: create_accumulator_update(
+                    &next_guardian_set,
+                    &(0..next_guardian_set.secrets.len()).collect::<Vec<_>>(),
+                    &DEFAULT_DATA_SOURCE,
+                    &price_feeds,
+                    false,
+                    false,
+                ),
+            },
+            TestVector {
+                name: "accumulator_update_truncated",
+                description: "An accumulator update whose last byte is missing.",
+                kind: Kind::PriceUpdate,
+                valid: false,
+                data: {
+                    let mut data = price_update(
+                        &all_signers,
+                        &DEFAULT_DATA_SOURCE,
+                        &price_feeds,
+                        false,
+                        false,
+                    );
+                    data.pop();
+                    data
+                },
+            },
+            TestVector {
+                name: "batch_attestation",
+                description: "A legacy batch price attestation of the prices of `FEED_IDS[..2]`.",
+                kind: Kind::PriceUpdate,
+                valid: true,
+                data: create_batch_attestation(
+                    &guardian_set,
+                    &DEFAULT_DATA_SOURCE,
+                    vec![
+                        create_price_attestation(
+                            FEED_IDS[0],
+                            100_000_000,
+                            50_000,
+                            -8,
+                            PUBLISH_TIME,
+                        ),
+                        create_price_attestation(FEED_IDS[1], 2_000_000, 1_000, -5, PUBLISH_TIME),
+                    ],
+                ),
+            },
+            TestVector {
+                name: "batch_attestation_wrong_emitter",
+                description: "A legacy batch price attestation emitted by an unknown data source.",
+                kind: Kind::PriceUpdate,
+                valid: false,
+                data: create_batch_attestation(
+                    &guardian_set,
+                    &WRONG_SOURCE,
+                    vec![create_price_attestation(
+                        FEED_IDS[0],
+                        100_000_000,
+                        50_000,
+                        -8,
+                        PUBLISH_TIME,
+                    )],
+                ),
+            },
+        ];
+
+        let mut sequence = 0;
+        let mut governance_vaa = |emitter: &DataSource, payload: &[u8]| {
+            sequence += 1;
+            guardian_set.sign_vaa(emitter, sequence, payload)
+        };
+        let instruction = |action| {
+            GovernanceInstruction {
+                action,
+                target_chain_id: 0,
+            }
+            .serialize()
+        };
+
+        let request_transfer = governance_vaa(
+            &SECONDARY_GOVERNANCE_SOURCE,
+            &instruction(GovernanceAction::RequestGovernanceDataSourceTransfer {
+                governance_data_source_index: 1,
+            }),
+        );
+        let governance_instructions = [
+            (
+                "governance_set_fee",
+                "Sets the update fee to 42 * 10^2.",
+                GovernanceAction::SetFee { value: 42, expo: 2 },
+            ),
+            (
+                "governance_set_fee_in_token",
+                "Sets the update fee in the token whose 32-byte address is 0x01..01 to 42 * 10^2.",
+                GovernanceAction::SetFeeInToken {
+                    value: 42,
+                    expo: 2,
+                    token: vec![1; 32],
+                },
+            ),
+            (
+                "governance_set_transaction_fee",
+                "Sets the transaction fee to 7 * 10^1.",
+                GovernanceAction::SetTransactionFee { value: 7, expo: 1 },
+            ),
+            (
+                "governance_set_valid_period",
+                "Sets the valid time period to 120 seconds.",
+                GovernanceAction::SetValidPeriod { valid_seconds: 120 },
+            ),
+            (
+                "governance_set_data_sources",
+                "Sets the data sources to the data source of the corpus and \
+                    `SECONDARY_DATA_SOURCE`.",
+                GovernanceAction::SetDataSources {
+                    data_sources: vec![
+                        data_source_argument(&DEFAULT_DATA_SOURCE),
+                        data_source_argument(&SECONDARY_DATA_SOURCE),
+                    ],
+                },
+            ),
+            (
+                "governance_set_wormhole_address",
+                "Sets the Wormhole contract to the 20-byte address 0x05..05.",
+                GovernanceAction::SetWormholeAddress {
+                    address: vec![5; 20],
+                },
+            ),
+            (
+                "governance_withdraw_fee",
+                "Withdraws 1 * 10^3 of the collected fees to the address 0x06..06.",
+                GovernanceAction::WithdrawFee {
+                    target_address: [6; 20],
+                    value: 1,
+                    expo: 3,
+                },
+            ),
+            (
+                "governance_upgrade_contract",
+                "Upgrades the contract to the version whose 32-byte hash is 0x07..07.",
+                GovernanceAction::UpgradeContract {
+                    payload: vec![7; 32],
+                },
+            ),
+            (
+                "governance_authorize_transfer",
+                "Authorizes the governance data source transfer of \
+                    `governance_request_transfer`, making `SECONDARY_GOVERNANCE_SOURCE` the \
+                    governance data source.",
+                GovernanceAction::AuthorizeGovernanceDataSourceTransfer {
+                    claim_vaa: request_transfer.clone(),
+                },
+            ),
+        ];
+        for (name, description, action) in governance_instructions {
+            vectors.push(TestVector {
+                name,
+                description,
+                kind: Kind::GovernanceInstruction,
+                valid: true,
+                data: governance_vaa(&DEFAULT_GOVERNANCE_SOURCE, &instruction(action)),
+            });
+        }
+        vectors.push(TestVector {
+            name: "governance_request_transfer",
+            description: "The governance data source transfer request of \
+                `SECONDARY_GOVERNANCE_SOURCE`, embedded in `governance_authorize_transfer`. It \
+                can't be executed directly.",
+            kind: Kind::GovernanceInstruction,
+            valid: false,
+            data: request_transfer,
+        });
+
+        let set_fee = instruction(GovernanceAction::SetFee { value: 1, expo: 0 });
+        vectors.extend([
+            TestVector {
+                name: "governance_wrong_emitter",
+                description: "An instruction setting the update fee emitted by an unknown data \
+                    source.",
+                kind: Kind::GovernanceInstruction,
+                valid: false,
+                data: governance_vaa(&WRONG_SOURCE, &set_fee),
+            },
+            TestVector {
+                name: "governance_wrong_magic",
+                description: "An instruction setting the update fee with an invalid magic.",
+                kind: Kind::GovernanceInstruction,
+                valid: false,
+                data: {
+                    let mut payload = set_fee.clone();
+                    payload[0] ^= 0xff;
+                    governance_vaa(&DEFAULT_GOVERNANCE_SOURCE, &payload)
+                },
+            },
+            TestVector {
+                name: "governance_wrong_target_chain",
+                description: "An instruction setting the update fee targeting the chain 65535.",
+                kind: Kind::GovernanceInstruction,
+                valid: false,
+                data: governance_vaa(
+                    &DEFAULT_GOVERNANCE_SOURCE,
+                    &GovernanceInstruction {
+                        action: GovernanceAction::SetFee { value: 1, expo: 0 },
+                        target_chain_id: u16::MAX,
+                    }
+                    .serialize(),
+                ),
+            },
+            TestVector {
+                name: "governance_unknown_action",
+                description: "An instruction with the unknown action 255.",
+                kind: Kind::GovernanceInstruction,
+                valid: false,
+                data: {
+                    let mut payload = set_fee.clone();
+                    payload[5] = u8::MAX;
+                    governance_vaa(&DEFAULT_GOVERNANCE_SOURCE, &payload)
+                },
+            },
+            TestVector {
+                name: "governance_trailing_bytes",
+                description: "An instruction setting the update fee followed by an extra byte.",
+                kind: Kind::GovernanceInstruction,
+                valid: false,
+                data: {
+                    let mut payload = set_fee.clone();
+                    payload.push(0);
+                    governance_vaa(&DEFAULT_GOVERNANCE_SOURCE, &payload)
+                },
+            },
+        ]);
+
+        let upgrade = |new_set_index, guardians| {
+            GuardianSetUpgrade {
+                chain_id: 0,
+                new_set_index,
+                guardians,
+            }
+            .serialize()
+        };
+        vectors.extend([
+            TestVector {
+                name: "guardian_set_upgrade",
+                description: "Upgrades the guardian set to `next_guardian_set`.",
+                kind: Kind::GuardianSetUpgrade,
+                valid: true,
+                data: governance_vaa(
+                    &WORMHOLE_GOVERNANCE_SOURCE,
+                    &upgrade(1, next_guardian_set.addresses()),
+                ),
+            },
+            TestVector {
+                name: "guardian_set_upgrade_empty",
+                description: "Upgrades the guardian set to an empty guardian set.",
+                kind: Kind::GuardianSetUpgrade,
+                valid: false,
+                data: governance_vaa(&WORMHOLE_GOVERNANCE_SOURCE, &upgrade(1, vec![])),
+            },
+            TestVector {
+                name: "guardian_set_upgrade_wrong_index",
+                description: "Upgrades the guardian set to `next_guardian_set` with the index 3 \
+                    instead of 1.",
+                kind: Kind::GuardianSetUpgrade,
+                valid: false,
+                data: governance_vaa(
+                    &WORMHOLE_GOVERNANCE_SOURCE,
+                    &upgrade(3, next_guardian_set.addresses()),
+                ),
+            },
+            TestVector {
+                name: "guardian_set_upgrade_wrong_emitter",
+                description: "Upgrades the guardian set to `next_guardian_set`, emitted by the \
+                    Pyth governance data source.",
+                kind: Kind::GuardianSetUpgrade,
+                valid: false,
+                data: governance_vaa(
+                    &DEFAULT_GOVERNANCE_SOURCE,
+                    &upgrade(1, next_guardian_set.addresses()),
+                ),
+            },
+            TestVector {
+                name: "guardian_set_upgrade_wrong_module",
+                description: "Upgrades the guardian set to `next_guardian_set` with a module \
+                    other than \"Core\".",
+                kind: Kind::GuardianSetUpgrade,
+                valid: false,
+                data: {
+                    let mut payload = upgrade(1, next_guardian_set.addresses());
+                    payload[31] ^= 0xff;
+                    governance_vaa(&WORMHOLE_GOVERNANCE_SOURCE, &payload)
+                },
+            },
+        ]);
+
+        Corpus {
+            guardian_set,
+            next_guardian_set,
+            data_source: DEFAULT_DATA_SOURCE,
+            governance_data_source: DEFAULT_GOVERNANCE_SOURCE,
+            vectors,
+        }
+    }
+}
+
+impl Default for Corpus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::Corpus, std::collections::HashSet};
+
+    #[test]
+    fn test_corpus_is_deterministic() {
+        let corpus = Corpus::new();
+        let names: HashSet<&str> = corpus.vectors.iter().map(|vector| vector.name).collect();
+        assert_eq!(names.len(), corpus.vectors.len());
+
+        let data: Vec<Vec<u8>> = corpus
+            .vectors
+            .into_iter()
+            .map(|vector| vector.data)
+            .collect();
+        let new_data: Vec<Vec<u8>> = Corpus::new()
+            .vectors
+            .into_iter()
+            .map(|vector| vector.data)
+            .collect();
+        assert_eq!(data, new_data);
+    }
+}
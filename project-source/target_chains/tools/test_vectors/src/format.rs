@@ -0,0 +1,272 @@
+//! Renders a corpus as JSON or as source code of the languages of the receivers.
+use {
+    crate::{
+        corpus::{Corpus, TestVector, WORMHOLE_GOVERNANCE_SOURCE},
+        guardians::GuardianSet,
+    },
+    primitive_types::U256,
+    pythnet_sdk::test_utils::DataSource,
+    serde_json::json,
+    std::fmt::Write,
+};
+
+const HEADER: &str = "Generated by pyth-test-vectors, do not edit.";
+
+/// The number of bytes stored in each item of a Cairo `pyth::byte_buffer::ByteBuffer`.
+const CAIRO_BYTES_PER_ITEM: usize = 31;
+
+/// The number of bytes of a Sway `b256`.
+const SWAY_BYTES_PER_CHUNK: usize = 32;
+
+fn data_source_json(data_source: &DataSource) -> serde_json::Value {
+    json!({
+        "chain_id": u16::from(data_source.chain),
+        "emitter_address": hex::encode(data_source.address.0),
+    })
+}
+
+/// Renders the corpus and the configuration of the receivers as JSON, with binary data
+/// encoded in hex.
+pub fn to_json(corpus: &Corpus) -> String {
+    let guardian_set_json = |guardian_set: &GuardianSet| {
+        json!({
+            "index": guardian_set.set_index,
+            "addresses": guardian_set
+                .addresses()
+                .iter()
+                .map(hex::encode)
+                .collect::<Vec<_>>(),
+        })
+    };
+    let vectors: Vec<serde_json::Value> = corpus
+        .vectors
+        .iter()
+        .map(|vector| {
+            json!({
+                "name": vector.name,
+                "description": vector.description,
+                "kind": vector.kind.name(),
+                "valid": vector.valid,
+                "data": hex::encode(&vector.data),
+            })
+        })
+        .collect();
+    let json = json!({
+        "guardian_set": guardian_set_json(&corpus.guardian_set),
+        "next_guardian_set": guardian_set_json(&corpus.next_guardian_set),
+        "data_source": data_source_json(&corpus.data_source),
+        "governance_data_source": data_source_json(&corpus.governance_data_source),
+        "wormhole_governance_data_source": data_source_json(&WORMHOLE_GOVERNANCE_SOURCE),
+        "vectors": vectors,
+    });
+    serde_json::to_string_pretty(&json).unwrap()
+}
+
+/// Renders the corpus as Cairo functions returning a `pyth::byte_buffer::ByteBuffer`.
+pub fn to_cairo(corpus: &Corpus) -> String {
+    let mut out = String::new();
+    writeln!(out, "// {HEADER}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "use pyth::byte_buffer::{{ByteBuffer, ByteBufferImpl}};"
+    )
+    .unwrap();
+    for vector in &corpus.vectors {
+        writeln!(out).unwrap();
+        out.push_str(&to_cairo_fn(
+            &vector.name,
+            &vector_comment(vector),
+            &vector.data,
+        ));
+    }
+    out
+}
+
+/// Renders `data` as a Cairo function returning a `pyth::byte_buffer::ByteBuffer`, preceded by
+/// `comment`.
+pub fn to_cairo_fn(name: &str, comment: &str, data: &[u8]) -> String {
+    let (items, num_last_bytes) = cairo_byte_buffer(data);
+    let mut out = String::new();
+    writeln!(out, "// {comment}").unwrap();
+    writeln!(out, "pub fn {name}() -> ByteBuffer {{").unwrap();
+    writeln!(out, "    let bytes = array![").unwrap();
+    for item in items {
+        writeln!(out, "        {item},").unwrap();
+    }
+    writeln!(out, "    ];").unwrap();
+    writeln!(out, "    ByteBufferImpl::new(bytes, {num_last_bytes})").unwrap();
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+/// Renders `data` as the arguments of a `pyth::byte_buffer::ByteBuffer` accepted by
+/// `starkli invoke` and `starkli call`.
+pub fn to_cairo_cli_input(data: &[u8]) -> String {
+    let (items, num_last_bytes) = cairo_byte_buffer(data);
+    let mut out = format!("{num_last_bytes} {}", items.len());
+    for item in items {
+        write!(out, " {item}").unwrap();
+    }
+    out
+}
+
+/// Splits `data` into the items of a `pyth::byte_buffer::ByteBuffer`, as decimal felt literals,
+/// and returns them with the number of bytes stored in the last item.
+fn cairo_byte_buffer(data: &[u8]) -> (Vec<String>, usize) {
+    let items = data
+        .chunks(CAIRO_BYTES_PER_ITEM)
+        .map(|chunk| U256::from_big_endian(chunk).to_string())
+        .collect();
+    // Every item except the last one stores `CAIRO_BYTES_PER_ITEM` bytes.
+    let num_last_bytes = match data.len() % CAIRO_BYTES_PER_ITEM {
+        0 if data.is_empty() => 0,
+        0 => CAIRO_BYTES_PER_ITEM,
+        n => n,
+    };
+    (items, num_last_bytes)
+}
+
+/// Renders the corpus as Sway functions returning `Bytes`.
+pub fn to_sway(corpus: &Corpus) -> String {
+    let mut out = String::new();
+    writeln!(out, "library;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "// {HEADER}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "use std::bytes::Bytes;").unwrap();
+    for vector in &corpus.vectors {
+        writeln!(out).unwrap();
+        writeln!(out, "// {}", vector_comment(vector)).unwrap();
+        writeln!(out, "pub fn {}() -> Bytes {{", vector.name).unwrap();
+        writeln!(out, "    let mut bytes = Bytes::new();").unwrap();
+        let chunks = vector.data.chunks_exact(SWAY_BYTES_PER_CHUNK);
+        let remainder = chunks.remainder();
+        for chunk in chunks {
+            writeln!(
+                out,
+                "    bytes.append(Bytes::from(0x{}));",
+                hex::encode(chunk)
+            )
+            .unwrap();
+        }
+        for byte in remainder {
+            writeln!(out, "    bytes.push(0x{byte:02x}u8);").unwrap();
+        }
+        writeln!(out, "    bytes").unwrap();
+        writeln!(out, "}}").unwrap();
+    }
+    out
+}
+
+/// Renders the corpus as constants of a Solidity library.
+pub fn to_solidity(corpus: &Corpus) -> String {
+    let mut out = String::new();
+    writeln!(out, "// SPDX-License-Identifier: Apache-2.0").unwrap();
+    writeln!(out, "// {HEADER}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "pragma solidity ^0.8.0;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "library PythTestVectors {{").unwrap();
+    for (i, vector) in corpus.vectors.iter().enumerate() {
+        if i > 0 {
+            writeln!(out).unwrap();
+        }
+        writeln!(out, "    // {}", vector_comment(vector)).unwrap();
+        writeln!(
+            out,
+            "    bytes internal constant {} = hex\"{}\";",
+            vector.name.to_uppercase(),
+            hex::encode(&vector.data)
+        )
+        .unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+/// Renders the corpus as a test-only Move module of the `pyth` package.
+pub fn to_move(corpus: &Corpus) -> String {
+    let mut out = String::new();
+    writeln!(out, "// {HEADER}").unwrap();
+    writeln!(out, "#[test_only]").unwrap();
+    writeln!(out, "module pyth::test_vectors {{").unwrap();
+    for (i, vector) in corpus.vectors.iter().enumerate() {
+        if i > 0 {
+            writeln!(out).unwrap();
+        }
+        writeln!(out, "    // {}", vector_comment(vector)).unwrap();
+        let constant = vector.name.to_uppercase();
+        writeln!(
+            out,
+            "    const {constant}: vector<u8> = x\"{}\";",
+            hex::encode(&vector.data)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    public fun {}(): vector<u8> {{ {constant} }}",
+            vector.name
+        )
+        .unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn vector_comment(vector: &TestVector) -> String {
+    let validity = if vector.valid { "Valid" } else { "Invalid" };
+    format!("{} {validity}.", vector.description)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{to_cairo, to_cairo_cli_input, to_json},
+        crate::corpus::Corpus,
+        primitive_types::U256,
+    };
+
+    #[test]
+    fn test_json_round_trip() {
+        let corpus = Corpus::new();
+        let json: serde_json::Value = serde_json::from_str(&to_json(&corpus)).unwrap();
+        let vectors = json["vectors"].as_array().unwrap();
+        assert_eq!(vectors.len(), corpus.vectors.len());
+        for (json, vector) in vectors.iter().zip(&corpus.vectors) {
+            assert_eq!(json["name"], vector.name);
+            assert_eq!(
+                hex::decode(json["data"].as_str().unwrap()).unwrap(),
+                vector.data
+            );
+        }
+        assert_eq!(
+            json["guardian_set"]["addresses"].as_array().unwrap().len(),
+            19
+        );
+    }
+
+    #[test]
+    fn test_cairo_byte_buffer() {
+        let corpus = Corpus::new();
+        let cairo = to_cairo(&corpus);
+        let vector = &corpus.vectors[0];
+        let num_items = (vector.data.len() + 30) / 31;
+        let num_last_bytes = vector.data.len() - (num_items - 1) * 31;
+        assert!(cairo.contains(&format!(
+            "{},\n    ];\n    ByteBufferImpl::new(bytes, {num_last_bytes})",
+            U256::from_big_endian(&vector.data[(num_items - 1) * 31..])
+        )));
+    }
+
+    #[test]
+    fn test_cairo_cli_input() {
+        assert_eq!(to_cairo_cli_input(&[]), "0 0");
+        assert_eq!(to_cairo_cli_input(&[1, 2]), "2 1 258");
+        let data: Vec<u8> = (0..32).collect();
+        assert_eq!(
+            to_cairo_cli_input(&data),
+            format!("1 2 {} 31", U256::from_big_endian(&data[..31]))
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use pythnet_sdk::test_utils::DataSource;
+
+/// The magic of Pyth governance instructions.
+pub const GOVERNANCE_MAGIC: &[u8; 4] = b"PTGM";
+
+/// The module of Wormhole core governance instructions ("Core", left-padded to 32 bytes).
+pub const CORE_MODULE: [u8; 32] = {
+    let mut module = [0u8; 32];
+    module[28] = b'C';
+    module[29] = b'o';
+    module[30] = b'r';
+    module[31] = b'e';
+    module
+};
+
+/// The action of a guardian set upgrade in the Wormhole core module.
+pub const GUARDIAN_SET_UPGRADE_ACTION: u8 = 2;
+
+/// The module that a Pyth governance instruction is directed to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GovernanceModule {
+    Executor = 0,
+    Target = 1,
+    EvmExecutor = 2,
+}
+
+/// An action of the target chain module of Pyth governance, as defined in
+/// `governance/xc_admin/packages/xc_admin_common/src/governance_payload`.
+///
+/// The encoding of addresses and contract versions is chain-specific, so they are given as raw
+/// bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GovernanceAction {
+    UpgradeContract {
+        payload: Vec<u8>,
+    },
+    AuthorizeGovernanceDataSourceTransfer {
+        claim_vaa: Vec<u8>,
+    },
+    SetDataSources {
+        data_sources: Vec<(u16, [u8; 32])>,
+    },
+    SetFee {
+        value: u64,
+        expo: u64,
+    },
+    SetValidPeriod {
+        valid_seconds: u64,
+    },
+    RequestGovernanceDataSourceTransfer {
+        governance_data_source_index: u32,
+    },
+    SetWormholeAddress {
+        address: Vec<u8>,
+    },
+    SetFeeInToken {
+        value: u64,
+        expo: u64,
+        token: Vec<u8>,
+    },
+    SetTransactionFee {
+        value: u64,
+        expo: u64,
+    },
+    WithdrawFee {
+        target_address: [u8; 20],
+        value: u64,
+        expo: u64,
+    },
+}
+
+impl GovernanceAction {
+    /// The identifier of the action in the target chain module.
+    pub fn id(&self) -> u8 {
+        match self {
+            GovernanceAction::UpgradeContract { .. } => 0,
+            GovernanceAction::AuthorizeGovernanceDataSourceTransfer { .. } => 1,
+            GovernanceAction::SetDataSources { .. } => 2,
+            GovernanceAction::SetFee { .. } => 3,
+            GovernanceAction::SetValidPeriod { .. } => 4,
+            GovernanceAction::RequestGovernanceDataSourceTransfer { .. } => 5,
+            GovernanceAction::SetWormholeAddress { .. } => 6,
+            GovernanceAction::SetFeeInToken { .. } => 7,
+            GovernanceAction::SetTransactionFee { .. } => 8,
+            GovernanceAction::WithdrawFee { .. } => 9,
+        }
+    }
+
+    /// Serializes the arguments of the action.
+    pub fn serialize_payload(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        match self {
+            GovernanceAction::UpgradeContract { payload: upgrade } => {
+                payload.extend_from_slice(upgrade);
+            }
+            GovernanceAction::AuthorizeGovernanceDataSourceTransfer { claim_vaa } => {
+                payload.extend_from_slice(claim_vaa);
+            }
+            GovernanceAction::SetDataSources { data_sources } => {
+                payload.push(data_sources.len().try_into().unwrap());
+                for (chain_id, emitter_address) in data_sources {
+                    payload.extend_from_slice(&chain_id.to_be_bytes());
+                    payload.extend_from_slice(emitter_address);
+                }
+            }
+            GovernanceAction::SetFee { value, expo }
+            | GovernanceAction::SetTransactionFee { value, expo } => {
+                payload.extend_from_slice(&value.to_be_bytes());
+                payload.extend_from_slice(&expo.to_be_bytes());
+            }
+            GovernanceAction::SetValidPeriod { valid_seconds } => {
+                payload.extend_from_slice(&valid_seconds.to_be_bytes());
+            }
+            GovernanceAction::RequestGovernanceDataSourceTransfer {
+                governance_data_source_index,
+            } => {
+                payload.extend_from_slice(&governance_data_source_index.to_be_bytes());
+            }
+            GovernanceAction::SetWormholeAddress { address } => {
+                payload.extend_from_slice(address);
+            }
+            GovernanceAction::SetFeeInToken { value, expo, token } => {
+                payload.extend_from_slice(&value.to_be_bytes());
+                payload.extend_from_slice(&expo.to_be_bytes());
+                payload.push(token.len().try_into().unwrap());
+                payload.extend_from_slice(token);
+            }
+            GovernanceAction::WithdrawFee {
+                target_address,
+                value,
+                expo,
+            } => {
+                payload.extend_from_slice(target_address);
+                payload.extend_from_slice(&value.to_be_bytes());
+                payload.extend_from_slice(&expo.to_be_bytes());
+            }
+        }
+        payload
+    }
+}
+
+/// A Pyth governance instruction directed to the target chain module.
+pub struct GovernanceInstruction {
+    pub action: GovernanceAction,
+    /// The Wormhole chain ID of the target chain, or 0 for all chains.
+    pub target_chain_id: u16,
+}
+
+impl GovernanceInstruction {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = GOVERNANCE_MAGIC.to_vec();
+        buf.push(GovernanceModule::Target as u8);
+        buf.push(self.action.id());
+        buf.extend_from_slice(&self.target_chain_id.to_be_bytes());
+        buf.extend_from_slice(&self.action.serialize_payload());
+        buf
+    }
+}
+
+/// A Wormhole core governance instruction to upgrade the guardian set.
+pub struct GuardianSetUpgrade {
+    /// The Wormhole chain ID of the target chain, or 0 for all chains.
+    pub chain_id: u16,
+    pub new_set_index: u32,
+    pub guardians: Vec<[u8; 20]>,
+}
+
+impl GuardianSetUpgrade {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = CORE_MODULE.to_vec();
+        buf.push(GUARDIAN_SET_UPGRADE_ACTION);
+        buf.extend_from_slice(&self.chain_id.to_be_bytes());
+        buf.extend_from_slice(&self.new_set_index.to_be_bytes());
+        buf.push(self.guardians.len().try_into().unwrap());
+        for guardian in &self.guardians {
+            buf.extend_from_slice(guardian);
+        }
+        buf
+    }
+}
+
+/// Converts a data source of `pythnet_sdk::test_utils` to the arguments of `SetDataSources`.
+pub fn data_source_argument(data_source: &DataSource) -> (u16, [u8; 32]) {
+    (data_source.chain.into(), data_source.address.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GovernanceAction, GovernanceInstruction, GuardianSetUpgrade};
+
+    // The expected payloads are copied from
+    // `governance/xc_admin/packages/xc_admin_common/src/__tests__/GovernancePayload.test.ts`
+    #[test]
+    fn test_serialize_governance_instruction() {
+        let set_fee = GovernanceInstruction {
+            action: GovernanceAction::SetFee { value: 42, expo: 2 },
+            target_chain_id: 60051,
+        };
+        assert_eq!(
+            set_fee.serialize(),
+            vec![80, 84, 71, 77, 1, 3, 234, 147, 0, 0, 0, 0, 0, 0, 0, 42, 0, 0, 0, 0, 0, 0, 0, 2]
+        );
+
+        let request_transfer = GovernanceInstruction {
+            action: GovernanceAction::RequestGovernanceDataSourceTransfer {
+                governance_data_source_index: 1,
+            },
+            target_chain_id: 60051,
+        };
+        assert_eq!(
+            request_transfer.serialize(),
+            vec![80, 84, 71, 77, 1, 5, 234, 147, 0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_serialize_guardian_set_upgrade() {
+        let upgrade = GuardianSetUpgrade {
+            chain_id: 0,
+            new_set_index: 1,
+            guardians: vec![[0xaa; 20]],
+        }
+        .serialize();
+        assert_eq!(upgrade.len(), 32 + 1 + 2 + 4 + 1 + 20);
+        assert_eq!(&upgrade[28..32], b"Core");
+        assert_eq!(&upgrade[32..40], &[2, 0, 0, 0, 0, 0, 1, 1]);
+        assert_eq!(&upgrade[40..], &[0xaa; 20]);
+    }
+}
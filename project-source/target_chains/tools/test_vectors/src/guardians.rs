@@ -0,0 +1,180 @@
+use {
+    libsecp256k1::{sign, Message as SecpMessage, PublicKey, SecretKey},
+    pythnet_sdk::{
+        hashers::{keccak256::Keccak256, Hasher},
+        test_utils::{dummy_guardians, DataSource},
+    },
+    serde_wormhole::RawMessage,
+    wormhole_sdk::{
+        vaa::{Body, Header, Signature},
+        Vaa,
+    },
+};
+
+/// A Wormhole guardian set whose secret keys are known, used to sign test VAAs.
+pub struct GuardianSet {
+    pub set_index: u32,
+    pub secrets: Vec<SecretKey>,
+}
+
+impl GuardianSet {
+    /// The guardian set of `pythnet_sdk::test_utils::dummy_guardians`, which the test
+    /// deployments of most receivers are initialized with.
+    pub fn test(set_index: u32) -> Self {
+        GuardianSet {
+            set_index,
+            secrets: dummy_guardians(),
+        }
+    }
+
+    /// A guardian set of `num_guardians` guardians whose keys are derived from `seed`.
+    ///
+    /// Different seeds give disjoint guardian sets, and seed 0 gives the guardians of `test`.
+    pub fn generate(set_index: u32, num_guardians: u8, seed: u8) -> Self {
+        let secrets = (0..num_guardians)
+            .map(|i| {
+                let mut secret_key_bytes = [0u8; 32];
+                secret_key_bytes[0] = i + 1;
+                secret_key_bytes[1] = seed;
+                SecretKey::parse(&secret_key_bytes).unwrap()
+            })
+            .collect();
+        GuardianSet { set_index, secrets }
+    }
+
+    /// The Ethereum addresses of the guardians, in order.
+    pub fn addresses(&self) -> Vec<[u8; 20]> {
+        self.secrets
+            .iter()
+            .map(|secret| {
+                let public_key = PublicKey::from_secret_key(secret).serialize();
+                let mut address = [0u8; 20];
+                address.copy_from_slice(&Keccak256::hashv(&[&public_key[1..]])[12..]);
+                address
+            })
+            .collect()
+    }
+
+    /// The minimum number of signatures of a valid VAA.
+    pub fn quorum(&self) -> usize {
+        self.secrets.len() * 2 / 3 + 1
+    }
+
+    /// Signs a VAA containing `payload` with all the guardians and serializes it.
+    pub fn sign_vaa(&self, emitter: &DataSource, sequence: u64, payload: &[u8]) -> Vec<u8> {
+        let signers: Vec<usize> = (0..self.secrets.len()).collect();
+        self.sign_vaa_with(&signers, emitter, sequence, payload)
+    }
+
+    /// Signs a VAA containing `payload` with the guardians at `signers` and serializes it.
+    /// `signers` must be sorted, as receivers reject signatures that aren't.
+    pub fn sign_vaa_with(
+        &self,
+        signers: &[usize],
+        emitter: &DataSource,
+        sequence: u64,
+        payload: &[u8],
+    ) -> Vec<u8> {
+        let body = Body {
+            emitter_chain: emitter.chain,
+            emitter_address: emitter.address,
+            sequence,
+            payload: <Box<RawMessage>>::from(payload.to_vec()),
+            ..Default::default()
+        };
+        self.sign_body(signers, body)
+    }
+
+    /// Signs a VAA of `body` with the guardians at `signers` and serializes it, for tests that
+    /// need to set the other fields of the body, such as its timestamp or its nonce.
+    pub fn sign_body(&self, signers: &[usize], body: Body<Box<RawMessage>>) -> Vec<u8> {
+        let digest = SecpMessage::parse_slice(&body.digest().unwrap().secp256k_hash).unwrap();
+
+        let signatures = signers
+            .iter()
+            .map(|&index| {
+                let (signature, recovery_id) = sign(&digest, &self.secrets[index]);
+                let mut signature_bytes = [0u8; 65];
+                signature_bytes[..64].copy_from_slice(&signature.serialize());
+                signature_bytes[64] = recovery_id.serialize();
+                Signature {
+                    index: index.try_into().unwrap(),
+                    signature: signature_bytes,
+                }
+            })
+            .collect();
+        let header = Header {
+            version: 1,
+            guardian_set_index: self.set_index,
+            signatures,
+        };
+
+        let vaa: Vaa<Box<RawMessage>> = (header, body).into();
+        serde_wormhole::to_vec(&vaa).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::GuardianSet,
+        libsecp256k1::{recover, Message as SecpMessage, RecoveryId, Signature},
+        pythnet_sdk::{
+            hashers::{keccak256::Keccak256, Hasher},
+            test_utils::{dummy_guardians_addresses, DEFAULT_DATA_SOURCE},
+        },
+        serde_wormhole::RawMessage,
+        wormhole_sdk::{
+            vaa::{Body, Header},
+            Vaa,
+        },
+    };
+
+    #[test]
+    fn test_guardian_set_matches_dummy_guardians() {
+        assert_eq!(
+            GuardianSet::test(0).addresses(),
+            dummy_guardians_addresses()
+        );
+        assert_eq!(
+            GuardianSet::generate(0, 19, 0).addresses(),
+            dummy_guardians_addresses()
+        );
+        assert_ne!(
+            GuardianSet::generate(0, 19, 1).addresses(),
+            dummy_guardians_addresses()
+        );
+    }
+
+    #[test]
+    fn test_sign_vaa() {
+        let guardian_set = GuardianSet::generate(3, 5, 1);
+        let vaa = guardian_set.sign_vaa_with(&[1, 3], &DEFAULT_DATA_SOURCE, 7, b"payload");
+
+        let vaa: Vaa<&RawMessage> = serde_wormhole::from_slice(&vaa).unwrap();
+        let (header, body): (Header, Body<&RawMessage>) = vaa.into();
+        assert_eq!(header.guardian_set_index, 3);
+        assert_eq!(body.emitter_chain, DEFAULT_DATA_SOURCE.chain);
+        assert_eq!(body.emitter_address, DEFAULT_DATA_SOURCE.address);
+        assert_eq!(body.sequence, 7);
+        assert_eq!(body.payload.as_ref(), b"payload");
+
+        let digest = SecpMessage::parse_slice(&body.digest().unwrap().secp256k_hash).unwrap();
+        let addresses = guardian_set.addresses();
+        let signers: Vec<u8> = header.signatures.iter().map(|s| s.index).collect();
+        assert_eq!(signers, vec![1, 3]);
+        for signature in header.signatures {
+            let public_key = recover(
+                &digest,
+                &Signature::parse_standard_slice(&signature.signature[..64]).unwrap(),
+                &RecoveryId::parse(signature.signature[64]).unwrap(),
+            )
+            .unwrap()
+            .serialize();
+            assert_eq!(
+                Keccak256::hashv(&[&public_key[1..]])[12..],
+                addresses[usize::from(signature.index)]
+            );
+        }
+    }
+}
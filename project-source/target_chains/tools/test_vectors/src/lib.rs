@@ -0,0 +1,16 @@
+//! Canonical test vectors for Pyth receiver contracts.
+//!
+//! The vectors are signed by test guardian sets, so that every receiver can be tested offline
+//! against the same corpus of price updates, governance instructions and guardian set upgrades,
+//! including malformed variants. See `Corpus` for the configuration that receivers must be
+//! initialized with.
+pub mod corpus;
+pub mod format;
+pub mod governance;
+pub mod guardians;
+pub mod price_updates;
+
+pub use {
+    corpus::{Corpus, Kind, TestVector},
+    guardians::GuardianSet,
+};
@@ -0,0 +1,35 @@
+use {
+    clap::{Parser, ValueEnum},
+    pyth_test_vectors::{format, Corpus},
+};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    Json,
+    Cairo,
+    Sway,
+    Solidity,
+    Move,
+}
+
+/// Prints the canonical test vectors of Pyth receivers.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    /// The format of the output.
+    #[arg(long, value_enum, default_value = "json")]
+    format: Format,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let corpus = Corpus::new();
+    let output = match cli.format {
+        Format::Json => format::to_json(&corpus),
+        Format::Cairo => format::to_cairo(&corpus),
+        Format::Sway => format::to_sway(&corpus),
+        Format::Solidity => format::to_solidity(&corpus),
+        Format::Move => format::to_move(&corpus),
+    };
+    print!("{output}");
+}
@@ -0,0 +1,296 @@
+use {
+    crate::guardians::GuardianSet,
+    byteorder::BigEndian,
+    pythnet_sdk::{
+        accumulators::{merkle::MerkleTree, Accumulator},
+        hashers::keccak256_160::Keccak160,
+        legacy::{BatchPriceAttestation, Identifier, PriceAttestation, PriceStatus},
+        messages::{FeedId, Message, PriceFeedMessage, TwapMessage},
+        test_utils::DataSource,
+        wire::{
+            to_vec,
+            v1::{
+                AccumulatorUpdateData, MerklePriceUpdate, Proof, WormholeMerkleRoot,
+                WormholeMessage, WormholePayload,
+            },
+            PrefixedVec,
+        },
+    },
+    serde_wormhole::RawMessage,
+    wormhole_sdk::{
+        vaa::{Body, Header},
+        Vaa,
+    },
+};
+
+/// Creates a price feed message whose EMA price is equal to its price.
+/// The previous publish time is one second before `publish_time`.
+pub fn create_price_feed_message(
+    feed_id: FeedId,
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+) -> Message {
+    Message::PriceFeedMessage(PriceFeedMessage {
+        feed_id,
+        price,
+        conf,
+        exponent,
+        publish_time,
+        prev_publish_time: publish_time - 1,
+        ema_price: price,
+        ema_conf: conf,
+    })
+}
+
+/// Creates a TWAP message. The previous publish time is one second before `publish_time`.
+pub fn create_twap_message(
+    feed_id: FeedId,
+    cumulative_price: i128,
+    cumulative_conf: u128,
+    num_down_slots: u64,
+    exponent: i32,
+    publish_time: i64,
+    publish_slot: u64,
+) -> Message {
+    Message::TwapMessage(TwapMessage {
+        feed_id,
+        cumulative_price,
+        cumulative_conf,
+        num_down_slots,
+        exponent,
+        publish_time,
+        prev_publish_time: publish_time - 1,
+        publish_slot,
+    })
+}
+
+/// Creates a trading price attestation of the legacy batch format whose EMA price is equal to
+/// its price. The previous publish time is one second before `publish_time`.
+pub fn create_price_attestation(
+    feed_id: FeedId,
+    price: i64,
+    conf: u64,
+    expo: i32,
+    publish_time: i64,
+) -> PriceAttestation {
+    PriceAttestation {
+        product_id: Identifier::new(feed_id),
+        price_id: Identifier::new(feed_id),
+        price,
+        conf,
+        expo,
+        ema_price: price,
+        ema_conf: conf,
+        status: PriceStatus::Trading,
+        num_publishers: 1,
+        max_num_publishers: 1,
+        attestation_time: publish_time,
+        publish_time,
+        prev_publish_time: publish_time - 1,
+        prev_price: price,
+        prev_conf: conf,
+        last_attested_publish_time: publish_time - 1,
+    }
+}
+
+/// Creates an accumulator update of `messages`, signed by `guardian_set` and emitted by
+/// `emitter`.
+///
+/// If `corrupt_proofs` is set, the signed merkle root doesn't match the messages, so that the
+/// merkle proofs of all the messages are invalid. If `corrupt_wormhole_message` is set, the
+/// magic of the signed wormhole message is invalid.
+pub fn create_accumulator_update(
+    guardian_set: &GuardianSet,
+    signers: &[usize],
+    emitter: &DataSource,
+    messages: &[Message],
+    corrupt_proofs: bool,
+    corrupt_wormhole_message: bool,
+) -> Vec<u8> {
+    let messages: Vec<Vec<u8>> = messages
+        .iter()
+        .map(|message| to_vec::<_, BigEndian>(message).unwrap())
+        .collect();
+    let message_refs: Vec<&[u8]> = messages.iter().map(|message| message.as_slice()).collect();
+    let tree = MerkleTree::<Keccak160>::new(&message_refs).unwrap();
+    let updates = messages
+        .iter()
+        .map(|message| MerklePriceUpdate {
+            proof: tree.prove(message).unwrap(),
+            message: PrefixedVec::from(message.clone()),
+        })
+        .collect();
+
+    let mut root = [0u8; 20];
+    root.copy_from_slice(tree.root.as_bytes());
+    if corrupt_proofs {
+        root[0] ^= 0xff;
+    }
+    let wormhole_message = WormholeMessage::new(WormholePayload::Merkle(WormholeMerkleRoot {
+        slot: 0,
+        ring_size: 0,
+        root,
+    }));
+    let mut payload = to_vec::<_, BigEndian>(&wormhole_message).unwrap();
+    if corrupt_wormhole_message {
+        payload[0] ^= 0xff;
+    }
+    // Receivers don't check the sequence of price updates.
+    let vaa = guardian_set.sign_vaa_with(signers, emitter, 0, &payload);
+
+    let update = AccumulatorUpdateData::new(Proof::WormholeMerkle {
+        vaa: PrefixedVec::from(vaa),
+        updates,
+    });
+    to_vec::<_, BigEndian>(&update).unwrap()
+}
+
+/// Returns the VAA of an accumulator update.
+pub fn accumulator_update_vaa(update: &[u8]) -> Vec<u8> {
+    let update = AccumulatorUpdateData::try_from_slice(update).unwrap();
+    let Proof::WormholeMerkle { vaa, .. } = update.proof;
+    Vec::from(vaa)
+}
+
+/// Signs the VAA of an accumulator update again with the guardians at `signers` of
+/// `guardian_set`, so that updates pulled from Hermes can be used with test guardian sets.
+/// The emitter of the VAA is replaced with `new_emitter` if it is set.
+pub fn re_sign_accumulator_update(
+    update: &[u8],
+    guardian_set: &GuardianSet,
+    signers: &[usize],
+    new_emitter: Option<&DataSource>,
+) -> Vec<u8> {
+    let mut update = AccumulatorUpdateData::try_from_slice(update).unwrap();
+    let Proof::WormholeMerkle { vaa, .. } = &mut update.proof;
+    let old_vaa: Vaa<Box<RawMessage>> = serde_wormhole::from_slice(vaa.as_ref()).unwrap();
+    let (_, mut body): (Header, Body<Box<RawMessage>>) = old_vaa.into();
+    if let Some(new_emitter) = new_emitter {
+        body.emitter_chain = new_emitter.chain;
+        body.emitter_address = new_emitter.address;
+    }
+    *vaa = PrefixedVec::from(guardian_set.sign_body(signers, body));
+    to_vec::<_, BigEndian>(&update).unwrap()
+}
+
+/// Creates a VAA of a legacy batch price attestation of `attestations`, signed by
+/// `guardian_set` and emitted by `emitter`.
+pub fn create_batch_attestation(
+    guardian_set: &GuardianSet,
+    emitter: &DataSource,
+    attestations: Vec<PriceAttestation>,
+) -> Vec<u8> {
+    let payload = BatchPriceAttestation {
+        price_attestations: attestations,
+    }
+    .serialize()
+    .unwrap();
+    guardian_set.sign_vaa(emitter, 0, &payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            accumulator_update_vaa, create_accumulator_update, create_price_feed_message,
+            re_sign_accumulator_update,
+        },
+        crate::guardians::GuardianSet,
+        byteorder::BigEndian,
+        pythnet_sdk::{
+            accumulators::merkle::MerkleRoot,
+            hashers::keccak256_160::Keccak160,
+            messages::Message,
+            test_utils::{DEFAULT_DATA_SOURCE, SECONDARY_DATA_SOURCE},
+            wire::{
+                from_slice,
+                v1::{AccumulatorUpdateData, Proof, WormholeMessage, WormholePayload},
+            },
+        },
+        serde_wormhole::RawMessage,
+        wormhole_sdk::{
+            vaa::{Body, Header},
+            Vaa,
+        },
+    };
+
+    fn check_proofs(update: &[u8]) -> Vec<bool> {
+        let update = AccumulatorUpdateData::try_from_slice(update).unwrap();
+        let Proof::WormholeMerkle { vaa, updates } = update.proof;
+        let vaa = Vec::from(vaa);
+        let vaa: Vaa<&RawMessage> = serde_wormhole::from_slice(&vaa).unwrap();
+        let WormholePayload::Merkle(merkle_root) = WormholeMessage::try_from_bytes(vaa.payload)
+            .unwrap()
+            .payload;
+        let root: MerkleRoot<Keccak160> = MerkleRoot::new(merkle_root.root);
+        updates
+            .into_iter()
+            .map(|update| {
+                let message = Vec::from(update.message);
+                from_slice::<BigEndian, Message>(&message).unwrap();
+                root.check(update.proof, &message)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_create_accumulator_update() {
+        let guardian_set = GuardianSet::test(0);
+        let messages: Vec<Message> = (1..=3)
+            .map(|i| create_price_feed_message([i; 32], 100 * i64::from(i), 1, -2, 1000))
+            .collect();
+        let signers: Vec<usize> = (0..guardian_set.quorum()).collect();
+
+        let update = create_accumulator_update(
+            &guardian_set,
+            &signers,
+            &DEFAULT_DATA_SOURCE,
+            &messages,
+            false,
+            false,
+        );
+        assert_eq!(check_proofs(&update), vec![true, true, true]);
+
+        let update = create_accumulator_update(
+            &guardian_set,
+            &signers,
+            &DEFAULT_DATA_SOURCE,
+            &messages,
+            true,
+            false,
+        );
+        assert_eq!(check_proofs(&update), vec![false, false, false]);
+    }
+
+    #[test]
+    fn test_re_sign_accumulator_update() {
+        let messages = vec![create_price_feed_message([1; 32], 100, 1, -2, 1000)];
+        let update = create_accumulator_update(
+            &GuardianSet::generate(0, 5, 1),
+            &[0, 1, 2, 3, 4],
+            &DEFAULT_DATA_SOURCE,
+            &messages,
+            false,
+            false,
+        );
+
+        let guardian_set = GuardianSet::test(2);
+        let re_signed = re_sign_accumulator_update(
+            &update,
+            &guardian_set,
+            &[0, 1],
+            Some(&SECONDARY_DATA_SOURCE),
+        );
+        assert_eq!(check_proofs(&re_signed), vec![true]);
+
+        let vaa = accumulator_update_vaa(&re_signed);
+        let vaa: Vaa<&RawMessage> = serde_wormhole::from_slice(&vaa).unwrap();
+        let (header, body): (Header, Body<&RawMessage>) = vaa.into();
+        assert_eq!(header.guardian_set_index, 2);
+        assert_eq!(header.signatures.len(), 2);
+        assert_eq!(body.emitter_chain, SECONDARY_DATA_SOURCE.chain);
+        assert_eq!(body.emitter_address, SECONDARY_DATA_SOURCE.address);
+    }
+}